@@ -0,0 +1,72 @@
+//! Accept-to-authorized throughput: for each mock connection (an in-memory duplex stream) create the
+//! `StratumContext` and run `mining.subscribe` and `mining.authorize` through the default handler
+//! map, as the read loop would.
+//!
+//! `cargo bench -p kaspa-stratum-bridge --bench bench_connections`
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use kaspa_stratum_bridge::default_client::default_handlers;
+use kaspa_stratum_bridge::jsonrpc_event::JsonRpcEvent;
+use kaspa_stratum_bridge::mining_state::MiningState;
+use kaspa_stratum_bridge::stratum_context::StratumContext;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+const CONNECTIONS: [u64; 3] = [1, 10, 100];
+const WALLET: &str = "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y";
+
+fn connections(c: &mut Criterion) {
+    let handlers = default_handlers();
+    let subscribe = &handlers["mining.subscribe"];
+    let authorize = &handlers["mining.authorize"];
+
+    let mut group = c.benchmark_group("accept_to_authorized");
+    for count in CONNECTIONS {
+        group.throughput(Throughput::Elements(count));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.iter(|| {
+                tokio_test::block_on(async {
+                    let (disconnect_tx, _disconnect_rx) = mpsc::unbounded_channel();
+                    // Client halves stay open until every session is authorized
+                    let mut clients = Vec::with_capacity(count as usize);
+                    for port in 0..count {
+                        let (server, client) = tokio::io::duplex(16 * 1024);
+                        clients.push(client);
+                        let ctx = StratumContext::new(
+                            "127.0.0.1".to_string(),
+                            port as u16,
+                            server,
+                            Arc::new(MiningState::new()),
+                            disconnect_tx.clone(),
+                        );
+                        subscribe(
+                            Arc::clone(&ctx),
+                            JsonRpcEvent::new(
+                                Some("1".to_string()),
+                                "mining.subscribe",
+                                vec![json!("IceRiverMiner-v1.1"), json!("EthereumStratum/1.0.0")],
+                            ),
+                        )
+                        .await
+                        .unwrap();
+                        authorize(
+                            ctx,
+                            JsonRpcEvent::new(
+                                Some("2".to_string()),
+                                "mining.authorize",
+                                vec![json!(format!("{}.rig{}", WALLET, port)), json!("x")],
+                            ),
+                        )
+                        .await
+                        .unwrap();
+                    }
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, connections);
+criterion_main!(benches);
@@ -0,0 +1,137 @@
+//! `ShareHandler::handle_submit` for one authorized IceRiver session against a stub node: nonce
+//! parsing and the extranonce2 range check, the duplicate guard, PoW and the difficulty
+//! comparison. The network target is far out of reach, so no share turns into a block submit.
+//!
+//! `cargo bench -p kaspa-stratum-bridge --bench bench_share_validation`
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use kaspa_consensus_core::block::Block;
+use kaspa_consensus_core::header::Header;
+use kaspa_hashes::Hash;
+use kaspa_stratum_bridge::default_client::{handle_authorize, handle_subscribe};
+use kaspa_stratum_bridge::hasher::KaspaDiff;
+use kaspa_stratum_bridge::jsonrpc_event::JsonRpcEvent;
+use kaspa_stratum_bridge::mining_state::{Job, MiningState};
+use kaspa_stratum_bridge::share_handler::{KaspaApiTrait, ShareHandler};
+use kaspa_stratum_bridge::stratum_context::StratumContext;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+const WALLET: &str = "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y";
+
+/// Node stub: the benchmark never finds a block, so only `submit_block` would matter.
+struct StubKaspaApi;
+
+#[async_trait::async_trait]
+impl KaspaApiTrait for StubKaspaApi {
+    async fn get_block_template(&self, _: &str, _: &str, _: &str) -> anyhow::Result<Block> {
+        anyhow::bail!("stub")
+    }
+
+    async fn submit_block(&self, _: Block) -> anyhow::Result<kaspa_rpc_core::SubmitBlockResponse> {
+        anyhow::bail!("stub")
+    }
+
+    async fn get_balances_by_addresses(&self, _: &[String]) -> anyhow::Result<Vec<(String, u64)>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_current_block_color(&self, _: &str) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    async fn is_node_synced_for_mining(&self) -> bool {
+        true
+    }
+}
+
+/// Authorized session with one job; replies are drained so writes never block.
+async fn session() -> (Arc<StratumContext>, u64) {
+    let (server, mut client) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move {
+        let _ = tokio::io::copy(&mut client, &mut tokio::io::sink()).await;
+    });
+    let (disconnect_tx, _disconnect_rx) = mpsc::unbounded_channel();
+    let ctx = StratumContext::new(
+        "127.0.0.1".to_string(),
+        4000,
+        server,
+        Arc::new(MiningState::new()),
+        disconnect_tx,
+    );
+    let subscribe = JsonRpcEvent::new(
+        Some("1".to_string()),
+        "mining.subscribe",
+        vec![json!("IceRiverMiner-v1.1"), json!("EthereumStratum/1.0.0")],
+    );
+    handle_subscribe(Arc::clone(&ctx), subscribe, None)
+        .await
+        .unwrap();
+    let authorize = JsonRpcEvent::new(
+        Some("2".to_string()),
+        "mining.authorize",
+        vec![json!(format!("{}.bench", WALLET)), json!("x")],
+    );
+    handle_authorize(Arc::clone(&ctx), authorize, None, None)
+        .await
+        .unwrap();
+
+    ctx.state.set_initialized(true);
+    let mut diff = KaspaDiff::new();
+    diff.set_diff_value(8192.0);
+    ctx.state.set_stratum_diff(diff);
+    let mut header = Header::from_precomputed_hash(Hash::from_bytes([1; 32]), vec![]);
+    header.timestamp = 1_700_000_000_000;
+    header.bits = 0x1b00ffff;
+    let job_id = ctx.state.add_job(Job {
+        block: Block::from_arcs(Arc::new(header), Arc::new(vec![])),
+        pre_pow_hash: Hash::from_bytes([2; 32]),
+    });
+    (ctx, job_id)
+}
+
+fn submit(job_id: u64, nonce: u64) -> JsonRpcEvent {
+    JsonRpcEvent::new(
+        Some("3".to_string()),
+        "mining.submit",
+        vec![
+            json!(format!("{}.bench", WALLET)),
+            json!(job_id.to_string()),
+            json!(format!("{:012x}", nonce & 0xffff_ffff_ffff)),
+        ],
+    )
+}
+
+fn share_validation(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let (ctx, job_id) = rt.block_on(session());
+    let handler = ShareHandler::new("bench".to_string());
+    let api: Arc<dyn KaspaApiTrait + Send + Sync> = Arc::new(StubKaspaApi);
+
+    let mut group = c.benchmark_group("share_validation");
+    let mut nonce = 0u64;
+    group.bench_function("fresh_nonce", |b| {
+        b.iter(|| {
+            nonce += 1;
+            let _ = rt.block_on(handler.handle_submit(
+                Arc::clone(&ctx),
+                submit(job_id, nonce),
+                Arc::clone(&api),
+            ));
+        })
+    });
+    group.bench_function("duplicate_nonce", |b| {
+        b.iter(|| {
+            let _ = rt.block_on(handler.handle_submit(
+                Arc::clone(&ctx),
+                submit(job_id, 1),
+                Arc::clone(&api),
+            ));
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, share_validation);
+criterion_main!(benches);
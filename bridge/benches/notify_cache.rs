@@ -0,0 +1,73 @@
+//! Broadcasting one template to 1, 10, 100 and 1000 sessions: serializing `mining.notify` per
+//! session vs. the shared `NotifyCache`.
+//!
+//! `cargo bench -p kaspa-stratum-bridge --bench notify_cache`
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use kaspa_hashes::Hash;
+use kaspa_stratum_bridge::JobFormat;
+use kaspa_stratum_bridge::client_handler::{NotifyCache, NotifyKey, serialize_notify};
+use serde_json::Value;
+
+const SESSIONS: [usize; 4] = [1, 10, 100, 1000];
+
+fn key(format: JobFormat, minimal: bool) -> NotifyKey {
+    NotifyKey {
+        job_id: 42,
+        pre_pow_hash: Hash::from_bytes([0xab; 32]),
+        timestamp: 1_700_000_000_000,
+        format,
+        minimal,
+    }
+}
+
+fn legacy_params() -> Vec<Value> {
+    vec![
+        Value::String("42".to_string()),
+        Value::Array(
+            [u64::MAX / 3, u64::MAX / 5, u64::MAX / 7, u64::MAX / 11]
+                .iter()
+                .map(|&v| Value::Number(v.into()))
+                .collect(),
+        ),
+        Value::Number(1_700_000_000_000u64.into()),
+    ]
+}
+
+fn broadcast(c: &mut Criterion) {
+    let mut group = c.benchmark_group("notify_broadcast");
+    for (name, format, minimal) in [
+        ("legacy", JobFormat::Legacy, false),
+        ("iceriver", JobFormat::IceRiver, true),
+    ] {
+        for sessions in SESSIONS {
+            group.bench_with_input(
+                BenchmarkId::new(format!("serialize_per_session/{name}"), sessions),
+                &sessions,
+                |b, &sessions| {
+                    b.iter(|| {
+                        for _ in 0..sessions {
+                            black_box(serialize_notify(&key(format, minimal), legacy_params()));
+                        }
+                    })
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("cached/{name}"), sessions),
+                &sessions,
+                |b, &sessions| {
+                    b.iter(|| {
+                        let cache = NotifyCache::default();
+                        for _ in 0..sessions {
+                            black_box(cache.notify_line(key(format, minimal), legacy_params));
+                        }
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, broadcast);
+criterion_main!(benches);
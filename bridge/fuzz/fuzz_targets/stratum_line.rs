@@ -0,0 +1,74 @@
+//! Arbitrary bytes through the Stratum read path, in the order `client_io::read_loop` applies it:
+//! NUL stripping, lossy UTF-8, the line-length cap, line framing, the HTTP probe check, batch or
+//! single-event JSON-RPC parsing, then the handler lookup and the param parsing `mining.subscribe` /
+//! `mining.authorize` do before touching session state. Any panic here is reachable by a peer.
+//!
+//! `cargo fuzz run stratum_line -- -max_total_time=60` from `bridge/`.
+
+#![no_main]
+
+use kaspa_stratum_bridge::default_client::default_handlers;
+use kaspa_stratum_bridge::jsonrpc_event::{JsonRpcEvent, unmarshal_event};
+use kaspa_stratum_bridge::miner_detect::{detect_miner_type, protocol_version_from_params};
+use kaspa_stratum_bridge::stratum_line_codec::{
+    append_line_data, line_looks_like_http, push_lossy_and_drain_lines, strip_nul_bytes,
+};
+use kaspa_stratum_bridge::user_agent::UserAgent;
+use kaspa_stratum_bridge::validation::validate_kaspa_address;
+use libfuzzer_sys::fuzz_target;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+static METHODS: LazyLock<HashSet<String>> =
+    LazyLock::new(|| default_handlers().into_keys().collect());
+
+fn dispatch(event: JsonRpcEvent) {
+    if !METHODS.contains(&event.method) {
+        return;
+    }
+    match event.method.as_str() {
+        "mining.subscribe" => {
+            let user_agent = event.params.first().and_then(Value::as_str).unwrap_or("");
+            let _ = UserAgent::parse(user_agent).metric_labels();
+            let _ = detect_miner_type(user_agent);
+            let _ = protocol_version_from_params(&event.params);
+        }
+        "mining.authorize" => {
+            if let Some(identity) = event.params.first().and_then(Value::as_str) {
+                let address = identity.split('.').next().unwrap_or_default();
+                let _ = validate_kaspa_address(address);
+            }
+        }
+        _ => {}
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let data = strip_nul_bytes(data);
+    let chunk = String::from_utf8_lossy(&data);
+    let mut line_buffer = String::new();
+    if !append_line_data(&mut line_buffer, &chunk) {
+        return;
+    }
+    // A trailing newline flushes whatever is left, like the next read would
+    line_buffer.push('\n');
+    for line in push_lossy_and_drain_lines(&mut line_buffer, "") {
+        if line_looks_like_http(&line) {
+            continue;
+        }
+        if line.trim_start().starts_with('[') {
+            if let Ok(items) = serde_json::from_str::<Vec<Value>>(&line) {
+                for item in items {
+                    if let Ok(event) = serde_json::from_value::<JsonRpcEvent>(item) {
+                        dispatch(event);
+                    }
+                }
+            }
+            continue;
+        }
+        if let Ok(event) = unmarshal_event(&line) {
+            dispatch(event);
+        }
+    }
+});
@@ -0,0 +1,282 @@
+//! `kaspa-miner-sim`: load generator for benchmarking a Stratum endpoint before production.
+//!
+//! Each simulated worker opens its own TCP connection, runs `mining.subscribe` / `mining.authorize`
+//! with the user-agent of the chosen miner type, tracks `mining.notify` jobs and submits random
+//! nonces against the latest job at `--submit-rate`. The shares are fake, so a real bridge answers
+//! most of them with low-difficulty rejects; the point is exercising the full request path.
+//! Aggregate counters are printed once per second.
+
+use clap::{Parser, ValueEnum};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Request ids below this are the handshake; everything from here on is a `mining.submit`.
+const FIRST_SUBMIT_ID: u64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MinerType {
+    Bitmain,
+    Iceriver,
+    Bzminer,
+}
+
+impl MinerType {
+    /// `mining.subscribe` params the real firmware sends.
+    fn subscribe_params(self) -> Value {
+        match self {
+            MinerType::Bitmain => json!(["GodMiner/2.0.0"]),
+            MinerType::Iceriver => json!(["IceRiverMiner-v1.1", "EthereumStratum/1.0.0"]),
+            MinerType::Bzminer => json!(["BzMiner-v21.3.0", "EthereumStratum/1.0.0"]),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "kaspa-miner-sim",
+    about = "Simulate concurrent Stratum miners against a Kaspa bridge"
+)]
+struct Args {
+    /// Stratum endpoint to connect to.
+    #[arg(long, value_name = "HOST:PORT")]
+    target: String,
+    /// Number of simulated workers (one connection each).
+    #[arg(long, default_value_t = 10)]
+    workers: u32,
+    /// Wallet address used in `mining.authorize`; workers are named `<wallet>.sim<N>`.
+    #[arg(long)]
+    wallet: String,
+    /// Firmware to impersonate (selects the subscribe user-agent).
+    #[arg(long, value_enum, default_value_t = MinerType::Iceriver)]
+    miner_type: MinerType,
+    /// Shares each worker submits per minute.
+    #[arg(long, value_name = "SHARES_PER_MIN", default_value_t = 20.0)]
+    submit_rate: f64,
+    /// Stop after this many seconds (runs until Ctrl-C when unset).
+    #[arg(long)]
+    duration_secs: Option<u64>,
+}
+
+/// Counters shared by every worker.
+#[derive(Debug, Default)]
+struct SimStats {
+    connections_established: AtomicU64,
+    jobs_received: AtomicU64,
+    shares_submitted: AtomicU64,
+    shares_accepted: AtomicU64,
+}
+
+impl SimStats {
+    fn snapshot(&self) -> [u64; 4] {
+        [
+            self.connections_established.load(Ordering::Relaxed),
+            self.jobs_received.load(Ordering::Relaxed),
+            self.shares_submitted.load(Ordering::Relaxed),
+            self.shares_accepted.load(Ordering::Relaxed),
+        ]
+    }
+}
+
+/// What one line from the server means to a simulated worker.
+#[derive(Debug, PartialEq)]
+enum ServerMessage {
+    /// Subscribe result or `mining.set_extranonce` carrying the extranonce prefix.
+    Extranonce(String),
+    Job(String),
+    SubmitResult {
+        accepted: bool,
+    },
+    Other,
+}
+
+fn classify(line: &str) -> ServerMessage {
+    let Ok(msg) = serde_json::from_str::<Value>(line) else {
+        return ServerMessage::Other;
+    };
+    match msg.get("method").and_then(Value::as_str) {
+        Some("mining.notify") => {
+            return match msg["params"].get(0) {
+                Some(Value::String(id)) => ServerMessage::Job(id.clone()),
+                Some(id @ Value::Number(_)) => ServerMessage::Job(id.to_string()),
+                _ => ServerMessage::Other,
+            };
+        }
+        Some("mining.set_extranonce") => {
+            return msg["params"][0].as_str().map_or(ServerMessage::Other, |e| {
+                ServerMessage::Extranonce(e.to_string())
+            });
+        }
+        Some(_) => return ServerMessage::Other,
+        None => {}
+    }
+    match msg.get("id").and_then(Value::as_u64) {
+        // `[null, extranonce, extranonce2_size]` style subscribe results
+        Some(1) => msg["result"][1].as_str().map_or(ServerMessage::Other, |e| {
+            ServerMessage::Extranonce(e.to_string())
+        }),
+        Some(id) if id >= FIRST_SUBMIT_ID => ServerMessage::SubmitResult {
+            accepted: msg["result"] == Value::Bool(true) && msg["error"].is_null(),
+        },
+        _ => ServerMessage::Other,
+    }
+}
+
+/// Random nonce that fits next to `extranonce` in the 16 hex digit Kaspa nonce.
+fn fake_nonce(extranonce: &str) -> String {
+    let width = 16usize.saturating_sub(extranonce.len()).max(1);
+    let nonce = format!("{:016x}", rand::random::<u64>());
+    nonce[16 - width..].to_string()
+}
+
+async fn send(
+    writer: &mut tokio::net::tcp::OwnedWriteHalf,
+    id: u64,
+    method: &str,
+    params: Value,
+) -> std::io::Result<()> {
+    let mut line = json!({ "id": id, "method": method, "params": params }).to_string();
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await
+}
+
+async fn run_worker(index: u32, args: Arc<Args>, stats: Arc<SimStats>) -> std::io::Result<()> {
+    let stream = TcpStream::connect(&args.target).await?;
+    stats
+        .connections_established
+        .fetch_add(1, Ordering::Relaxed);
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let worker = format!("{}.sim{}", args.wallet, index);
+    send(
+        &mut writer,
+        1,
+        "mining.subscribe",
+        args.miner_type.subscribe_params(),
+    )
+    .await?;
+    send(&mut writer, 2, "mining.authorize", json!([worker, "x"])).await?;
+
+    let mut extranonce = String::new();
+    let mut job_id: Option<String> = None;
+    let mut next_id = FIRST_SUBMIT_ID;
+    let mut submit_timer =
+        tokio::time::interval(Duration::from_secs_f64(60.0 / args.submit_rate.max(0.01)));
+    submit_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    return Ok(());
+                };
+                match classify(&line) {
+                    ServerMessage::Extranonce(e) => extranonce = e,
+                    ServerMessage::Job(id) => {
+                        stats.jobs_received.fetch_add(1, Ordering::Relaxed);
+                        job_id = Some(id);
+                    }
+                    ServerMessage::SubmitResult { accepted: true } => {
+                        stats.shares_accepted.fetch_add(1, Ordering::Relaxed);
+                    }
+                    ServerMessage::SubmitResult { accepted: false } | ServerMessage::Other => {}
+                }
+            }
+            _ = submit_timer.tick() => {
+                let Some(job) = &job_id else {
+                    continue;
+                };
+                let params = json!([worker, job, fake_nonce(&extranonce)]);
+                send(&mut writer, next_id, "mining.submit", params).await?;
+                next_id += 1;
+                stats.shares_submitted.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+async fn report(stats: Arc<SimStats>) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    let mut last = stats.snapshot();
+    let mut elapsed = 0u64;
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        elapsed += 1;
+        let now = stats.snapshot();
+        println!(
+            "t={}s connections_established={} jobs_received={}/s shares_submitted={}/s shares_accepted={}/s (totals: jobs={} submitted={} accepted={})",
+            elapsed,
+            now[0],
+            now[1] - last[1],
+            now[2] - last[2],
+            now[3] - last[3],
+            now[1],
+            now[2],
+            now[3]
+        );
+        last = now;
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let args = Arc::new(Args::parse());
+    if args.submit_rate <= 0.0 {
+        anyhow::bail!("--submit-rate must be greater than 0");
+    }
+    let stats = Arc::new(SimStats::default());
+
+    for index in 0..args.workers {
+        let args = Arc::clone(&args);
+        let stats = Arc::clone(&stats);
+        tokio::spawn(async move {
+            if let Err(e) = run_worker(index, args, stats).await {
+                eprintln!("worker sim{}: {}", index, e);
+            }
+        });
+    }
+    tokio::spawn(report(Arc::clone(&stats)));
+
+    match args.duration_secs {
+        Some(secs) => tokio::time::sleep(Duration::from_secs(secs)).await,
+        None => tokio::signal::ctrl_c().await?,
+    }
+    let [connections, jobs, submitted, accepted] = stats.snapshot();
+    println!(
+        "done: connections_established={} jobs_received={} shares_submitted={} shares_accepted={}",
+        connections, jobs, submitted, accepted
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_server_lines() {
+        assert_eq!(
+            classify(r#"{"id":1,"result":[null,"ab12",2],"error":null}"#),
+            ServerMessage::Extranonce("ab12".to_string())
+        );
+        assert_eq!(
+            classify(r#"{"id":null,"method":"mining.notify","params":["7","00ff",1]}"#),
+            ServerMessage::Job("7".to_string())
+        );
+        assert_eq!(
+            classify(r#"{"id":4,"result":true,"error":null}"#),
+            ServerMessage::SubmitResult { accepted: true }
+        );
+        assert_eq!(
+            classify(r#"{"id":5,"result":null,"error":[23,"Low difficulty share",null]}"#),
+            ServerMessage::SubmitResult { accepted: false }
+        );
+        assert_eq!(fake_nonce("ab12").len(), 12);
+        assert_eq!(fake_nonce("").len(), 16);
+    }
+}
@@ -1,12 +1,16 @@
 //! Crate-wide error type for boundaries that still box into [`std::error::Error`] (e.g. Stratum `EventHandler`).
 
 use crate::share_handler::SubmitRunError;
+use crate::stratum_error::StratumError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum BridgeError {
     #[error(transparent)]
     Submit(#[from] SubmitRunError),
+    /// A method arrived before the session reached the state it needs; the miner got `error`.
+    #[error("{method} out of order: {error}")]
+    OutOfOrder { method: String, error: StratumError },
 }
 
 impl BridgeError {
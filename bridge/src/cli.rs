@@ -65,6 +65,14 @@ pub(crate) fn parse_instance_spec(
                     Some(normalized)
                 };
             }
+            "ws" | "ws_port" => {
+                let normalized = normalize_port(v);
+                instance.ws_port = if normalized.is_empty() {
+                    None
+                } else {
+                    Some(normalized)
+                };
+            }
             "diff" | "min_share_diff" => {
                 instance.min_share_diff = v
                     .parse::<u32>()
@@ -126,12 +134,21 @@ pub(crate) fn parse_instance_spec(
     Ok(instance)
 }
 
-#[derive(Debug, Clone, Parser)]
+#[derive(Debug, Clone, Default, Parser)]
 #[command(author, version, about)]
 pub struct Cli {
     #[arg(long)]
     pub config: Option<PathBuf>,
 
+    /// Load and validate the config (with CLI overrides), print the result, and exit without
+    /// binding ports or contacting kaspad.
+    #[arg(long = "check-config", alias = "check")]
+    pub check_config: bool,
+
+    /// Fork into the background and write a PID file (Unix only); same as `daemonize: true`.
+    #[arg(long)]
+    pub daemon: bool,
+
     #[arg(long)]
     pub testnet: bool,
 
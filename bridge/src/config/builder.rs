@@ -0,0 +1,295 @@
+//! Fluent builders for [`GlobalConfig`] and [`InstanceConfig`], for embedders that construct config in
+//! code instead of YAML. `build()` runs the same value checks the YAML loader relies on.
+
+use super::app_config::{ConfigError, GlobalConfig, InstanceConfig};
+use crate::net_utils::parse_bind_address;
+use std::time::Duration;
+
+/// Extranonce widths (bytes) the bridge can hand out; 0 disables extranonce assignment.
+const VALID_EXTRANONCE_SIZES: [u8; 4] = [0, 1, 2, 4];
+
+pub(crate) fn check_extranonce_size(size: u8) -> Result<(), ConfigError> {
+    if VALID_EXTRANONCE_SIZES.contains(&size) {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidSetting {
+            field: "extranonce_size",
+            reason: format!("{size} is not one of 0, 1, 2, 4"),
+        })
+    }
+}
+
+fn check_shares_per_min(shares_per_min: u32) -> Result<(), ConfigError> {
+    if shares_per_min == 0 {
+        return Err(ConfigError::InvalidSetting {
+            field: "shares_per_min",
+            reason: "must be positive".to_string(),
+        });
+    }
+    Ok(())
+}
+
+impl GlobalConfig {
+    pub fn builder() -> GlobalConfigBuilder {
+        GlobalConfigBuilder::default()
+    }
+}
+
+impl InstanceConfig {
+    pub fn builder() -> InstanceConfigBuilder {
+        InstanceConfigBuilder::default()
+    }
+}
+
+/// Builder for [`GlobalConfig`]; unset fields keep [`GlobalConfig::default`] values.
+#[derive(Debug, Clone, Default)]
+pub struct GlobalConfigBuilder {
+    config: GlobalConfig,
+}
+
+impl GlobalConfigBuilder {
+    pub fn kaspad_address(mut self, addr: impl Into<String>) -> Self {
+        self.config.kaspad_address = addr.into();
+        self
+    }
+
+    pub fn block_wait_time(mut self, ms: u64) -> Self {
+        self.config.block_wait_time = Duration::from_millis(ms);
+        self
+    }
+
+    pub fn print_stats(mut self, enabled: bool) -> Self {
+        self.config.print_stats = enabled;
+        self
+    }
+
+    pub fn log_to_file(mut self, enabled: bool) -> Self {
+        self.config.log_to_file = enabled;
+        self
+    }
+
+    pub fn health_check_port(mut self, port: impl Into<String>) -> Self {
+        self.config.health_check_port = port.into();
+        self
+    }
+
+    pub fn web_dashboard_port(mut self, port: impl Into<String>) -> Self {
+        self.config.web_dashboard_port = port.into();
+        self
+    }
+
+    pub fn var_diff(mut self, enabled: bool) -> Self {
+        self.config.var_diff = enabled;
+        self
+    }
+
+    pub fn shares_per_min(mut self, shares_per_min: u32) -> Self {
+        self.config.shares_per_min = shares_per_min;
+        self
+    }
+
+    pub fn var_diff_stats(mut self, enabled: bool) -> Self {
+        self.config.var_diff_stats = enabled;
+        self
+    }
+
+    pub fn extranonce_size(mut self, size: u8) -> Self {
+        self.config.extranonce_size = size;
+        self
+    }
+
+    pub fn pow2_clamp(mut self, enabled: bool) -> Self {
+        self.config.pow2_clamp = enabled;
+        self
+    }
+
+    pub fn coinbase_tag_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.config.coinbase_tag_suffix = Some(suffix.into());
+        self
+    }
+
+    pub fn allowed_ips(mut self, entries: Vec<String>) -> Self {
+        self.config.allowed_ips = Some(entries);
+        self
+    }
+
+    pub fn denied_ips(mut self, entries: Vec<String>) -> Self {
+        self.config.denied_ips = Some(entries);
+        self
+    }
+
+    /// Validate and return the config: positive `shares_per_min`, `extranonce_size` of 0/1/2/4, and
+    /// parseable `web_dashboard_port` / IP filter entries.
+    pub fn build(self) -> Result<GlobalConfig, anyhow::Error> {
+        let mut config = self.config;
+        check_shares_per_min(config.shares_per_min)?;
+        check_extranonce_size(config.extranonce_size)?;
+        if !config.web_dashboard_port.trim().is_empty() {
+            config.web_dashboard_port =
+                parse_bind_address(&config.web_dashboard_port).map_err(|reason| {
+                    ConfigError::InvalidSetting {
+                        field: "web_dashboard_port",
+                        reason,
+                    }
+                })?;
+        }
+        crate::ip_filter::IpAccessList::parse(
+            config.allowed_ips.as_deref().unwrap_or_default(),
+            config.denied_ips.as_deref().unwrap_or_default(),
+        )?;
+        Ok(config)
+    }
+}
+
+/// Builder for [`InstanceConfig`]; unset fields keep [`InstanceConfig::default`] values.
+#[derive(Debug, Clone, Default)]
+pub struct InstanceConfigBuilder {
+    config: InstanceConfig,
+}
+
+impl InstanceConfigBuilder {
+    pub fn stratum_port(mut self, port: impl Into<String>) -> Self {
+        self.config.stratum_port = port.into();
+        self
+    }
+
+    pub fn min_share_diff(mut self, diff: u32) -> Self {
+        self.config.min_share_diff = diff;
+        self
+    }
+
+    pub fn prom_port(mut self, port: impl Into<String>) -> Self {
+        self.config.prom_port = Some(port.into());
+        self
+    }
+
+    pub fn ws_port(mut self, port: impl Into<String>) -> Self {
+        self.config.ws_port = Some(port.into());
+        self
+    }
+
+    pub fn log_to_file(mut self, enabled: bool) -> Self {
+        self.config.log_to_file = Some(enabled);
+        self
+    }
+
+    pub fn block_wait_time(mut self, ms: u64) -> Self {
+        self.config.block_wait_time = Some(Duration::from_millis(ms));
+        self
+    }
+
+    pub fn extranonce_size(mut self, size: u8) -> Self {
+        self.config.extranonce_size = Some(size);
+        self
+    }
+
+    pub fn var_diff(mut self, enabled: bool) -> Self {
+        self.config.var_diff = Some(enabled);
+        self
+    }
+
+    pub fn shares_per_min(mut self, shares_per_min: u32) -> Self {
+        self.config.shares_per_min = Some(shares_per_min);
+        self
+    }
+
+    pub fn var_diff_stats(mut self, enabled: bool) -> Self {
+        self.config.var_diff_stats = Some(enabled);
+        self
+    }
+
+    pub fn pow2_clamp(mut self, enabled: bool) -> Self {
+        self.config.pow2_clamp = Some(enabled);
+        self
+    }
+
+    /// Inclusive extranonce block for this instance.
+    pub fn extranonce_range(mut self, start: u16, end: u16) -> Self {
+        self.config.extranonce_range_start = Some(start);
+        self.config.extranonce_range_end = Some(end);
+        self
+    }
+
+    /// Validate and return the config: bind addresses parse, `extranonce_size` is 0/1/2/4, and
+    /// `shares_per_min` (when set) is positive.
+    pub fn build(self) -> Result<InstanceConfig, anyhow::Error> {
+        let mut config = self.config;
+        let invalid_port =
+            |field: &'static str| move |reason| ConfigError::InvalidSetting { field, reason };
+        config.stratum_port =
+            parse_bind_address(&config.stratum_port).map_err(invalid_port("stratum_port"))?;
+        if let Some(prom_port) = config.prom_port.as_mut() {
+            *prom_port = parse_bind_address(prom_port).map_err(invalid_port("prom_port"))?;
+        }
+        if let Some(ws_port) = config.ws_port.as_mut() {
+            *ws_port = parse_bind_address(ws_port).map_err(invalid_port("ws_port"))?;
+        }
+        if let Some(size) = config.extranonce_size {
+            check_extranonce_size(size)?;
+        }
+        if let Some(shares_per_min) = config.shares_per_min {
+            check_shares_per_min(shares_per_min)?;
+        }
+        if let (Some(start), Some(end)) =
+            (config.extranonce_range_start, config.extranonce_range_end)
+            && start > end
+        {
+            return Err(ConfigError::InvalidSetting {
+                field: "extranonce_range_start",
+                reason: format!("{start} is after extranonce_range_end {end}"),
+            }
+            .into());
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builders_apply_settings_and_validate() {
+        let global = GlobalConfig::builder()
+            .kaspad_address("10.0.0.2:16110")
+            .block_wait_time(500)
+            .var_diff(false)
+            .extranonce_size(2)
+            .build()
+            .unwrap();
+        assert_eq!(global.kaspad_address, "10.0.0.2:16110");
+        assert_eq!(global.block_wait_time, Duration::from_millis(500));
+        assert!(!global.var_diff);
+        assert_eq!(
+            global.shares_per_min,
+            GlobalConfig::default().shares_per_min
+        );
+
+        assert!(GlobalConfig::builder().shares_per_min(0).build().is_err());
+        assert!(GlobalConfig::builder().extranonce_size(3).build().is_err());
+        assert!(GlobalConfig::builder().extranonce_size(1).build().is_ok());
+
+        let instance = InstanceConfig::builder()
+            .stratum_port("5556")
+            .min_share_diff(4096)
+            .var_diff(true)
+            .build()
+            .unwrap();
+        assert_eq!(instance.stratum_port, ":5556");
+        assert_eq!(instance.min_share_diff, 4096);
+        assert_eq!(instance.var_diff, Some(true));
+
+        assert!(
+            InstanceConfig::builder()
+                .stratum_port("not-an-ip:5555")
+                .build()
+                .is_err()
+        );
+        assert!(
+            InstanceConfig::builder()
+                .extranonce_range(10, 5)
+                .build()
+                .is_err()
+        );
+    }
+}
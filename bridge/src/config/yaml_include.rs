@@ -0,0 +1,84 @@
+//! `!include path.yaml` for config files: every node tagged `!include` is replaced by the document it
+//! names, read relative to the directory of the file containing the tag.
+
+use super::app_config::ConfigError;
+use serde_yaml::Value;
+use std::path::Path;
+
+/// Nesting limit for `!include` chains; also what stops a file from including itself.
+pub const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Read `path` and inline its `!include` nodes recursively.
+pub fn load_with_includes(path: &Path) -> Result<Value, ConfigError> {
+    load(path, 0)
+}
+
+fn load(path: &Path, depth: usize) -> Result<Value, ConfigError> {
+    if depth > MAX_INCLUDE_DEPTH {
+        return Err(ConfigError::IncludeTooDeep {
+            path: path.display().to_string(),
+            max: MAX_INCLUDE_DEPTH,
+        });
+    }
+    let include_error = |reason: String| ConfigError::Include {
+        path: path.display().to_string(),
+        reason,
+    };
+    let content = std::fs::read_to_string(path).map_err(|e| include_error(e.to_string()))?;
+    let mut value: Value =
+        serde_yaml::from_str(&content).map_err(|e| include_error(e.to_string()))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    resolve(&mut value, base_dir, depth)?;
+    Ok(value)
+}
+
+fn resolve(value: &mut Value, base_dir: &Path, depth: usize) -> Result<(), ConfigError> {
+    match value {
+        Value::Tagged(tagged) if tagged.tag == "include" => {
+            let Value::String(target) = &tagged.value else {
+                return Err(ConfigError::Include {
+                    path: base_dir.display().to_string(),
+                    reason: "!include expects a file path".to_string(),
+                });
+            };
+            *value = load(&base_dir.join(target), depth + 1)?;
+        }
+        Value::Tagged(tagged) => resolve(&mut tagged.value, base_dir, depth)?,
+        Value::Sequence(items) => {
+            for item in items {
+                resolve(item, base_dir, depth)?;
+            }
+        }
+        Value::Mapping(map) => {
+            for (_, item) in map.iter_mut() {
+                resolve(item, base_dir, depth)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_include_stops_at_max_depth() {
+        let dir =
+            std::env::temp_dir().join(format!("rkstratum_include_loop_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("loop.yaml");
+        std::fs::write(&path, "instances: !include loop.yaml\n").unwrap();
+
+        let err = load_with_includes(&path).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::IncludeTooDeep {
+                max: MAX_INCLUDE_DEPTH,
+                ..
+            }
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,113 @@
+//! Background (`--daemon` / `daemonize: true`) mode for SysV-style supervisors: fork and detach before
+//! the tokio runtime starts, then hold a PID file for the life of the process.
+
+use crate::BridgeConfig;
+use crate::cli::Cli;
+use crate::runner::config_yaml_candidate_paths;
+use std::path::{Path, PathBuf};
+
+/// Default for `pid_file` when the config leaves it unset.
+pub const DEFAULT_PID_FILE: &str = "rustbridge.pid";
+
+/// Removes the PID file when dropped, i.e. when `main` returns after a normal or signal-driven shutdown.
+pub struct PidFileGuard {
+    path: PathBuf,
+}
+
+impl PidFileGuard {
+    /// Write the current process id to `path`.
+    pub fn create(path: PathBuf) -> Result<Self, anyhow::Error> {
+        std::fs::write(&path, format!("{}\n", std::process::id()))
+            .map_err(|e| anyhow::anyhow!("Failed to write PID file {}: {}", path.display(), e))?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFileGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Whether daemon mode is on (`--daemon` or `daemonize` in the config), with the PID file to use.
+/// Config errors are ignored here; `run` reports them once logging is up.
+pub fn daemon_pid_file(cli: &Cli) -> Option<PathBuf> {
+    let config = find_config(cli).and_then(|path| BridgeConfig::from_file(&path).ok());
+    let global = config.as_ref().map(|c| &c.global);
+    let enabled = cli.daemon || global.and_then(|g| g.daemonize).unwrap_or(false);
+    enabled.then(|| {
+        global
+            .and_then(|g| g.pid_file.clone())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_PID_FILE))
+    })
+}
+
+fn find_config(cli: &Cli) -> Option<PathBuf> {
+    let requested = cli
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("config.yaml"));
+    config_yaml_candidate_paths(&requested)
+        .into_iter()
+        .find(|path| path.exists())
+}
+
+/// Detach from the terminal (`daemon(3)`: fork, new session, `chdir("/")`, stdio to `/dev/null`) and
+/// write the child's PID to `pid_file`. Relative paths in `cli` are made absolute first, since the
+/// working directory changes. Must run before the tokio runtime is built.
+#[cfg(unix)]
+pub fn daemonize(cli: &mut Cli, pid_file: &Path) -> Result<PidFileGuard, anyhow::Error> {
+    let pid_file = std::path::absolute(pid_file)?;
+    if let Some(pid) = running_pid(&pid_file) {
+        eprintln!(
+            "warning: PID file {} references running process {}",
+            pid_file.display(),
+            pid
+        );
+    }
+    if let Some(config) = find_config(cli) {
+        cli.config = Some(std::path::absolute(config)?);
+    }
+    if let Some(appdir) = cli.appdir.as_mut() {
+        *appdir = std::path::absolute(&*appdir)?;
+    }
+
+    nix::unistd::daemon(false, false)?;
+    PidFileGuard::create(pid_file)
+}
+
+/// PID recorded in `pid_file`, if that process is still alive.
+#[cfg(unix)]
+fn running_pid(pid_file: &Path) -> Option<i32> {
+    let pid: i32 = std::fs::read_to_string(pid_file)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None)
+        .is_ok()
+        .then_some(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pid_file_is_removed_on_drop() {
+        let path =
+            std::env::temp_dir().join(format!("rkstratum_pid_test_{}.pid", std::process::id()));
+        let guard = PidFileGuard::create(path.clone()).unwrap();
+        let written = std::fs::read_to_string(guard.path()).unwrap();
+        assert_eq!(written.trim(), std::process::id().to_string());
+        #[cfg(unix)]
+        assert_eq!(running_pid(&path), Some(std::process::id() as i32));
+        drop(guard);
+        assert!(!path.exists());
+    }
+}
@@ -1,4 +1,131 @@
-pub(crate) fn spawn_health_check_server(health_port: String) {
+//! Minimal HTTP health server for orchestrators.
+//!
+//! - `GET /healthz/live`: 200 whenever the runtime can serve the request (process up, event loop not stuck).
+//! - `GET /healthz/ready`: 200 only when at least one stratum instance is listening and kaspad is connected;
+//!   otherwise 503 with a JSON body naming the failed condition. Both bodies carry `kaspad_rtt_ms`, the
+//!   round-trip time of the last `KaspaApi::ping` (`null` if it failed), and `total_workers`, the sessions
+//!   open across all instances.
+//! - Any other request: 200 with an empty body (legacy behavior).
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+
+use crate::kaspaapi::NODE_STATUS;
+
+/// Number of stratum listeners currently bound and accepting connections.
+static RUNNING_INSTANCES: AtomicUsize = AtomicUsize::new(0);
+
+/// Marks one stratum instance as `Running` for readiness until dropped.
+pub(crate) struct RunningInstanceGuard(());
+
+impl RunningInstanceGuard {
+    pub(crate) fn new() -> Self {
+        RUNNING_INSTANCES.fetch_add(1, Ordering::AcqRel);
+        Self(())
+    }
+}
+
+impl Drop for RunningInstanceGuard {
+    fn drop(&mut self) {
+        RUNNING_INSTANCES.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+pub(crate) fn running_instances() -> usize {
+    RUNNING_INSTANCES.load(Ordering::Acquire)
+}
+
+/// Sessions open across every instance. The runner creates one and hands a clone to each instance
+/// and to the health server; `ks_stratum_total_workers` follows it.
+#[derive(Debug, Clone, Default)]
+pub struct GlobalWorkerCount(Arc<AtomicI32>);
+
+impl GlobalWorkerCount {
+    /// Count one session until the returned guard is dropped.
+    pub(crate) fn track(&self) -> WorkerCountGuard {
+        let total = self.0.fetch_add(1, Ordering::Relaxed) + 1;
+        crate::prom::record_total_workers(total);
+        WorkerCountGuard(self.clone())
+    }
+
+    pub fn load(&self) -> i32 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// One session in a [`GlobalWorkerCount`]; decrements it when the session task ends (or panics).
+pub(crate) struct WorkerCountGuard(GlobalWorkerCount);
+
+impl Drop for WorkerCountGuard {
+    fn drop(&mut self) {
+        let total = (self.0).0.fetch_sub(1, Ordering::Relaxed) - 1;
+        crate::prom::record_total_workers(total);
+    }
+}
+
+fn json_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Build the readiness response from the current instance count, kaspad connection flag, ping RTT and
+/// session total.
+fn readiness_response(
+    running: usize,
+    kaspad_connected: bool,
+    rtt_ms: Option<f64>,
+    total_workers: i32,
+) -> String {
+    let rtt = rtt_ms.map_or_else(|| "null".to_string(), |ms| format!("{:.1}", ms));
+    let reason = match (running > 0, kaspad_connected) {
+        (true, true) => None,
+        (false, true) => Some("no stratum instance is running"),
+        (true, false) => Some("kaspad is not connected"),
+        (false, false) => Some("no stratum instance is running and kaspad is not connected"),
+    };
+    match reason {
+        None => json_response(
+            "200 OK",
+            &format!(
+                r#"{{"ready":true,"running_instances":{},"kaspad_connected":true,"kaspad_rtt_ms":{},"total_workers":{}}}"#,
+                running, rtt, total_workers
+            ),
+        ),
+        Some(reason) => json_response(
+            "503 Service Unavailable",
+            &format!(
+                r#"{{"ready":false,"reason":"{}","running_instances":{},"kaspad_connected":{},"kaspad_rtt_ms":{},"total_workers":{}}}"#,
+                reason, running, kaspad_connected, rtt, total_workers
+            ),
+        ),
+    }
+}
+
+/// Route a raw request using a prefix check on the request line (no full HTTP parsing).
+fn health_response(request: &str, workers: &GlobalWorkerCount) -> String {
+    if request.starts_with("GET /healthz/live ") || request.starts_with("GET /healthz/live?") {
+        return json_response("200 OK", r#"{"alive":true}"#);
+    }
+    if request.starts_with("GET /healthz/ready ") || request.starts_with("GET /healthz/ready?") {
+        let (kaspad_connected, rtt_ms) = {
+            let status = NODE_STATUS.lock();
+            (status.is_connected, status.rtt_ms)
+        };
+        return readiness_response(
+            running_instances(),
+            kaspad_connected,
+            rtt_ms,
+            workers.load(),
+        );
+    }
+    "HTTP/1.1 200 OK\r\n\r\n".to_string()
+}
+
+pub(crate) fn spawn_health_check_server(health_port: String, workers: GlobalWorkerCount) {
     tokio::spawn(async move {
         use tokio::io::{AsyncReadExt, AsyncWriteExt};
         use tokio::net::TcpListener;
@@ -8,8 +135,9 @@ pub(crate) fn spawn_health_check_server(health_port: String) {
             loop {
                 if let Ok((mut stream, _)) = listener.accept().await {
                     let mut buffer = [0; 1024];
-                    if stream.read(&mut buffer).await.is_ok() {
-                        let response = "HTTP/1.1 200 OK\r\n\r\n";
+                    if let Ok(n) = stream.read(&mut buffer).await {
+                        let request = String::from_utf8_lossy(&buffer[..n]);
+                        let response = health_response(&request, &workers);
                         let _ = stream.write_all(response.as_bytes()).await;
                     }
                 }
@@ -17,3 +145,49 @@ pub(crate) fn spawn_health_check_server(health_port: String) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_and_legacy_paths_return_ok() {
+        let workers = GlobalWorkerCount::default();
+        assert!(
+            health_response("GET /healthz/live HTTP/1.1\r\n\r\n", &workers)
+                .starts_with("HTTP/1.1 200 OK")
+        );
+        assert!(health_response("GET / HTTP/1.1\r\n\r\n", &workers).starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[test]
+    fn readiness_requires_instance_and_kaspad() {
+        let ready = readiness_response(1, true, Some(3.5), 4);
+        assert!(ready.starts_with("HTTP/1.1 200 OK"));
+        assert!(ready.contains(r#""kaspad_rtt_ms":3.5"#));
+        assert!(ready.contains(r#""total_workers":4"#));
+
+        let no_node = readiness_response(2, false, None, 0);
+        assert!(no_node.starts_with("HTTP/1.1 503"));
+        assert!(no_node.contains("kaspad is not connected"));
+        assert!(no_node.contains(r#""kaspad_rtt_ms":null"#));
+
+        let no_instance = readiness_response(0, true, Some(1.0), 0);
+        assert!(no_instance.starts_with("HTTP/1.1 503"));
+        assert!(no_instance.contains("no stratum instance is running"));
+    }
+
+    #[test]
+    fn worker_count_is_shared_by_clones() {
+        let workers = GlobalWorkerCount::default();
+        let instance_a = workers.clone();
+        let instance_b = workers.clone();
+        let first = instance_a.track();
+        let second = instance_b.track();
+        assert_eq!(workers.load(), 2);
+        drop(first);
+        assert_eq!(workers.load(), 1);
+        drop(second);
+        assert_eq!(workers.load(), 0);
+    }
+}
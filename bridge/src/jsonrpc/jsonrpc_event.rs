@@ -62,6 +62,7 @@ pub struct JsonRpcEvent {
     #[serde(default = "default_version")]
     pub jsonrpc: String,
     pub method: String, // We'll parse this as string and convert to StratumMethod when needed
+    #[serde(default, deserialize_with = "null_as_empty_params")]
     pub params: Vec<Value>,
 }
 
@@ -69,6 +70,14 @@ fn default_version() -> String {
     "2.0".to_string()
 }
 
+/// `"params": null` (and a missing `params`) is an empty list, so the handler can answer it.
+fn null_as_empty_params<'de, D>(deserializer: D) -> Result<Vec<Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<Vec<Value>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
 impl JsonRpcEvent {
     pub fn new(id: Option<String>, method: &str, params: Vec<Value>) -> Self {
         Self {
@@ -113,6 +122,15 @@ impl JsonRpcResponse {
         }
     }
 
+    /// Error response from a standard [`StratumError`](crate::stratum_error::StratumError).
+    pub fn stratum_error(id: Option<Value>, err: crate::stratum_error::StratumError) -> Self {
+        Self {
+            id,
+            result: None,
+            error: Some(err.to_error_array()),
+        }
+    }
+
     pub fn error(id: Option<Value>, code: i32, message: &str, data: Option<Value>) -> Self {
         let mut error_vec = vec![
             Value::Number(code.into()),
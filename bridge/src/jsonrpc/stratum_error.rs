@@ -0,0 +1,122 @@
+//! Standard Stratum error codes, serialized as `[code, message, custom_message | null]`.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StratumError {
+    /// 20: anything without a more specific code (bad share, malformed request, ...).
+    UnknownError { custom_message: Option<String> },
+    /// 21: stale share; the job is no longer stored.
+    JobNotFound { custom_message: Option<String> },
+    /// 22
+    DuplicateShare { custom_message: Option<String> },
+    /// 23
+    LowDifficultyShare { custom_message: Option<String> },
+    /// 24: rejected `mining.authorize` (bad address, wrong password).
+    Unauthorized { custom_message: Option<String> },
+    /// 25
+    NotSubscribed { custom_message: Option<String> },
+}
+
+impl StratumError {
+    pub fn unknown(custom_message: impl Into<String>) -> Self {
+        StratumError::UnknownError {
+            custom_message: Some(custom_message.into()),
+        }
+    }
+
+    pub fn unauthorized(custom_message: impl Into<String>) -> Self {
+        StratumError::Unauthorized {
+            custom_message: Some(custom_message.into()),
+        }
+    }
+
+    pub fn code(&self) -> i32 {
+        match self {
+            StratumError::UnknownError { .. } => 20,
+            StratumError::JobNotFound { .. } => 21,
+            StratumError::DuplicateShare { .. } => 22,
+            StratumError::LowDifficultyShare { .. } => 23,
+            StratumError::Unauthorized { .. } => 24,
+            StratumError::NotSubscribed { .. } => 25,
+        }
+    }
+
+    /// Standard text for the second array slot.
+    pub fn message(&self) -> &'static str {
+        match self {
+            StratumError::UnknownError { .. } => "Unknown problem",
+            StratumError::JobNotFound { .. } => "Job not found",
+            StratumError::DuplicateShare { .. } => "Duplicate share submitted",
+            StratumError::LowDifficultyShare { .. } => "Invalid difficulty",
+            StratumError::Unauthorized { .. } => "Unauthorized worker",
+            StratumError::NotSubscribed { .. } => "Not subscribed",
+        }
+    }
+
+    /// Pool-specific diagnostic text for the third array slot.
+    pub fn custom_message(&self) -> Option<&str> {
+        match self {
+            StratumError::UnknownError { custom_message }
+            | StratumError::JobNotFound { custom_message }
+            | StratumError::DuplicateShare { custom_message }
+            | StratumError::LowDifficultyShare { custom_message }
+            | StratumError::Unauthorized { custom_message }
+            | StratumError::NotSubscribed { custom_message } => custom_message.as_deref(),
+        }
+    }
+
+    /// The `error` array of a JSON-RPC response.
+    pub fn to_error_array(&self) -> Vec<Value> {
+        vec![
+            Value::Number(self.code().into()),
+            Value::String(self.message().to_string()),
+            self.custom_message()
+                .map_or(Value::Null, |m| Value::String(m.to_string())),
+        ]
+    }
+}
+
+impl std::fmt::Display for StratumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.custom_message() {
+            Some(custom) => write!(f, "{} ({}): {}", self.message(), self.code(), custom),
+            None => write!(f, "{} ({})", self.message(), self.code()),
+        }
+    }
+}
+
+impl From<StratumError> for Value {
+    fn from(err: StratumError) -> Self {
+        Value::Array(err.to_error_array())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_code_message_and_custom_slot() {
+        let v: Value = StratumError::JobNotFound {
+            custom_message: None,
+        }
+        .into();
+        assert_eq!(v, json!([21, "Job not found", null]));
+
+        let v: Value = StratumError::unauthorized("Invalid Kaspa address format").into();
+        assert_eq!(
+            v,
+            json!([24, "Unauthorized worker", "Invalid Kaspa address format"])
+        );
+
+        assert_eq!(
+            StratumError::NotSubscribed {
+                custom_message: None
+            }
+            .code(),
+            25
+        );
+    }
+}
@@ -18,7 +18,10 @@ use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
 use super::coinbase_tag::build_coinbase_tag_bytes;
+use super::dns_cache::{DEFAULT_DNS_CACHE_TTL, DnsCache, split_host_port};
 use super::node_status::NODE_STATUS;
+use super::reconnect::{ReconnectBackoff, ReconnectBackoffConfig};
+use super::socks_proxy::{Socks5ProxyConfig, spawn_socks5_forwarder};
 
 mod block_submit_guard;
 mod streams;
@@ -26,6 +29,37 @@ mod template_submit;
 
 const MIN_MINING_READY_STABLE: Duration = Duration::from_secs(2);
 const MINING_READY_STABLE_POLL: Duration = Duration::from_millis(400);
+/// Distinct wallets whose last block template is kept for serving miners while kaspad is unreachable.
+const LAST_TEMPLATE_CACHE_CAPACITY: usize = 256;
+/// How often the connection-status poller checks the gRPC client.
+const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Default for `template_cache_ttl_ms`.
+pub const DEFAULT_TEMPLATE_CACHE_TTL: Duration = Duration::from_millis(100);
+
+/// Connection settings for [`KaspaApi::new_with_options`].
+#[derive(Debug, Clone)]
+pub struct KaspaConnectOptions {
+    /// Delays between attempts to connect and subscribe to notifications at startup, and between
+    /// connection checks while kaspad is unreachable later on.
+    pub reconnect_backoff: ReconnectBackoffConfig,
+    /// Reach kaspad through this SOCKS5 proxy.
+    pub proxy: Option<Socks5ProxyConfig>,
+    /// How long a resolved kaspad hostname is reused before it is looked up again.
+    pub dns_cache_ttl: Duration,
+    /// A wallet's block template younger than this is reused instead of asking kaspad again.
+    pub template_cache_ttl: Duration,
+}
+
+impl Default for KaspaConnectOptions {
+    fn default() -> Self {
+        Self {
+            reconnect_backoff: ReconnectBackoffConfig::default(),
+            proxy: None,
+            dns_cache_ttl: DEFAULT_DNS_CACHE_TTL,
+            template_cache_ttl: DEFAULT_TEMPLATE_CACHE_TTL,
+        }
+    }
+}
 
 /// Kaspa API client wrapper using RPC client
 /// Both use gRPC under the hood, but through an RPC client wrapper abstraction
@@ -34,20 +68,63 @@ pub struct KaspaApi {
     pub(crate) notification_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<Notification>>>>,
     pub(crate) connected: Arc<Mutex<bool>>,
     pub(crate) coinbase_tag: Vec<u8>,
+    /// Last good template per wallet and when it was fetched. Returned by `get_block_template` while
+    /// kaspad is unreachable, or while younger than `template_cache_ttl`.
+    pub(crate) last_templates: Arc<Mutex<lru::LruCache<String, (Block, Instant)>>>,
+    pub(crate) template_cache_ttl: Duration,
+    /// Paces the connection-status poller's checks during an outage.
+    pub(crate) reconnect_backoff: ReconnectBackoffConfig,
+    /// Called by the connection-status poller each time kaspad comes back after an outage.
+    pub(crate) reconnect_handlers: Arc<Mutex<Vec<ReconnectHandler>>>,
 }
 
+/// Callback registered with [`KaspaApi::set_reconnect_handler`].
+pub type ReconnectHandler = Arc<dyn Fn() + Send + Sync>;
+
 impl KaspaApi {
     /// Create a new Kaspa API client
     pub async fn new(
+        address: String,
+        coinbase_tag_suffix: Option<String>,
+        shutdown_rx: watch::Receiver<bool>,
+    ) -> Result<Arc<Self>> {
+        Self::new_with_options(
+            address,
+            coinbase_tag_suffix,
+            shutdown_rx,
+            KaspaConnectOptions::default(),
+        )
+        .await
+    }
+
+    /// Like [`KaspaApi::new`], with custom reconnect delays and an optional SOCKS5 proxy.
+    pub async fn new_with_options(
         address: String,
         coinbase_tag_suffix: Option<String>,
         mut shutdown_rx: watch::Receiver<bool>,
+        options: KaspaConnectOptions,
     ) -> Result<Arc<Self>> {
+        let reconnect_backoff = options.reconnect_backoff;
+        let dns_cache_ttl = options.dns_cache_ttl;
+        let template_cache_ttl = options.template_cache_ttl;
         info!("Connecting to Kaspa node at {}", address);
 
         // GrpcClient requires explicit "grpc://" prefix for connection
         // Always add it if not present (avoids unnecessary connection failure)
-        let grpc_address = if address.starts_with("grpc://") {
+        // Hostnames are resolved through the DNS cache on every attempt; the proxy resolves for itself
+        let dns_target = split_host_port(&address).filter(|(host, _)| {
+            options.proxy.is_none() && host.parse::<std::net::IpAddr>().is_err()
+        });
+        let grpc_address = if let Some(proxy) = options.proxy {
+            // The gRPC client dials a loopback forwarder that tunnels each connection through the proxy
+            let target = address.trim_start_matches("grpc://").to_string();
+            info!(
+                "Routing Kaspa node connection through SOCKS5 proxy {}",
+                proxy.proxy_addr
+            );
+            let local = spawn_socks5_forwarder(proxy, target, shutdown_rx.clone()).await?;
+            format!("grpc://{}", local)
+        } else if address.starts_with("grpc://") {
             address.clone()
         } else {
             format!("grpc://{}", address)
@@ -73,13 +150,25 @@ impl KaspaApi {
         );
 
         let mut attempt: u64 = 0;
-        let mut backoff_ms: u64 = 250;
+        let mut backoff = ReconnectBackoff::new(reconnect_backoff);
 
         let client = loop {
             attempt += 1;
+            let dial_address = match &dns_target {
+                Some((host, port)) => {
+                    match DnsCache::global().resolve(host, *port, dns_cache_ttl).await {
+                        Ok(addr) => format!("grpc://{}", addr),
+                        Err(e) => {
+                            debug!("DNS lookup for {} failed: {}", host, e);
+                            grpc_address.clone()
+                        }
+                    }
+                }
+                None => grpc_address.clone(),
+            };
             let connect_fut = GrpcClient::connect_with_args(
                 NotificationMode::Direct,
-                grpc_address.clone(),
+                dial_address,
                 None,
                 true,
                 None,
@@ -98,23 +187,21 @@ impl KaspaApi {
             match res {
                 Ok(client) => break Arc::new(client),
                 Err(e) => {
-                    let backoff = Duration::from_millis(backoff_ms);
+                    let delay = backoff.next_delay();
                     warn!(
                         "failed to connect to kaspa node at {} (attempt {}): {}, retrying in {:.2}s",
                         grpc_address,
                         attempt,
                         e,
-                        backoff.as_secs_f64()
+                        delay.as_secs_f64()
                     );
 
                     tokio::select! {
                         _ = shutdown_rx.wait_for(|v| *v) => {
                             return Err(anyhow::anyhow!("shutdown requested"));
                         }
-                        _ = sleep(backoff) => {}
+                        _ = sleep(delay) => {}
                     }
-
-                    backoff_ms = (backoff_ms.saturating_mul(2)).min(5_000);
                 }
             }
         };
@@ -146,7 +233,7 @@ impl KaspaApi {
         // This retry logic with exponential backoff handles transient failures where nodes are not
         // immediately ready to accept subscriptions after connection, preventing tight-looping and log spam.
         let mut attempt: u64 = 0;
-        let mut backoff_ms: u64 = 250;
+        backoff.reset();
         loop {
             attempt += 1;
             let notify_fut =
@@ -162,21 +249,20 @@ impl KaspaApi {
             match res {
                 Ok(_) => break,
                 Err(e) => {
-                    let backoff = Duration::from_millis(backoff_ms);
+                    let delay = backoff.next_delay();
                     warn!(
                         "failed to subscribe to block template notifications (attempt {}): {}, retrying in {:.2}s",
                         attempt,
                         e,
-                        backoff.as_secs_f64()
+                        delay.as_secs_f64()
                     );
 
                     tokio::select! {
                         _ = shutdown_rx.wait_for(|v| *v) => {
                             return Err(anyhow::anyhow!("shutdown requested"));
                         }
-                        _ = sleep(backoff) => {}
+                        _ = sleep(delay) => {}
                     }
-                    backoff_ms = (backoff_ms.saturating_mul(2)).min(5_000);
                 }
             }
         }
@@ -201,6 +287,19 @@ impl KaspaApi {
             notification_rx,
             connected: Arc::new(Mutex::new(true)),
             coinbase_tag,
+            last_templates: Arc::new(Mutex::new(lru::LruCache::new(
+                std::num::NonZeroUsize::new(LAST_TEMPLATE_CACHE_CAPACITY).unwrap(),
+            ))),
+            template_cache_ttl,
+            reconnect_backoff,
+            reconnect_handlers: Arc::new(Mutex::new(Vec::new())),
+        });
+
+        // Track kaspad outages (the gRPC client reconnects on its own; miners keep the last template)
+        let api_clone = Arc::clone(&api);
+        let monitor_shutdown_rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            api_clone.poll_connection_status(monitor_shutdown_rx).await;
         });
 
         // Start network stats thread
@@ -381,9 +480,25 @@ impl KaspaApi {
         loop {
             interval.tick().await;
             self.refresh_node_status_snapshot().await;
+            let rtt = self.ping().await.ok();
+            NODE_STATUS.lock().rtt_ms = rtt.map(|d| d.as_secs_f64() * 1000.0);
         }
     }
 
+    /// Measure the kaspad round-trip time with a `getInfo` call, without fetching a block template.
+    /// The node status poller calls this every 10s for `/healthz/ready` (`kaspad_rtt_ms`) and
+    /// `ks_kaspad_ping_latency_milliseconds`.
+    pub async fn ping(&self) -> Result<Duration> {
+        let start = Instant::now();
+        self.client
+            .get_info_call(None, GetInfoRequest {})
+            .await
+            .map_err(|e| anyhow::anyhow!("kaspad ping failed: {}", e))?;
+        let rtt = start.elapsed();
+        crate::prom::record_kaspad_ping_latency(rtt);
+        Ok(rtt)
+    }
+
     /// Mining-safe sync: node's `getSyncStatus` (sink recent + not in transitional IBD), no active
     /// P2P IBD peer (`getConnectedPeerInfo`: `is_ibd_peer`), and `getBlockDagInfo` **block/header
     /// parity** (`block_count == header_count`). Headers can run ahead of bodies during catch-up; the
@@ -517,13 +632,14 @@ impl KaspaApi {
     }
 
     /// Block template notifications plus ticker fallback (implementation in `streams` submodule).
+    /// `block_cb(true)` marks a new block, `block_cb(false)` a ticker refresh.
     pub async fn start_block_template_listener<F>(
         self: Arc<Self>,
         block_wait_time: Duration,
         block_cb: F,
     ) -> Result<()>
     where
-        F: FnMut() + Send + 'static,
+        F: FnMut(bool) + Send + 'static,
     {
         streams::start_block_template_listener(self, block_wait_time, block_cb).await
     }
@@ -536,7 +652,7 @@ impl KaspaApi {
         block_cb: F,
     ) -> Result<()>
     where
-        F: FnMut() + Send + 'static,
+        F: FnMut(bool) + Send + 'static,
     {
         streams::start_block_template_listener_with_shutdown(
             self,
@@ -547,10 +663,60 @@ impl KaspaApi {
         .await
     }
 
-    /// Check if connected
+    /// Register `handler` to run whenever the connection to kaspad is restored. Handlers run on the
+    /// connection-status poller task, so anything slow should be spawned.
+    pub fn set_reconnect_handler(&self, handler: ReconnectHandler) {
+        self.reconnect_handlers.lock().push(handler);
+    }
+
+    /// Last connection state seen by the connection-status poller; does not touch the network.
     pub fn is_connected(&self) -> bool {
         *self.connected.lock()
     }
+
+    /// Poll the gRPC client's connection state every [`CONNECTION_CHECK_INTERVAL`] while connected.
+    /// During an outage each failed check is one reconnect attempt, spaced by [`ReconnectBackoff`]
+    /// (`kaspad_reconnect_*`); the backoff resets and the reconnect handlers run once kaspad is back.
+    async fn poll_connection_status(&self, mut shutdown_rx: watch::Receiver<bool>) {
+        let mut backoff = ReconnectBackoff::new(self.reconnect_backoff);
+        let mut lost_at: Option<Instant> = None;
+        let mut attempt: u64 = 0;
+        loop {
+            let connected = self.client.is_connected();
+            *self.connected.lock() = connected;
+            let wait = if connected {
+                if let Some(since) = lost_at.take() {
+                    info!(
+                        "reconnected to kaspa node after {:.1}s ({} attempt(s))",
+                        since.elapsed().as_secs_f64(),
+                        attempt
+                    );
+                    attempt = 0;
+                    backoff.reset();
+                    let handlers = self.reconnect_handlers.lock().clone();
+                    for handler in handlers {
+                        handler();
+                    }
+                }
+                CONNECTION_CHECK_INTERVAL
+            } else {
+                lost_at.get_or_insert_with(Instant::now);
+                attempt += 1;
+                let delay = backoff.next_delay();
+                warn!(
+                    "kaspa node connection lost, reconnect attempt {} (next check in {:.2}s); miners keep their last template",
+                    attempt,
+                    delay.as_secs_f64()
+                );
+                delay
+            };
+
+            tokio::select! {
+                _ = shutdown_rx.wait_for(|v| *v) => return,
+                _ = sleep(wait) => {}
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
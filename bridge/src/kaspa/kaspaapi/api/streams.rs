@@ -46,7 +46,8 @@ async fn block_until_synced_or_shutdown(
 
 /// Start listening for block template notifications
 /// Uses RegisterForNewBlockTemplateNotifications with ticker fallback
-/// This provides immediate notifications when new blocks are available, with polling as fallback
+/// This provides immediate notifications when new blocks are available, with polling as fallback.
+/// `block_cb` receives `true` for a new-block notification and `false` for a ticker refresh.
 ///
 /// **Sync safety:** templates are only dispatched while the node is mining-ready (same as
 /// [`KaspaApi::is_node_synced_for_mining`](crate::kaspaapi::KaspaApi::is_node_synced_for_mining)). If sync is lost or P2P IBD resumes, we stop calling the callback.
@@ -56,7 +57,7 @@ pub(super) async fn start_block_template_listener<F>(
     mut block_cb: F,
 ) -> Result<()>
 where
-    F: FnMut() + Send + 'static,
+    F: FnMut(bool) + Send + 'static,
 {
     let mut rx = api
         .notification_rx
@@ -110,7 +111,7 @@ where
                             break 'inner;
                         }
 
-                        block_cb();
+                        block_cb(true);
                         ticker = tokio::time::interval(block_wait_time);
                         ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
                     }
@@ -127,7 +128,7 @@ where
                             break 'inner;
                         }
 
-                        block_cb();
+                        block_cb(false);
                     }
                 }
             }
@@ -144,7 +145,7 @@ pub(super) async fn start_block_template_listener_with_shutdown<F>(
     mut block_cb: F,
 ) -> Result<()>
 where
-    F: FnMut() + Send + 'static,
+    F: FnMut(bool) + Send + 'static,
 {
     let mut rx = api
         .notification_rx
@@ -213,7 +214,7 @@ where
                             break 'inner;
                         }
 
-                        block_cb();
+                        block_cb(true);
                         ticker = tokio::time::interval(block_wait_time);
                         ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
                     }
@@ -234,7 +235,7 @@ where
                             break 'inner;
                         }
 
-                        block_cb();
+                        block_cb(false);
                     }
                 }
             }
@@ -372,6 +372,25 @@ impl KaspaApi {
         _remote_app: &str,
         _canxium_addr: &str,
     ) -> Result<Block> {
+        // kaspad unreachable: keep miners busy on the last template rather than leaving them idle
+        if !self.client.is_connected()
+            && let Some((block, _)) = self.last_templates.lock().get(wallet_addr).cloned()
+        {
+            debug!(
+                "kaspa node disconnected, serving last known block template for {}",
+                wallet_addr
+            );
+            return Ok(block);
+        }
+
+        // Absorb bursts (e.g. every instance refreshing after a reconnect) with a short-lived cache
+        if let Some((block, fetched_at)) = self.last_templates.lock().get(wallet_addr)
+            && fetched_at.elapsed() < self.template_cache_ttl
+        {
+            crate::prom::record_template_cache_hit();
+            return Ok(block.clone());
+        }
+
         if !self.is_node_synced_for_mining().await {
             return Err(anyhow::anyhow!(
                 "refusing block template: node not mining-ready (sync, P2P IBD, or DAG block/header count mismatch)"
@@ -433,6 +452,9 @@ impl KaspaApi {
 
                     match serialize_result {
                         Ok(_) => {
+                            self.last_templates
+                                .lock()
+                                .put(wallet_addr.to_string(), (block.clone(), Instant::now()));
                             return Ok(block);
                         }
                         Err(error_str) => {
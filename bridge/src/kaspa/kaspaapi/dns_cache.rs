@@ -0,0 +1,136 @@
+//! Process-wide cache of kaspad hostname lookups, so reconnects skip DNS while an entry is fresh.
+//!
+//! Expired entries are re-resolved; when that lookup fails the last known address is used.
+
+use crate::prom::record_dns_lookup;
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Default for `dns_cache_ttl_secs` when the config leaves it unset.
+pub const DEFAULT_DNS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Hostname → (address, resolved at); shared by every instance through [`DnsCache::global`].
+#[derive(Default)]
+pub struct DnsCache {
+    entries: Mutex<HashMap<String, (IpAddr, Instant)>>,
+}
+
+static DNS_CACHE: OnceLock<DnsCache> = OnceLock::new();
+
+impl DnsCache {
+    pub fn global() -> &'static DnsCache {
+        DNS_CACHE.get_or_init(DnsCache::default)
+    }
+
+    /// Resolve `host:port`, serving the cached address while it is younger than `ttl`.
+    pub async fn resolve(&self, host: &str, port: u16, ttl: Duration) -> Result<SocketAddr> {
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(SocketAddr::new(ip, port));
+        }
+
+        let cached = self.entries.lock().get(host).copied();
+        match cached {
+            Some((ip, resolved_at)) if resolved_at.elapsed() < ttl => {
+                record_dns_lookup("hit");
+                Ok(SocketAddr::new(ip, port))
+            }
+            Some((ip, _)) => {
+                record_dns_lookup("refresh");
+                match self.lookup(host, port).await {
+                    Ok(addr) => Ok(addr),
+                    Err(e) => {
+                        warn!(
+                            "re-resolving {} failed ({}), using cached address {}",
+                            host, e, ip
+                        );
+                        Ok(SocketAddr::new(ip, port))
+                    }
+                }
+            }
+            None => {
+                record_dns_lookup("miss");
+                self.lookup(host, port).await
+            }
+        }
+    }
+
+    async fn lookup(&self, host: &str, port: u16) -> Result<SocketAddr> {
+        let addr = tokio::net::lookup_host((host, port))
+            .await?
+            .next()
+            .with_context(|| format!("no address resolved for {host}"))?;
+        debug!("resolved {} to {}", host, addr.ip());
+        self.insert(host, addr.ip(), Instant::now());
+        Ok(addr)
+    }
+
+    fn insert(&self, host: &str, ip: IpAddr, resolved_at: Instant) {
+        self.entries
+            .lock()
+            .insert(host.to_string(), (ip, resolved_at));
+    }
+}
+
+/// Split a kaspad address (`host:port`, `[v6]:port`, optionally `grpc://`-prefixed) into host and port.
+pub fn split_host_port(address: &str) -> Option<(String, u16)> {
+    let rest = address.trim_start_matches("grpc://");
+    let (host, port) = rest.rsplit_once(':')?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_string(), port.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_kaspad_addresses() {
+        assert_eq!(
+            split_host_port("grpc://node.example:16110"),
+            Some(("node.example".to_string(), 16110))
+        );
+        assert_eq!(
+            split_host_port("[::1]:16110"),
+            Some(("::1".to_string(), 16110))
+        );
+        assert_eq!(split_host_port("node.example"), None);
+    }
+
+    #[tokio::test]
+    async fn serves_fresh_entries_and_falls_back_to_stale_ones() {
+        let cache = DnsCache::default();
+        let ip: IpAddr = "10.1.2.3".parse().unwrap();
+        // `.invalid` never resolves (RFC 6761), so any answer must come from the cache
+        cache.insert("kaspad.invalid", ip, Instant::now());
+        let addr = cache
+            .resolve("kaspad.invalid", 16110, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(addr, SocketAddr::new(ip, 16110));
+
+        let addr = cache
+            .resolve("kaspad.invalid", 16110, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(
+            addr,
+            SocketAddr::new(ip, 16110),
+            "failed refresh keeps the old address"
+        );
+
+        assert!(
+            cache
+                .resolve("other.invalid", 16110, Duration::from_secs(60))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,12 +1,21 @@
 //! Kaspa gRPC client (`KaspaApi`), node status snapshot for dashboards, and coinbase tag helpers.
 //!
-//! Split across `coinbase_tag`, `node_status`, and `api` (`block_submit_guard`, `streams`, `template_submit`).
+//! Split across `coinbase_tag`, `dns_cache`, `node_status`, `reconnect`, `socks_proxy`, and `api` (`block_submit_guard`, `streams`, `template_submit`).
 
 mod api;
 mod coinbase_tag;
+mod dns_cache;
 mod node_status;
+mod reconnect;
+mod socks_proxy;
 
-pub use api::KaspaApi;
+pub use api::{DEFAULT_TEMPLATE_CACHE_TTL, KaspaApi, KaspaConnectOptions, ReconnectHandler};
+pub use dns_cache::{DEFAULT_DNS_CACHE_TTL, DnsCache, split_host_port};
 pub use node_status::{
     NODE_STATUS, NodeStatusApi, NodeStatusSnapshot, network_display_from_id, node_status_for_api,
 };
+pub use reconnect::{
+    DEFAULT_RECONNECT_BASE_DELAY, DEFAULT_RECONNECT_MAX_DELAY, ReconnectBackoff,
+    ReconnectBackoffConfig,
+};
+pub use socks_proxy::{Socks5ProxyConfig, expand_env_vars, spawn_socks5_forwarder};
@@ -19,6 +19,8 @@ pub struct NodeStatusSnapshot {
     pub tip_hash: Option<String>,
     pub peers: Option<usize>,
     pub mempool_size: Option<u64>,
+    /// Round-trip time of the last [`KaspaApi::ping`](super::KaspaApi::ping); `None` if it failed.
+    pub rtt_ms: Option<f64>,
 }
 
 pub static NODE_STATUS: Lazy<Mutex<NodeStatusSnapshot>> =
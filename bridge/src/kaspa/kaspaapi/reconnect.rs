@@ -0,0 +1,94 @@
+//! Exponential backoff (with optional ±20% jitter) between kaspad connection attempts.
+
+use rand::Rng;
+use std::time::Duration;
+
+pub const DEFAULT_RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+pub const DEFAULT_RECONNECT_MAX_DELAY: Duration = Duration::from_millis(30_000);
+
+/// Fraction of the delay added or removed at random when jitter is on.
+const JITTER_FRACTION: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectBackoffConfig {
+    /// Delay after the first failure.
+    pub base_delay: Duration,
+    /// Upper bound for the doubled delay (jitter included).
+    pub max_delay: Duration,
+    /// Spread delays by ±20% so bridges restarted together do not retry in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for ReconnectBackoffConfig {
+    fn default() -> Self {
+        Self {
+            base_delay: DEFAULT_RECONNECT_BASE_DELAY,
+            max_delay: DEFAULT_RECONNECT_MAX_DELAY,
+            jitter: true,
+        }
+    }
+}
+
+/// Delay sequence `base, 2*base, 4*base, ...` capped at `max_delay`; [`reset`](Self::reset) after a success.
+#[derive(Debug, Clone)]
+pub struct ReconnectBackoff {
+    config: ReconnectBackoffConfig,
+    current: Duration,
+}
+
+impl ReconnectBackoff {
+    pub fn new(config: ReconnectBackoffConfig) -> Self {
+        Self {
+            current: config.base_delay.min(config.max_delay),
+            config,
+        }
+    }
+
+    /// Delay before the next attempt; doubles the delay for the attempt after.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = self.current.saturating_mul(2).min(self.config.max_delay);
+        if self.config.jitter {
+            let factor =
+                rand::thread_rng().gen_range(1.0 - JITTER_FRACTION..=1.0 + JITTER_FRACTION);
+            delay.mul_f64(factor).min(self.config.max_delay)
+        } else {
+            delay
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.config.base_delay.min(self.config.max_delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles_caps_and_resets() {
+        let mut backoff = ReconnectBackoff::new(ReconnectBackoffConfig {
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_millis(3_000),
+            jitter: false,
+        });
+        let delays: Vec<u64> = (0..5)
+            .map(|_| backoff.next_delay().as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![500, 1_000, 2_000, 3_000, 3_000]);
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn jitter_stays_within_twenty_percent() {
+        let mut backoff = ReconnectBackoff::new(ReconnectBackoffConfig {
+            base_delay: Duration::from_millis(1_000),
+            max_delay: Duration::from_millis(30_000),
+            jitter: true,
+        });
+        let delay = backoff.next_delay();
+        assert!((Duration::from_millis(800)..=Duration::from_millis(1_200)).contains(&delay));
+    }
+}
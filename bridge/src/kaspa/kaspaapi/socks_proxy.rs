@@ -0,0 +1,234 @@
+//! SOCKS5 tunnel for the kaspad gRPC connection.
+//!
+//! The gRPC client only dials plain TCP, so a loopback forwarder accepts its connections and opens
+//! each one to kaspad through the proxy (`tokio-socks` handshake) before relaying bytes.
+
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio_socks::tcp::Socks5Stream;
+use tracing::{debug, warn};
+
+/// Parsed `socks5://[user:pass@]host:port`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Socks5ProxyConfig {
+    /// `host:port` of the proxy.
+    pub proxy_addr: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl std::fmt::Debug for Socks5ProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Socks5ProxyConfig")
+            .field("proxy_addr", &self.proxy_addr)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+/// Replace every `${NAME}` with the value of environment variable `NAME` (unset variables are an error).
+pub fn expand_env_vars(raw: &str) -> Result<String> {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .with_context(|| format!("unterminated '${{' in '{raw}'"))?;
+        let name = &after[..end];
+        let value = std::env::var(name)
+            .with_context(|| format!("environment variable {name} is not set"))?;
+        out.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+impl Socks5ProxyConfig {
+    /// Parse a `kaspad_proxy` value after `${VAR}` expansion (so Docker secrets can be injected).
+    pub fn parse(raw: &str) -> Result<Self> {
+        let expanded = expand_env_vars(raw.trim())?;
+        let rest = expanded
+            .strip_prefix("socks5://")
+            .or_else(|| expanded.strip_prefix("socks5h://"))
+            .context("kaspad_proxy must start with socks5://")?;
+        let (credentials, host) = match rest.rsplit_once('@') {
+            Some((credentials, host)) => (Some(credentials), host),
+            None => (None, rest),
+        };
+        let host = host.trim_end_matches('/');
+        if host.is_empty() || !host.contains(':') {
+            anyhow::bail!("kaspad_proxy must include host:port");
+        }
+        let (username, password) = match credentials {
+            Some(credentials) => {
+                let (user, pass) = credentials
+                    .split_once(':')
+                    .context("kaspad_proxy credentials must be user:pass")?;
+                (Some(user.to_string()), Some(pass.to_string()))
+            }
+            None => (None, None),
+        };
+        Ok(Self {
+            proxy_addr: host.to_string(),
+            username,
+            password,
+        })
+    }
+
+    async fn connect(&self, target: &str) -> Result<TcpStream> {
+        let stream = match (&self.username, &self.password) {
+            (Some(user), Some(pass)) => {
+                Socks5Stream::connect_with_password(self.proxy_addr.as_str(), target, user, pass)
+                    .await?
+            }
+            _ => Socks5Stream::connect(self.proxy_addr.as_str(), target).await?,
+        };
+        Ok(stream.into_inner())
+    }
+}
+
+/// Start a loopback listener whose connections are tunnelled to `target` (`host:port`) through `proxy`.
+/// Returns the local address to hand to the gRPC client.
+pub async fn spawn_socks5_forwarder(
+    proxy: Socks5ProxyConfig,
+    target: String,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let local_addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        loop {
+            let (mut inbound, _) = tokio::select! {
+                _ = shutdown_rx.wait_for(|v| *v) => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        warn!("SOCKS5 forwarder accept failed: {}", e);
+                        continue;
+                    }
+                },
+            };
+            let proxy = proxy.clone();
+            let target = target.clone();
+            tokio::spawn(async move {
+                match proxy.connect(&target).await {
+                    Ok(mut outbound) => {
+                        debug!("SOCKS5 tunnel to {} via {} open", target, proxy.proxy_addr);
+                        let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                    }
+                    Err(e) => warn!(
+                        "SOCKS5 proxy {} could not reach {}: {}",
+                        proxy.proxy_addr, target, e
+                    ),
+                }
+            });
+        }
+    });
+    Ok(local_addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn parses_proxy_urls_with_env_substitution() {
+        let p = Socks5ProxyConfig::parse("socks5://proxy.local:1080").unwrap();
+        assert_eq!(p.proxy_addr, "proxy.local:1080");
+        assert_eq!(p.username, None);
+
+        // SAFETY: test-only env change scoped to this process; no concurrent mutation expected.
+        unsafe {
+            std::env::set_var("RKSTRATUM_TEST_SOCKS5_PROXY", "socks5://u:p@10.0.0.1:9050");
+        }
+        let p = Socks5ProxyConfig::parse("${RKSTRATUM_TEST_SOCKS5_PROXY}").unwrap();
+        assert_eq!(p.proxy_addr, "10.0.0.1:9050");
+        assert_eq!(p.username.as_deref(), Some("u"));
+        assert_eq!(p.password.as_deref(), Some("p"));
+
+        assert!(Socks5ProxyConfig::parse("http://proxy:8080").is_err());
+        assert!(Socks5ProxyConfig::parse("${RKSTRATUM_TEST_SOCKS5_UNSET}").is_err());
+    }
+
+    /// Minimal SOCKS5 server: username/password auth, CONNECT by domain, then echo.
+    async fn mock_socks5_server(listener: TcpListener) -> (String, String, u16) {
+        let (mut s, _) = listener.accept().await.unwrap();
+        let mut head = [0u8; 2];
+        s.read_exact(&mut head).await.unwrap();
+        assert_eq!(head[0], 5, "SOCKS version");
+        let mut methods = vec![0u8; head[1] as usize];
+        s.read_exact(&mut methods).await.unwrap();
+        assert!(methods.contains(&2), "offers username/password auth");
+        s.write_all(&[5, 2]).await.unwrap();
+
+        let mut ver_ulen = [0u8; 2];
+        s.read_exact(&mut ver_ulen).await.unwrap();
+        let mut user = vec![0u8; ver_ulen[1] as usize];
+        s.read_exact(&mut user).await.unwrap();
+        let mut plen = [0u8; 1];
+        s.read_exact(&mut plen).await.unwrap();
+        let mut pass = vec![0u8; plen[0] as usize];
+        s.read_exact(&mut pass).await.unwrap();
+        s.write_all(&[1, 0]).await.unwrap();
+
+        let mut req = [0u8; 4];
+        s.read_exact(&mut req).await.unwrap();
+        assert_eq!(&req[..2], &[5, 1], "CONNECT command");
+        assert_eq!(req[3], 3, "target sent as a domain name");
+        let mut len = [0u8; 1];
+        s.read_exact(&mut len).await.unwrap();
+        let mut host = vec![0u8; len[0] as usize];
+        s.read_exact(&mut host).await.unwrap();
+        let mut port = [0u8; 2];
+        s.read_exact(&mut port).await.unwrap();
+        s.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0])
+            .await
+            .unwrap();
+
+        let mut buf = [0u8; 4];
+        s.read_exact(&mut buf).await.unwrap();
+        s.write_all(&buf).await.unwrap();
+
+        (
+            format!(
+                "{}:{}",
+                String::from_utf8(user).unwrap(),
+                String::from_utf8(pass).unwrap()
+            ),
+            String::from_utf8(host).unwrap(),
+            u16::from_be_bytes(port),
+        )
+    }
+
+    #[tokio::test]
+    async fn forwarder_performs_socks5_handshake() {
+        let proxy_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_addr = proxy_listener.local_addr().unwrap();
+        let server = tokio::spawn(mock_socks5_server(proxy_listener));
+
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let proxy =
+            Socks5ProxyConfig::parse(&format!("socks5://miner:secret@{proxy_addr}")).unwrap();
+        let local = spawn_socks5_forwarder(proxy, "kaspad.internal:16110".to_string(), shutdown_rx)
+            .await
+            .unwrap();
+
+        let mut client = TcpStream::connect(local).await.unwrap();
+        client.write_all(b"grpc").await.unwrap();
+        let mut echoed = [0u8; 4];
+        client.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"grpc");
+
+        let (credentials, host, port) = server.await.unwrap();
+        assert_eq!(credentials, "miner:secret");
+        assert_eq!(host, "kaspad.internal");
+        assert_eq!(port, 16110);
+    }
+}
@@ -9,31 +9,47 @@
 
 mod util {
     pub mod errors;
+    pub mod ip_filter;
     pub mod log_colors;
     pub mod net_utils;
+    pub mod validation;
 }
 
 mod jsonrpc {
     pub mod jsonrpc_event;
+    pub mod stratum_error;
 }
 
 mod mining {
+    pub mod difficulty;
     pub mod hasher;
     pub mod mining_state;
     pub mod pow_diagnostic;
+    pub mod wallet_rotator;
 }
 
 mod stratum {
+    pub mod block_webhook;
     pub mod client_handler;
+    pub mod control;
     pub mod default_client;
+    pub mod miner_detect;
+    pub mod pool_events;
     pub mod stratum_context;
     pub mod stratum_line_codec;
     pub mod stratum_listener;
     pub mod stratum_server;
+    pub mod user_agent;
+    pub mod wallet_sessions;
+    pub mod worker_auth;
+    pub mod worker_credentials;
+    pub mod worker_registry;
 }
 
 mod config {
     pub mod app_config;
+    pub mod builder;
+    pub mod yaml_include;
 }
 
 mod kaspa {
@@ -53,30 +69,50 @@ mod cpu_miner {
 pub use config::app_config;
 pub use host::host_metrics;
 pub use jsonrpc::jsonrpc_event;
+pub use jsonrpc::stratum_error;
 pub use kaspa::kaspaapi;
+pub use mining::difficulty;
 pub use mining::hasher;
 pub use mining::mining_state;
 pub use mining::pow_diagnostic;
+pub use mining::wallet_rotator;
+pub use stratum::block_webhook;
 pub use stratum::client_handler;
+pub use stratum::control;
 pub use stratum::default_client;
+pub use stratum::miner_detect;
+pub use stratum::pool_events;
 pub use stratum::stratum_context;
 pub use stratum::stratum_line_codec;
 pub use stratum::stratum_listener;
 pub use stratum::stratum_server;
+pub use stratum::user_agent;
+pub use stratum::wallet_sessions;
+pub use stratum::worker_auth;
+pub use stratum::worker_credentials;
+pub use stratum::worker_registry;
 pub use util::errors;
+pub use util::ip_filter;
 pub use util::log_colors;
 pub use util::net_utils;
+pub use util::validation;
 
 pub mod prom;
 pub mod share_handler;
 
 pub mod app_dirs;
 pub mod cli;
+pub mod daemon;
 pub mod health_check;
 pub mod inprocess_node;
+pub mod log_rotation;
+pub mod log_suppress;
 pub mod runner;
+pub mod syslog_sink;
 pub mod tracing_setup;
 
+#[cfg(test)]
+mod test_support;
 #[cfg(test)]
 mod tests;
 
@@ -85,26 +121,45 @@ mod bridge_error;
 #[cfg(feature = "rkstratum_cpu_miner")]
 pub use cpu_miner::rkstratum_cpu_miner;
 
-pub use app_config::{BridgeConfig, InstanceConfig};
+pub use app_config::{
+    BridgeConfig, ConfigChange, ConfigError, EffectiveInstanceConfig, GlobalConfig, InstanceConfig,
+};
+pub use block_webhook::{BlockWebhook, WebhookFormat};
 pub use bridge_error::BridgeError;
-pub use client_handler::ClientHandler;
+pub use client_handler::{ClientHandler, JobTemplate};
+pub use config::builder::{GlobalConfigBuilder, InstanceConfigBuilder};
+pub use control::ControlMessage;
 pub use default_client::{default_handlers, default_logger};
+pub use difficulty::{MAX_SUGGESTED_SHARE_DIFF, target_hex_to_difficulty};
 pub use errors::ErrorShortCode;
 pub use hasher::{
     KaspaDiff, big_diff_to_little, calculate_target, diff_to_hash, diff_to_target,
     diff_to_target_alternative, generate_iceriver_job_params, generate_job_header,
-    generate_large_job_params, serialize_block_header, stratum_difficulty_to_target_kaspa,
+    generate_large_job_params, pow_value_to_diff, serialize_block_header,
+    stratum_difficulty_to_target_kaspa,
 };
 pub use jsonrpc_event::{
     JsonRpcEvent, JsonRpcResponse, StratumMethod, unmarshal_event, unmarshal_response,
 };
 pub use kaspaapi::{
-    KaspaApi, NODE_STATUS, NodeStatusApi, NodeStatusSnapshot, network_display_from_id,
+    DnsCache, KaspaApi, KaspaConnectOptions, NODE_STATUS, NodeStatusApi, NodeStatusSnapshot,
+    ReconnectBackoffConfig, ReconnectHandler, Socks5ProxyConfig, network_display_from_id,
     node_status_for_api,
 };
 pub use log_colors::LogColors;
+pub use miner_detect::{
+    DefaultMinerDetector, GoldshellMinerProfile, JobFormat, MinerDetector, MinerProfile,
+    SrbMinerProfile, SubscribeFormat, clear_miner_type_cache, detect_miner_type,
+    protocol_version_from_params, resolve_miner_profile, subscribe_format_for_protocol,
+};
 pub use mining_state::{GetMiningState, Job, MiningState};
-pub use net_utils::{bind_addr_for_operator_http, bind_addr_from_port, normalize_port};
+pub use net_utils::{
+    bind_addr_for_operator_http, bind_addr_from_port, normalize_port, parse_bind_address,
+};
+pub use pool_events::{
+    BlockFoundEvent, NoopPoolEventHandler, PoolEventHandler, ShareAcceptedEvent,
+    WorkerConnectedEvent, WorkerDisconnectedEvent, WorkerInfo,
+};
 pub use prom::{
     WorkerContext, init_metrics, init_worker_counters, record_balances,
     record_block_accepted_by_node, record_block_found, record_block_not_confirmed_blue,
@@ -118,26 +173,47 @@ pub use rkstratum_cpu_miner::{
     InternalCpuMinerConfig, InternalMinerMetrics, spawn_internal_cpu_miner,
 };
 pub use share_handler::{
-    KaspaApiTrait, STATS_PRINTER_STARTED, ShareHandler, SubmitError, SubmitRunError, WorkStats,
-    average_worker_spm,
+    DEFAULT_KASPAD_RTT_WARN_THRESHOLD_MS, DEFAULT_MAX_SUBMIT_CONCURRENCY,
+    DEFAULT_VAR_DIFF_EMERGENCY_THRESHOLD, DEFAULT_VAR_DIFF_STEP_DOWN, DEFAULT_VAR_DIFF_STEP_UP,
+    KaspaApiTrait, SHARE_QUEUE_CAPACITY, STATS_PRINTER_STARTED, ShareAuditLog, ShareAuditRecord,
+    ShareHandler, ShareQueue, SubmitError, SubmitRunError, VarDiffRecord, VarDiffState,
+    VarDiffStore, WorkStats, average_worker_spm,
 };
 #[cfg(feature = "rkstratum_cpu_miner")]
 pub use share_handler::{RKSTRATUM_CPU_MINER_METRICS, set_rkstratum_cpu_miner_metrics};
-pub use stratum_context::{ClientIdentity, ContextSummary, ErrorDisconnected, StratumContext};
+pub use stratum_context::{
+    ClientIdentity, ContextSummary, DEFAULT_MAX_SUBMITS_PER_SECOND, DEFAULT_NOTIFY_QUEUE_DEPTH,
+    ErrorDisconnected, MAX_NONCE_RANGE_BITS, NotifyQueue, SessionState, StratumContext,
+    StratumTransport, SubmitRateDecision, SubmitRateLimiter, SubmitRateWarning,
+};
+pub use stratum_error::StratumError;
 pub use stratum_line_codec::{
     MAX_STRATUM_LINE_BYTES, append_line_data, line_looks_like_http, push_lossy_and_drain_lines,
     strip_nul_bytes,
 };
 pub use stratum_listener::{
-    EventHandler, StateGenerator, StratumClientListener, StratumListener, StratumListenerConfig,
-    StratumStats,
+    AcceptFloodConfig, ConnectionFloodDetector, EventHandler, SessionAction, ShareRejectionReason,
+    StateGenerator, StratumClientListener, StratumListener, StratumListenerConfig,
+    StratumSessionError, StratumStats,
 };
 /// Per-instance stratum listener settings (distinct from `BridgeConfig` in `app_config`).
 pub use stratum_server::BridgeConfig as StratumServerBridgeConfig;
 pub use stratum_server::{
     listen_and_serve, listen_and_serve_with_shutdown, start_block_template_listener_with_api,
 };
+pub use user_agent::UserAgent;
+pub use validation::{DEFAULT_WORKER_NAME_MAX_LENGTH, WorkerNamePolicy, validate_kaspa_address};
+pub use wallet_rotator::WalletRotator;
+pub use wallet_sessions::{
+    DEFAULT_MAX_WORKERS_PER_WALLET, WalletLimitExceeded, WalletSessionGuard, WalletSessionLimiter,
+};
+pub use worker_auth::{AllowAllAuthenticator, AuthResult, HttpAuthenticator, WorkerAuthenticator};
+pub use worker_credentials::{CredentialRejection, WorkerCredentialStore};
+pub use worker_registry::{
+    ConnectedWorker, SessionId, SharedWorkerRegistry, WORKER_REGISTRY, WorkerFilter, WorkerRow,
+};
 
 pub use runner::{
     config_yaml_candidate_paths, default_dashboard_iframe_url, request_bridge_shutdown, run,
+    run_from_config_str, run_with_config,
 };
@@ -0,0 +1,166 @@
+//! Size-based rotation for the bridge log file (`log_max_file_bytes` / `log_max_files`). Each file is
+//! named `RKStratum_<unix seconds>.log` after the moment it was opened; once the current file would
+//! pass the size limit the appender opens a new one and deletes the oldest beyond the retention count.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default for `log_max_file_bytes` (100 MB).
+pub const DEFAULT_LOG_MAX_FILE_BYTES: u64 = 100 * 1024 * 1024;
+/// Default for `log_max_files`.
+pub const DEFAULT_LOG_MAX_FILES: u32 = 5;
+
+const LOG_FILE_PREFIX: &str = "RKStratum_";
+const LOG_FILE_SUFFIX: &str = ".log";
+
+/// `Write` target for the non-blocking file writer that starts a new file past `max_bytes` and keeps
+/// `max_files` previous files next to the current one.
+pub struct SizeRotatingAppender {
+    dir: PathBuf,
+    file: File,
+    path: PathBuf,
+    written: u64,
+    max_bytes: u64,
+    max_files: u32,
+}
+
+impl SizeRotatingAppender {
+    /// Open a new log file in `dir`, pruning old ones right away.
+    pub fn new(dir: impl Into<PathBuf>, max_bytes: u64, max_files: u32) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        let (file, path) = open_new_file(&dir)?;
+        let appender = Self {
+            dir,
+            file,
+            path,
+            written: 0,
+            max_bytes: max_bytes.max(1),
+            max_files,
+        };
+        appender.prune();
+        Ok(appender)
+    }
+
+    /// The file being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let _ = self.file.flush();
+        let (file, path) = open_new_file(&self.dir)?;
+        self.file = file;
+        self.path = path;
+        self.written = 0;
+        self.prune();
+        Ok(())
+    }
+
+    /// Delete the oldest log files until at most `max_files` remain besides the current one.
+    fn prune(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut old: Vec<(SystemTime, PathBuf)> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| *path != self.path && is_log_file(path))
+            .map(|path| {
+                let modified = std::fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .unwrap_or(UNIX_EPOCH);
+                (modified, path)
+            })
+            .collect();
+        if old.len() <= self.max_files as usize {
+            return;
+        }
+        old.sort();
+        let excess = old.len() - self.max_files as usize;
+        for (_, path) in old.into_iter().take(excess) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl Write for SizeRotatingAppender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            // A failed rotation keeps writing to the current file
+            if let Err(e) = self.rotate() {
+                eprintln!("Failed to rotate log file {}: {}", self.path.display(), e);
+                self.written = 0;
+            }
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn is_log_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX))
+}
+
+/// Create `RKStratum_<secs>.log`, or `RKStratum_<secs>_<n>.log` when that name is taken.
+fn open_new_file(dir: &Path) -> io::Result<(File, PathBuf)> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut n = 0u32;
+    loop {
+        let name = if n == 0 {
+            format!("{LOG_FILE_PREFIX}{secs}{LOG_FILE_SUFFIX}")
+        } else {
+            format!("{LOG_FILE_PREFIX}{secs}_{n}{LOG_FILE_SUFFIX}")
+        };
+        let path = dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_past_the_size_limit_and_keeps_max_files() {
+        let dir =
+            std::env::temp_dir().join(format!("rkstratum-log-rotation-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut appender = SizeRotatingAppender::new(&dir, 10, 2).unwrap();
+        let first = appender.path().to_path_buf();
+
+        // A line larger than the limit still goes into an empty file
+        appender.write_all(b"0123456789abc\n").unwrap();
+        assert_eq!(appender.path(), first);
+        for _ in 0..6 {
+            appender.write_all(b"line\n").unwrap();
+        }
+        assert_ne!(appender.path(), first);
+
+        let logs: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| is_log_file(path))
+            .collect();
+        // The current file plus two rotated ones; the first is gone
+        assert_eq!(logs.len(), 3, "{logs:?}");
+        assert!(!first.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,161 @@
+//! Quiets DEBUG and TRACE logs of chatty miners (`log_suppress_wallets`). `mining.authorize` records
+//! the wallet on the session span; [`WalletSuppressLayer`] keeps it in the span's extensions and drops
+//! low-level events logged inside a session whose wallet is on the list. INFO and above always pass.
+//! Wallets can be added at runtime with `POST /api/v1/log_suppress`.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Span field `mining.authorize` fills in with the wallet address.
+pub const WALLET_FIELD: &str = "wallet";
+
+static SUPPRESSED_WALLETS: Lazy<RwLock<HashSet<String>>> =
+    Lazy::new(|| RwLock::new(HashSet::new()));
+/// Skips the span walk while the list is empty.
+static ANY_SUPPRESSED: AtomicBool = AtomicBool::new(false);
+
+/// Replace the list with `wallets` (from the config).
+pub fn set_suppressed_wallets<I, S>(wallets: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut set = SUPPRESSED_WALLETS.write();
+    set.clear();
+    set.extend(
+        wallets
+            .into_iter()
+            .map(|wallet| wallet.as_ref().trim().to_string())
+            .filter(|wallet| !wallet.is_empty()),
+    );
+    ANY_SUPPRESSED.store(!set.is_empty(), Ordering::Relaxed);
+}
+
+/// Add `wallet` to the list; `false` when it was already there.
+pub fn suppress_wallet(wallet: &str) -> bool {
+    let mut set = SUPPRESSED_WALLETS.write();
+    let added = set.insert(wallet.trim().to_string());
+    ANY_SUPPRESSED.store(true, Ordering::Relaxed);
+    added
+}
+
+pub fn is_suppressed(wallet: &str) -> bool {
+    ANY_SUPPRESSED.load(Ordering::Relaxed) && SUPPRESSED_WALLETS.read().contains(wallet)
+}
+
+/// Wallet recorded on a session span.
+struct SessionWallet(String);
+
+#[derive(Default)]
+struct WalletVisitor(Option<String>);
+
+impl Visit for WalletVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == WALLET_FIELD {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == WALLET_FIELD {
+            self.0 = Some(format!("{:?}", value).trim_matches('"').to_string());
+        }
+    }
+}
+
+/// Drops DEBUG/TRACE events inside spans whose `wallet` is suppressed, for every output layer.
+pub struct WalletSuppressLayer;
+
+impl WalletSuppressLayer {
+    fn store<S>(id: &Id, ctx: &Context<'_, S>, wallet: Option<String>)
+    where
+        S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    {
+        if let (Some(wallet), Some(span)) = (wallet, ctx.span(id)) {
+            span.extensions_mut().replace(SessionWallet(wallet));
+        }
+    }
+}
+
+impl<S> Layer<S> for WalletSuppressLayer
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = WalletVisitor::default();
+        attrs.record(&mut visitor);
+        Self::store(id, &ctx, visitor.0);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = WalletVisitor::default();
+        values.record(&mut visitor);
+        Self::store(id, &ctx, visitor.0);
+    }
+
+    fn event_enabled(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) -> bool {
+        // More verbose levels compare greater: only DEBUG and TRACE are dropped
+        if *event.metadata().level() <= tracing::Level::INFO
+            || !ANY_SUPPRESSED.load(Ordering::Relaxed)
+        {
+            return true;
+        }
+        let Some(scope) = ctx.event_scope(event) else {
+            return true;
+        };
+        for span in scope {
+            if let Some(SessionWallet(wallet)) = span.extensions().get::<SessionWallet>() {
+                return !is_suppressed(wallet);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Counts the events that reach it.
+    struct Count(Arc<AtomicUsize>);
+
+    impl<S: tracing::Subscriber> Layer<S> for Count {
+        fn on_event(&self, _: &tracing::Event<'_>, _: Context<'_, S>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn drops_only_debug_events_of_suppressed_sessions() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let subscriber = tracing_subscriber::registry()
+            .with(WalletSuppressLayer)
+            .with(Count(Arc::clone(&seen)));
+        suppress_wallet("kaspa:qchatty");
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("session", wallet = tracing::field::Empty);
+            let _entered = span.enter();
+            tracing::debug!("before authorize");
+            span.record(WALLET_FIELD, "kaspa:qchatty");
+            tracing::debug!("dropped");
+            tracing::trace!("dropped");
+            tracing::warn!("kept");
+
+            let other = tracing::info_span!("session", wallet = "kaspa:qquiet");
+            let _entered = other.enter();
+            tracing::debug!("kept");
+        });
+        assert_eq!(seen.load(Ordering::SeqCst), 3);
+    }
+}
@@ -1,13 +1,33 @@
 //! `stratum-bridge` binary entrypoint. All mining, Stratum listeners, and kaspad handling run in
 //! `kaspa_stratum_bridge::runner::run` (the same entrypoint as `rkstratum-bridge-desktop`).
+//!
+//! Daemon mode forks here, before the tokio runtime exists, since a fork does not carry its threads.
 
 use clap::Parser;
 use kaspa_alloc::init_allocator_with_default_settings;
 use kaspa_stratum_bridge::cli::Cli;
+use kaspa_stratum_bridge::daemon;
 use kaspa_stratum_bridge::runner::run;
 
-#[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
+fn main() -> Result<(), anyhow::Error> {
     init_allocator_with_default_settings();
-    run(Cli::parse()).await
+    #[allow(unused_mut)]
+    let mut cli = Cli::parse();
+
+    // Held until `main` returns so the PID file is removed on shutdown
+    let _pid_file = match daemon::daemon_pid_file(&cli) {
+        #[cfg(unix)]
+        Some(pid_file) if !cli.check_config => Some(daemon::daemonize(&mut cli, &pid_file)?),
+        #[cfg(not(unix))]
+        Some(_) => {
+            eprintln!("warning: daemon mode is only supported on Unix; running in the foreground");
+            None
+        }
+        _ => None,
+    };
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(cli))
 }
@@ -0,0 +1,84 @@
+//! Difficulty helpers for miner-suggested values (`mining.suggest_target`).
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+/// Upper clamp for miner-suggested share difficulty.
+pub const MAX_SUGGESTED_SHARE_DIFF: f64 = 1_099_511_627_776.0; // 2^40
+
+/// Convert a 256-bit big-endian hex target to `difficulty = 2^256 / target`.
+///
+/// Accepts an optional `0x` prefix and up to 64 hex digits. Results above `u64::MAX` saturate.
+pub fn target_hex_to_difficulty(hex: &str) -> Result<u64, anyhow::Error> {
+    let digits = hex.trim();
+    let digits = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+        .unwrap_or(digits);
+
+    if digits.is_empty() {
+        return Err(anyhow::anyhow!("empty target"));
+    }
+    if digits.len() > 64 {
+        return Err(anyhow::anyhow!(
+            "target '{}' is longer than 64 hex digits",
+            hex
+        ));
+    }
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow::anyhow!("target '{}' is not valid hex", hex));
+    }
+
+    let target = BigUint::parse_bytes(digits.as_bytes(), 16)
+        .ok_or_else(|| anyhow::anyhow!("target '{}' is not valid hex", hex))?;
+    if target.is_zero() {
+        return Err(anyhow::anyhow!("target must be non-zero"));
+    }
+
+    let difficulty = (BigUint::one() << 256usize) / target;
+    Ok(difficulty.to_u64().unwrap_or(u64::MAX))
+}
+
+/// Convert a `2^256 / target` difficulty to the bridge's stratum share difficulty.
+///
+/// Stratum difficulty 1 corresponds to a `2^224` target (see `hasher::diff_to_target`), so the
+/// two scales differ by a factor of `2^32`.
+pub fn hash_difficulty_to_share_diff(difficulty: u64) -> f64 {
+    difficulty as f64 / 2_f64.powi(32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_across_the_full_range() {
+        // 2^256 - 1 is the easiest possible target.
+        assert_eq!(target_hex_to_difficulty(&"f".repeat(64)).unwrap(), 1);
+        // 2^224 target -> stratum difficulty 1.
+        let one = format!("1{}", "0".repeat(56));
+        assert_eq!(target_hex_to_difficulty(&one).unwrap(), 1u64 << 32);
+        assert_eq!(
+            hash_difficulty_to_share_diff(target_hex_to_difficulty(&one).unwrap()),
+            1.0
+        );
+        // 2^193 target, with 0x prefix.
+        let hard = format!("0x2{}", "0".repeat(48));
+        assert_eq!(target_hex_to_difficulty(&hard).unwrap(), 1u64 << 63);
+        // Anything harder saturates.
+        assert_eq!(target_hex_to_difficulty("1").unwrap(), u64::MAX);
+        // Leading zeros are allowed.
+        let padded = format!("{}{}", "0".repeat(8), "f".repeat(56));
+        assert_eq!(target_hex_to_difficulty(&padded).unwrap(), 1u64 << 32);
+    }
+
+    #[test]
+    fn rejects_malformed_targets() {
+        assert!(target_hex_to_difficulty("").is_err());
+        assert!(target_hex_to_difficulty("0x").is_err());
+        assert!(target_hex_to_difficulty("00").is_err());
+        assert!(target_hex_to_difficulty("xyz").is_err());
+        assert!(target_hex_to_difficulty("12 34").is_err());
+        assert!(target_hex_to_difficulty(&"f".repeat(65)).is_err());
+    }
+}
@@ -196,6 +196,17 @@ pub fn diff_to_hash(diff: f64) -> f64 {
     hash_val / BIG_GIG
 }
 
+/// Stratum difficulty a PoW hash actually reached: `maxTarget / pow_value`, the inverse of
+/// [`diff_to_target`]. Usually far above the share's assigned difficulty.
+pub fn pow_value_to_diff(pow_value: &BigUint) -> f64 {
+    use num_traits::Num;
+
+    let max_target = <BigUint as Num>::from_str_radix(MAX_TARGET, 16).unwrap();
+    let max_target_f = max_target.to_f64().unwrap_or(f64::MAX);
+    let pow_f = pow_value.to_f64().unwrap_or(f64::MAX).max(1.0);
+    max_target_f / pow_f
+}
+
 /// Serialize block header for mining
 /// This creates the pre-PoW hash (hash WITHOUT timestamp and nonce)
 /// Uses kaspa_hashes::BlockHash to match the working stratum implementation
@@ -488,6 +499,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pow_value_to_diff_inverts_diff_to_target() {
+        for diff in [1024.0, 4096.0, 65536.0] {
+            let reached = pow_value_to_diff(&diff_to_target(diff));
+            assert!((reached - diff).abs() / diff < 1e-9, "{reached} != {diff}");
+        }
+        assert!(pow_value_to_diff(&BigUint::zero()).is_finite());
+    }
+
     #[test]
     fn diff_to_target_non_positive_uses_max_target() {
         let t0 = diff_to_target(0.0);
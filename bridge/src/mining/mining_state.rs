@@ -32,6 +32,8 @@ pub struct MiningState {
     stratum_diff: Arc<Mutex<Option<KaspaDiff>>>,
     max_jobs: u16,
     last_header: Arc<Mutex<Option<kaspa_consensus_core::header::Header>>>, // Track previous header for change logging
+    /// Job ID of the last `mining.notify` written to the miner.
+    last_notified_job_id: Arc<Mutex<Option<u64>>>,
 }
 
 impl MiningState {
@@ -47,6 +49,7 @@ impl MiningState {
             stratum_diff: Arc::new(Mutex::new(None)),
             max_jobs: MAX_JOBS as u16,
             last_header: Arc::new(Mutex::new(None)),
+            last_notified_job_id: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -116,6 +119,11 @@ impl MiningState {
         *self.initialized.lock() = initialized;
     }
 
+    /// Mark initialized; `true` only for the caller that flipped it, so one path sets the first difficulty
+    pub fn mark_initialized(&self) -> bool {
+        !std::mem::replace(&mut *self.initialized.lock(), true)
+    }
+
     /// Check if using big job format
     pub fn use_big_job(&self) -> bool {
         *self.use_big_job.lock()
@@ -166,6 +174,28 @@ impl MiningState {
     pub fn set_last_header(&self, header: kaspa_consensus_core::header::Header) {
         *self.last_header.lock() = Some(header);
     }
+
+    /// Job ID of the last `mining.notify` the miner received
+    pub fn last_notified_job_id(&self) -> Option<u64> {
+        *self.last_notified_job_id.lock()
+    }
+
+    /// Record that the miner received `mining.notify` for `id`
+    pub fn set_last_notified_job_id(&self, id: u64) {
+        *self.last_notified_job_id.lock() = Some(id);
+    }
+
+    /// Whether the last notified job (still stored) is for the template `pre_pow_hash` / `timestamp`,
+    /// so sending it again would only restart the miner's nonce scan
+    pub fn holds_job_for(&self, pre_pow_hash: &Hash, timestamp: u64) -> bool {
+        let Some(id) = self.last_notified_job_id() else {
+            return false;
+        };
+        self.get_job_id_at_slot(id) == Some(id)
+            && self.get_job(id).is_some_and(|job| {
+                job.pre_pow_hash == *pre_pow_hash && job.block.header.timestamp == timestamp
+            })
+    }
 }
 
 impl Default for MiningState {
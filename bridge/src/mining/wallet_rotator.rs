@@ -0,0 +1,67 @@
+//! Round-robin payout wallets (`kaspad_wallets`): every block template fetch pays the next wallet in
+//! the list instead of the miner's own address, so rewards are spread across several wallets.
+
+use crate::prom::record_active_wallet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Payout wallets shared by all instances, with the cursor of the next one to hand out.
+#[derive(Debug)]
+pub struct WalletRotator {
+    wallets: Vec<String>,
+    cursor: AtomicUsize,
+}
+
+impl WalletRotator {
+    /// Rotator over the non-blank entries of `wallets`; `None` if there are none.
+    pub fn new(wallets: Vec<String>) -> Option<Self> {
+        let wallets: Vec<String> = wallets
+            .into_iter()
+            .map(|wallet| wallet.trim().to_string())
+            .filter(|wallet| !wallet.is_empty())
+            .collect();
+        (!wallets.is_empty()).then(|| Self {
+            wallets,
+            cursor: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn wallets(&self) -> &[String] {
+        &self.wallets
+    }
+
+    /// Wallet for the next block template; advances the cursor.
+    pub fn next_wallet(&self) -> &str {
+        let index = self.cursor.fetch_add(1, Ordering::Relaxed) % self.wallets.len();
+        let wallet = &self.wallets[index];
+        record_active_wallet(index, wallet);
+        wallet
+    }
+
+    /// Index and address handed out by the latest [`Self::next_wallet`] (`None` before the first).
+    pub fn active(&self) -> Option<(usize, &str)> {
+        let handed_out = self.cursor.load(Ordering::Relaxed);
+        (handed_out > 0).then(|| {
+            let index = (handed_out - 1) % self.wallets.len();
+            (index, self.wallets[index].as_str())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wallets_are_handed_out_in_turn() {
+        assert!(WalletRotator::new(vec![" ".to_string()]).is_none());
+
+        let rotator =
+            WalletRotator::new(vec!["kaspa:a".to_string(), "kaspa:b".to_string()]).unwrap();
+        assert_eq!(rotator.active(), None);
+        assert_eq!(rotator.next_wallet(), "kaspa:a");
+        assert_eq!(rotator.next_wallet(), "kaspa:b");
+        assert_eq!(rotator.active(), Some((1, "kaspa:b")));
+        assert_eq!(rotator.next_wallet(), "kaspa:a");
+        assert_eq!(rotator.active(), Some((0, "kaspa:a")));
+    }
+}
@@ -0,0 +1,226 @@
+//! `POST /api/v1/instances/{id}/{action}`: runtime commands for one instance.
+//!
+//! - `reconnect` (`{"host", "port", "delay_secs"?}`): ask every miner to move to another pool.
+//! - `broadcast_job`: send every miner a fresh job now.
+//! - `disconnect_wallet` (`{"wallet"}`): close the sessions of one wallet.
+//! - `min_diff` (`{"min_share_diff"}`): starting difficulty for sessions that have none yet.
+//! - `shutdown`: stop the instance; the others keep running.
+//!
+//! The request is forwarded to the running stratum server over its [`crate::control`] channel.
+
+use crate::control::{ControlMessage, control_sender};
+use serde::Deserialize;
+use std::time::Duration;
+
+const DEFAULT_RECONNECT_DELAY_SECS: u64 = 10;
+
+/// Actions accepted after `/api/v1/instances/{id}/`.
+const CONTROL_ACTIONS: [&str; 5] = [
+    "reconnect",
+    "broadcast_job",
+    "disconnect_wallet",
+    "min_diff",
+    "shutdown",
+];
+
+#[derive(Deserialize)]
+struct DisconnectWalletBody {
+    wallet: String,
+}
+
+#[derive(Deserialize)]
+struct MinDiffBody {
+    min_share_diff: u32,
+}
+
+#[derive(Deserialize)]
+struct ReconnectBody {
+    host: String,
+    port: u16,
+    #[serde(default)]
+    delay_secs: Option<u64>,
+}
+
+/// Instance number and action from `/api/v1/instances/{id}/{action}`, or `None` for any other path.
+pub(super) fn instance_control_from_path(path: &str) -> Option<(&str, &str)> {
+    let (id, action) = path.strip_prefix("/api/v1/instances/")?.split_once('/')?;
+    (!id.is_empty() && CONTROL_ACTIONS.contains(&action)).then_some((id, action))
+}
+
+fn parse_instance_num(id: &str) -> Result<usize, String> {
+    id.parse()
+        .map_err(|_| format!("invalid instance id '{}'", id))
+}
+
+fn parse_body<'a, B: Deserialize<'a>>(body: &'a str) -> Result<B, String> {
+    serde_json::from_str(body.trim()).map_err(|e| format!("invalid request body: {}", e))
+}
+
+pub(super) fn parse_control_request(
+    id: &str,
+    action: &str,
+    body: &str,
+) -> Result<(usize, ControlMessage), String> {
+    if action == "reconnect" {
+        return parse_reconnect_request(id, body);
+    }
+    let instance_num = parse_instance_num(id)?;
+    let msg = match action {
+        "broadcast_job" => ControlMessage::BroadcastNewJob,
+        "disconnect_wallet" => {
+            let body: DisconnectWalletBody = parse_body(body)?;
+            let wallet = body.wallet.trim().to_string();
+            if wallet.is_empty() {
+                return Err("wallet must not be empty".to_string());
+            }
+            ControlMessage::DisconnectWallet(wallet)
+        }
+        "min_diff" => {
+            let body: MinDiffBody = parse_body(body)?;
+            if body.min_share_diff == 0 {
+                return Err("min_share_diff must be at least 1".to_string());
+            }
+            ControlMessage::UpdateMinDiff(body.min_share_diff)
+        }
+        "shutdown" => ControlMessage::Shutdown,
+        _ => return Err(format!("unknown action '{}'", action)),
+    };
+    Ok((instance_num, msg))
+}
+
+pub(super) fn parse_reconnect_request(
+    id: &str,
+    body: &str,
+) -> Result<(usize, ControlMessage), String> {
+    let instance_num = parse_instance_num(id)?;
+    let body: ReconnectBody = parse_body(body)?;
+    let host = body.host.trim().to_string();
+    if host.is_empty() {
+        return Err("host must not be empty".to_string());
+    }
+    if body.port == 0 {
+        return Err("port must be between 1 and 65535".to_string());
+    }
+    Ok((
+        instance_num,
+        ControlMessage::Reconnect {
+            host,
+            port: body.port,
+            delay: Duration::from_secs(body.delay_secs.unwrap_or(DEFAULT_RECONNECT_DELAY_SECS)),
+        },
+    ))
+}
+
+/// Handle the request; returns the HTTP status code and JSON body.
+pub(super) async fn post_instance_control(id: &str, action: &str, body: &str) -> (u16, String) {
+    let (instance_num, msg) = match parse_control_request(id, action, body) {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                400,
+                serde_json::json!({ "success": false, "message": e }).to_string(),
+            );
+        }
+    };
+    let Some(tx) = control_sender(instance_num) else {
+        return (
+            404,
+            serde_json::json!({
+                "success": false,
+                "message": format!("instance {} not found", instance_num),
+            })
+            .to_string(),
+        );
+    };
+    if tx.send(msg).await.is_err() {
+        return (
+            503,
+            serde_json::json!({
+                "success": false,
+                "message": format!("instance {} is not running", instance_num),
+            })
+            .to_string(),
+        );
+    }
+    let message = match action {
+        "reconnect" => "reconnect broadcast queued",
+        "broadcast_job" => "job broadcast queued",
+        "disconnect_wallet" => "wallet disconnect queued",
+        "min_diff" => "min_share_diff update queued",
+        _ => "shutdown queued",
+    };
+    (
+        202,
+        serde_json::json!({ "success": true, "message": message }).to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_path_and_body() {
+        assert_eq!(
+            instance_control_from_path("/api/v1/instances/2/reconnect"),
+            Some(("2", "reconnect"))
+        );
+        assert_eq!(
+            instance_control_from_path("/api/v1/instances//reconnect"),
+            None
+        );
+        assert_eq!(instance_control_from_path("/api/v1/instances/2"), None);
+        assert_eq!(
+            instance_control_from_path("/api/v1/instances/2/restart"),
+            None
+        );
+
+        let (num, msg) = parse_reconnect_request(
+            "2",
+            r#"{"host":"new-pool.example.com","port":5555,"delay_secs":3}"#,
+        )
+        .unwrap();
+        assert_eq!(num, 2);
+        assert_eq!(
+            msg,
+            ControlMessage::Reconnect {
+                host: "new-pool.example.com".to_string(),
+                port: 5555,
+                delay: Duration::from_secs(3),
+            }
+        );
+
+        assert!(parse_reconnect_request("x", r#"{"host":"a","port":1}"#).is_err());
+        assert!(parse_reconnect_request("1", r#"{"host":"","port":1}"#).is_err());
+        assert!(parse_reconnect_request("1", r#"{"host":"a","port":0}"#).is_err());
+        assert!(parse_reconnect_request("1", "not json").is_err());
+    }
+
+    #[test]
+    fn parses_instance_commands() {
+        assert_eq!(
+            parse_control_request("1", "broadcast_job", "").unwrap(),
+            (1, ControlMessage::BroadcastNewJob)
+        );
+        assert_eq!(
+            parse_control_request("3", "disconnect_wallet", r#"{"wallet":" kaspa:qabc "}"#)
+                .unwrap(),
+            (
+                3,
+                ControlMessage::DisconnectWallet("kaspa:qabc".to_string())
+            )
+        );
+        assert_eq!(
+            parse_control_request("2", "min_diff", r#"{"min_share_diff":512}"#).unwrap(),
+            (2, ControlMessage::UpdateMinDiff(512))
+        );
+        assert_eq!(
+            parse_control_request("2", "shutdown", "").unwrap(),
+            (2, ControlMessage::Shutdown)
+        );
+
+        assert!(parse_control_request("1", "disconnect_wallet", r#"{"wallet":""}"#).is_err());
+        assert!(parse_control_request("1", "min_diff", r#"{"min_share_diff":0}"#).is_err());
+        assert!(parse_control_request("x", "shutdown", "").is_err());
+    }
+}
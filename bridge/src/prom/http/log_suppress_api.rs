@@ -0,0 +1,62 @@
+//! `POST /api/v1/log_suppress`: add a wallet to `log_suppress_wallets` at runtime, quieting the DEBUG
+//! and TRACE logs of its sessions until the bridge restarts.
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct LogSuppressBody {
+    wallet: String,
+}
+
+/// Handle the request; returns the HTTP status code and JSON body.
+pub(super) fn post_log_suppress(body: &str) -> (u16, String) {
+    let wallet = match serde_json::from_str::<LogSuppressBody>(body.trim()) {
+        Ok(body) if !body.wallet.trim().is_empty() => body.wallet.trim().to_string(),
+        Ok(_) => {
+            return (
+                400,
+                serde_json::json!({ "success": false, "message": "wallet must not be empty" })
+                    .to_string(),
+            );
+        }
+        Err(e) => {
+            return (
+                400,
+                serde_json::json!({
+                    "success": false,
+                    "message": format!("invalid request body: {}", e),
+                })
+                .to_string(),
+            );
+        }
+    };
+    let message = if crate::log_suppress::suppress_wallet(&wallet) {
+        tracing::info!("[LOG] suppressing DEBUG/TRACE logs for wallet {}", wallet);
+        format!("debug logs suppressed for {}", wallet)
+    } else {
+        format!("{} was already suppressed", wallet)
+    };
+    (
+        200,
+        serde_json::json!({ "success": true, "message": message }).to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_wallets_and_rejects_bad_bodies() {
+        let wallet = "kaspa:qlogsuppresstest";
+        let (status, body) = post_log_suppress(&format!(r#"{{"wallet":" {wallet} "}}"#));
+        assert_eq!(status, 200, "{body}");
+        assert!(crate::log_suppress::is_suppressed(wallet));
+        let (status, body) = post_log_suppress(&format!(r#"{{"wallet":"{wallet}"}}"#));
+        assert_eq!(status, 200);
+        assert!(body.contains("already suppressed"), "{body}");
+
+        assert_eq!(post_log_suppress(r#"{"wallet":""}"#).0, 400);
+        assert_eq!(post_log_suppress("not json").0, 400);
+    }
+}
@@ -1,8 +1,11 @@
 //! Prometheus HTTP: `/metrics`, `/api/*`, static dashboard files.
 //!
-//! Split across `static_files`, `stats_json/` (types + parse + aggregate), `config_api`, and `serve`.
+//! Split across `static_files`, `stats_json/` (types + parse + aggregate), `config_api`, `control_api`,
+//! `log_suppress_api`, and `serve`.
 
 mod config_api;
+mod control_api;
+mod log_suppress_api;
 mod ops_access;
 mod serve;
 mod static_files;
@@ -81,6 +84,14 @@ min_share_diff: 8192
         assert!(stats_resp.contains("200 OK"));
         assert!(stats_resp.contains("application/json"));
 
+        let workers_resp = send_request(
+            mode.clone(),
+            "GET /api/v1/workers?wallet=kaspa:none HTTP/1.1\r\n\r\n",
+        )
+        .await;
+        assert!(workers_resp.contains("200 OK"));
+        assert!(workers_resp.contains(r#"{"workers":[]}"#));
+
         let config_resp = send_request(mode.clone(), "GET /api/config HTTP/1.1\r\n\r\n").await;
         assert!(config_resp.contains("200 OK"));
         assert!(config_resp.contains("\"kaspad_address\""));
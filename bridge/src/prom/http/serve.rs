@@ -6,18 +6,23 @@
 //! without changing bodies or `Access-Control-Allow-Origin` behavior used by dashboards.
 //!
 //! Optional hardening for `/api/config` is in [`super::ops_access`] (bearer token, CSRF header, localhost-only,
-//! POST rate limit). **TLS:** terminate HTTPS in front of the bridge (reverse proxy or load balancer).
+//! POST rate limit); the same checks guard `POST /api/v1/instances/{id}/{action}` and
+//! `POST /api/v1/log_suppress`. **TLS:** terminate HTTPS
+//! in front of the bridge (reverse proxy or load balancer).
 
 use super::super::metrics::{filter_metric_families_for_instance, init_metrics};
 use super::config_api::{
     config_write_allowed, get_config_json, get_web_status_config, update_config_from_json,
 };
+use super::control_api::{instance_control_from_path, post_instance_control};
+use super::log_suppress_api::post_log_suppress;
 use super::ops_access::{ConfigRouteDeny, check_config_route_access};
 use super::static_files::{content_type_for_path, try_read_static_file};
 use super::stats_json::{get_stats_json, get_stats_json_all};
 use crate::host_metrics::{geoip_effective, get_host_snapshot, host_metrics_compiled};
 use crate::kaspaapi::node_status_for_api;
 use crate::net_utils::bind_addr_for_operator_http;
+use crate::worker_registry::{WORKER_REGISTRY, WorkerFilter, WorkerRow};
 use serde::Serialize;
 use std::net::SocketAddr;
 #[derive(Serialize)]
@@ -36,6 +41,11 @@ struct WebStatusResponse {
     host: Option<crate::host_metrics::HostSnapshot>,
 }
 
+#[derive(Serialize)]
+struct WorkersResponse {
+    workers: Vec<WorkerRow>,
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum HttpMode {
     Aggregated {
@@ -166,7 +176,7 @@ pub(crate) async fn handle_http_request(
         return Ok(());
     }
 
-    if request.starts_with("GET /api/stats") {
+    if request.starts_with("GET /api/stats") || request.starts_with("GET /api/v1/stats") {
         let stats = match mode {
             HttpMode::Aggregated { .. } => get_stats_json_all().await,
             HttpMode::Instance { instance_id, .. } => get_stats_json(instance_id).await,
@@ -177,6 +187,27 @@ pub(crate) async fn handle_http_request(
         return Ok(());
     }
 
+    if request.starts_with("GET /") && path == "/api/v1/workers" {
+        let query = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|target| target.split_once('?'))
+            .map_or("", |(_, q)| q.split('#').next().unwrap_or(q));
+        let mut filter = WorkerFilter::from_query(query);
+        // A per-instance server only lists its own workers
+        if let HttpMode::Instance { instance_id, .. } = mode {
+            filter.instance = Some(crate::worker_registry::instance_number(instance_id));
+        }
+        let body = WorkersResponse {
+            workers: WORKER_REGISTRY.list(&filter),
+        };
+        let json = serde_json::to_string(&body).unwrap_or_else(|_| "{}".to_string());
+        let response = format!("{}{}", json_ok_headers(json.len()), json);
+        stream.write_all(response.as_bytes()).await?;
+        return Ok(());
+    }
+
     if matches!(mode, HttpMode::Instance { .. }) && request.starts_with("GET /api/config") {
         if let Err(deny) = check_config_route_access(request, peer.ip(), false) {
             let response = json_deny_response(deny);
@@ -219,6 +250,57 @@ pub(crate) async fn handle_http_request(
         return Ok(());
     }
 
+    if request.starts_with("POST /api/v1/instances/")
+        && let Some((id, action)) = instance_control_from_path(path)
+    {
+        if let Err(deny) = check_config_route_access(request, peer.ip(), true) {
+            let response = json_deny_response(deny);
+            stream.write_all(response.as_bytes()).await?;
+            return Ok(());
+        }
+        let body_start = request.find("\r\n\r\n").unwrap_or(request.len());
+        let body = request.get(body_start + 4..).unwrap_or("");
+        let (status, json) = post_instance_control(id, action, body).await;
+        let status = match status {
+            202 => "202 Accepted",
+            400 => "400 Bad Request",
+            404 => "404 Not Found",
+            _ => "503 Service Unavailable",
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nX-Content-Type-Options: nosniff\r\nReferrer-Policy: no-referrer\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            json.len(),
+            json
+        );
+        stream.write_all(response.as_bytes()).await?;
+        return Ok(());
+    }
+
+    if request.starts_with("POST /") && path == "/api/v1/log_suppress" {
+        if let Err(deny) = check_config_route_access(request, peer.ip(), true) {
+            let response = json_deny_response(deny);
+            stream.write_all(response.as_bytes()).await?;
+            return Ok(());
+        }
+        let body_start = request.find("\r\n\r\n").unwrap_or(request.len());
+        let body = request.get(body_start + 4..).unwrap_or("");
+        let (status, json) = post_log_suppress(body);
+        let status = if status == 200 {
+            "200 OK"
+        } else {
+            "400 Bad Request"
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nX-Content-Type-Options: nosniff\r\nReferrer-Policy: no-referrer\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            json.len(),
+            json
+        );
+        stream.write_all(response.as_bytes()).await?;
+        return Ok(());
+    }
+
     if request.starts_with("GET /") {
         if let Some((rel, bytes)) = try_read_static_file(path) {
             let ct = content_type_for_path(&rel);
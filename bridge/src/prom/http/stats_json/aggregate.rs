@@ -11,7 +11,7 @@ use super::parse::{
 use super::types::{BlockInfo, InternalCpuStats, StatsResponse, WorkerInfo};
 
 use crate::prom::metrics::{
-    BRIDGE_START_TIME, WORKER_LAST_ACTIVITY, filter_metric_families_for_instance,
+    ACTIVE_WALLET, BRIDGE_START_TIME, WORKER_LAST_ACTIVITY, filter_metric_families_for_instance,
 };
 #[cfg(feature = "rkstratum_cpu_miner")]
 use crate::prom::metrics::{INTERNAL_CPU_MINING_ADDRESS, INTERNAL_CPU_RECENT_BLOCKS};
@@ -39,6 +39,7 @@ pub(crate) async fn get_stats_json_filtered(instance_id: Option<&str>) -> StatsR
         blocks: Vec::new(),
         workers: Vec::new(),
         bridgeUptime: None,
+        activeWallet: None,
     };
 
     let mut worker_stats: HashMap<String, WorkerInfo> = HashMap::new();
@@ -599,6 +600,11 @@ pub(crate) async fn get_stats_json_filtered(instance_id: Option<&str>) -> StatsR
         stats.bridgeUptime = Some(uptime_secs);
     }
 
+    let active_wallet = ACTIVE_WALLET.lock().clone();
+    if !active_wallet.is_empty() {
+        stats.activeWallet = Some(active_wallet);
+    }
+
     stats
 }
 
@@ -31,6 +31,8 @@ pub(crate) struct StatsResponse {
     pub(crate) workers: Vec<WorkerInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) bridgeUptime: Option<u64>, // Bridge uptime in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) activeWallet: Option<String>, // Round-robin payout wallet (`kaspad_wallets`)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
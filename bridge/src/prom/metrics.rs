@@ -1,14 +1,13 @@
 use prometheus::proto::MetricFamily;
-#[cfg(feature = "rkstratum_cpu_miner")]
-use prometheus::{Counter, register_counter};
 use prometheus::{
-    CounterVec, Gauge, GaugeVec, register_counter_vec, register_gauge, register_gauge_vec,
+    Counter, CounterVec, Gauge, GaugeVec, HistogramVec, register_counter, register_counter_vec,
+    register_gauge, register_gauge_vec, register_histogram_vec,
 };
 use std::collections::HashMap;
 #[cfg(feature = "rkstratum_cpu_miner")]
 use std::collections::VecDeque;
 use std::sync::OnceLock;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// Worker labels for Prometheus metrics
 const WORKER_LABELS: &[&str] = &["instance", "worker", "miner", "wallet", "ip"];
@@ -75,6 +74,104 @@ static NETWORK_DIFFICULTY: OnceLock<Gauge> = OnceLock::new();
 /// Network block count gauge
 static NETWORK_BLOCK_COUNT: OnceLock<Gauge> = OnceLock::new();
 
+/// Position in `kaspad_wallets` of the payout wallet used by the latest block template
+static ACTIVE_WALLET_INDEX: OnceLock<Gauge> = OnceLock::new();
+
+/// Round-trip time of the last `KaspaApi::ping`, in milliseconds
+static KASPAD_PING_LATENCY: OnceLock<Gauge> = OnceLock::new();
+
+/// Sessions open across all instances (`health_check::GlobalWorkerCount`)
+static TOTAL_WORKERS: OnceLock<Gauge> = OnceLock::new();
+
+/// Payout wallet used by the latest block template (`kaspad_wallets`), for `/api/stats`
+pub(crate) static ACTIVE_WALLET: parking_lot::Mutex<String> =
+    parking_lot::const_mutex(String::new());
+
+/// Stratum connections refused by the IP allowlist/denylist
+static CONNECTIONS_DENIED_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Miners refused at `mining.authorize` (or at accept, for `ip_denied`), by reason
+static AUTHORIZE_REJECTIONS_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Queued `mining.notify` lines dropped for slow miners (`notify_queue_depth`)
+static NOTIFICATIONS_DROPPED_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// `client.reconnect` broadcasts triggered via the control API
+static RECONNECT_BROADCASTS_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Connected sessions that sent `mining.extranonce.subscribe`
+static EXTRANONCE_SUBSCRIBERS: OnceLock<GaugeVec> = OnceLock::new();
+
+/// Session tasks that panicked and were recovered
+static SESSION_PANICS_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Block submissions that needed a retry, by final outcome
+static BLOCK_SUBMIT_RETRIES_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Connections turned away because the instance was at `max_workers`
+static CONNECTIONS_REJECTED_CAPACITY_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Authorizations refused because the wallet was at `max_sessions_per_wallet`
+static SESSIONS_REJECTED_WALLET_LIMIT_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Authorizations refused because the wallet was at `max_workers_per_wallet`
+static SESSIONS_REJECTED_WORKER_LIMIT_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// kaspad hostname lookups by cache outcome
+static DNS_LOOKUPS_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Block template requests answered from the `KaspaApi` TTL cache
+static TEMPLATE_CACHE_HITS_COUNTER: OnceLock<Counter> = OnceLock::new();
+
+/// Shares queued or still being handled by an instance's share processor
+static SHARE_QUEUE_DEPTH: OnceLock<GaugeVec> = OnceLock::new();
+
+/// Shares that had to wait because `max_submit_concurrency` shares were already being handled
+static SUBMIT_QUEUE_FULL_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Pauses inserted between Stratum accepts while `max_global_accepts_per_second` is exceeded
+static ACCEPT_BACKOFF_EVENTS_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Sessions closed after `max_parse_errors` consecutive unparsable lines
+static SESSIONS_CLOSED_PARSE_ERRORS_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Estimated new Stratum connections per second, updated on every accept
+static CURRENT_ACCEPT_RATE: OnceLock<GaugeVec> = OnceLock::new();
+
+/// How long Stratum sessions lasted, recorded when the session task exits
+static WORKER_SESSION_DURATION: OnceLock<HistogramVec> = OnceLock::new();
+
+/// Age of the longest-lived session still connected to an instance
+static SESSION_ACTIVE_DURATION: OnceLock<GaugeVec> = OnceLock::new();
+
+/// Duration of each `submit_block` RPC to kaspad
+static KASPAD_SUBMIT_RTT: OnceLock<HistogramVec> = OnceLock::new();
+
+/// Bucket bounds for `ks_kaspad_submit_rtt_milliseconds`.
+const SUBMIT_RTT_BUCKETS_MS: &[f64] = &[
+    5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+];
+
+/// Bucket bounds for `ks_worker_session_duration_seconds`: 30s, 5m, 30m, 1h, 6h, 24h.
+const SESSION_DURATION_BUCKETS: &[f64] = &[30.0, 300.0, 1800.0, 3600.0, 21600.0, 86400.0];
+
+/// Difficulty reached by every PoW-checked share (accepted, low difficulty, stale, or rejected)
+static SHARE_DIFFICULTY_SUBMITTED: OnceLock<HistogramVec> = OnceLock::new();
+
+/// Difficulty reached by accepted shares
+static SHARE_DIFFICULTY_ACCEPTED: OnceLock<HistogramVec> = OnceLock::new();
+
+/// Bucket bounds for the share difficulty histograms: doublings from 2^10 to 2^32.
+fn share_difficulty_buckets() -> Vec<f64> {
+    (10..=32).map(|exp| 2_f64.powi(exp)).collect()
+}
+
+/// Job broadcasts to all sessions, split by whether they replaced the pending queue
+static JOBS_BROADCAST_COUNTER: OnceLock<CounterVec> = OnceLock::new();
+
+/// Connected sessions by miner software and version (parsed from the subscribe user-agent)
+static SOFTWARE_VERSION_GAUGE: OnceLock<GaugeVec> = OnceLock::new();
+
 /// Worker start time gauge (Unix timestamp in seconds)
 static WORKER_START_TIME: OnceLock<GaugeVec> = OnceLock::new();
 
@@ -236,6 +333,249 @@ pub fn init_metrics() {
         .unwrap()
     });
 
+    ACTIVE_WALLET_INDEX.get_or_init(|| {
+        register_gauge!(
+            "ks_active_wallet_index",
+            "Index into kaspad_wallets of the wallet paid by the latest block template"
+        )
+        .unwrap()
+    });
+
+    KASPAD_PING_LATENCY.get_or_init(|| {
+        register_gauge!(
+            "ks_kaspad_ping_latency_milliseconds",
+            "Round-trip time of the last kaspad ping in milliseconds"
+        )
+        .unwrap()
+    });
+
+    TOTAL_WORKERS.get_or_init(|| {
+        register_gauge!(
+            "ks_stratum_total_workers",
+            "Stratum sessions open across all bridge instances"
+        )
+        .unwrap()
+    });
+
+    CONNECTIONS_DENIED_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_connections_denied_total",
+            "Stratum connections dropped by the IP denylist or allowlist",
+            &["reason"]
+        )
+        .unwrap()
+    });
+
+    AUTHORIZE_REJECTIONS_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_authorize_rejections_total",
+            "Miners refused authorization, by reason",
+            &["reason"]
+        )
+        .unwrap()
+    });
+
+    NOTIFICATIONS_DROPPED_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_notifications_dropped_total",
+            "Queued mining.notify lines dropped because the miner was not reading them",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    RECONNECT_BROADCASTS_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_reconnect_broadcasts_total",
+            "client.reconnect broadcasts sent to all sessions of an instance",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    EXTRANONCE_SUBSCRIBERS.get_or_init(|| {
+        register_gauge_vec!(
+            "ks_extranonce_subscribers",
+            "Connected sessions that accept mining.set_extranonce mid-session (mining.extranonce.subscribe)",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    SESSION_PANICS_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_session_panics_total",
+            "Stratum session tasks that panicked; the session is closed and the process keeps running",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    BLOCK_SUBMIT_RETRIES_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_block_submission_retries_total",
+            "Block submissions whose first submit_block RPC failed, by outcome after retrying (success, failed)",
+            &["instance", "outcome"]
+        )
+        .unwrap()
+    });
+
+    CONNECTIONS_REJECTED_CAPACITY_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_connections_rejected_capacity_total",
+            "Connections closed because the instance already had max_workers sessions",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    SESSIONS_REJECTED_WALLET_LIMIT_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_sessions_rejected_wallet_limit_total",
+            "Sessions closed at authorize because the wallet already had max_sessions_per_wallet sessions",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    SESSIONS_REJECTED_WORKER_LIMIT_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_sessions_rejected_worker_limit_total",
+            "Sessions closed at authorize because the wallet already had max_workers_per_wallet worker names",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    DNS_LOOKUPS_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_dns_lookups_total",
+            "kaspad hostname lookups by DNS cache result (hit, miss, refresh)",
+            &["result"]
+        )
+        .unwrap()
+    });
+
+    TEMPLATE_CACHE_HITS_COUNTER.get_or_init(|| {
+        register_counter!(
+            "ks_template_cache_hits_total",
+            "Block template requests served from the template cache instead of kaspad"
+        )
+        .unwrap()
+    });
+
+    SHARE_QUEUE_DEPTH.get_or_init(|| {
+        register_gauge_vec!(
+            "ks_share_queue_depth",
+            "Shares queued for submission or still being handled",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    SUBMIT_QUEUE_FULL_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_submit_queue_full_total",
+            "Shares that waited for a submit slot because max_submit_concurrency shares were being handled",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    ACCEPT_BACKOFF_EVENTS_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_accept_backoff_events_total",
+            "Pauses between Stratum accepts while the connection rate is above max_global_accepts_per_second",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    SESSIONS_CLOSED_PARSE_ERRORS_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_sessions_closed_parse_errors_total",
+            "Stratum sessions closed after max_parse_errors consecutive unparsable messages",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    CURRENT_ACCEPT_RATE.get_or_init(|| {
+        register_gauge_vec!(
+            "ks_current_accept_rate",
+            "Estimated new Stratum connections per second over the last second",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    WORKER_SESSION_DURATION.get_or_init(|| {
+        register_histogram_vec!(
+            "ks_worker_session_duration_seconds",
+            "Lifetime of closed Stratum sessions; many short sessions point at reconnect storms",
+            &["instance"],
+            SESSION_DURATION_BUCKETS.to_vec()
+        )
+        .unwrap()
+    });
+
+    KASPAD_SUBMIT_RTT.get_or_init(|| {
+        register_histogram_vec!(
+            "ks_kaspad_submit_rtt_milliseconds",
+            "Milliseconds each submit_block RPC to kaspad took",
+            &["instance"],
+            SUBMIT_RTT_BUCKETS_MS.to_vec()
+        )
+        .unwrap()
+    });
+
+    SHARE_DIFFICULTY_SUBMITTED.get_or_init(|| {
+        register_histogram_vec!(
+            "ks_share_difficulty_submitted",
+            "Difficulty actually reached by submitted shares, including low-difficulty, stale, and rejected ones",
+            &["instance"],
+            share_difficulty_buckets()
+        )
+        .unwrap()
+    });
+
+    SHARE_DIFFICULTY_ACCEPTED.get_or_init(|| {
+        register_histogram_vec!(
+            "ks_share_difficulty_accepted",
+            "Difficulty actually reached by accepted shares; compare with ks_share_difficulty_submitted per tier",
+            &["instance"],
+            share_difficulty_buckets()
+        )
+        .unwrap()
+    });
+
+    SESSION_ACTIVE_DURATION.get_or_init(|| {
+        register_gauge_vec!(
+            "ks_session_active_duration_seconds",
+            "Age of the longest-lived connected session (0 when none)",
+            &["instance"]
+        )
+        .unwrap()
+    });
+
+    JOBS_BROADCAST_COUNTER.get_or_init(|| {
+        register_counter_vec!(
+            "ks_jobs_broadcast_total",
+            "Job broadcasts to connected sessions; clean=true for new blocks that superseded pending jobs",
+            &["instance", "clean"]
+        )
+        .unwrap()
+    });
+
+    SOFTWARE_VERSION_GAUGE.get_or_init(|| {
+        register_gauge_vec!(
+            "ks_software_version",
+            "Connected sessions by miner software (known names, else other) and major.minor version",
+            &["software", "version"]
+        )
+        .unwrap()
+    });
+
     WORKER_START_TIME.get_or_init(|| {
         register_gauge_vec!(
             "ks_worker_start_time",
@@ -537,6 +877,201 @@ pub fn record_disconnect(worker: &WorkerContext) {
     activity_map.lock().remove(&key);
 }
 
+/// Record a Stratum connection refused by IP access control (`reason`: `denylist` | `allowlist`)
+pub fn record_connection_denied(reason: &str) {
+    if let Some(counter) = CONNECTIONS_DENIED_COUNTER.get() {
+        counter.with_label_values(&[reason]).inc();
+    }
+}
+
+/// Record a refused miner (`reason`: `invalid_address` | `invalid_worker_name` | `missing_password` |
+/// `wrong_password` | `auth_backend` | `wallet_limit` | `ip_denied`)
+pub fn record_authorize_rejection(reason: &str) {
+    if let Some(counter) = AUTHORIZE_REJECTIONS_COUNTER.get() {
+        counter.with_label_values(&[reason]).inc();
+    }
+}
+
+/// Record `dropped` queued `mining.notify` lines a slow miner never received
+pub fn record_notifications_dropped(instance_id: &str, dropped: usize) {
+    if let Some(counter) = NOTIFICATIONS_DROPPED_COUNTER.get() {
+        counter
+            .with_label_values(&[instance_id])
+            .inc_by(dropped as f64);
+    }
+}
+
+/// Record a `client.reconnect` broadcast to every session of an instance
+pub fn record_reconnect_broadcast(instance_id: &str) {
+    if let Some(counter) = RECONNECT_BROADCASTS_COUNTER.get() {
+        counter.with_label_values(&[instance_id]).inc();
+    }
+}
+
+/// Record a session opting in to (`true`) or leaving with (`false`) `mining.extranonce.subscribe`
+pub fn record_extranonce_subscriber(instance_id: &str, subscribed: bool) {
+    if let Some(gauge) = EXTRANONCE_SUBSCRIBERS.get() {
+        let gauge = gauge.with_label_values(&[instance_id]);
+        if subscribed {
+            gauge.inc();
+        } else {
+            gauge.dec();
+        }
+    }
+}
+
+/// Record a panicked Stratum session
+pub fn record_session_panic(instance_id: &str) {
+    if let Some(counter) = SESSION_PANICS_COUNTER.get() {
+        counter.with_label_values(&[instance_id]).inc();
+    }
+}
+
+/// Record a block submission that was retried, with whether a retry got through
+pub fn record_block_submission_retry(instance_id: &str, success: bool) {
+    if let Some(counter) = BLOCK_SUBMIT_RETRIES_COUNTER.get() {
+        let outcome = if success { "success" } else { "failed" };
+        counter.with_label_values(&[instance_id, outcome]).inc();
+    }
+}
+
+/// Record a connection rejected because the instance was full
+pub fn record_connection_rejected_capacity(instance_id: &str) {
+    if let Some(counter) = CONNECTIONS_REJECTED_CAPACITY_COUNTER.get() {
+        counter.with_label_values(&[instance_id]).inc();
+    }
+}
+
+/// Record a session refused by the per-wallet session limit
+pub fn record_session_rejected_wallet_limit(instance_id: &str) {
+    if let Some(counter) = SESSIONS_REJECTED_WALLET_LIMIT_COUNTER.get() {
+        counter.with_label_values(&[instance_id]).inc();
+    }
+}
+
+/// Record an authorization refused by `max_workers_per_wallet`
+pub fn record_session_rejected_worker_limit(instance_id: &str) {
+    if let Some(counter) = SESSIONS_REJECTED_WORKER_LIMIT_COUNTER.get() {
+        counter.with_label_values(&[instance_id]).inc();
+    }
+}
+
+/// Record a block template served from the template cache
+pub fn record_template_cache_hit() {
+    if let Some(counter) = TEMPLATE_CACHE_HITS_COUNTER.get() {
+        counter.inc();
+    }
+}
+
+/// Record a kaspad hostname lookup (`hit`, `miss`, or `refresh`)
+pub fn record_dns_lookup(result: &str) {
+    if let Some(counter) = DNS_LOOKUPS_COUNTER.get() {
+        counter.with_label_values(&[result]).inc();
+    }
+}
+
+/// Set the current depth of an instance's share submit queue
+pub fn record_share_queue_depth(instance_id: &str, depth: usize) {
+    if let Some(gauge) = SHARE_QUEUE_DEPTH.get() {
+        gauge.with_label_values(&[instance_id]).set(depth as f64);
+    }
+}
+
+/// Record a share that waited for one of the `max_submit_concurrency` submit slots
+pub fn record_submit_queue_full(instance_id: &str) {
+    if let Some(counter) = SUBMIT_QUEUE_FULL_COUNTER.get() {
+        counter.with_label_values(&[instance_id]).inc();
+    }
+}
+
+/// Record the accept rate after a new connection, and whether the accept loop backed off
+pub fn record_accept_rate(instance_id: &str, rate: f64, backed_off: bool) {
+    if let Some(gauge) = CURRENT_ACCEPT_RATE.get() {
+        gauge.with_label_values(&[instance_id]).set(rate);
+    }
+    if backed_off && let Some(counter) = ACCEPT_BACKOFF_EVENTS_COUNTER.get() {
+        counter.with_label_values(&[instance_id]).inc();
+    }
+}
+
+/// Record a session closed for sending too many unparsable messages
+pub fn record_session_closed_parse_errors(instance_id: &str) {
+    if let Some(counter) = SESSIONS_CLOSED_PARSE_ERRORS_COUNTER.get() {
+        counter.with_label_values(&[instance_id]).inc();
+    }
+}
+
+/// Record how long a finished session was connected
+pub fn record_session_duration(instance_id: &str, duration: Duration) {
+    if let Some(histogram) = WORKER_SESSION_DURATION.get() {
+        histogram
+            .with_label_values(&[instance_id])
+            .observe(duration.as_secs_f64());
+    }
+}
+
+/// Record one `submit_block` RPC round trip in milliseconds
+pub fn record_kaspad_submit_rtt(instance_id: &str, rtt_ms: f64) {
+    if let Some(histogram) = KASPAD_SUBMIT_RTT.get() {
+        histogram.with_label_values(&[instance_id]).observe(rtt_ms);
+    }
+}
+
+/// Record the difficulty a submitted share reached, whatever its outcome
+pub fn record_share_difficulty_submitted(instance_id: &str, difficulty: f64) {
+    if let Some(histogram) = SHARE_DIFFICULTY_SUBMITTED.get() {
+        histogram
+            .with_label_values(&[instance_id])
+            .observe(difficulty);
+    }
+}
+
+/// Record the difficulty an accepted share reached
+pub fn record_share_difficulty_accepted(instance_id: &str, difficulty: f64) {
+    if let Some(histogram) = SHARE_DIFFICULTY_ACCEPTED.get() {
+        histogram
+            .with_label_values(&[instance_id])
+            .observe(difficulty);
+    }
+}
+
+/// Set the age of an instance's oldest connected session (`None` when it has no sessions)
+pub fn record_longest_session_age(instance_id: &str, age: Option<Duration>) {
+    if let Some(gauge) = SESSION_ACTIVE_DURATION.get() {
+        gauge
+            .with_label_values(&[instance_id])
+            .set(age.map_or(0.0, |age| age.as_secs_f64()));
+    }
+}
+
+/// Count one job broadcast to an instance's sessions
+pub fn record_jobs_broadcast(instance_id: &str, clean: bool) {
+    if let Some(counter) = JOBS_BROADCAST_COUNTER.get() {
+        let clean = if clean { "true" } else { "false" };
+        counter.with_label_values(&[instance_id, clean]).inc();
+    }
+}
+
+/// Count a session under its miner software/version
+pub fn record_software_version_connected(user_agent: &crate::user_agent::UserAgent) {
+    if let Some(gauge) = SOFTWARE_VERSION_GAUGE.get() {
+        let (software, version) = user_agent.metric_labels();
+        gauge
+            .with_label_values(&[software.as_str(), version.as_str()])
+            .inc();
+    }
+}
+
+/// Remove a session from its miner software/version count
+pub fn record_software_version_disconnected(user_agent: &crate::user_agent::UserAgent) {
+    if let Some(gauge) = SOFTWARE_VERSION_GAUGE.get() {
+        let (software, version) = user_agent.metric_labels();
+        gauge
+            .with_label_values(&[software.as_str(), version.as_str()])
+            .dec();
+    }
+}
+
 /// Record a new job sent
 pub fn record_new_job(worker: &WorkerContext) {
     if let Some(counter) = JOB_COUNTER.get() {
@@ -556,6 +1091,32 @@ pub fn record_network_stats(hashrate: u64, block_count: u64, difficulty: f64) {
         gauge.set(difficulty);
     }
 }
+
+/// Record the round-trip time of a kaspad ping
+pub fn record_kaspad_ping_latency(rtt: std::time::Duration) {
+    if let Some(gauge) = KASPAD_PING_LATENCY.get() {
+        gauge.set(rtt.as_secs_f64() * 1000.0);
+    }
+}
+
+/// Record the round-robin payout wallet picked for a block template
+pub fn record_active_wallet(index: usize, wallet: &str) {
+    if let Some(gauge) = ACTIVE_WALLET_INDEX.get() {
+        gauge.set(index as f64);
+    }
+    let mut active = ACTIVE_WALLET.lock();
+    if active.as_str() != wallet {
+        *active = wallet.to_string();
+    }
+}
+
+/// Record the number of sessions open across all instances
+pub fn record_total_workers(total: i32) {
+    if let Some(gauge) = TOTAL_WORKERS.get() {
+        gauge.set(f64::from(total));
+    }
+}
+
 /// Record a worker error
 pub fn record_worker_error(instance_id: &str, wallet: &str, error: &str) {
     if let Some(counter) = ERROR_BY_WALLET.get() {
@@ -0,0 +1,130 @@
+//! Prometheus Pushgateway client for networks that cannot be scraped.
+//!
+//! Each instance pushes its own (instance-filtered) metric families to
+//! `<gateway>/metrics/job/rustbridge_<instance_num>` on a fixed interval. Failures are logged at
+//! WARN and retried on the next tick.
+
+use super::metrics::{filter_metric_families_for_instance, init_metrics};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Default push interval when `prom_push_interval_secs` is unset.
+pub const DEFAULT_PUSH_INTERVAL_SECS: u64 = 15;
+
+/// Pushgateway target for one stratum instance.
+#[derive(Clone, Debug)]
+pub struct PushGatewayConfig {
+    pub gateway_url: String,
+    pub interval: Duration,
+    /// Optional basic auth in `user:password` form.
+    pub auth: Option<String>,
+    /// Value of the `instance` label used to filter metric families (e.g. `[Instance 1]`).
+    pub instance_id: String,
+    /// Pushgateway job name (e.g. `rustbridge_1`).
+    pub job: String,
+}
+
+fn push_url(gateway_url: &str, job: &str) -> String {
+    format!("{}/metrics/job/{}", gateway_url.trim_end_matches('/'), job)
+}
+
+fn split_basic_auth(auth: &str) -> (&str, Option<&str>) {
+    match auth.split_once(':') {
+        Some((user, pass)) => (user, Some(pass)),
+        None => (auth, None),
+    }
+}
+
+fn encode_instance_metrics(instance_id: &str) -> Result<Vec<u8>, prometheus::Error> {
+    use prometheus::Encoder;
+    let encoder = prometheus::TextEncoder::new();
+    let families = filter_metric_families_for_instance(prometheus::gather(), instance_id);
+    let mut buf = Vec::new();
+    encoder.encode(&families, &mut buf)?;
+    Ok(buf)
+}
+
+async fn push_once(
+    client: &reqwest::Client,
+    cfg: &PushGatewayConfig,
+    url: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let body = encode_instance_metrics(&cfg.instance_id)?;
+    let mut req = client
+        .post(url)
+        .header("Content-Type", "text/plain; version=0.0.4")
+        .body(body);
+    if let Some(auth) = cfg.auth.as_deref() {
+        let (user, pass) = split_basic_auth(auth);
+        req = req.basic_auth(user, pass);
+    }
+    let resp = req.send().await?;
+    if !resp.status().is_success() {
+        return Err(format!("pushgateway returned HTTP {}", resp.status()).into());
+    }
+    Ok(())
+}
+
+/// Spawn the periodic push task for one instance. Stops when `shutdown_rx` flips to `true`.
+pub fn spawn_push_gateway_task(cfg: PushGatewayConfig, mut shutdown_rx: watch::Receiver<bool>) {
+    init_metrics();
+
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+        {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!("{} Pushgateway client init failed: {}", cfg.instance_id, e);
+                return;
+            }
+        };
+        let url = push_url(&cfg.gateway_url, &cfg.job);
+        tracing::info!(
+            "{} Pushing metrics to {} every {:?}",
+            cfg.instance_id,
+            url,
+            cfg.interval
+        );
+
+        let mut interval = tokio::time::interval(cfg.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() { break; }
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = push_once(&client, &cfg, &url).await {
+                        tracing::warn!("{} Pushgateway push failed: {}", cfg.instance_id, e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_url_trims_trailing_slash() {
+        assert_eq!(
+            push_url("http://gw:9091/", "rustbridge_1"),
+            "http://gw:9091/metrics/job/rustbridge_1"
+        );
+        assert_eq!(
+            push_url("http://gw:9091", "rustbridge_2"),
+            "http://gw:9091/metrics/job/rustbridge_2"
+        );
+    }
+
+    #[test]
+    fn split_basic_auth_handles_missing_password() {
+        assert_eq!(split_basic_auth("user:pa:ss"), ("user", Some("pa:ss")));
+        assert_eq!(split_basic_auth("user"), ("user", None));
+    }
+}
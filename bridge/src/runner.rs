@@ -19,7 +19,6 @@ use std::sync::{Arc, OnceLock};
 #[cfg(feature = "rkstratum_cpu_miner")]
 use std::time::Duration;
 use tokio::sync::watch;
-use tracing_subscriber::EnvFilter;
 
 #[cfg(windows)]
 use windows_sys::Win32::System::Console::{CTRL_C_EVENT, SetConsoleCtrlHandler};
@@ -150,10 +149,7 @@ pub fn default_dashboard_iframe_url(cli: &Cli) -> String {
         if !path.exists() {
             continue;
         }
-        let Ok(content) = std::fs::read_to_string(&path) else {
-            continue;
-        };
-        let Ok(cfg) = BridgeConfig::from_yaml(&content) else {
+        let Ok(cfg) = BridgeConfig::from_file(&path) else {
             continue;
         };
         let w = cfg.global.web_dashboard_port.trim();
@@ -168,6 +164,33 @@ pub fn default_dashboard_iframe_url(cli: &Cli) -> String {
         .unwrap_or_else(|| "http://127.0.0.1:3030/".to_string())
 }
 
+/// `--check-config`: load the config the bridge would use, apply CLI overrides, and run every load-time
+/// check. Returns the instance count; touches no global state, sockets, or logging.
+pub(crate) fn check_config(cli: &Cli) -> Result<usize, anyhow::Error> {
+    let requested = cli
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("config.yaml"));
+    let path = config_yaml_candidate_paths(&requested)
+        .into_iter()
+        .find(|path| path.exists())
+        .ok_or_else(|| anyhow::anyhow!("Config file {} not found", requested.display()))?;
+    let mut config = BridgeConfig::from_file(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to parse config file {}: {}", path.display(), e))?;
+    apply_cli_overrides(&mut config, cli)?;
+    if crate::kaspaapi::split_host_port(&config.global.kaspad_address).is_none() {
+        return Err(anyhow::anyhow!(
+            "kaspad_address '{}' must be host:port",
+            config.global.kaspad_address
+        ));
+    }
+    if let Some(proxy) = config.global.kaspad_proxy.as_deref() {
+        crate::kaspaapi::Socks5ProxyConfig::parse(proxy)?;
+    }
+    config.extranonce_ranges()?;
+    Ok(config.instances.len())
+}
+
 fn load_initial_config() -> Result<BridgeConfig, anyhow::Error> {
     let config_path = REQUESTED_CONFIG_PATH
         .get()
@@ -179,11 +202,7 @@ fn load_initial_config() -> Result<BridgeConfig, anyhow::Error> {
     let mut config: Option<BridgeConfig> = None;
     for path in candidates.iter() {
         if path.exists() {
-            let content = std::fs::read_to_string(path).map_err(|e| {
-                anyhow::anyhow!("Failed to read config file {}: {}", path.display(), e)
-            })?;
-
-            let parsed = BridgeConfig::from_yaml(&content).map_err(|e| {
+            let parsed = BridgeConfig::from_file(path).map_err(|e| {
                 anyhow::anyhow!("Failed to parse config file {}: {}", path.display(), e)
             })?;
 
@@ -199,6 +218,71 @@ fn load_initial_config() -> Result<BridgeConfig, anyhow::Error> {
     Ok(config.unwrap_or_default())
 }
 
+/// Re-read the config file on `SIGHUP` and log each changed field at INFO. Listeners are configured at
+/// startup, so the logged changes take effect on the next restart; `log_levels` is the exception and
+/// applies at once. No-op on non-Unix targets.
+fn spawn_config_reload_log(path: PathBuf, mut shutdown_rx: watch::Receiver<bool>) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut current = match BridgeConfig::from_file(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!(
+                    "Config reload disabled, cannot re-read {}: {}",
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        };
+        let mut hup = match signal(SignalKind::hangup()) {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Failed to install SIGHUP handler for config reload: {}", e);
+                return;
+            }
+        };
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() { break; }
+                }
+                received = hup.recv() => {
+                    if received.is_none() { break; }
+                    let reloaded = match BridgeConfig::from_file(&path) {
+                        Ok(config) => config,
+                        Err(e) => {
+                            tracing::warn!("Config reload of {} failed, keeping previous config: {}", path.display(), e);
+                            continue;
+                        }
+                    };
+                    if reloaded.global.log_levels != current.global.log_levels {
+                        tracing_setup::reload_log_levels(&reloaded.global);
+                    }
+                    let changes = BridgeConfig::diff(&current, &reloaded);
+                    for change in &changes {
+                        tracing::info!("config change: {}: {} -> {}", change.field, change.old_value, change.new_value);
+                    }
+                    tracing::info!(
+                        "Reloaded {} ({} change{}; listener settings apply on restart)",
+                        path.display(),
+                        changes.len(),
+                        if changes.len() == 1 { "" } else { "s" }
+                    );
+                    current = reloaded;
+                }
+            }
+        }
+    });
+
+    #[cfg(not(unix))]
+    {
+        let _ = (path, &mut shutdown_rx);
+    }
+}
+
 /// Log the bridge configuration at startup
 fn log_bridge_configuration(config: &BridgeConfig) {
     let instance_count = config.instances.len();
@@ -213,11 +297,25 @@ fn log_bridge_configuration(config: &BridgeConfig) {
         config.global.kaspad_address
     );
     tracing::info!("\tblock wait:      {:?}", config.global.block_wait_time);
+    for warning in config.block_wait_time_warnings() {
+        tracing::warn!("{}", warning);
+    }
     tracing::info!("\tprint stats:     {}", config.global.print_stats);
     tracing::info!("\tvar diff:        {}", config.global.var_diff);
     tracing::info!("\tshares per min:  {}", config.global.shares_per_min);
     tracing::info!("\tvar diff stats:  {}", config.global.var_diff_stats);
     tracing::info!("\tpow2 clamp:      {}", config.global.pow2_clamp);
+    if let Some(max) = config.global.pow2_clamp_max {
+        tracing::info!("\tpow2 clamp max:  {}", max);
+    }
+    tracing::info!(
+        "\tretarget jobs:   {}",
+        if config.global.clean_jobs_on_retarget.unwrap_or(true) {
+            "fresh job after each VarDiff retarget"
+        } else {
+            "new difficulty applies from the next template"
+        }
+    );
     tracing::info!("\textranonce:      auto-detected per client");
     tracing::info!("\thealth check:    {}", config.global.health_check_port);
     tracing::info!(
@@ -235,12 +333,35 @@ fn log_bridge_configuration(config: &BridgeConfig) {
         if let Some(log_to_file) = instance.log_to_file {
             tracing::info!("\t  log to file:   {}", log_to_file);
         }
+        if let Some(pool_name) = instance
+            .pool_name
+            .as_ref()
+            .or(config.global.pool_name.as_ref())
+        {
+            tracing::info!("\t  pool name:     {}", pool_name);
+        }
+        if let Some(max_workers) = instance.max_workers {
+            tracing::info!("\t  max workers:   {}", max_workers);
+        }
     }
     tracing::info!("----------------------------------");
 }
 
 /// Run the stratum bridge (Kaspa RPC, optional in-process node, stratum listeners, dashboard). Used by the CLI binary and desktop embedders.
 pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
+    if cli.check_config {
+        match check_config(&cli) {
+            Ok(instances) => {
+                println!("Config valid: {instances} instances configured");
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     // Single-config model: default to `config.yaml` for both mainnet and testnet runs.
     // `--testnet` affects the network behavior, but does not imply a different config file.
     let requested_config = cli
@@ -252,10 +373,41 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
         tracing::warn!("Failed to set requested config path - may already be initialized");
     }
 
-    let node_mode = cli.node_mode.unwrap_or(NodeMode::Inprocess);
-
     let mut config = load_initial_config()?;
     apply_cli_overrides(&mut config, &cli)?;
+    run_config(cli, config, true).await
+}
+
+/// Run the bridge from YAML text instead of a config file, for library users that build config
+/// programmatically. Everything else uses the CLI defaults (in-process node).
+pub async fn run_from_config_str(yaml: &str) -> Result<(), anyhow::Error> {
+    run_with_config(BridgeConfig::from_yaml(yaml)?).await
+}
+
+/// Run the bridge with an already-built config; everything else uses the CLI defaults (in-process node).
+pub async fn run_with_config(config: BridgeConfig) -> Result<(), anyhow::Error> {
+    run_config(Cli::default(), config, false).await
+}
+
+/// Shared body of [`run`] and [`run_with_config`]; `from_file` is false when no config file was searched.
+async fn run_config(
+    cli: Cli,
+    mut config: BridgeConfig,
+    from_file: bool,
+) -> Result<(), anyhow::Error> {
+    let requested_config = cli
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("config.yaml"));
+    let node_mode = cli.node_mode.unwrap_or(NodeMode::Inprocess);
+
+    if cli.daemon || config.global.daemonize.unwrap_or(false) {
+        // stdout/stderr are /dev/null once detached, so the log file is the only output
+        config.global.log_to_file = true;
+        for instance in &mut config.instances {
+            instance.log_to_file = Some(true);
+        }
+    }
 
     crate::host_metrics::set_embedded_kaspad(node_mode == NodeMode::Inprocess);
     crate::host_metrics::set_geoip_enabled_from_config(config.global.approximate_geo_lookup);
@@ -278,17 +430,14 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
     // Initialize tracing with WARN level by default (less verbose)
     // Can be overridden with RUST_LOG environment variable (e.g., RUST_LOG=info,debug)
     // To see more details, set RUST_LOG=info or RUST_LOG=debug
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-        // Default: warn level, but allow info from the bridge. In inprocess mode we also
-        // enable info-level logs from the embedded node (which uses the `log` crate).
-        if node_mode == NodeMode::Inprocess {
-            EnvFilter::new(
-                "warn,kaspa_stratum_bridge=info,kaspa=info,kaspad=info,kaspad_lib=info,log=info",
-            )
-        } else {
-            EnvFilter::new("warn,kaspa_stratum_bridge=info")
-        }
-    });
+    // `log_levels` in the config adds per-target levels on top (RUST_LOG wins for the targets it names)
+    // Default: warn level, but allow info from the bridge. In inprocess mode we also
+    // enable info-level logs from the embedded node (which uses the `log` crate).
+    let default_filter = if node_mode == NodeMode::Inprocess {
+        "warn,kaspa_stratum_bridge=info,kaspa=info,kaspad=info,kaspad_lib=info,log=info"
+    } else {
+        "warn,kaspa_stratum_bridge=info"
+    };
 
     // Note: The file_guard must be kept alive for the lifetime of the program
     // to ensure logs are flushed to the file
@@ -296,10 +445,13 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
     static FILE_GUARD: std::sync::OnceLock<tracing_appender::non_blocking::WorkerGuard> =
         std::sync::OnceLock::new();
     if let Some(guard) =
-        tracing_setup::init_tracing(&config, filter, node_mode == NodeMode::Inprocess)
+        tracing_setup::init_tracing(&config, default_filter, node_mode == NodeMode::Inprocess)
     {
         let _ = FILE_GUARD.set(guard);
     }
+    crate::log_suppress::set_suppressed_wallets(
+        config.global.log_suppress_wallets.iter().flatten(),
+    );
 
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
     let _ = BRIDGE_SHUTDOWN_TX.set(shutdown_tx.clone());
@@ -349,7 +501,7 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
     #[cfg(windows)]
     install_windows_ctrl_handler(shutdown_tx.clone())?;
 
-    if CONFIG_LOADED_FROM.get().and_then(|p| p.as_ref()).is_none() {
+    if from_file && CONFIG_LOADED_FROM.get().and_then(|p| p.as_ref()).is_none() {
         let config_path = requested_config.as_path();
         let cwd = std::env::current_dir().ok();
         tracing::warn!(
@@ -361,17 +513,51 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
 
     log_bridge_configuration(&config);
 
+    // Sessions across every instance, for the health check and `ks_stratum_total_workers`
+    let total_workers = health_check::GlobalWorkerCount::default();
+
     // Start global health check server if port is specified
     if !config.global.health_check_port.is_empty() {
         let health_port = config.global.health_check_port.clone();
-        health_check::spawn_health_check_server(health_port);
+        health_check::spawn_health_check_server(health_port, total_workers.clone());
     }
 
     // Create shared kaspa API client (all instances use the same node)
-    let kaspa_api = KaspaApi::new(
+    let proxy = config
+        .global
+        .kaspad_proxy
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+        .map(crate::kaspaapi::Socks5ProxyConfig::parse)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid kaspad_proxy: {}", e))?;
+    let reconnect_backoff = crate::kaspaapi::ReconnectBackoffConfig {
+        base_delay: config.global.kaspad_reconnect_base_delay_ms.map_or(
+            crate::kaspaapi::DEFAULT_RECONNECT_BASE_DELAY,
+            Duration::from_millis,
+        ),
+        max_delay: config.global.kaspad_reconnect_max_delay_ms.map_or(
+            crate::kaspaapi::DEFAULT_RECONNECT_MAX_DELAY,
+            Duration::from_millis,
+        ),
+        jitter: config.global.kaspad_reconnect_jitter.unwrap_or(true),
+    };
+    let kaspa_api = KaspaApi::new_with_options(
         config.global.kaspad_address.clone(),
         config.global.coinbase_tag_suffix.clone(),
         shutdown_rx.clone(),
+        crate::kaspaapi::KaspaConnectOptions {
+            reconnect_backoff,
+            proxy,
+            dns_cache_ttl: config
+                .global
+                .dns_cache_ttl_secs
+                .map_or(crate::kaspaapi::DEFAULT_DNS_CACHE_TTL, Duration::from_secs),
+            template_cache_ttl: config.global.template_cache_ttl_ms.map_or(
+                crate::kaspaapi::DEFAULT_TEMPLATE_CACHE_TTL,
+                Duration::from_millis,
+            ),
+        },
     )
     .await
     .map_err(|e| anyhow::anyhow!("Failed to create Kaspa API client: {}", e))?;
@@ -486,6 +672,83 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
         }
     }
 
+    // Optional worker credential store (shared by all instances, reloaded on SIGHUP)
+    let worker_credentials = if config.global.worker_password_required.unwrap_or(false)
+        || config.global.worker_credentials_file.is_some()
+    {
+        let required = config.global.worker_password_required.unwrap_or(false);
+        let path = config
+            .global
+            .worker_credentials_file
+            .as_ref()
+            .map(PathBuf::from);
+        if required && path.is_none() {
+            tracing::warn!(
+                "worker_password_required is set without worker_credentials_file; every authorize will be rejected"
+            );
+        }
+        let store = Arc::new(crate::WorkerCredentialStore::load(path, required)?);
+        crate::worker_credentials::spawn_sighup_reload(Arc::clone(&store), shutdown_rx.clone());
+        Some(store)
+    } else {
+        None
+    };
+
+    // Optional share audit log (one writer shared by all instances)
+    let share_audit = match config.global.share_audit_log.as_deref().map(str::trim) {
+        Some(path) if !path.is_empty() => {
+            let log = crate::ShareAuditLog::start(
+                &PathBuf::from(path),
+                config.global.share_audit_max_days,
+            )?;
+            tracing::info!("Share audit log: {}", path);
+            Some(Arc::new(log))
+        }
+        _ => None,
+    };
+
+    // Optional VarDiff state file (one store shared by all instances, saved every minute)
+    let vardiff_store = match config.global.var_diff_state_file.as_deref().map(str::trim) {
+        Some(path) if !path.is_empty() => {
+            let max_age_hours = config
+                .global
+                .var_diff_hint_max_age_hours
+                .unwrap_or(crate::share_handler::DEFAULT_VAR_DIFF_HINT_MAX_AGE_HOURS);
+            let store = Arc::new(crate::VarDiffStore::open(
+                path,
+                std::time::Duration::from_secs(max_age_hours.saturating_mul(3600)),
+            )?);
+            tracing::info!(
+                "VarDiff state file: {} ({} saved workers)",
+                path,
+                store.len()
+            );
+            crate::share_handler::spawn_periodic_save(Arc::clone(&store), shutdown_rx.clone());
+            Some(store)
+        }
+        _ => None,
+    };
+
+    // Optional round-robin payout wallets (one cursor shared by all instances)
+    let wallet_rotator = config
+        .global
+        .kaspad_wallets
+        .clone()
+        .and_then(crate::WalletRotator::new)
+        .map(Arc::new);
+    if let Some(rotator) = &wallet_rotator {
+        tracing::info!(
+            "Paying block templates to {} wallets in turn (kaspad_wallets)",
+            rotator.wallets().len()
+        );
+    }
+
+    if let Some(path) = CONFIG_LOADED_FROM.get().cloned().flatten() {
+        spawn_config_reload_log(path, shutdown_rx.clone());
+    }
+
+    let extranonce_ranges = config.extranonce_ranges()?;
+
     let mut instance_handles = Vec::new();
     for (idx, instance_config) in config.instances.iter().enumerate() {
         let instance_num = idx + 1;
@@ -495,8 +758,20 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
         let instance_shutdown_rx = shutdown_rx.clone();
 
         let is_first_instance = idx == 0;
+        let worker_credentials_instance = worker_credentials.clone();
+        let share_audit_instance = share_audit.clone();
+        let vardiff_store_instance = vardiff_store.clone();
+        let wallet_rotator_instance = wallet_rotator.clone();
+        let total_workers_instance = total_workers.clone();
 
         let instance_id_str = LogColors::format_instance_id(instance_num);
+        let control_rx = crate::control::register_control_channel(instance_num);
+        let extranonce_range = extranonce_ranges[idx];
+        tracing::info!(
+            "[Instance {}] extranonce range {}",
+            instance_num,
+            extranonce_range
+        );
 
         if let Some(ref prom_port) = instance.prom_port {
             let prom_port = prom_port.clone();
@@ -513,6 +788,26 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
             });
         }
 
+        if let Some(ref gateway_url) = config.global.prom_push_gateway
+            && !gateway_url.trim().is_empty()
+        {
+            let interval_secs = config
+                .global
+                .prom_push_interval_secs
+                .unwrap_or(prom::DEFAULT_PUSH_INTERVAL_SECS)
+                .max(1);
+            prom::spawn_push_gateway_task(
+                prom::PushGatewayConfig {
+                    gateway_url: gateway_url.trim().to_string(),
+                    interval: std::time::Duration::from_secs(interval_secs),
+                    auth: config.global.prom_push_gateway_auth.clone(),
+                    instance_id: instance_id_str.clone(),
+                    job: format!("rustbridge_{}", instance_num),
+                },
+                shutdown_rx.clone(),
+            );
+        }
+
         let handle = tokio::spawn(async move {
             tracing_setup::register_instance(instance_id_str.clone(), instance_num);
 
@@ -525,20 +820,17 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
 
             let bridge_config = StratumBridgeConfig {
                 instance_id: instance_id_str.clone(),
-                stratum_port: instance.stratum_port.clone(),
-                kaspad_address: global.kaspad_address.clone(),
+                // Prometheus and health check servers are started by the runner, not the listener
                 prom_port: String::new(),
-                print_stats: global.print_stats,
-                log_to_file: instance.log_to_file.unwrap_or(global.log_to_file),
                 health_check_port: String::new(),
-                block_wait_time: instance.block_wait_time.unwrap_or(global.block_wait_time),
-                min_share_diff: instance.min_share_diff,
-                var_diff: instance.var_diff.unwrap_or(global.var_diff),
-                shares_per_min: instance.shares_per_min.unwrap_or(global.shares_per_min),
-                var_diff_stats: instance.var_diff_stats.unwrap_or(global.var_diff_stats),
-                extranonce_size: instance.extranonce_size.unwrap_or(global.extranonce_size),
-                pow2_clamp: instance.pow2_clamp.unwrap_or(global.pow2_clamp),
-                coinbase_tag_suffix: global.coinbase_tag_suffix.clone(),
+                worker_credentials: worker_credentials_instance,
+                share_audit: share_audit_instance,
+                vardiff_store: vardiff_store_instance,
+                wallet_rotator: wallet_rotator_instance,
+                total_workers: Some(total_workers_instance),
+                control_rx: Some(control_rx),
+                extranonce_range: Some(extranonce_range),
+                ..instance.effective_config(&global).into()
             };
 
             listen_and_serve_with_shutdown(
@@ -585,7 +877,23 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
         {
             let _ = shutdown_wait_rx.wait_for(|v| *v).await;
         }
-        #[cfg(not(windows))]
+        #[cfg(unix)]
+        {
+            // Supervisors (and daemon mode) stop the bridge with SIGTERM
+            use tokio::signal::unix::{SignalKind, signal};
+            match signal(SignalKind::terminate()) {
+                Ok(mut term) => {
+                    tokio::select! {
+                        _ = tokio::signal::ctrl_c() => {}
+                        _ = term.recv() => {}
+                    }
+                }
+                Err(_) => {
+                    let _ = tokio::signal::ctrl_c().await;
+                }
+            }
+        }
+        #[cfg(not(any(windows, unix)))]
         {
             let _ = tokio::signal::ctrl_c().await;
         }
@@ -601,7 +909,7 @@ pub async fn run(cli: Cli) -> Result<(), anyhow::Error> {
             res
         }
         _ = &mut ctrl_c_fut => {
-            tracing::info!("Ctrl+C received, starting shutdown");
+            tracing::info!("Shutdown signal received, starting shutdown");
 
             #[cfg(not(windows))]
             {
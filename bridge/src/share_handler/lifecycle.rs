@@ -1,15 +1,19 @@
+use super::ShareAuditLog;
 use super::ShareHandler;
 use super::duplicate_submit::DuplicateSubmitGuard;
-use super::vardiff::{VAR_DIFF_THREAD_SLEEP, vardiff_compute_next_diff};
+use super::vardiff::{VAR_DIFF_THREAD_SLEEP, VARDIFF_EMERGENCY_INTERVALS, VarDiffState};
 #[cfg(feature = "rkstratum_cpu_miner")]
 use super::work_stats::RKSTRATUM_CPU_MINER_METRICS;
 use super::work_stats::{
     STATS_PRINTER_REGISTRY, STATS_PRINTER_STARTED, StatsPrinterEntry, WorkStats, format_hashrate,
 };
+use crate::block_webhook::BlockWebhook;
 use crate::kaspaapi::NODE_STATUS;
 use crate::mining_state::GetMiningState;
+use crate::pool_events::{NoopPoolEventHandler, PoolEventHandler};
 use crate::prom::*;
 use crate::stratum_context::StratumContext;
+use crate::wallet_rotator::WalletRotator;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -41,9 +45,131 @@ impl ShareHandler {
                 Duration::from_secs(180),
                 50_000,
             ))),
+            ntime_max_delta_secs: None,
+            pool_events: Arc::new(NoopPoolEventHandler),
+            kaspad_rtt_warn_threshold: Duration::from_millis(
+                super::DEFAULT_KASPAD_RTT_WARN_THRESHOLD_MS,
+            ),
+            block_submit_retries: 0,
+            block_submit_retry_delay: Duration::ZERO,
+            block_webhook: None,
+            share_audit: None,
+            wallet_rotator: None,
+            var_diff: VarDiffState::default(),
+            var_diff_target: std::sync::OnceLock::new(),
         }
     }
 
+    /// Reject shares whose `ntime` is more than this many seconds from the current time.
+    pub fn with_ntime_max_delta_secs(mut self, max_delta: Option<i64>) -> Self {
+        self.ntime_max_delta_secs = max_delta;
+        self
+    }
+
+    /// Warn about `submit_block` RPCs slower than `threshold`.
+    pub fn with_kaspad_rtt_warn_threshold(mut self, threshold: Duration) -> Self {
+        self.kaspad_rtt_warn_threshold = threshold;
+        self
+    }
+
+    /// Retry a failed `submit_block` up to `retries` times, `delay` apart (0 submits once).
+    pub fn with_block_submit_retries(mut self, retries: u32, delay: Duration) -> Self {
+        self.block_submit_retries = retries;
+        self.block_submit_retry_delay = delay;
+        self
+    }
+
+    /// Limit each VarDiff retarget to `steps.step_up` when raising and `steps.step_down` when lowering.
+    pub fn with_var_diff_steps(mut self, steps: VarDiffState) -> Self {
+        self.var_diff = steps;
+        self
+    }
+
+    /// Retarget right after an accepted share when the worker's last few shares came
+    /// `var_diff_emergency_threshold` times faster or slower than the VarDiff target, instead of
+    /// waiting for the next VarDiff tick. No-op until the VarDiff thread runs.
+    pub(crate) fn check_emergency_vardiff(&self, stats: &WorkStats) {
+        let Some(&(expected_spm, clamp)) = self.var_diff_target.get() else {
+            return;
+        };
+        let Some(window_start) = *stats.var_diff_start_time.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        let recent_spm = {
+            let mut times = stats.recent_share_times.lock();
+            times.retain(|at| *at >= window_start);
+            times.push_back(now);
+            if times.len() > VARDIFF_EMERGENCY_INTERVALS + 1 {
+                times.pop_front();
+            }
+            if times.len() <= VARDIFF_EMERGENCY_INTERVALS {
+                return;
+            }
+            let span = now.duration_since(times[0]).as_secs_f64().max(1e-3);
+            VARDIFF_EMERGENCY_INTERVALS as f64 * 60.0 / span
+        };
+
+        let current = *stats.min_diff.lock();
+        let expected_spm = self.var_diff.target_spm(expected_spm, current);
+        let Some((next, ratio)) =
+            self.var_diff
+                .emergency_next_diff(current, recent_spm, expected_spm, clamp)
+        else {
+            return;
+        };
+        *stats.min_diff.lock() = next;
+        *stats.var_diff_start_time.lock() = Some(now);
+        *stats.var_diff_shares_found.lock() = 0;
+        *stats.var_diff_window.lock() = 0;
+        stats.recent_share_times.lock().clear();
+        info!(
+            "{} VarDiff emergency retarget for {}: {:.1} spm is {:.2}x the target {:.1}, diff {:.0} -> {:.0}",
+            self.log_prefix(),
+            stats.worker_name.lock(),
+            recent_spm,
+            ratio,
+            expected_spm,
+            current,
+            next
+        );
+    }
+
+    /// Pay block templates to `rotator`'s wallets in turn instead of the miner's address.
+    pub fn with_wallet_rotator(mut self, rotator: Option<Arc<WalletRotator>>) -> Self {
+        self.wallet_rotator = rotator;
+        self
+    }
+
+    /// Address a block template for `miner_wallet` pays to: the next rotated wallet when
+    /// `kaspad_wallets` is set, otherwise the miner's own.
+    pub fn template_wallet(&self, miner_wallet: &str) -> String {
+        match &self.wallet_rotator {
+            Some(rotator) => rotator.next_wallet().to_string(),
+            None => miner_wallet.to_string(),
+        }
+    }
+
+    /// Write every `mining.submit` outcome to `log`.
+    pub fn with_share_audit(mut self, log: Option<Arc<ShareAuditLog>>) -> Self {
+        self.share_audit = log;
+        self
+    }
+
+    /// POST found blocks to `webhook` in the background.
+    pub fn with_block_webhook(mut self, webhook: Option<Arc<BlockWebhook>>) -> Self {
+        self.block_webhook = webhook;
+        self
+    }
+
+    /// Report accepted shares and found blocks to `handler` (`None` keeps the no-op default).
+    pub fn with_pool_event_handler(mut self, handler: Option<Arc<dyn PoolEventHandler>>) -> Self {
+        if let Some(handler) = handler {
+            self.pool_events = handler;
+        }
+        self
+    }
+
     fn workstats_session_start_unix(stats: &WorkStats) -> f64 {
         let now_unix = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -630,6 +756,10 @@ impl ShareHandler {
     ) {
         let stats = Arc::clone(&self.stats);
         let prefix = self.log_prefix();
+        let steps = self.var_diff;
+        let _ = self
+            .var_diff_target
+            .set((expected_share_rate.max(1) as f64, clamp));
 
         tokio::spawn(async move {
             let expected_spm = expected_share_rate.max(1) as f64;
@@ -637,8 +767,13 @@ impl ShareHandler {
 
             if log_stats {
                 info!(
-                    "{} VarDiff enabled (target={} shares/min, tick={}s, pow2_clamp={})",
-                    prefix, expected_spm, VAR_DIFF_THREAD_SLEEP, clamp
+                    "{} VarDiff enabled (target={} shares/min, tick={}s, pow2_clamp={}, step up {}x / down {}x)",
+                    prefix,
+                    expected_spm,
+                    VAR_DIFF_THREAD_SLEEP,
+                    clamp,
+                    steps.step_up,
+                    steps.step_down
                 );
             } else {
                 debug!(
@@ -671,8 +806,9 @@ impl ShareHandler {
                     let elapsed = now.duration_since(start).as_secs_f64().max(0.0);
                     let shares = *v.var_diff_shares_found.lock() as f64;
                     let current = *v.min_diff.lock();
+                    let expected_spm = steps.target_spm(expected_spm, current);
                     let next_opt =
-                        vardiff_compute_next_diff(current, shares, elapsed, expected_spm, clamp);
+                        steps.compute_next_diff(current, shares, elapsed, expected_spm, clamp);
                     let Some(next) = next_opt else { continue };
 
                     *v.min_diff.lock() = next;
@@ -706,22 +842,11 @@ impl ShareHandler {
 #[cfg(test)]
 mod retention_tests {
     use super::*;
-    use crate::mining_state::MiningState;
     use std::sync::Arc;
-    use tokio::sync::mpsc;
 
     fn test_ctx() -> Arc<StratumContext> {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
-            let addr = listener.local_addr().unwrap();
-            let accept_handle = tokio::spawn(async move { listener.accept().await });
-            let _stream = tokio::net::TcpStream::connect(addr).await.unwrap();
-            let (accepted_stream, _) = accept_handle.await.unwrap().unwrap();
-            let state = Arc::new(MiningState::new());
-            let (tx, _rx) = mpsc::unbounded_channel();
-            StratumContext::new("127.0.0.1".to_string(), 12345, accepted_stream, state, tx)
-        })
+        rt.block_on(crate::test_support::test_context()).0
     }
 
     #[test]
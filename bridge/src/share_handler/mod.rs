@@ -1,17 +1,33 @@
 mod duplicate_submit;
 mod kaspa_api_trait;
 mod lifecycle;
+mod share_audit;
 mod submit;
+mod submit_queue;
 mod vardiff;
+mod vardiff_store;
 mod work_stats;
 
 pub use kaspa_api_trait::KaspaApiTrait;
 pub use lifecycle::average_worker_spm;
+pub use share_audit::{ShareAuditLog, ShareAuditRecord};
 pub use submit::{SubmitError, SubmitRunError};
+pub use submit_queue::{
+    DEFAULT_KASPAD_RTT_WARN_THRESHOLD_MS, DEFAULT_MAX_SUBMIT_CONCURRENCY, SHARE_QUEUE_CAPACITY,
+    ShareQueue,
+};
+pub use vardiff::{
+    DEFAULT_VAR_DIFF_EMERGENCY_THRESHOLD, DEFAULT_VAR_DIFF_STEP_DOWN, DEFAULT_VAR_DIFF_STEP_UP,
+    VarDiffState,
+};
+pub use vardiff_store::{
+    DEFAULT_VAR_DIFF_HINT_MAX_AGE_HOURS, VarDiffRecord, VarDiffStore, spawn_periodic_save,
+};
 #[cfg(feature = "rkstratum_cpu_miner")]
 pub use work_stats::{RKSTRATUM_CPU_MINER_METRICS, set_rkstratum_cpu_miner_metrics};
 pub use work_stats::{STATS_PRINTER_STARTED, WorkStats};
 
+use crate::pool_events::PoolEventHandler;
 use duplicate_submit::DuplicateSubmitGuard;
 use parking_lot::Mutex;
 use std::collections::HashMap;
@@ -24,6 +40,26 @@ pub struct ShareHandler {
     overall: Arc<WorkStats>,
     instance_id: String, // Instance identifier for logging
     duplicate_submit_guard: Arc<Mutex<DuplicateSubmitGuard>>,
+    /// Largest accepted gap between a submitted `ntime` and the clock (`None` skips the check).
+    ntime_max_delta_secs: Option<i64>,
+    /// Embedder callbacks for accepted shares and found blocks.
+    pool_events: Arc<dyn PoolEventHandler>,
+    /// `submit_block` RPCs slower than this are logged at WARN.
+    kaspad_rtt_warn_threshold: std::time::Duration,
+    /// Extra `submit_block` attempts after an RPC error, and the pause between them.
+    block_submit_retries: u32,
+    block_submit_retry_delay: std::time::Duration,
+    /// Chat webhook told about blocks kaspad accepted.
+    block_webhook: Option<Arc<crate::block_webhook::BlockWebhook>>,
+    /// Per-submit JSON lines (`share_audit_log`).
+    share_audit: Option<Arc<ShareAuditLog>>,
+    /// Round-robin payout wallets; `None` pays each miner's own address.
+    wallet_rotator: Option<Arc<crate::wallet_rotator::WalletRotator>>,
+    /// Up/down retarget limits for the VarDiff thread.
+    var_diff: VarDiffState,
+    /// Target shares/min and pow2 clamp, set once the VarDiff thread starts (emergency retargets
+    /// stay off until then).
+    var_diff_target: std::sync::OnceLock<(f64, bool)>,
 }
 
 impl ShareHandler {
@@ -0,0 +1,189 @@
+//! Append-only share audit trail (`share_audit_log`): one JSON line per `mining.submit`, written by a
+//! background task through a daily-rotated file (`share_audit_max_days` rotated files are kept).
+
+use super::ShareHandler;
+use crate::jsonrpc_event::JsonRpcEvent;
+use crate::stratum_context::StratumContext;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use tokio::sync::mpsc;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+/// One audited `mining.submit`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShareAuditRecord {
+    /// Unix seconds when the submit was answered.
+    pub ts: u64,
+    /// `wallet.worker`.
+    pub worker: String,
+    pub job_id: String,
+    pub nonce: String,
+    /// Stratum difficulty the share was checked against.
+    pub diff: f64,
+    /// `accepted` or `rejected`, as answered to the miner.
+    pub result: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ShareAuditRecord {
+    /// Record for `event` as submitted on `ctx`; job id and nonce are kept as sent.
+    pub fn new(
+        ctx: &StratumContext,
+        event: &JsonRpcEvent,
+        accepted: bool,
+        reason: Option<&str>,
+    ) -> Self {
+        let param = |idx: usize| match event.params.get(idx) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        let worker = {
+            let id = ctx.identity.lock();
+            format!("{}.{}", id.wallet_addr, id.worker_name)
+        };
+        Self {
+            ts: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            worker,
+            job_id: param(1),
+            nonce: param(2),
+            diff: ctx
+                .state
+                .stratum_diff()
+                .map(|d| d.diff_value)
+                .unwrap_or(0.0),
+            result: if accepted { "accepted" } else { "rejected" },
+            reason: reason.map(str::to_string),
+        }
+    }
+}
+
+/// Handle to the audit writer task; cheap to share between instances.
+#[derive(Debug)]
+pub struct ShareAuditLog {
+    tx: mpsc::UnboundedSender<ShareAuditRecord>,
+}
+
+impl ShareAuditLog {
+    /// Open `path` (rotated daily as `<name>.YYYY-MM-DD`) and start the writer. Must run inside a
+    /// tokio runtime.
+    pub fn start(path: &Path, max_days: Option<u32>) -> Result<Self, anyhow::Error> {
+        let file_name = path.file_name().ok_or_else(|| {
+            anyhow::anyhow!("share_audit_log {} has no file name", path.display())
+        })?;
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let mut builder = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(file_name.to_string_lossy());
+        if let Some(days) = max_days.filter(|days| *days > 0) {
+            builder = builder.max_log_files(days as usize);
+        }
+        let mut appender = builder.build(dir).map_err(|e| {
+            anyhow::anyhow!("Failed to open share audit log {}: {}", path.display(), e)
+        })?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<ShareAuditRecord>();
+        tokio::task::spawn_blocking(move || {
+            while let Some(record) = rx.blocking_recv() {
+                let Ok(mut line) = serde_json::to_string(&record) else {
+                    continue;
+                };
+                line.push('\n');
+                if let Err(e) = appender.write_all(line.as_bytes()) {
+                    tracing::warn!("share audit log write failed: {}", e);
+                }
+            }
+            let _ = appender.flush();
+        });
+        Ok(Self { tx })
+    }
+
+    pub fn record(&self, record: ShareAuditRecord) {
+        let _ = self.tx.send(record);
+    }
+}
+
+impl ShareHandler {
+    /// Append the answer given to `event` to the audit log, when one is configured.
+    pub(crate) fn audit_share(
+        &self,
+        ctx: &StratumContext,
+        event: &JsonRpcEvent,
+        accepted: bool,
+        reason: Option<&str>,
+    ) {
+        if let Some(log) = &self.share_audit {
+            log.record(ShareAuditRecord::new(ctx, event, accepted, reason));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn submits_are_appended_as_json_lines() {
+        let dir = std::env::temp_dir().join(format!("rkstratum_audit_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = ShareAuditLog::start(&dir.join("shares.log"), Some(7)).unwrap();
+
+        let (ctx, _client) = crate::test_support::test_context().await;
+        {
+            let mut id = ctx.identity.lock();
+            id.wallet_addr = "kaspa:qtest".to_string();
+            id.worker_name = "rig1".to_string();
+        }
+        let event = JsonRpcEvent::new(
+            Some("4".to_string()),
+            "mining.submit",
+            vec![
+                serde_json::json!("kaspa:qtest.rig1"),
+                serde_json::json!("7"),
+                serde_json::json!("00ff"),
+            ],
+        );
+        log.record(ShareAuditRecord::new(&ctx, &event, true, None));
+        log.record(ShareAuditRecord::new(
+            &ctx,
+            &event,
+            false,
+            Some("low difficulty"),
+        ));
+        drop(log);
+
+        let mut lines = Vec::new();
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let content: String = std::fs::read_dir(&dir)
+                .unwrap()
+                .filter_map(|entry| std::fs::read_to_string(entry.unwrap().path()).ok())
+                .collect();
+            lines = content.lines().map(str::to_string).collect();
+            if lines.len() == 2 {
+                break;
+            }
+        }
+        assert_eq!(lines.len(), 2);
+        let first: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first["worker"], "kaspa:qtest.rig1");
+        assert_eq!(first["job_id"], "7");
+        assert_eq!(first["nonce"], "00ff");
+        assert_eq!(first["result"], "accepted");
+        assert!(first.get("reason").is_none());
+        let second: serde_json::Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(second["result"], "rejected");
+        assert_eq!(second["reason"], "low difficulty");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
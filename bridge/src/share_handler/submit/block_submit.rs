@@ -7,14 +7,18 @@ use super::error::{BlockSubmitRejection, SubmitRunError, classify_block_submit_e
 use super::parse::PreparedSubmit;
 use crate::{
     log_colors::LogColors,
+    pool_events::{BlockFoundEvent, WorkerInfo},
     prom::{
         record_block_accepted_by_node, record_block_found, record_block_not_confirmed_blue,
-        record_invalid_share, record_stale_share,
+        record_block_submission_retry, record_invalid_share, record_kaspad_submit_rtt,
+        record_stale_share,
     },
     stratum_context::StratumContext,
+    worker_registry::WORKER_REGISTRY,
 };
 use kaspa_consensus_core::block::Block;
 use kaspa_consensus_core::header::Header;
+use kaspa_rpc_core::SubmitBlockResponse;
 use num_bigint::BigUint;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -31,6 +35,61 @@ pub(super) enum BlockSubmitFlowResult {
     Finished,
 }
 
+/// One `submit_block` RPC. Returns the result and how long the RPC took.
+async fn submit_block_once(
+    handler: &ShareHandler,
+    kaspa_api: &Arc<dyn KaspaApiTrait + Send + Sync>,
+    block: &Block,
+) -> (anyhow::Result<SubmitBlockResponse>, Duration) {
+    let started = Instant::now();
+    let result = kaspa_api.submit_block(block.clone()).await;
+    let rtt = started.elapsed();
+    let rtt_ms = rtt.as_secs_f64() * 1000.0;
+    record_kaspad_submit_rtt(&handler.instance_id, rtt_ms);
+    if rtt > handler.kaspad_rtt_warn_threshold {
+        warn!(
+            "{} submit_block took {:.0} ms (threshold {} ms); kaspad may be overloaded",
+            handler.log_prefix(),
+            rtt_ms,
+            handler.kaspad_rtt_warn_threshold.as_millis()
+        );
+    }
+    (result, rtt)
+}
+
+/// `submit_block`, tried again up to `block_submit_retries` times after RPC errors other than a
+/// duplicate block. Returns the last result, how many retries it took and the last RPC's round trip.
+async fn submit_block_with_retries(
+    handler: &ShareHandler,
+    kaspa_api: &Arc<dyn KaspaApiTrait + Send + Sync>,
+    block: &Block,
+) -> (anyhow::Result<SubmitBlockResponse>, u32, Duration) {
+    let (mut result, mut rtt) = submit_block_once(handler, kaspa_api, block).await;
+    let mut retries = 0;
+    while let Err(e) = &result
+        && retries < handler.block_submit_retries
+        && classify_block_submit_error_message(&e.to_string())
+            != BlockSubmitRejection::DuplicateBlockStale
+    {
+        retries += 1;
+        warn!(
+            "{} {} {}",
+            handler.log_prefix(),
+            LogColors::block("[BLOCK]"),
+            format!(
+                "submit_block failed ({}), retry {}/{} in {:?}",
+                e, retries, handler.block_submit_retries, handler.block_submit_retry_delay
+            )
+        );
+        tokio::time::sleep(handler.block_submit_retry_delay).await;
+        (result, rtt) = submit_block_once(handler, kaspa_api, block).await;
+    }
+    if retries > 0 {
+        record_block_submission_retry(&handler.instance_id, result.is_ok());
+    }
+    (result, retries, rtt)
+}
+
 /// Logs, builds the block, submits, spawns blue-confirm task, or handles duplicate / RPC errors.
 #[allow(clippy::too_many_arguments)]
 pub(super) async fn run_block_found_submit_flow(
@@ -248,7 +307,9 @@ pub(super) async fn run_block_found_submit_flow(
         "Calling kaspa_api.submit_block()..."
     );
 
-    let block_submit_result = kaspa_api.submit_block(block.clone()).await;
+    let (block_submit_result, retries, rtt) =
+        submit_block_with_retries(handler, kaspa_api, &block).await;
+    ctx.set_submit_rtt_ms(rtt.as_secs_f64() * 1000.0);
 
     match block_submit_result {
         Ok(response) => {
@@ -309,6 +370,16 @@ pub(super) async fn run_block_found_submit_flow(
             let prom_worker = crate::prom::worker_context(&handler.instance_id, ctx.as_ref(), "");
 
             record_block_accepted_by_node(&prom_worker);
+            let found = BlockFoundEvent {
+                worker: WorkerInfo::from_context(&handler.instance_id, ctx.as_ref()),
+                block_hash: block_hash.clone(),
+                nonce: nonce_val,
+                blue_score,
+            };
+            if let Some(webhook) = &handler.block_webhook {
+                webhook.notify(&found);
+            }
+            handler.pool_events.on_block_found(found);
 
             let kaspa_api = Arc::clone(kaspa_api);
             let block_hash_for_confirm = block_hash.clone();
@@ -366,12 +437,15 @@ pub(super) async fn run_block_found_submit_flow(
         }
         Err(e) => {
             let prefix = handler.log_prefix();
-            let error_str = e.to_string();
+            let error_str = format!("{e:#}");
             error!(
                 "{} {} {}",
                 prefix,
                 LogColors::block("[BLOCK]"),
-                LogColors::error("Block submission FAILED")
+                LogColors::error(&format!(
+                    "Block submission FAILED after {} attempt(s)",
+                    retries + 1
+                ))
             );
             error!(
                 "{} {} {} {}",
@@ -421,12 +495,14 @@ pub(super) async fn run_block_found_submit_flow(
                 let stats = handler.get_create_stats(ctx.as_ref());
                 *stats.stale_shares.lock() += 1;
                 *handler.overall.stale_shares.lock() += 1;
+                WORKER_REGISTRY.record_rejected(&handler.instance_id, ctx.as_ref());
 
                 record_stale_share(&crate::prom::worker_context(
                     &handler.instance_id,
                     ctx.as_ref(),
                     "",
                 ));
+                handler.audit_share(ctx.as_ref(), event, false, Some("stale block"));
                 ctx.reply_stale_share(event.id.clone()).await?;
                 return Ok(BlockSubmitFlowResult::Finished);
             }
@@ -455,6 +531,7 @@ pub(super) async fn run_block_found_submit_flow(
             let stats = handler.get_create_stats(ctx.as_ref());
             *stats.invalid_shares.lock() += 1;
             *handler.overall.invalid_shares.lock() += 1;
+            WORKER_REGISTRY.record_rejected(&handler.instance_id, ctx.as_ref());
 
             record_invalid_share(&crate::prom::worker_context(
                 &handler.instance_id,
@@ -467,6 +544,7 @@ pub(super) async fn run_block_found_submit_flow(
                 let mut guard = handler.duplicate_submit_guard.lock();
                 guard.set_outcome(&prep.submit_key, now, DuplicateSubmitOutcome::Bad);
             }
+            handler.audit_share(ctx.as_ref(), event, false, Some("block rejected by node"));
             ctx.reply_bad_share(event.id.clone()).await?;
             Ok(BlockSubmitFlowResult::Finished)
         }
@@ -475,8 +553,88 @@ pub(super) async fn run_block_found_submit_flow(
 
 #[cfg(test)]
 mod tests {
-    use super::{BlockSubmitRejection, classify_block_submit_error_message};
+    use super::{
+        BlockSubmitRejection, KaspaApiTrait, ShareHandler, classify_block_submit_error_message,
+        submit_block_with_retries,
+    };
+    use kaspa_consensus_core::block::Block;
+    use kaspa_hashes::Hash;
     use kaspa_rpc_core::{SubmitBlockReport, SubmitBlockResponse};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    /// Fails `submit_block` with `error` for the first `failures` calls, then accepts.
+    struct FlakyKaspaApi {
+        failures: u32,
+        error: &'static str,
+        calls: AtomicU32,
+    }
+
+    #[async_trait::async_trait]
+    impl KaspaApiTrait for FlakyKaspaApi {
+        async fn get_block_template(&self, _: &str, _: &str, _: &str) -> anyhow::Result<Block> {
+            anyhow::bail!("offline")
+        }
+
+        async fn submit_block(&self, _: Block) -> anyhow::Result<SubmitBlockResponse> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                anyhow::bail!(self.error)
+            }
+            Ok(SubmitBlockResponse {
+                report: SubmitBlockReport::Success,
+            })
+        }
+
+        async fn get_balances_by_addresses(
+            &self,
+            _: &[String],
+        ) -> anyhow::Result<Vec<(String, u64)>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_current_block_color(&self, _: &str) -> anyhow::Result<bool> {
+            Ok(false)
+        }
+
+        async fn is_node_synced_for_mining(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn block_submit_retries_transient_errors_only() {
+        let handler = ShareHandler::new("retry-test".to_string())
+            .with_block_submit_retries(2, Duration::from_millis(1));
+        let block = Block::from_precomputed_hash(Hash::from_bytes([1; 32]), vec![]);
+
+        let flaky: Arc<dyn KaspaApiTrait + Send + Sync> = Arc::new(FlakyKaspaApi {
+            failures: 1,
+            error: "connection reset by peer",
+            calls: AtomicU32::new(0),
+        });
+        let (result, retries, _) = submit_block_with_retries(&handler, &flaky, &block).await;
+        assert!(result.is_ok());
+        assert_eq!(retries, 1);
+
+        let down: Arc<dyn KaspaApiTrait + Send + Sync> = Arc::new(FlakyKaspaApi {
+            failures: u32::MAX,
+            error: "timed out",
+            calls: AtomicU32::new(0),
+        });
+        let (result, retries, _) = submit_block_with_retries(&handler, &down, &block).await;
+        assert!(result.is_err());
+        assert_eq!(retries, 2);
+
+        let duplicate: Arc<dyn KaspaApiTrait + Send + Sync> = Arc::new(FlakyKaspaApi {
+            failures: 1,
+            error: "rpc error: ErrDuplicateBlock",
+            calls: AtomicU32::new(0),
+        });
+        let (result, retries, _) = submit_block_with_retries(&handler, &duplicate, &block).await;
+        assert!(result.is_err());
+        assert_eq!(retries, 0);
+    }
 
     #[test]
     fn submit_block_report_success_is_accepted() {
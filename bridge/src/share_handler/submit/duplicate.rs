@@ -29,6 +29,7 @@ pub(super) async fn respond_on_duplicate(
     if let Some(outcome) = duplicate_outcome {
         match outcome {
             DuplicateSubmitOutcome::Accepted | DuplicateSubmitOutcome::InFlight => {
+                handler.audit_share(ctx, event, true, Some("duplicate"));
                 ctx.reply(JsonRpcResponse {
                     id: event.id.clone(),
                     result: Some(serde_json::Value::Bool(true)),
@@ -38,16 +39,19 @@ pub(super) async fn respond_on_duplicate(
                 return Ok(true);
             }
             DuplicateSubmitOutcome::Stale => {
+                handler.audit_share(ctx, event, false, Some("duplicate (stale)"));
                 ctx.reply_stale_share(event.id.clone()).await?;
                 return Ok(true);
             }
             DuplicateSubmitOutcome::LowDiff => {
+                handler.audit_share(ctx, event, false, Some("duplicate (low difficulty)"));
                 if let Some(id) = &event.id {
                     let _ = ctx.reply_low_diff_share(id).await;
                 }
                 return Ok(true);
             }
             DuplicateSubmitOutcome::Bad => {
+                handler.audit_share(ctx, event, false, Some("duplicate (bad share)"));
                 ctx.reply_bad_share(event.id.clone()).await?;
                 return Ok(true);
             }
@@ -7,19 +7,42 @@ use super::parse::PreparedSubmit;
 use crate::{
     jsonrpc_event::{JsonRpcEvent, JsonRpcResponse},
     mining_state::GetMiningState,
-    prom::{record_share_found, record_weak_share, worker_context},
+    pool_events::{ShareAcceptedEvent, WorkerInfo},
+    prom::{
+        record_invalid_share, record_share_difficulty_accepted, record_share_found,
+        record_weak_share, worker_context,
+    },
     stratum_context::StratumContext,
+    worker_registry::WORKER_REGISTRY,
 };
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::debug;
 
+/// Turn a share down as low difficulty (code 23) without saying which check failed.
+pub(super) async fn reject_as_low_diff(
+    handler: &ShareHandler,
+    ctx: &StratumContext,
+    event: &JsonRpcEvent,
+) -> Result<(), SubmitRunError> {
+    let stats = handler.get_create_stats(ctx);
+    *stats.invalid_shares.lock() += 1;
+    *handler.overall.invalid_shares.lock() += 1;
+    WORKER_REGISTRY.record_rejected(&handler.instance_id, ctx);
+    record_invalid_share(&worker_context(&handler.instance_id, ctx, ""));
+    if let Some(id) = &event.id {
+        let _ = ctx.reply_low_diff_share(id).await;
+    }
+    Ok(())
+}
+
 pub(super) async fn after_pow_loop(
     handler: &ShareHandler,
     ctx: Arc<StratumContext>,
     event: &JsonRpcEvent,
     prep: &PreparedSubmit,
     invalid_share: bool,
+    share_diff: f64,
 ) -> Result<(), SubmitRunError> {
     let state = GetMiningState(ctx.as_ref());
 
@@ -29,8 +52,10 @@ pub(super) async fn after_pow_loop(
         debug!("low diff share confirmed");
         *stats.invalid_shares.lock() += 1;
         *handler.overall.invalid_shares.lock() += 1;
+        WORKER_REGISTRY.record_rejected(&handler.instance_id, ctx.as_ref());
 
         record_weak_share(&worker_context(&handler.instance_id, ctx.as_ref(), ""));
+        handler.audit_share(ctx.as_ref(), event, false, Some("low difficulty"));
 
         if let Some(id) = &event.id {
             let _ = ctx.reply_low_diff_share(id).await;
@@ -48,16 +73,21 @@ pub(super) async fn after_pow_loop(
     *stats.shares_found.lock() += 1;
     *stats.var_diff_shares_found.lock() += 1;
 
-    let hash_value = state.stratum_diff().map(|d| d.hash_value).unwrap_or(0.0);
+    let stratum_diff = state.stratum_diff();
+    let hash_value = stratum_diff.as_ref().map(|d| d.hash_value).unwrap_or(0.0);
 
     *stats.shares_diff.lock() += hash_value;
     *stats.last_share.lock() = Instant::now();
+    ctx.record_accepted_share();
+    handler.check_emergency_vardiff(&stats);
     *handler.overall.shares_found.lock() += 1;
+    WORKER_REGISTRY.record_accepted(&handler.instance_id, ctx.as_ref(), hash_value);
 
     record_share_found(
         &worker_context(&handler.instance_id, ctx.as_ref(), ""),
         hash_value,
     );
+    record_share_difficulty_accepted(&handler.instance_id, share_diff);
 
     {
         let now = Instant::now();
@@ -65,6 +95,12 @@ pub(super) async fn after_pow_loop(
         guard.set_outcome(&prep.submit_key, now, DuplicateSubmitOutcome::Accepted);
     }
 
+    handler.pool_events.on_share_accepted(ShareAcceptedEvent {
+        worker: WorkerInfo::from_context(&handler.instance_id, ctx.as_ref()),
+        difficulty: stratum_diff.map(|d| d.diff_value).unwrap_or(0.0),
+    });
+
+    handler.audit_share(ctx.as_ref(), event, true, None);
     ctx.reply(JsonRpcResponse {
         id: event.id.clone(),
         result: Some(serde_json::Value::Bool(true)),
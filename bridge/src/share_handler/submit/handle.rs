@@ -10,6 +10,7 @@ use super::pow_loop::{self, PowDone};
 use crate::jsonrpc_event::JsonRpcEvent;
 use crate::stratum_context::StratumContext;
 use std::sync::Arc;
+use tracing::debug;
 
 pub(super) async fn handle_submit(
     handler: &ShareHandler,
@@ -17,7 +18,59 @@ pub(super) async fn handle_submit(
     event: JsonRpcEvent,
     kaspa_api: Arc<dyn KaspaApiTrait + Send + Sync>,
 ) -> Result<(), SubmitRunError> {
-    let prep = parse::prepare(handler, ctx.as_ref(), &event)?;
+    let prep = match parse::prepare(handler, ctx.as_ref(), &event) {
+        Ok(prep) => prep,
+        Err(e) => {
+            handler.audit_share(ctx.as_ref(), &event, false, Some(&e.to_string()));
+            return Err(e.into());
+        }
+    };
+
+    if prep.extranonce2_out_of_range {
+        debug!(
+            "{} extranonce2 from {} outside the assigned {} bytes, rejecting",
+            handler.log_prefix(),
+            ctx.remote_addr,
+            ctx.extranonce2_size()
+        );
+        handler.audit_share(
+            ctx.as_ref(),
+            &event,
+            false,
+            Some("extranonce2 out of range"),
+        );
+        return finish::reject_as_low_diff(handler, ctx.as_ref(), &event).await;
+    }
+
+    if prep.version_bits_out_of_mask {
+        debug!(
+            "{} version bits from {} outside the negotiated mask {:08x}, rejecting",
+            handler.log_prefix(),
+            ctx.remote_addr,
+            ctx.version_rolling_mask().unwrap_or_default()
+        );
+        handler.audit_share(
+            ctx.as_ref(),
+            &event,
+            false,
+            Some("version bits outside mask"),
+        );
+        return finish::reject_as_low_diff(handler, ctx.as_ref(), &event).await;
+    }
+
+    if let (Some(delta), Some(max_delta)) = (prep.ntime_delta_secs, handler.ntime_max_delta_secs)
+        && delta.abs() > max_delta
+    {
+        debug!(
+            "{} ntime from {} is {}s off the current time (max {}s), rejecting",
+            handler.log_prefix(),
+            ctx.remote_addr,
+            delta,
+            max_delta
+        );
+        handler.audit_share(ctx.as_ref(), &event, false, Some("ntime out of range"));
+        return finish::reject_as_low_diff(handler, ctx.as_ref(), &event).await;
+    }
 
     if duplicate::respond_on_duplicate(handler, ctx.as_ref(), &event, &prep.submit_key).await? {
         return Ok(());
@@ -27,8 +80,9 @@ pub(super) async fn handle_submit(
         .await?
     {
         PowDone::AlreadyFinished => Ok(()),
-        PowDone::Continue { invalid_share } => {
-            finish::after_pow_loop(handler, ctx, &event, &prep, invalid_share).await
-        }
+        PowDone::Continue {
+            invalid_share,
+            share_diff,
+        } => finish::after_pow_loop(handler, ctx, &event, &prep, invalid_share, share_diff).await,
     }
 }
@@ -20,6 +20,61 @@ pub(super) struct PreparedSubmit {
     #[allow(dead_code)]
     pub final_nonce_str: String,
     pub submit_key: String,
+    /// Extranonce2 rolled past the bytes assigned under `max_nonce_range_bits`.
+    pub extranonce2_out_of_range: bool,
+    /// Version bits outside the mask negotiated with `mining.multi_version`.
+    pub version_bits_out_of_mask: bool,
+    /// Submitted `ntime` minus the current time, when the miner sent one.
+    pub ntime_delta_secs: Option<i64>,
+}
+
+/// `ntime` of a 5-param submit (`[worker, job, extranonce2, ntime, nonce]`) in Unix seconds.
+/// Accepts a JSON number or hex string, in seconds or (Kaspa header) milliseconds.
+pub(super) fn submitted_ntime_secs(params: &[Value]) -> Option<i64> {
+    if params.len() < 5 {
+        return None;
+    }
+    let raw = match &params[3] {
+        Value::Number(n) => n.as_u64()?,
+        Value::String(s) => u64::from_str_radix(s.trim_start_matches("0x"), 16).ok()?,
+        _ => return None,
+    };
+    // Anything past year 5138 in seconds is really milliseconds
+    let secs = if raw >= 100_000_000_000 {
+        raw / 1000
+    } else {
+        raw
+    };
+    i64::try_from(secs).ok()
+}
+
+/// Whether the version bits of a 6-param submit (`[worker, job, extranonce2, ntime, nonce,
+/// version_bits]`) stay inside `mask`. Submits without them pass; unparsable bits do not.
+pub(super) fn version_bits_in_mask(params: &[Value], mask: u32) -> bool {
+    let Some(bits) = params.get(5) else {
+        return true;
+    };
+    let bits = match bits {
+        Value::String(s) => u32::from_str_radix(s.trim_start_matches("0x"), 16).ok(),
+        Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+        _ => None,
+    };
+    bits.is_some_and(|bits| bits & !mask == 0)
+}
+
+/// Whether the miner-rolled part of `nonce_hex` fits in `extranonce2_size` bytes. A full 8-byte
+/// nonce that starts with the session extranonce is checked without that prefix.
+pub(super) fn extranonce2_in_range(
+    nonce_hex: &str,
+    extranonce: &str,
+    extranonce2_size: usize,
+) -> bool {
+    let extranonce2 = if nonce_hex.len() > 16 - extranonce.len().min(16) {
+        nonce_hex.strip_prefix(extranonce).unwrap_or(nonce_hex)
+    } else {
+        nonce_hex
+    };
+    extranonce2.trim_start_matches('0').len() <= extranonce2_size * 2
 }
 
 /// Validate params, resolve job, parse nonce; build `submit_key` for duplicate guard.
@@ -180,6 +235,28 @@ pub(super) fn prepare(
         nonce_str.len()
     );
 
+    // Only miners told an `extranonce2_size` are held to it
+    let profile = ctx.miner_profile();
+    let extranonce2_out_of_range = ctx.nonce_range_limited()
+        && (profile.sends_extranonce2_size() || profile.is_nicehash())
+        && !extranonce2_in_range(
+            &nonce_str,
+            ctx.extranonce.lock().as_str(),
+            ctx.extranonce2_size(),
+        );
+
+    let version_bits_out_of_mask = ctx
+        .version_rolling_mask()
+        .is_some_and(|mask| !version_bits_in_mask(&event.params, mask));
+
+    let ntime_delta_secs = submitted_ntime_secs(&event.params).map(|ntime| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        ntime - now
+    });
+
     let mut final_nonce_str = nonce_str.clone();
     {
         let extranonce = ctx.extranonce.lock();
@@ -229,5 +306,80 @@ pub(super) fn prepare(
         nonce_val,
         final_nonce_str,
         submit_key,
+        extranonce2_out_of_range,
+        version_bits_out_of_mask,
+        ntime_delta_secs,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extranonce2_range_check() {
+        // Bitmain (no extranonce) limited to 4 rolled bytes
+        assert!(extranonce2_in_range("00000000deadbeef", "", 4));
+        assert!(extranonce2_in_range("deadbeef", "", 4));
+        assert!(!extranonce2_in_range("00000001deadbeef", "", 4));
+        // Full nonce echoing the 2-byte extranonce is checked past the prefix
+        assert!(extranonce2_in_range("abcd00000000beef", "abcd", 4));
+        assert!(!extranonce2_in_range("abcd0001deadbeef", "abcd", 4));
+        assert!(extranonce2_in_range("0000beef", "abcd", 4));
+    }
+
+    #[test]
+    fn reads_ntime_from_five_param_submits() {
+        let submit = |ntime: Value| {
+            vec![
+                Value::from("kaspa:addr.rig"),
+                Value::from("7"),
+                Value::from("00000000"),
+                ntime,
+                Value::from("00000000deadbeef"),
+            ]
+        };
+        assert_eq!(
+            submitted_ntime_secs(&submit(Value::from("6553f100"))),
+            Some(0x6553_f100)
+        );
+        assert_eq!(
+            submitted_ntime_secs(&submit(Value::from(1_700_000_000_123u64))),
+            Some(1_700_000_000)
+        );
+        assert_eq!(submitted_ntime_secs(&submit(Value::Null)), None);
+        assert_eq!(submitted_ntime_secs(&submit(Value::from("xyz"))), None);
+        assert_eq!(submitted_ntime_secs(&submit(Value::from(1))[..3]), None);
+    }
+
+    #[test]
+    fn version_bits_checked_against_mask() {
+        let submit = |version: Value| {
+            vec![
+                Value::from("kaspa:addr.rig"),
+                Value::from("7"),
+                Value::from("00000000"),
+                Value::from("6553f100"),
+                Value::from("00000000deadbeef"),
+                version,
+            ]
+        };
+        let mask = 0x1fff_e000;
+        assert!(version_bits_in_mask(&submit(Value::from("00002000")), mask));
+        assert!(version_bits_in_mask(
+            &submit(Value::from(0x1fff_e000u32)),
+            mask
+        ));
+        assert!(!version_bits_in_mask(
+            &submit(Value::from("20000000")),
+            mask
+        ));
+        assert!(!version_bits_in_mask(
+            &submit(Value::from("00001000")),
+            mask
+        ));
+        assert!(!version_bits_in_mask(&submit(Value::from("xyz")), mask));
+        // Without version bits there is nothing to check
+        assert!(version_bits_in_mask(&submit(Value::Null)[..5], mask));
+    }
+}
@@ -7,7 +7,10 @@ use super::error::SubmitRunError;
 use super::parse::PreparedSubmit;
 use super::pow_math;
 use super::pow_step::evaluate_job_pow;
-use crate::{log_colors::LogColors, mining_state::GetMiningState, stratum_context::StratumContext};
+use crate::{
+    hasher::pow_value_to_diff, log_colors::LogColors, mining_state::GetMiningState,
+    prom::record_share_difficulty_submitted, stratum_context::StratumContext,
+};
 use num_traits::ToPrimitive;
 use num_traits::Zero;
 use std::sync::Arc;
@@ -16,8 +19,12 @@ use tracing::{debug, warn};
 pub(super) enum PowDone {
     /// `mining.submit` already answered (stale/bad block path).
     AlreadyFinished,
-    /// Run weak-share vs accepted-share finishing in `finish`.
-    Continue { invalid_share: bool },
+    /// Run weak-share vs accepted-share finishing in `finish`; `share_diff` is the difficulty the
+    /// share's hash reached.
+    Continue {
+        invalid_share: bool,
+        share_diff: f64,
+    },
 }
 
 pub(super) async fn run_pow_validation_loop(
@@ -242,7 +249,13 @@ pub(super) async fn run_pow_validation_loop(
                     invalid_share = inv;
                     break;
                 }
-                BlockSubmitFlowResult::Finished => return Ok(PowDone::AlreadyFinished),
+                BlockSubmitFlowResult::Finished => {
+                    record_share_difficulty_submitted(
+                        &handler.instance_id,
+                        pow_value_to_diff(&pow_value),
+                    );
+                    return Ok(PowDone::AlreadyFinished);
+                }
             }
         }
 
@@ -361,5 +374,10 @@ pub(super) async fn run_pow_validation_loop(
         }
     }
 
-    Ok(PowDone::Continue { invalid_share })
+    let share_diff = pow_value_to_diff(&pow_value);
+    record_share_difficulty_submitted(&handler.instance_id, share_diff);
+    Ok(PowDone::Continue {
+        invalid_share,
+        share_diff,
+    })
 }
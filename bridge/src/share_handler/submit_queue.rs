@@ -0,0 +1,386 @@
+//! Per-instance share queue: session read loops enqueue `mining.submit` and return, and one
+//! `share_processor` task drains the queue in FIFO order, so slow kaspad RPCs do not stall socket
+//! reads. A full queue rejects the share instead of blocking the session that sent it. At most
+//! `max_submit_concurrency` shares are handled at once (one by default, in arrival order).
+
+use super::ShareHandler;
+use super::kaspa_api_trait::KaspaApiTrait;
+use crate::jsonrpc_event::{JsonRpcEvent, JsonRpcResponse};
+use crate::prom::{record_share_queue_depth, record_submit_queue_full};
+use crate::stratum_context::StratumContext;
+use crate::stratum_error::StratumError;
+use crate::stratum_listener::{ShareRejectionReason, StratumSessionError};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc};
+use tracing::{debug, warn};
+
+/// Shares buffered per instance; a share arriving while the queue is full is rejected.
+pub const SHARE_QUEUE_CAPACITY: usize = 4096;
+
+/// Default for `max_submit_concurrency` when the config leaves it unset.
+pub const DEFAULT_MAX_SUBMIT_CONCURRENCY: u8 = 1;
+
+/// Default for `kaspad_rtt_warn_threshold_ms` when the config leaves it unset.
+pub const DEFAULT_KASPAD_RTT_WARN_THRESHOLD_MS: u64 = 500;
+
+struct QueuedShare {
+    ctx: Arc<StratumContext>,
+    event: JsonRpcEvent,
+}
+
+/// Sending side of an instance's share queue; cheap to clone into every submit handler.
+#[derive(Clone)]
+pub struct ShareQueue {
+    tx: mpsc::Sender<QueuedShare>,
+    depth: Arc<AtomicUsize>,
+    instance_id: String,
+    share_handler: Arc<ShareHandler>,
+    kaspa_api: Arc<dyn KaspaApiTrait + Send + Sync>,
+    /// One slot per share being handled (`max_submit_concurrency`).
+    permits: Arc<Semaphore>,
+}
+
+impl ShareQueue {
+    /// Start the `share_processor` task for `share_handler` and return its queue. At most
+    /// `max_concurrency` shares (at least 1) are handled at once.
+    pub fn spawn(
+        share_handler: Arc<ShareHandler>,
+        kaspa_api: Arc<dyn KaspaApiTrait + Send + Sync>,
+        capacity: usize,
+        max_concurrency: u8,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let depth = Arc::new(AtomicUsize::new(0));
+        let permits = Arc::new(Semaphore::new(max_concurrency.max(1) as usize));
+        let instance_id = share_handler.instance_id.clone();
+        tokio::spawn(share_processor(
+            Arc::clone(&share_handler),
+            Arc::clone(&kaspa_api),
+            rx,
+            Arc::clone(&depth),
+            Arc::clone(&permits),
+        ));
+        record_share_queue_depth(&instance_id, 0);
+        Self {
+            tx,
+            depth,
+            instance_id,
+            share_handler,
+            kaspa_api,
+            permits,
+        }
+    }
+
+    /// Handle a share in place of queueing it. Used inside a JSON-RPC batch, whose replies are
+    /// collected until the batch ends and would otherwise miss the queued share's answer.
+    pub async fn submit_now(&self, ctx: Arc<StratumContext>, event: JsonRpcEvent) {
+        let Some(_permit) = acquire_permit(&self.permits, &self.instance_id).await else {
+            return;
+        };
+        run_share(
+            Arc::clone(&self.share_handler),
+            Arc::clone(&self.kaspa_api),
+            ctx,
+            event,
+        )
+        .await;
+    }
+
+    /// Queue a share without waiting. When the queue is full the share is answered with a Stratum
+    /// error right away, so one flooding session cannot stall its own read loop or anyone else's.
+    pub async fn enqueue(
+        &self,
+        ctx: Arc<StratumContext>,
+        event: JsonRpcEvent,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Count before sending so the processor never sees a share it cannot account for
+        let depth = self.depth.fetch_add(1, Ordering::Relaxed) + 1;
+        record_share_queue_depth(&self.instance_id, depth);
+        match self.tx.try_send(QueuedShare { ctx, event }) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(QueuedShare { ctx, event })) => {
+                let depth = self.depth.fetch_sub(1, Ordering::Relaxed) - 1;
+                record_share_queue_depth(&self.instance_id, depth);
+                warn!(
+                    "[{}] share queue full, rejecting mining.submit from {} ({})",
+                    self.instance_id,
+                    ctx.remote_addr,
+                    ctx.effective_worker_name()
+                );
+                ctx.reply(JsonRpcResponse::stratum_error(
+                    event.id,
+                    StratumError::unknown("Share queue full"),
+                ))
+                .await?;
+                Ok(())
+            }
+            Err(TrySendError::Closed(_)) => {
+                self.depth.fetch_sub(1, Ordering::Relaxed);
+                Err("share processor stopped".into())
+            }
+        }
+    }
+
+    /// Shares queued or being handled.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+}
+
+/// A share-handling slot, waiting for one when all `max_submit_concurrency` are taken.
+async fn acquire_permit(
+    permits: &Arc<Semaphore>,
+    instance_id: &str,
+) -> Option<OwnedSemaphorePermit> {
+    match Arc::clone(permits).try_acquire_owned() {
+        Ok(permit) => Some(permit),
+        Err(_) => {
+            record_submit_queue_full(instance_id);
+            Arc::clone(permits).acquire_owned().await.ok()
+        }
+    }
+}
+
+/// Dequeue shares in arrival order, each holding a `max_submit_concurrency` slot until it has been
+/// handled; ends once every `ShareQueue` handle is dropped.
+async fn share_processor(
+    share_handler: Arc<ShareHandler>,
+    kaspa_api: Arc<dyn KaspaApiTrait + Send + Sync>,
+    mut rx: mpsc::Receiver<QueuedShare>,
+    depth: Arc<AtomicUsize>,
+    permits: Arc<Semaphore>,
+) {
+    while let Some(QueuedShare { ctx, event }) = rx.recv().await {
+        let Some(permit) = acquire_permit(&permits, &share_handler.instance_id).await else {
+            break;
+        };
+        let share_handler = Arc::clone(&share_handler);
+        let kaspa_api = Arc::clone(&kaspa_api);
+        let depth = Arc::clone(&depth);
+        tokio::spawn(async move {
+            let instance_id = share_handler.instance_id.clone();
+            run_share(share_handler, kaspa_api, ctx, event).await;
+            drop(permit);
+            // A share counts toward the depth until it has been handled, not just dequeued
+            let remaining = depth.fetch_sub(1, Ordering::Relaxed) - 1;
+            record_share_queue_depth(&instance_id, remaining);
+        });
+    }
+    debug!("{} share processor stopped", share_handler.log_prefix());
+}
+
+/// Run `handle_submit` for one share and log how it failed, if it did.
+async fn run_share(
+    share_handler: Arc<ShareHandler>,
+    kaspa_api: Arc<dyn KaspaApiTrait + Send + Sync>,
+    ctx: Arc<StratumContext>,
+    event: JsonRpcEvent,
+) {
+    let remote_addr = ctx.remote_addr.clone();
+    let Err(e) = share_handler.handle_submit(ctx, event, kaspa_api).await else {
+        return;
+    };
+    let err = StratumSessionError::from(e);
+    match err {
+        StratumSessionError::ShareRejected {
+            reason: ShareRejectionReason::Stale,
+        }
+        | StratumSessionError::ConnectionClosed => debug!(
+            "{} share from {}: {}",
+            share_handler.log_prefix(),
+            remote_addr,
+            err
+        ),
+        _ => warn!(
+            "{} share from {} failed: {}",
+            share_handler.log_prefix(),
+            remote_addr,
+            err
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kaspa_consensus_core::block::Block;
+    use std::time::Duration;
+    use tokio::io::AsyncReadExt;
+
+    struct OfflineKaspaApi;
+
+    #[async_trait::async_trait]
+    impl KaspaApiTrait for OfflineKaspaApi {
+        async fn get_block_template(&self, _: &str, _: &str, _: &str) -> anyhow::Result<Block> {
+            anyhow::bail!("offline")
+        }
+
+        async fn submit_block(
+            &self,
+            _: Block,
+        ) -> anyhow::Result<kaspa_rpc_core::SubmitBlockResponse> {
+            anyhow::bail!("offline")
+        }
+
+        async fn get_balances_by_addresses(
+            &self,
+            _: &[String],
+        ) -> anyhow::Result<Vec<(String, u64)>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_current_block_color(&self, _: &str) -> anyhow::Result<bool> {
+            Ok(false)
+        }
+
+        async fn is_node_synced_for_mining(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn queued_shares_are_drained_by_the_processor() {
+        let (ctx, _client) = crate::test_support::test_context().await;
+
+        let queue = ShareQueue::spawn(
+            Arc::new(ShareHandler::new("queue-test".to_string())),
+            Arc::new(OfflineKaspaApi),
+            2,
+            DEFAULT_MAX_SUBMIT_CONCURRENCY,
+        );
+        for id in 0..5 {
+            let submit = JsonRpcEvent::new(Some(id.to_string()), "mining.submit", vec![]);
+            queue.enqueue(Arc::clone(&ctx), submit).await.unwrap();
+        }
+
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while queue.depth() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("share processor drains the queue");
+
+        // These shares never reach `submit_block`, so no kaspad round trip is recorded
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(ctx.submit_rtt_ms(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn shares_wait_for_a_submit_slot() {
+        let (ctx, _client) = crate::test_support::test_context().await;
+
+        let queue = ShareQueue::spawn(
+            Arc::new(ShareHandler::new("slot-test".to_string())),
+            Arc::new(OfflineKaspaApi),
+            4,
+            1,
+        );
+        // Take the only slot: nothing is handled until it is released
+        let slot = Arc::clone(&queue.permits).acquire_owned().await.unwrap();
+        for id in 0..2 {
+            let submit = JsonRpcEvent::new(Some(id.to_string()), "mining.submit", vec![]);
+            queue.enqueue(Arc::clone(&ctx), submit).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(queue.depth(), 2);
+
+        drop(slot);
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while queue.depth() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("shares are handled once the slot is free");
+    }
+
+    #[tokio::test]
+    async fn full_queue_rejects_the_share_without_waiting() {
+        let (ctx, mut client) = crate::test_support::test_context().await;
+
+        // No processor drains this queue, so the second share finds it full
+        let (tx, _rx) = mpsc::channel(1);
+        let queue = ShareQueue {
+            tx,
+            depth: Arc::new(AtomicUsize::new(0)),
+            instance_id: "queue-full-test".to_string(),
+            share_handler: Arc::new(ShareHandler::new("queue-full-test".to_string())),
+            kaspa_api: Arc::new(OfflineKaspaApi),
+            permits: Arc::new(Semaphore::new(1)),
+        };
+        for id in 0..2 {
+            let submit = JsonRpcEvent::new(Some(id.to_string()), "mining.submit", vec![]);
+            tokio::time::timeout(
+                Duration::from_secs(1),
+                queue.enqueue(Arc::clone(&ctx), submit),
+            )
+            .await
+            .expect("enqueue never waits for room")
+            .unwrap();
+        }
+        assert_eq!(queue.depth(), 1);
+
+        let mut buf = vec![0u8; 256];
+        let n = tokio::time::timeout(Duration::from_secs(1), client.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        let reply = String::from_utf8_lossy(&buf[..n]);
+        assert!(reply.contains("\"id\":\"1\""), "{reply}");
+        assert!(reply.contains("Share queue full"), "{reply}");
+    }
+
+    #[tokio::test]
+    async fn batched_shares_are_answered_before_the_batch_ends() {
+        use crate::mining_state::{GetMiningState, Job};
+        use kaspa_hashes::Hash;
+        use serde_json::Value;
+        use tokio::io::AsyncBufReadExt;
+
+        let (ctx, client) = crate::test_support::test_context().await;
+        let job_id = GetMiningState(&ctx).add_job(Job {
+            block: Block::from_precomputed_hash(Hash::from_bytes([1; 32]), vec![]),
+            pre_pow_hash: Hash::default(),
+        });
+        // Version bits outside the mask get an immediate low-difficulty reply
+        ctx.set_version_rolling_mask(Some(0x1fff_e000));
+
+        let queue = ShareQueue::spawn(
+            Arc::new(ShareHandler::new("batch-test".to_string())),
+            Arc::new(OfflineKaspaApi),
+            1,
+            DEFAULT_MAX_SUBMIT_CONCURRENCY,
+        );
+        ctx.begin_batch();
+        let submit = JsonRpcEvent::new(
+            Some("7".to_string()),
+            "mining.submit",
+            vec![
+                Value::from("kaspa:addr.rig"),
+                Value::from(job_id.to_string()),
+                Value::from("00000000"),
+                Value::from("6553f100"),
+                Value::from("00000000deadbeef"),
+                Value::from("20000000"),
+            ],
+        );
+        queue.submit_now(Arc::clone(&ctx), submit).await;
+        ctx.finish_batch().await.unwrap();
+
+        let mut line = String::new();
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            tokio::io::BufReader::new(client).read_line(&mut line),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let replies: Vec<Value> = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(replies.len(), 1, "{line}");
+        assert_eq!(replies[0]["id"], Value::from("7"));
+        assert_eq!(replies[0]["error"][0], Value::from(23));
+    }
+}
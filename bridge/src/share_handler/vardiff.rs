@@ -7,10 +7,52 @@ const WORK_WINDOW: u64 = 80;
 const VARDIFF_MIN_ELAPSED_SECS: f64 = 30.0;
 const VARDIFF_MAX_ELAPSED_SECS_NO_SHARES: f64 = 90.0;
 const VARDIFF_MIN_SHARES: f64 = 3.0;
-const VARDIFF_LOWER_RATIO: f64 = 0.75; // below this => decrease diff
-const VARDIFF_UPPER_RATIO: f64 = 1.25; // above this => increase diff
-const VARDIFF_MAX_STEP_UP: f64 = 2.0; // max 2x per adjustment tick
-const VARDIFF_MAX_STEP_DOWN: f64 = 0.5; // max -50% per adjustment tick
+const VARDIFF_LOWER_RATIO: f64 = 0.9; // below this => decrease diff
+const VARDIFF_UPPER_RATIO: f64 = 1.1; // above this => increase diff
+
+/// Default `var_diff_step_up`: at most double the difficulty per retarget.
+pub const DEFAULT_VAR_DIFF_STEP_UP: f64 = 2.0;
+/// Default `var_diff_step_down`: at most halve the difficulty per retarget.
+pub const DEFAULT_VAR_DIFF_STEP_DOWN: f64 = 0.5;
+
+/// Default `var_diff_emergency_threshold`: retarget at once when shares come 10x too fast or slow.
+pub const DEFAULT_VAR_DIFF_EMERGENCY_THRESHOLD: f64 = 10.0;
+/// Share intervals averaged for the emergency check. A single interval is too noisy: one under a
+/// tenth of the expected gap happens for about 10% of shares at a steady rate.
+pub(crate) const VARDIFF_EMERGENCY_INTERVALS: usize = 8;
+
+/// Per-instance retarget limits. Raising and lowering have separate multipliers so difficulty can
+/// climb quickly but fall gently, avoiding the share flood a sudden large drop causes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VarDiffState {
+    /// Largest multiplier applied when shares come faster than the target (> 1).
+    pub step_up: f64,
+    /// Smallest multiplier applied when shares come slower than the target (0..1).
+    pub step_down: f64,
+    /// Rate ratio (either direction) that retargets right after a share instead of waiting for
+    /// the next tick; `None` disables emergency retargets.
+    pub emergency_threshold: Option<f64>,
+    /// Highest difficulty a `pow2_clamp` retarget may reach (`pow2_clamp_max`), a power of 2.
+    pub pow2_clamp_max: Option<f64>,
+    /// `var_diff_target_ratio`: when set, the shares/min target scales with
+    /// `reference_difficulty / current difficulty` (see [`VarDiffState::target_spm`]).
+    pub target_ratio: Option<f64>,
+    /// Difficulty at which the scaled target equals `shares_per_min * target_ratio`.
+    pub reference_difficulty: f64,
+}
+
+impl Default for VarDiffState {
+    fn default() -> Self {
+        Self {
+            step_up: DEFAULT_VAR_DIFF_STEP_UP,
+            step_down: DEFAULT_VAR_DIFF_STEP_DOWN,
+            emergency_threshold: Some(DEFAULT_VAR_DIFF_EMERGENCY_THRESHOLD),
+            pow2_clamp_max: None,
+            target_ratio: None,
+            reference_difficulty: 1.0,
+        }
+    }
+}
 
 fn vardiff_pow2_clamp_towards(current: f64, next: f64) -> f64 {
     if !next.is_finite() || next <= 0.0 {
@@ -26,67 +68,117 @@ fn vardiff_pow2_clamp_towards(current: f64, next: f64) -> f64 {
     if clamped < 1.0 { 1.0 } else { clamped }
 }
 
-pub(crate) fn vardiff_compute_next_diff(
-    current: f64,
-    shares: f64,
-    elapsed_secs: f64,
-    expected_spm: f64,
-    clamp_pow2: bool,
-) -> Option<f64> {
-    if !current.is_finite() || current <= 0.0 {
-        return None;
+impl VarDiffState {
+    /// Shares/min target for a worker at `current` difficulty: `base_spm` unless
+    /// `var_diff_target_ratio` is set, then `reference_difficulty / current * base_spm * ratio`.
+    pub fn target_spm(&self, base_spm: f64, current: f64) -> f64 {
+        match self.target_ratio {
+            Some(ratio) if current.is_finite() && current > 0.0 => {
+                self.reference_difficulty / current * base_spm * ratio
+            }
+            _ => base_spm,
+        }
     }
-    if !elapsed_secs.is_finite() || elapsed_secs <= 0.0 {
-        return None;
+
+    /// `next` rounded to a power of 2 away from `current`, capped at `pow2_clamp_max`.
+    fn clamp_pow2(&self, current: f64, next: f64) -> f64 {
+        let next = vardiff_pow2_clamp_towards(current, next);
+        match self.pow2_clamp_max {
+            Some(max) => next.min(max),
+            None => next,
+        }
     }
 
-    if shares == 0.0 && elapsed_secs >= VARDIFF_MAX_ELAPSED_SECS_NO_SHARES {
-        let mut next = current * VARDIFF_MAX_STEP_DOWN;
-        if next < 1.0 {
-            next = 1.0;
+    /// Difficulty for a worker whose recent rate of `recent_spm` is at least `emergency_threshold`
+    /// times above or below `expected_spm`, with the triggering ratio. The full ratio is applied,
+    /// not the per-tick steps, so a sudden hashrate jump is absorbed in one move.
+    pub fn emergency_next_diff(
+        &self,
+        current: f64,
+        recent_spm: f64,
+        expected_spm: f64,
+        clamp_pow2: bool,
+    ) -> Option<(f64, f64)> {
+        let threshold = self.emergency_threshold?;
+        if !current.is_finite() || current <= 0.0 || !recent_spm.is_finite() || recent_spm <= 0.0 {
+            return None;
         }
+        let ratio = recent_spm / expected_spm.max(1.0);
+        if ratio < threshold && ratio > 1.0 / threshold {
+            return None;
+        }
+        let mut next = (current * ratio).max(1.0);
         if clamp_pow2 {
-            next = vardiff_pow2_clamp_towards(current, next);
+            next = self.clamp_pow2(current, next);
         }
-        return if (next - current).abs() > f64::EPSILON {
-            Some(next)
-        } else {
-            None
-        };
+        ((next - current).abs() > f64::EPSILON).then_some((next, ratio))
     }
 
-    if elapsed_secs < VARDIFF_MIN_ELAPSED_SECS || shares < VARDIFF_MIN_SHARES {
-        return None;
-    }
+    /// Next difficulty for a worker that found `shares` in `elapsed_secs`, or `None` to keep
+    /// `current`. Rates within 10% of `expected_spm` are a dead band and leave it alone; outside it
+    /// the difficulty follows the observed/expected rate ratio, but never by more than `step_up`
+    /// (too fast) or `step_down` (too slow) in one retarget.
+    pub fn compute_next_diff(
+        &self,
+        current: f64,
+        shares: f64,
+        elapsed_secs: f64,
+        expected_spm: f64,
+        clamp_pow2: bool,
+    ) -> Option<f64> {
+        if !current.is_finite() || current <= 0.0 {
+            return None;
+        }
+        if !elapsed_secs.is_finite() || elapsed_secs <= 0.0 {
+            return None;
+        }
 
-    let observed_spm = (shares / elapsed_secs) * 60.0;
-    let ratio = observed_spm / expected_spm.max(1.0);
-    if !ratio.is_finite() || ratio <= 0.0 {
-        return None;
-    }
-    if ratio > VARDIFF_LOWER_RATIO && ratio < VARDIFF_UPPER_RATIO {
-        return None;
-    }
+        if shares == 0.0 && elapsed_secs >= VARDIFF_MAX_ELAPSED_SECS_NO_SHARES {
+            let mut next = current * self.step_down;
+            if next < 1.0 {
+                next = 1.0;
+            }
+            if clamp_pow2 {
+                next = self.clamp_pow2(current, next);
+            }
+            return if (next - current).abs() > f64::EPSILON {
+                Some(next)
+            } else {
+                None
+            };
+        }
 
-    let step = ratio
-        .sqrt()
-        .clamp(VARDIFF_MAX_STEP_DOWN, VARDIFF_MAX_STEP_UP);
-    let mut next = current * step;
-    if next < 1.0 {
-        next = 1.0;
-    }
-    if clamp_pow2 {
-        next = vardiff_pow2_clamp_towards(current, next);
-    }
+        if elapsed_secs < VARDIFF_MIN_ELAPSED_SECS || shares < VARDIFF_MIN_SHARES {
+            return None;
+        }
 
-    let rel_change = (next - current).abs() / current.max(1.0);
-    if rel_change < 0.10 {
-        return None;
-    }
-    if (next - current).abs() > f64::EPSILON {
-        Some(next)
-    } else {
-        None
+        let observed_spm = (shares / elapsed_secs) * 60.0;
+        let ratio = observed_spm / expected_spm.max(1.0);
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return None;
+        }
+        if ratio > VARDIFF_LOWER_RATIO && ratio < VARDIFF_UPPER_RATIO {
+            return None;
+        }
+
+        let step = ratio.clamp(self.step_down, self.step_up);
+        let mut next = current * step;
+        if next < 1.0 {
+            next = 1.0;
+        }
+        if clamp_pow2 {
+            next = self.clamp_pow2(current, next);
+        }
+
+        let rel_change = (next - current).abs() / current.max(1.0);
+        if rel_change < 0.10 {
+            return None;
+        }
+        if (next - current).abs() > f64::EPSILON {
+            Some(next)
+        } else {
+            None
+        }
     }
 }
 
@@ -96,19 +188,85 @@ mod tests {
 
     #[test]
     fn no_shares_long_wait_lowers_diff() {
-        let next = vardiff_compute_next_diff(100.0, 0.0, 95.0, 10.0, false).expect("should adjust");
+        let next = VarDiffState::default()
+            .compute_next_diff(100.0, 0.0, 95.0, 10.0, false)
+            .expect("should adjust");
         assert!(next < 100.0);
         assert!(next >= 1.0);
     }
 
     #[test]
     fn no_change_when_ratio_in_band() {
-        assert!(vardiff_compute_next_diff(64.0, 5.0, 60.0, 5.0, false).is_none());
+        assert!(
+            VarDiffState::default()
+                .compute_next_diff(64.0, 5.0, 60.0, 5.0, false)
+                .is_none()
+        );
+        // 5.25 spm against a target of 5: inside the 10% dead band
+        assert!(
+            VarDiffState::default()
+                .compute_next_diff(64.0, 21.0, 240.0, 5.0, false)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn steps_up_and_down_use_separate_limits() {
+        let steps = VarDiffState {
+            step_up: 1.5,
+            step_down: 0.8,
+            emergency_threshold: None,
+            ..VarDiffState::default()
+        };
+        // 40 spm against 10: capped at 1.5x
+        let up = steps
+            .compute_next_diff(1000.0, 40.0, 60.0, 10.0, false)
+            .unwrap();
+        assert!((up - 1500.0).abs() < 1e-9);
+        // 3 spm against 10: capped at 0.8x
+        let down = steps
+            .compute_next_diff(1000.0, 3.0, 60.0, 10.0, false)
+            .unwrap();
+        assert!((down - 800.0).abs() < 1e-9);
+        // 12 spm against 10: within the step, so it moves by the rate ratio
+        let near = steps
+            .compute_next_diff(1000.0, 12.0, 60.0, 10.0, false)
+            .unwrap();
+        assert!((near - 1200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn emergency_retarget_only_beyond_threshold() {
+        let steps = VarDiffState::default();
+        assert!(
+            steps
+                .emergency_next_diff(1000.0, 150.0, 20.0, false)
+                .is_none()
+        );
+        let (next, ratio) = steps
+            .emergency_next_diff(1000.0, 240.0, 20.0, false)
+            .unwrap();
+        assert_eq!(ratio, 12.0);
+        assert!((next - 12_000.0).abs() < 1e-9);
+        let (next, _) = steps.emergency_next_diff(1000.0, 1.0, 20.0, false).unwrap();
+        assert!((next - 50.0).abs() < 1e-9);
+
+        let disabled = VarDiffState {
+            emergency_threshold: None,
+            ..steps
+        };
+        assert!(
+            disabled
+                .emergency_next_diff(1000.0, 240.0, 20.0, false)
+                .is_none()
+        );
     }
 
     #[test]
     fn pow2_clamp_rounds_to_power_of_two() {
-        let next = vardiff_compute_next_diff(8.0, 0.0, 95.0, 10.0, true).expect("adjust");
+        let next = VarDiffState::default()
+            .compute_next_diff(8.0, 0.0, 95.0, 10.0, true)
+            .expect("adjust");
         assert!(next.is_finite() && next >= 1.0);
         let log2 = next.log2();
         assert!(
@@ -118,9 +276,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pow2_clamp_max_caps_retargets() {
+        let steps = VarDiffState {
+            pow2_clamp_max: Some(1024.0),
+            ..VarDiffState::default()
+        };
+        // 60 shares/min against 20: would double 600 to 1200, rounded up to 2048
+        assert_eq!(
+            steps.compute_next_diff(600.0, 60.0, 60.0, 20.0, true),
+            Some(1024.0)
+        );
+        assert_eq!(
+            steps.compute_next_diff(1024.0, 60.0, 60.0, 20.0, true),
+            None
+        );
+        assert_eq!(
+            steps.emergency_next_diff(512.0, 240.0, 20.0, true),
+            Some((1024.0, 12.0))
+        );
+        // Without pow2_clamp the cap does not apply
+        assert_eq!(
+            steps.compute_next_diff(600.0, 60.0, 60.0, 20.0, false),
+            Some(1200.0)
+        );
+    }
+
+    #[test]
+    fn target_ratio_scales_target_with_difficulty() {
+        let steps = VarDiffState {
+            target_ratio: Some(1.5),
+            reference_difficulty: 1024.0,
+            ..VarDiffState::default()
+        };
+        assert_eq!(steps.target_spm(20.0, 1024.0), 30.0);
+        assert_eq!(steps.target_spm(20.0, 4096.0), 7.5);
+        assert_eq!(VarDiffState::default().target_spm(20.0, 4096.0), 20.0);
+    }
+
     #[test]
     fn invalid_current_returns_none() {
-        assert!(vardiff_compute_next_diff(0.0, 1.0, 60.0, 5.0, false).is_none());
-        assert!(vardiff_compute_next_diff(f64::NAN, 1.0, 60.0, 5.0, false).is_none());
+        assert!(
+            VarDiffState::default()
+                .compute_next_diff(0.0, 1.0, 60.0, 5.0, false)
+                .is_none()
+        );
+        assert!(
+            VarDiffState::default()
+                .compute_next_diff(f64::NAN, 1.0, 60.0, 5.0, false)
+                .is_none()
+        );
     }
 }
@@ -0,0 +1,203 @@
+//! VarDiff state kept across restarts (`var_diff_state_file`): the last difficulty of every
+//! `wallet.worker`, so a reconnecting worker starts near it instead of flooding the pool with
+//! `min_share_diff` shares while VarDiff recalibrates.
+//!
+//! The file is JSON, rewritten periodically and on shutdown; a missing file starts empty.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default for `var_diff_hint_max_age_hours`.
+pub const DEFAULT_VAR_DIFF_HINT_MAX_AGE_HOURS: u64 = 6;
+/// Share of the last difficulty a returning worker starts at, leaving a little warm-up room.
+const HINT_FRACTION: f64 = 0.75;
+/// How often the state file is rewritten while running.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Saved VarDiff state for one worker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarDiffRecord {
+    pub last_difficulty: f64,
+    /// Unix seconds of the last update.
+    pub last_update: u64,
+    /// Starting difficulty for the worker's next session (75% of `last_difficulty`).
+    pub initial_difficulty_hint: Option<u32>,
+}
+
+pub struct VarDiffStore {
+    path: PathBuf,
+    max_hint_age: Duration,
+    records: Mutex<HashMap<String, VarDiffRecord>>,
+    dirty: AtomicBool,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn record_key(wallet: &str, worker: &str) -> String {
+    format!("{wallet}.{worker}")
+}
+
+impl VarDiffStore {
+    /// Load `path` if it exists. Hints older than `max_hint_age` are ignored.
+    pub fn open(path: impl Into<PathBuf>, max_hint_age: Duration) -> Result<Self, anyhow::Error> {
+        let path = path.into();
+        let records = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to parse VarDiff state file {}: {}",
+                    path.display(),
+                    e
+                )
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to read VarDiff state file {}: {}",
+                    path.display(),
+                    e
+                ));
+            }
+        };
+        Ok(Self {
+            path,
+            max_hint_age,
+            records: Mutex::new(records),
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.lock().is_empty()
+    }
+
+    /// Remember `difficulty` as the worker's latest.
+    pub fn record(&self, wallet: &str, worker: &str, difficulty: f64) {
+        if !difficulty.is_finite() || difficulty <= 0.0 {
+            return;
+        }
+        let hint = difficulty * HINT_FRACTION;
+        let record = VarDiffRecord {
+            last_difficulty: difficulty,
+            last_update: unix_now(),
+            initial_difficulty_hint: (hint >= 1.0).then(|| hint.min(u32::MAX as f64) as u32),
+        };
+        self.records
+            .lock()
+            .insert(record_key(wallet, worker), record);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Starting difficulty for a returning worker, if its record is recent enough.
+    pub fn initial_difficulty_hint(&self, wallet: &str, worker: &str) -> Option<u32> {
+        let records = self.records.lock();
+        let record = records.get(&record_key(wallet, worker))?;
+        if unix_now().saturating_sub(record.last_update) > self.max_hint_age.as_secs() {
+            return None;
+        }
+        record.initial_difficulty_hint
+    }
+
+    pub fn get(&self, wallet: &str, worker: &str) -> Option<VarDiffRecord> {
+        self.records
+            .lock()
+            .get(&record_key(wallet, worker))
+            .cloned()
+    }
+
+    /// Write the file if anything changed since the last save (via a temp file and rename).
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(&*self.records.lock())?;
+        let tmp = self.path.with_extension("tmp");
+        let result = std::fs::write(&tmp, json).and_then(|()| std::fs::rename(&tmp, &self.path));
+        if let Err(e) = result {
+            self.dirty.store(true, Ordering::Release);
+            return Err(anyhow::anyhow!(
+                "Failed to write VarDiff state file {}: {}",
+                self.path.display(),
+                e
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Save `store` every minute and once more on shutdown.
+pub fn spawn_periodic_save(
+    store: Arc<VarDiffStore>,
+    mut shutdown_rx: tokio::sync::watch::Receiver<bool>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SAVE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() { break; }
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = store.save() {
+                        tracing::warn!("{}", e);
+                    }
+                }
+            }
+        }
+        if let Err(e) = store.save() {
+            tracing::warn!("{}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hints_survive_a_save_and_reload() {
+        let path = std::env::temp_dir().join(format!(
+            "rkstratum_vardiff_test_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let max_age = Duration::from_secs(DEFAULT_VAR_DIFF_HINT_MAX_AGE_HOURS * 3600);
+
+        let store = VarDiffStore::open(&path, max_age).unwrap();
+        assert!(store.is_empty());
+        store.record("kaspa:a", "rig1", 8192.0);
+        store.save().unwrap();
+
+        let reloaded = VarDiffStore::open(&path, max_age).unwrap();
+        assert_eq!(
+            reloaded.initial_difficulty_hint("kaspa:a", "rig1"),
+            Some(6144)
+        );
+        assert_eq!(reloaded.initial_difficulty_hint("kaspa:a", "rig2"), None);
+
+        reloaded
+            .records
+            .lock()
+            .get_mut("kaspa:a.rig1")
+            .unwrap()
+            .last_update -= 7 * 3600;
+        assert_eq!(reloaded.initial_difficulty_hint("kaspa:a", "rig1"), None);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -2,7 +2,7 @@
 use crate::rkstratum_cpu_miner::InternalMinerMetrics;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::time::Instant;
@@ -21,6 +21,8 @@ pub struct WorkStats {
     pub var_diff_shares_found: Arc<Mutex<i64>>,
     pub var_diff_window: Arc<Mutex<usize>>,
     pub min_diff: Arc<Mutex<f64>>,
+    /// Latest accepted share times, for the VarDiff emergency check.
+    pub recent_share_times: Arc<Mutex<VecDeque<Instant>>>,
 }
 
 impl WorkStats {
@@ -38,6 +40,7 @@ impl WorkStats {
             var_diff_shares_found: Arc::new(Mutex::new(0)),
             var_diff_window: Arc::new(Mutex::new(0)),
             min_diff: Arc::new(Mutex::new(0.0)),
+            recent_share_times: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 }
@@ -0,0 +1,225 @@
+//! Block-found notifications for small private pools: a fire-and-forget JSON POST to a Discord, Slack,
+//! Telegram, or generic webhook (`block_found_webhook` / `block_found_webhook_format`). Failures are
+//! logged at WARN and never reach the share pipeline.
+
+use crate::app_config::{ConfigError, GlobalConfig};
+use crate::pool_events::BlockFoundEvent;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Upper bound for one webhook request.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Payload shape expected by the webhook receiver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// `{"content": ...}`
+    Discord,
+    /// `{"chat_id": ..., "text": ...}` for the Bot API `sendMessage` call.
+    Telegram { chat_id: String },
+    /// `{"text": ...}`
+    Slack,
+    /// The event fields as JSON, plus a `message` line.
+    Generic,
+}
+
+/// Where and how to announce found blocks.
+#[derive(Debug, Clone)]
+pub struct BlockWebhook {
+    url: String,
+    format: WebhookFormat,
+    client: reqwest::Client,
+}
+
+impl BlockWebhook {
+    pub fn new(url: impl Into<String>, format: WebhookFormat) -> Self {
+        Self {
+            url: url.into(),
+            format,
+            client: reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Webhook described by the global config, or `None` when notifications are off. Telegram may
+    /// give `telegram_bot_token` instead of a URL; it then posts to the Bot API `sendMessage`.
+    pub fn from_global(global: &GlobalConfig) -> Result<Option<Self>, ConfigError> {
+        let invalid = |reason: &str| ConfigError::InvalidSetting {
+            field: "block_found_webhook_format",
+            reason: reason.to_string(),
+        };
+        let format_name = global
+            .block_found_webhook_format
+            .as_deref()
+            .unwrap_or("generic")
+            .trim()
+            .to_ascii_lowercase();
+        let url = global
+            .block_found_webhook
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty());
+
+        let (url, format) = match format_name.as_str() {
+            "telegram" => {
+                let token = global.telegram_bot_token.as_deref().map(str::trim);
+                let url = match (url, token) {
+                    (Some(url), _) => url.to_string(),
+                    (None, Some(token)) if !token.is_empty() => {
+                        format!("https://api.telegram.org/bot{token}/sendMessage")
+                    }
+                    _ => return Ok(None),
+                };
+                let chat_id = global
+                    .telegram_chat_id
+                    .clone()
+                    .filter(|id| !id.trim().is_empty())
+                    .ok_or_else(|| invalid("telegram needs telegram_chat_id"))?;
+                (url, WebhookFormat::Telegram { chat_id })
+            }
+            format => {
+                let Some(url) = url else {
+                    return Ok(None);
+                };
+                let format = match format {
+                    "discord" => WebhookFormat::Discord,
+                    "slack" => WebhookFormat::Slack,
+                    "generic" => WebhookFormat::Generic,
+                    other => {
+                        return Err(invalid(&format!(
+                            "'{other}' is not one of discord, telegram, slack, generic"
+                        )));
+                    }
+                };
+                (url.to_string(), format)
+            }
+        };
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(ConfigError::InvalidSetting {
+                field: "block_found_webhook",
+                reason: "must be an http:// or https:// URL".to_string(),
+            });
+        }
+        Ok(Some(Self::new(url, format)))
+    }
+
+    pub fn format(&self) -> &WebhookFormat {
+        &self.format
+    }
+
+    /// JSON body for `event` in this webhook's format.
+    pub fn payload(&self, event: &BlockFoundEvent) -> Value {
+        let message = format!(
+            "🎉 Block found! {} worker {} ({}) — hash {}, blue score {}",
+            event.worker.instance_id,
+            event.worker.worker_name,
+            event.worker.wallet_addr,
+            event.block_hash,
+            event.blue_score
+        );
+        match &self.format {
+            WebhookFormat::Discord => json!({ "content": message }),
+            WebhookFormat::Telegram { chat_id } => json!({ "chat_id": chat_id, "text": message }),
+            WebhookFormat::Slack => json!({ "text": message }),
+            WebhookFormat::Generic => json!({
+                "event": "block_found",
+                "instance": event.worker.instance_id,
+                "worker": event.worker.worker_name,
+                "wallet": event.worker.wallet_addr,
+                "remote_addr": event.worker.remote_addr,
+                "block_hash": event.block_hash,
+                "nonce": event.nonce,
+                "blue_score": event.blue_score,
+                "message": message,
+            }),
+        }
+    }
+
+    /// POST `event` in the background; the caller never waits on the receiver.
+    pub fn notify(self: &Arc<Self>, event: &BlockFoundEvent) {
+        let webhook = Arc::clone(self);
+        let body = self.payload(event);
+        let instance_id = event.worker.instance_id.clone();
+        tokio::spawn(async move {
+            let result = webhook
+                .client
+                .post(&webhook.url)
+                .header("Content-Type", "application/json")
+                .body(body.to_string())
+                .send()
+                .await;
+            match result {
+                Ok(resp) if resp.status().is_success() => {}
+                Ok(resp) => tracing::warn!(
+                    "[{}] block webhook returned HTTP {}",
+                    instance_id,
+                    resp.status()
+                ),
+                Err(e) => tracing::warn!("[{}] block webhook failed: {}", instance_id, e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool_events::WorkerInfo;
+
+    fn event() -> BlockFoundEvent {
+        BlockFoundEvent {
+            worker: WorkerInfo {
+                instance_id: "Instance 1".to_string(),
+                session_id: 3,
+                wallet_addr: "kaspa:qtest".to_string(),
+                worker_name: "rig1".to_string(),
+                remote_addr: "10.0.0.7".to_string(),
+            },
+            block_hash: "abc123".to_string(),
+            nonce: 42,
+            blue_score: 1000,
+        }
+    }
+
+    #[test]
+    fn payloads_follow_the_configured_format() {
+        let mut global = GlobalConfig {
+            block_found_webhook: Some("https://discord.example/api/webhooks/1".to_string()),
+            block_found_webhook_format: Some("discord".to_string()),
+            ..GlobalConfig::default()
+        };
+        let discord = BlockWebhook::from_global(&global).unwrap().unwrap();
+        let body = discord.payload(&event());
+        assert!(body["content"].as_str().unwrap().contains("abc123"));
+
+        global.block_found_webhook = None;
+        global.block_found_webhook_format = Some("telegram".to_string());
+        global.telegram_bot_token = Some("123:token".to_string());
+        global.telegram_chat_id = Some("-100200".to_string());
+        let telegram = BlockWebhook::from_global(&global).unwrap().unwrap();
+        assert_eq!(
+            telegram.url,
+            "https://api.telegram.org/bot123:token/sendMessage"
+        );
+        let body = telegram.payload(&event());
+        assert_eq!(body["chat_id"], "-100200");
+        assert!(body["text"].as_str().unwrap().contains("rig1"));
+
+        let generic = BlockWebhook::new("http://hooks.local/", WebhookFormat::Generic);
+        let body = generic.payload(&event());
+        assert_eq!(body["event"], "block_found");
+        assert_eq!(body["blue_score"], 1000);
+
+        global.block_found_webhook = Some("https://hooks.example/x".to_string());
+        global.block_found_webhook_format = Some("teams".to_string());
+        assert!(BlockWebhook::from_global(&global).is_err());
+        assert!(
+            BlockWebhook::from_global(&GlobalConfig::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
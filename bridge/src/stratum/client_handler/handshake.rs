@@ -1,25 +1,149 @@
 //! Extranonce assignment after miner type is detected (`mining.subscribe`).
 
+use crate::miner_detect::MinerProfile;
 use crate::stratum_context::StratumContext;
-use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::atomic::{AtomicU32, Ordering};
 use tracing::{debug, warn};
 
-static GLOBAL_NEXT_EXTRANONCE: AtomicI32 = AtomicI32::new(0);
+/// Largest 2-byte extranonce before the allocator wraps.
+pub const MAX_EXTRANONCE_VALUE: u32 = 0xFFFF;
 
-/// Assign extranonce to a client based on detected miner type.
-/// Called from `handle_subscribe` after miner type is detected.
-pub fn assign_extranonce_for_miner(ctx: &StratumContext, remote_app: &str) {
-    let remote_app_lower = remote_app.to_lowercase();
-    let is_bitmain = remote_app_lower.contains("godminer")
-        || remote_app_lower.contains("bitmain")
-        || remote_app_lower.contains("antminer");
+/// Largest 4-byte extranonce (`extranonce_size: 4`) before the allocator wraps.
+pub const MAX_EXTRANONCE_VALUE_U32: u32 = 0xFFFF_FFFF;
+
+static GLOBAL_NEXT_EXTRANONCE: AtomicU32 = AtomicU32::new(0);
+
+/// Extranonce bytes extranonce-using miners get under `extranonce_size`: 1 or 4 as configured, 2
+/// otherwise (0 and 2 keep the per-miner default).
+pub fn allocated_extranonce_size(size: i8) -> i8 {
+    match size {
+        1 | 4 => size,
+        _ => 2,
+    }
+}
+
+/// Largest extranonce value that fits in `size` bytes (0 for no extranonce).
+pub fn max_extranonce_for_size(size: i8) -> u32 {
+    match size.clamp(0, 4) {
+        0 => 0,
+        4 => MAX_EXTRANONCE_VALUE_U32,
+        n => (1u32 << (8 * n as u32)) - 1,
+    }
+}
+
+/// Half-open block `[start, end)` of extranonce values owned by one instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtranonceRange {
+    pub start: u64,
+    pub end: u64,
+}
 
-    let required_extranonce_size = if is_bitmain { 0 } else { 2 };
+impl ExtranonceRange {
+    /// Slice `index` of `count` equal slices of the extranonce space `size` hands out (see
+    /// [`allocated_extranonce_size`]).
+    pub fn split(index: usize, count: usize, size: i8) -> Self {
+        let space = max_extranonce_for_size(allocated_extranonce_size(size)) as u64 + 1;
+        let count = count.max(1) as u64;
+        Self {
+            start: index as u64 * space / count,
+            end: (index as u64 + 1) * space / count,
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// This range of `size`-byte extranonces as the 4-byte nonce prefixes it covers, so ranges of
+    /// different widths can be compared.
+    pub fn widened(&self, size: i8) -> Self {
+        let shift = 8 * (4 - allocated_extranonce_size(size) as u32);
+        Self {
+            start: self.start << shift,
+            end: self.end << shift,
+        }
+    }
+
+    pub fn overlaps(&self, other: &ExtranonceRange) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+impl std::fmt::Display for ExtranonceRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:#x}, {:#x})", self.start, self.end)
+    }
+}
+
+/// Per-instance allocator over an [`ExtranonceRange`]; only this instance's connections touch the counter.
+#[derive(Debug)]
+pub struct RangeAllocator {
+    range: ExtranonceRange,
+    next_offset: AtomicU32,
+}
+
+impl RangeAllocator {
+    pub fn new(range: ExtranonceRange) -> Self {
+        Self {
+            range,
+            next_offset: AtomicU32::new(0),
+        }
+    }
+
+    pub fn range(&self) -> ExtranonceRange {
+        self.range
+    }
+
+    /// Next value in the range. Config validation keeps ranges inside the extranonce width; one
+    /// that still overruns `max_extranonce` is clamped to it, never handed to the global counter.
+    fn next(&self, max_extranonce: u32) -> u32 {
+        let end = self.range.end.min(max_extranonce as u64 + 1);
+        let start = self.range.start.min(end - 1);
+        if end < self.range.end {
+            warn!(
+                "extranonce range {} does not fit {:#x}, using [{:#x}, {:#x})",
+                self.range, max_extranonce, start, end
+            );
+        }
+        let len = end - start;
+        let prev = self
+            .next_offset
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |off| {
+                Some(if off as u64 + 1 < len { off + 1 } else { 0 })
+            })
+            .unwrap_or(0);
+        if prev as u64 + 1 == len {
+            warn!(
+                "wrapped extranonce range {}! new clients may be duplicating work...",
+                self.range
+            );
+        }
+        (start + prev as u64 % len) as u32
+    }
+}
+
+/// Assign extranonce to a client based on its detected [`MinerProfile`].
+/// Called from `handle_subscribe` after miner type is detected.
+/// Values come from `allocator` when set, otherwise from the process-wide counter.
+pub fn assign_extranonce_for_miner(
+    ctx: &StratumContext,
+    profile: &MinerProfile,
+    remote_app: &str,
+    allocator: Option<&RangeAllocator>,
+) {
+    let is_bitmain = profile.is_bitmain();
+    let required_extranonce_size = profile.extranonce_size.clamp(0, 4);
 
     let extranonce = if required_extranonce_size > 0 {
-        let max_extranonce = (2_f64.powi(16) - 1.0) as i32;
+        let max_extranonce = max_extranonce_for_size(required_extranonce_size);
 
-        let extranonce_val =
+        let extranonce_val = if let Some(allocator) = allocator {
+            allocator.next(max_extranonce)
+        } else {
             match GLOBAL_NEXT_EXTRANONCE.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| {
                 if val < max_extranonce {
                     Some(val + 1)
@@ -34,7 +158,8 @@ pub fn assign_extranonce_for_miner(ctx: &StratumContext, remote_app: &str) {
                     if prev < max_extranonce { prev + 1 } else { 0 }
                 }
                 Err(_) => 0,
-            };
+            }
+        };
         let extranonce_str = format!(
             "{:0width$x}",
             extranonce_val,
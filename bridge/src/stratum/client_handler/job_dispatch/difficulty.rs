@@ -1,9 +1,53 @@
 use crate::{
-    jsonrpc_event::JsonRpcEvent, mining_state::MiningState, prom::*,
-    stratum_context::StratumContext,
+    hasher::KaspaDiff, jsonrpc_event::JsonRpcEvent, miner_detect::JobFormat,
+    mining_state::MiningState, prom::*, stratum_context::StratumContext,
 };
+use num_bigint::BigUint;
+use num_traits::Zero;
 use tracing::{debug, error};
 
+/// Initialize a session's mining state and starting difficulty (a value from `mining.suggest_target`
+/// or `mining.suggest_difficulty` takes precedence over `min_diff`). Returns the difficulty to send,
+/// or `None` when `mining.authorize` or a job path already initialized the session.
+pub(crate) fn initialize_client_diff(
+    instance_id: &str,
+    client: &StratumContext,
+    state: &MiningState,
+    min_diff: f64,
+) -> Option<f64> {
+    if !state.mark_initialized() {
+        return None;
+    }
+    state.set_use_big_job(client.miner_profile().job_format == JobFormat::BigJob);
+
+    let diff = state
+        .stratum_diff()
+        .map(|d| d.diff_value)
+        .filter(|d| *d > 0.0)
+        .unwrap_or(min_diff);
+    // Use miner-specific calculation (IceRiver uses different formula)
+    let remote_app = client.identity.lock().remote_app.clone();
+    let mut stratum_diff = KaspaDiff::new();
+    stratum_diff.set_diff_value_for_miner(diff, &remote_app);
+    state.set_stratum_diff(stratum_diff);
+
+    update_worker_difficulty(&worker_context(instance_id, client, remote_app), diff);
+
+    let target = state
+        .stratum_diff()
+        .map(|d| d.target_value.clone())
+        .unwrap_or_else(BigUint::zero);
+    let target_bytes = target.to_bytes_be();
+    debug!(
+        "Initialized per-client MiningState with difficulty: {}, target: {:x} ({} bytes, {} bits)",
+        diff,
+        target,
+        target_bytes.len(),
+        target_bytes.len() * 8
+    );
+    Some(diff)
+}
+
 /// Send `mining.set_difficulty` to a client (spawned).
 pub fn send_client_diff(
     instance_id: &str,
@@ -1,17 +1,16 @@
-use super::{BIG_JOB_REGEX, send_client_diff};
+use super::{initialize_client_diff, send_client_diff};
 use crate::{
     hasher::{
         calculate_target, generate_iceriver_job_params, generate_job_header,
         generate_large_job_params, serialize_block_header,
     },
     jsonrpc_event::JsonRpcEvent,
+    miner_detect::JobFormat,
     mining_state::{GetMiningState, Job},
     prom::*,
     share_handler::{KaspaApiTrait, ShareHandler},
     stratum_context::StratumContext,
 };
-use num_bigint::BigUint;
-use num_traits::Zero;
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, error, warn};
@@ -22,6 +21,7 @@ pub(crate) async fn send_immediate_job_task<T: KaspaApiTrait + Send + Sync + ?Si
     share_handler: Arc<ShareHandler>,
     min_diff: f64,
     instance_id: String,
+    resend_difficulty: bool,
 ) {
     // Get per-client mining state from context
     let state = GetMiningState(&client_clone);
@@ -41,7 +41,11 @@ pub(crate) async fn send_immediate_job_task<T: KaspaApiTrait + Send + Sync + ?Si
 
     // Get block template
     let template_result = kaspa_api_clone
-        .get_block_template(&wallet_addr, &remote_app, &canxium_addr)
+        .get_block_template(
+            &share_handler.template_wallet(&wallet_addr),
+            &remote_app,
+            &canxium_addr,
+        )
         .await;
 
     let block = match template_result {
@@ -174,41 +178,14 @@ pub(crate) async fn send_immediate_job_task<T: KaspaApiTrait + Send + Sync + ?Si
         job_id, client_clone.remote_addr, counter_after, stored_ids
     );
 
-    // Initialize state if first time
-    if !state.is_initialized() {
-        state.set_initialized(true);
-        let use_big_job = BIG_JOB_REGEX.is_match(&remote_app);
-        state.set_use_big_job(use_big_job);
+    // `mining.authorize` initializes new sessions and sends their first difficulty; this only
+    // covers a session it could not
+    initialize_client_diff(&instance_id, &client_clone, &state, min_diff);
 
-        // Initialize stratum diff
-        use crate::hasher::KaspaDiff;
-        let mut stratum_diff = KaspaDiff::new();
-        let remote_app_clone = remote_app.clone();
-        stratum_diff.set_diff_value_for_miner(min_diff, &remote_app_clone);
-        state.set_stratum_diff(stratum_diff);
-
-        update_worker_difficulty(
-            &crate::prom::worker_context(&instance_id, &client_clone, remote_app_clone.clone()),
-            min_diff,
-        );
-
-        let target = state
-            .stratum_diff()
-            .map(|d| d.target_value.clone())
-            .unwrap_or_else(BigUint::zero);
-        let target_bytes = target.to_bytes_be();
-        debug!(
-            "send_immediate_job: Initialized MiningState with difficulty: {}, target: {:x} ({} bytes, {} bits)",
-            min_diff,
-            target,
-            target_bytes.len(),
-            target_bytes.len() * 8
-        );
-    }
-
-    // CRITICAL: Always send difficulty to each client (IceRiver expects this on every connection)
-    // Even if state is already initialized, we need to send difficulty to this specific client
-    // Use the actual current difficulty from state if available, otherwise use min_diff
+    // CRITICAL: Every client gets its difficulty before the job (IceRiver expects this on every
+    // connection). `mining.authorize` sends it before the first job; a re-authorizing client gets it
+    // here (`resend_difficulty`). Use the actual current difficulty from state if available,
+    // otherwise use min_diff
     let current_diff = state
         .stratum_diff()
         .map(|d| d.diff_value)
@@ -222,33 +199,31 @@ pub(crate) async fn send_immediate_job_task<T: KaspaApiTrait + Send + Sync + ?Si
         current_diff,
     );
 
-    debug!(
-        "[DIFFICULTY] ===== SENDING DIFFICULTY TO {} =====",
-        client_clone.remote_addr
-    );
-    debug!(
-        "[DIFFICULTY] Difficulty value: {} (from state: {})",
-        current_diff,
-        state.stratum_diff().is_some()
-    );
-    send_client_diff(&instance_id, &client_clone, &state, current_diff);
-    share_handler.set_client_vardiff(&client_clone, min_diff);
-    debug!(
-        "[DIFFICULTY] ===== DIFFICULTY SENT TO {} =====",
-        client_clone.remote_addr
-    );
+    share_handler.set_client_vardiff(&client_clone, current_diff);
+    if resend_difficulty {
+        debug!(
+            "[DIFFICULTY] ===== SENDING DIFFICULTY TO {} =====",
+            client_clone.remote_addr
+        );
+        debug!(
+            "[DIFFICULTY] Difficulty value: {} (from state: {})",
+            current_diff,
+            state.stratum_diff().is_some()
+        );
+        send_client_diff(&instance_id, &client_clone, &state, current_diff);
+        debug!(
+            "[DIFFICULTY] ===== DIFFICULTY SENT TO {} =====",
+            client_clone.remote_addr
+        );
 
-    // Small delay to ensure difficulty is sent before job
-    tokio::time::sleep(Duration::from_millis(100)).await;
+        // Small delay to ensure difficulty is sent before job
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
 
     // Build job params - check if this is an IceRiver or Bitmain miner
-    let remote_app_lower = remote_app.to_lowercase();
-    let is_iceriver = remote_app_lower.contains("iceriver")
-        || remote_app_lower.contains("icemining")
-        || remote_app_lower.contains("icm");
-    let is_bitmain = remote_app_lower.contains("godminer")
-        || remote_app_lower.contains("bitmain")
-        || remote_app_lower.contains("antminer");
+    let profile = client_clone.miner_profile();
+    let is_iceriver = profile.job_format == JobFormat::IceRiver;
+    let is_bitmain = profile.is_bitmain();
 
     debug!(
         "[JOB] ===== BUILDING JOB FOR {} =====",
@@ -362,6 +337,9 @@ pub(crate) async fn send_immediate_job_task<T: KaspaApiTrait + Send + Sync + ?Si
         job_params.len()
     );
 
+    // Mark before sending so a submit racing the notify is not refused
+    client_clone.mark_mining();
+
     // IceRiver expects minimal notification format (method + params only, no id or jsonrpc)
     // Send job ID in mining.notify
     let send_result = if is_iceriver {
@@ -404,6 +382,7 @@ pub(crate) async fn send_immediate_job_task<T: KaspaApiTrait + Send + Sync + ?Si
             client_clone.remote_addr
         );
     } else {
+        state.set_last_notified_job_id(job_id);
         record_new_job(&crate::prom::worker_context(
             &instance_id,
             &client_clone,
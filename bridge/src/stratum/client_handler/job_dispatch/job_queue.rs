@@ -0,0 +1,105 @@
+//! Job broadcasts still being sent to an instance's sessions. A new block clears the queue and goes
+//! to the front, so an in-progress refresh of the previous template stops instead of racing it.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// One template broadcast to every connected session.
+///
+/// Kaspa `mining.notify` has no clean-jobs field; `clean_jobs` decides whether the broadcast
+/// supersedes the ones still pending, and whether sessions that already hold the template get it
+/// again (a refresh skips them).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobTemplate {
+    pub clean_jobs: bool,
+    seq: u64,
+}
+
+impl JobTemplate {
+    /// A new block from kaspad.
+    pub fn new_block() -> Self {
+        Self {
+            clean_jobs: true,
+            seq: 0,
+        }
+    }
+
+    /// A periodic refresh of the current template.
+    pub fn refresh() -> Self {
+        Self {
+            clean_jobs: false,
+            seq: 0,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct JobQueue {
+    pending: Mutex<VecDeque<JobTemplate>>,
+    next_seq: AtomicU64,
+}
+
+impl JobQueue {
+    fn numbered(&self, job: JobTemplate) -> JobTemplate {
+        JobTemplate {
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            ..job
+        }
+    }
+
+    /// Queue `job` behind the broadcasts already pending.
+    pub(crate) fn push(&self, job: JobTemplate) -> JobTemplate {
+        let job = self.numbered(job);
+        self.pending.lock().push_back(job);
+        job
+    }
+
+    /// Drop every pending broadcast and put `job` (marked clean) at the front, under one lock.
+    /// Returns the queued job and how many were superseded.
+    pub(crate) fn prioritize(&self, job: JobTemplate) -> (JobTemplate, usize) {
+        let job = self.numbered(JobTemplate {
+            clean_jobs: true,
+            ..job
+        });
+        let mut pending = self.pending.lock();
+        let superseded = pending.drain(..).count();
+        pending.push_front(job);
+        (job, superseded)
+    }
+
+    /// False once a later [`Self::prioritize`] has superseded `job`.
+    pub(crate) fn is_pending(&self, job: &JobTemplate) -> bool {
+        self.pending.lock().iter().any(|j| j.seq == job.seq)
+    }
+
+    /// Remove `job` after it has reached every session.
+    pub(crate) fn finish(&self, job: &JobTemplate) {
+        self.pending.lock().retain(|j| j.seq != job.seq);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_block_supersedes_pending_refreshes() {
+        let queue = JobQueue::default();
+        let first = queue.push(JobTemplate::refresh());
+        let second = queue.push(JobTemplate::refresh());
+
+        let (block, superseded) = queue.prioritize(JobTemplate::refresh());
+        assert!(block.clean_jobs);
+        assert_eq!(superseded, 2);
+        assert!(!queue.is_pending(&first));
+        assert!(!queue.is_pending(&second));
+        assert!(queue.is_pending(&block));
+
+        let later = queue.push(JobTemplate::refresh());
+        assert_eq!(queue.pending.lock().front(), Some(&block));
+        queue.finish(&block);
+        assert!(queue.is_pending(&later));
+        assert!(!queue.is_pending(&block));
+    }
+}
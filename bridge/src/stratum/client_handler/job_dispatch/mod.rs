@@ -1,22 +1,23 @@
 //! Per-client job templates, difficulty notifications, and `mining.notify` dispatch.
 //!
 //! Split into [`difficulty`] (`mining.set_difficulty`), [`immediate_job`] (first job after subscribe),
-//! and [`new_block_job`] (template refresh / vardiff).
+//! [`new_block_job`] (template refresh / vardiff), [`job_queue`] (pending broadcasts), and
+//! [`notify_cache`] (serialized `mining.notify` lines).
 
 mod difficulty;
 mod immediate_job;
+mod job_queue;
 mod new_block_job;
+mod notify_cache;
 
-pub(crate) use difficulty::send_client_diff;
+pub(crate) use difficulty::{initialize_client_diff, send_client_diff};
 pub(crate) use immediate_job::send_immediate_job_task;
+pub(crate) use job_queue::JobQueue;
+pub use job_queue::JobTemplate;
 pub(crate) use new_block_job::new_block_job_task;
+pub use notify_cache::{NOTIFY_CACHE_CAPACITY, NotifyCache, NotifyKey, serialize_notify};
 
-use once_cell::sync::Lazy;
-use regex::Regex;
 use std::time::Duration;
 
-pub(crate) static BIG_JOB_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r".*(BzMiner|IceRiverMiner).*").unwrap());
-
 pub(crate) const BALANCE_DELAY: Duration = Duration::from_secs(60);
 pub(crate) const CLIENT_TIMEOUT: Duration = Duration::from_secs(20);
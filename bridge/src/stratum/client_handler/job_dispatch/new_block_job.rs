@@ -1,17 +1,17 @@
-use super::{BIG_JOB_REGEX, CLIENT_TIMEOUT, send_client_diff};
+use super::notify_cache::{NotifyCache, NotifyKey};
+use super::{CLIENT_TIMEOUT, initialize_client_diff, send_client_diff};
 use crate::{
     hasher::{
         calculate_target, generate_iceriver_job_params, generate_job_header,
         generate_large_job_params, serialize_block_header,
     },
-    jsonrpc_event::JsonRpcEvent,
+    miner_detect::JobFormat,
     mining_state::{GetMiningState, Job},
     prom::*,
     share_handler::{KaspaApiTrait, ShareHandler},
     stratum_context::StratumContext,
 };
-use num_bigint::BigUint;
-use num_traits::Zero;
+use kaspa_hashes::Hash;
 use std::sync::Arc;
 use tracing::{debug, error, warn};
 
@@ -21,6 +21,10 @@ pub(crate) async fn new_block_job_task<T: KaspaApiTrait + Send + Sync + 'static>
     share_handler: Arc<ShareHandler>,
     min_diff: f64,
     instance_id: String,
+    notify_cache: Arc<NotifyCache>,
+    clean_jobs: bool,
+    clean_jobs_on_retarget: bool,
+    notify_queue_depth: usize,
 ) {
     let state = GetMiningState(&client_clone);
 
@@ -67,7 +71,11 @@ pub(crate) async fn new_block_job_task<T: KaspaApiTrait + Send + Sync + 'static>
     };
 
     let template_result = kaspa_api_clone
-        .get_block_template(&wallet_addr, &remote_app, &canxium_addr)
+        .get_block_template(
+            &share_handler.template_wallet(&wallet_addr),
+            &remote_app,
+            &canxium_addr,
+        )
         .await;
 
     let block = match template_result {
@@ -137,54 +145,11 @@ pub(crate) async fn new_block_job_task<T: KaspaApiTrait + Send + Sync + 'static>
         }
     };
 
-    // Create Job struct with both block and pre_pow_hash
-    let job = Job {
-        block: block.clone(),
-        pre_pow_hash,
-    };
-
-    // Add job
-    let job_id = state.add_job(job);
-    let counter_after = state.current_job_counter();
-    let stored_ids = state.get_stored_job_ids();
-    debug!(
-        "[JOB CREATION] new_block_available: created job ID {} for client {} (counter: {}, stored IDs: {:?})",
-        job_id, client_clone.remote_addr, counter_after, stored_ids
-    );
-
     // Initialize state if first time (per-client state initialization)
-    if !state.is_initialized() {
-        state.set_initialized(true);
-        let use_big_job = BIG_JOB_REGEX.is_match(&remote_app);
-        state.set_use_big_job(use_big_job);
-
-        // Send initial difficulty
-        use crate::hasher::KaspaDiff;
-        let mut stratum_diff = KaspaDiff::new();
-        // Use miner-specific calculation (IceRiver uses different formula)
-        let remote_app = client_clone.identity.lock().remote_app.clone();
-        stratum_diff.set_diff_value_for_miner(min_diff, &remote_app);
-        state.set_stratum_diff(stratum_diff);
-
-        update_worker_difficulty(
-            &crate::prom::worker_context(&instance_id, &client_clone, remote_app.clone()),
-            min_diff,
-        );
-
-        let target = state
-            .stratum_diff()
-            .map(|d| d.target_value.clone())
-            .unwrap_or_else(BigUint::zero);
-        let target_bytes = target.to_bytes_be();
-        debug!(
-            "Initialized per-client MiningState with difficulty: {}, target: {:x} ({} bytes, {} bits)",
-            min_diff,
-            target,
-            target_bytes.len(),
-            target_bytes.len() * 8
-        );
-        send_client_diff(&instance_id, &client_clone, &state, min_diff);
-        share_handler.set_client_vardiff(&client_clone, min_diff);
+    let mut retargeted = false;
+    if let Some(diff) = initialize_client_diff(&instance_id, &client_clone, &state, min_diff) {
+        send_client_diff(&instance_id, &client_clone, &state, diff);
+        share_handler.set_client_vardiff(&client_clone, diff);
     } else {
         // Check for vardiff update
         if let Some(mut stratum_diff) = state.stratum_diff() {
@@ -213,20 +178,46 @@ pub(crate) async fn new_block_job_task<T: KaspaApiTrait + Send + Sync + 'static>
 
                 send_client_diff(&instance_id, &client_clone, &state, var_diff);
                 share_handler.start_client_vardiff(&client_clone);
+                retargeted = true;
             }
         }
     }
 
+    // A refresh with a template the miner already holds: a repeat notify would only restart its
+    // nonce scan, unless a retarget should take effect right away (`clean_jobs_on_retarget`)
+    if !clean_jobs
+        && !(retargeted && clean_jobs_on_retarget)
+        && state.holds_job_for(&pre_pow_hash, block.header.timestamp)
+    {
+        debug!(
+            "new_block_available: client {} already holds job {:?} for this template, skipping notify",
+            client_clone.remote_addr,
+            state.last_notified_job_id()
+        );
+        return;
+    }
+
+    // Create Job struct with both block and pre_pow_hash
+    let job = Job {
+        block: block.clone(),
+        pre_pow_hash,
+    };
+
+    // Add job
+    let job_id = state.add_job(job);
+    let counter_after = state.current_job_counter();
+    let stored_ids = state.get_stored_job_ids();
+    debug!(
+        "[JOB CREATION] new_block_available: created job ID {} for client {} (counter: {}, stored IDs: {:?})",
+        job_id, client_clone.remote_addr, counter_after, stored_ids
+    );
+
     // Build job params
     // Check if this is an IceRiver or Bitmain miner - they need single hex string format
     let remote_app = client_clone.identity.lock().remote_app.clone();
-    let remote_app_lower = remote_app.to_lowercase();
-    let is_iceriver = remote_app_lower.contains("iceriver")
-        || remote_app_lower.contains("icemining")
-        || remote_app_lower.contains("icm");
-    let is_bitmain = remote_app_lower.contains("godminer")
-        || remote_app_lower.contains("bitmain")
-        || remote_app_lower.contains("antminer");
+    let profile = client_clone.miner_profile();
+    let is_iceriver = profile.job_format == JobFormat::IceRiver;
+    let is_bitmain = profile.is_bitmain();
 
     debug!(
         "[JOB] new_block_available: client {}, is_iceriver: {}, is_bitmain: {}, use_big_job: {}",
@@ -236,79 +227,46 @@ pub(crate) async fn new_block_job_task<T: KaspaApiTrait + Send + Sync + 'static>
         state.use_big_job()
     );
 
-    let mut job_params = vec![serde_json::Value::String(job_id.to_string())];
-    if is_iceriver {
-        // IceRiver format - single hex string (uses Hash::to_string() to match working stratum code)
-        // This matches Ghostpool and other working implementations
-        debug!("[JOB] new_block_available: Generating IceRiver format job params");
-        let iceriver_params = generate_iceriver_job_params(&pre_pow_hash, block.header.timestamp);
-        debug!(
-            "[JOB] new_block_available: IceRiver job_data length: {} (expected 80)",
-            iceriver_params.len()
-        );
-        job_params.push(serde_json::Value::String(iceriver_params));
-    } else if state.use_big_job() && !is_iceriver {
-        // BzMiner format - single hex string (big endian hash)
-        // Convert Hash to bytes for BzMiner format
-        debug!("[JOB] new_block_available: Generating BzMiner format job params");
-        let header_bytes = pre_pow_hash.as_bytes();
-        let large_params = generate_large_job_params(&header_bytes, block.header.timestamp);
-        debug!(
-            "[JOB] new_block_available: BzMiner job_data length: {} (expected 80)",
-            large_params.len()
-        );
-        job_params.push(serde_json::Value::String(large_params));
+    let format = if is_iceriver {
+        JobFormat::IceRiver
+    } else if state.use_big_job() {
+        JobFormat::BigJob
     } else {
-        // Legacy format - array + number (for Bitmain and other miners)
-        debug!("[JOB] new_block_available: Using Legacy format (array + timestamp)");
-        let header_bytes = pre_pow_hash.as_bytes();
-        let job_header = generate_job_header(&header_bytes);
-        job_params.push(serde_json::Value::Array(
-            job_header
-                .iter()
-                .map(|&v| serde_json::Value::Number(v.into()))
-                .collect(),
-        ));
-        job_params.push(serde_json::Value::Number(block.header.timestamp.into()));
-    }
+        JobFormat::Legacy
+    };
 
-    // IceRiver expects minimal notification format (method + params only, no id or jsonrpc)
-    // This matches StratumNotification format used by the stratum crate
-    let (is_iceriver_client, is_bitmain_client) = {
-        let app = client_clone.identity.lock().remote_app.clone();
-        let lower = app.to_lowercase();
-        (
-            app.contains("IceRiver"),
-            lower.contains("godminer") || lower.contains("bitmain") || lower.contains("antminer"),
-        )
+    // Sessions on the same template share one serialized line per notify shape
+    let key = NotifyKey {
+        job_id,
+        pre_pow_hash,
+        timestamp: block.header.timestamp,
+        format,
+        // IceRiver expects minimal notification format (method + params only, no id or jsonrpc)
+        minimal: is_iceriver,
     };
+    let line = notify_cache.notify_line(key, || {
+        notify_params(job_id, &pre_pow_hash, block.header.timestamp, format)
+    });
 
     debug!(
-        "new_block_available: sending job ID {} to client {} (params count: {}, is_iceriver: {}, is_bitmain: {})",
-        job_id,
-        client_clone.remote_addr,
-        job_params.len(),
-        is_iceriver_client,
-        is_bitmain_client
+        "new_block_available: sending job ID {} to client {} (format: {:?}, is_iceriver: {}, is_bitmain: {})",
+        job_id, client_clone.remote_addr, format, is_iceriver, is_bitmain
     );
 
-    // Send job ID in mining.notify
-    // })
-    let send_result = if is_iceriver_client {
-        // IceRiver expects minimal notification format (method + params only, no id or jsonrpc)
-        client_clone
-            .send_notification("mining.notify", job_params.clone())
-            .await
-    } else {
-        // For non-IceRiver, use standard JSON-RPC format with job ID
-        let notify_event = JsonRpcEvent {
-            jsonrpc: "2.0".to_string(),
-            method: "mining.notify".to_string(),
-            id: Some(serde_json::Value::Number(job_id.into())),
-            params: job_params.clone(),
-        };
-        client_clone.send(notify_event).await
-    };
+    // Send job ID in mining.notify (mark first so a submit racing the notify is not refused)
+    client_clone.mark_mining();
+    let send_result = client_clone
+        .send_notify(line, clean_jobs, notify_queue_depth)
+        .await;
+    if let Ok(dropped) = send_result
+        && dropped > 0
+    {
+        debug!(
+            "new_block_available: client {} is behind, dropped {} queued notify(s) before job {}",
+            client_clone.remote_addr, dropped, job_id
+        );
+        record_notifications_dropped(&instance_id, dropped);
+    }
 
     if let Err(e) = send_result {
         if e.to_string().contains("disconnected") {
@@ -334,6 +292,7 @@ pub(crate) async fn new_block_job_task<T: KaspaApiTrait + Send + Sync + 'static>
             );
         }
     } else {
+        state.set_last_notified_job_id(job_id);
         record_new_job(&crate::prom::worker_context(
             &instance_id,
             &client_clone,
@@ -345,3 +304,52 @@ pub(crate) async fn new_block_job_task<T: KaspaApiTrait + Send + Sync + 'static>
         );
     }
 }
+
+/// `mining.notify` params for `format`.
+fn notify_params(
+    job_id: u64,
+    pre_pow_hash: &Hash,
+    timestamp: u64,
+    format: JobFormat,
+) -> Vec<serde_json::Value> {
+    let mut job_params = vec![serde_json::Value::String(job_id.to_string())];
+    match format {
+        JobFormat::IceRiver => {
+            // IceRiver format - single hex string (uses Hash::to_string() to match working stratum code)
+            // This matches Ghostpool and other working implementations
+            debug!("[JOB] new_block_available: Generating IceRiver format job params");
+            let iceriver_params = generate_iceriver_job_params(pre_pow_hash, timestamp);
+            debug!(
+                "[JOB] new_block_available: IceRiver job_data length: {} (expected 80)",
+                iceriver_params.len()
+            );
+            job_params.push(serde_json::Value::String(iceriver_params));
+        }
+        JobFormat::BigJob => {
+            // BzMiner format - single hex string (big endian hash)
+            // Convert Hash to bytes for BzMiner format
+            debug!("[JOB] new_block_available: Generating BzMiner format job params");
+            let header_bytes = pre_pow_hash.as_bytes();
+            let large_params = generate_large_job_params(&header_bytes, timestamp);
+            debug!(
+                "[JOB] new_block_available: BzMiner job_data length: {} (expected 80)",
+                large_params.len()
+            );
+            job_params.push(serde_json::Value::String(large_params));
+        }
+        JobFormat::Legacy => {
+            // Legacy format - array + number (for Bitmain and other miners)
+            debug!("[JOB] new_block_available: Using Legacy format (array + timestamp)");
+            let header_bytes = pre_pow_hash.as_bytes();
+            let job_header = generate_job_header(&header_bytes);
+            job_params.push(serde_json::Value::Array(
+                job_header
+                    .iter()
+                    .map(|&v| serde_json::Value::Number(v.into()))
+                    .collect(),
+            ));
+            job_params.push(serde_json::Value::Number(timestamp.into()));
+        }
+    }
+    job_params
+}
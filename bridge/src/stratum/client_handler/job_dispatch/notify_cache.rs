@@ -0,0 +1,118 @@
+//! Serialized `mining.notify` lines shared across sessions, so a template broadcast to many miners on
+//! the same wallet builds and serializes each notify shape once.
+//!
+//! Job ids are per-session counters, so the key also carries the template (`pre_pow_hash` and
+//! timestamp): two sessions only share a line when every byte of it would match.
+
+use crate::jsonrpc_event::JsonRpcEvent;
+use crate::miner_detect::JobFormat;
+use bytes::Bytes;
+use kaspa_hashes::Hash;
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::num::NonZeroUsize;
+
+/// Distinct notify lines kept; old templates fall out as new ones arrive.
+pub const NOTIFY_CACHE_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NotifyKey {
+    pub job_id: u64,
+    pub pre_pow_hash: Hash,
+    pub timestamp: u64,
+    pub format: JobFormat,
+    /// IceRiver-style notification without `id`/`jsonrpc`.
+    pub minimal: bool,
+}
+
+pub struct NotifyCache {
+    lines: Mutex<LruCache<NotifyKey, Bytes>>,
+}
+
+impl Default for NotifyCache {
+    fn default() -> Self {
+        Self::new(NOTIFY_CACHE_CAPACITY)
+    }
+}
+
+impl NotifyCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Mutex::new(LruCache::new(
+                NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN),
+            )),
+        }
+    }
+
+    /// The `\n`-terminated notify line for `key`; `params` only runs on a miss.
+    pub fn notify_line(&self, key: NotifyKey, params: impl FnOnce() -> Vec<Value>) -> Bytes {
+        if let Some(line) = self.lines.lock().get(&key) {
+            return line.clone();
+        }
+        let line = serialize_notify(&key, params());
+        self.lines.lock().put(key, line.clone());
+        line
+    }
+}
+
+/// Same JSON the uncached `send` / `send_notification` paths write.
+pub fn serialize_notify(key: &NotifyKey, params: Vec<Value>) -> Bytes {
+    let mut line = if key.minimal {
+        serde_json::to_vec(&serde_json::json!({
+            "method": "mining.notify",
+            "params": params
+        }))
+    } else {
+        serde_json::to_vec(&JsonRpcEvent {
+            jsonrpc: "2.0".to_string(),
+            method: "mining.notify".to_string(),
+            id: Some(Value::Number(key.job_id.into())),
+            params,
+        })
+    }
+    .expect("notify params serialize");
+    line.push(b'\n');
+    Bytes::from(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(job_id: u64, minimal: bool) -> NotifyKey {
+        NotifyKey {
+            job_id,
+            pre_pow_hash: Hash::from_bytes([7; 32]),
+            timestamp: 1_700_000_000_000,
+            format: JobFormat::IceRiver,
+            minimal,
+        }
+    }
+
+    #[test]
+    fn serializes_each_shape_once() {
+        let cache = NotifyCache::default();
+        let mut builds = 0;
+        let params = || vec![Value::String("1".into()), Value::String("ab".into())];
+
+        let first = cache.notify_line(key(1, true), || {
+            builds += 1;
+            params()
+        });
+        let again = cache.notify_line(key(1, true), || {
+            builds += 1;
+            params()
+        });
+        assert_eq!(builds, 1);
+        assert_eq!(first, again);
+        assert_eq!(
+            &first[..],
+            b"{\"method\":\"mining.notify\",\"params\":[\"1\",\"ab\"]}\n"
+        );
+
+        let full = cache.notify_line(key(1, false), params);
+        assert_ne!(first, full, "minimal and full notifies are cached apart");
+        assert!(full.starts_with(b"{\"id\":1,\"jsonrpc\":\"2.0\""));
+    }
+}
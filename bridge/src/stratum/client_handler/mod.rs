@@ -1,28 +1,79 @@
 use crate::{
+    difficulty::MAX_SUGGESTED_SHARE_DIFF,
+    hasher::KaspaDiff,
+    miner_detect::{
+        DefaultMinerDetector, EXTRANONCE_SIZE_U8, EXTRANONCE_SIZE_U32, MinerDetector, MinerProfile,
+        resolve_miner_profile, subscribe_format_for_protocol,
+    },
+    mining_state::GetMiningState,
+    pool_events::{NoopPoolEventHandler, PoolEventHandler, WorkerDisconnectedEvent, WorkerInfo},
     prom::*,
-    share_handler::{KaspaApiTrait, ShareHandler},
-    stratum_context::StratumContext,
+    share_handler::{KaspaApiTrait, ShareHandler, VarDiffStore},
+    stratum_context::{DEFAULT_NOTIFY_QUEUE_DEPTH, MAX_NONCE_RANGE_BITS, StratumContext},
+    validation::WorkerNamePolicy,
+    wallet_sessions::WalletSessionLimiter,
+    worker_auth::{AllowAllAuthenticator, WorkerAuthenticator},
+    worker_credentials::WorkerCredentialStore,
+    worker_registry::WORKER_REGISTRY,
 };
 use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
 use std::time::{Duration, Instant};
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
 mod handshake;
 mod job_dispatch;
 
+pub use handshake::{
+    ExtranonceRange, MAX_EXTRANONCE_VALUE, MAX_EXTRANONCE_VALUE_U32, RangeAllocator,
+    allocated_extranonce_size, max_extranonce_for_size,
+};
+pub use job_dispatch::{
+    JobTemplate, NOTIFY_CACHE_CAPACITY, NotifyCache, NotifyKey, serialize_notify,
+};
+
 pub struct ClientHandler {
     clients: Arc<Mutex<HashMap<i32, Arc<StratumContext>>>>,
     client_counter: AtomicI32,
-    min_share_diff: f64,
-    _extranonce_size: i8, // Kept for backward compatibility, but now auto-detected per client
-    _max_extranonce: i32, // Kept for backward compatibility
+    /// `f64` bits; operators can change it at runtime (`ControlMessage::UpdateMinDiff`).
+    min_share_diff: AtomicU64,
+    /// Configured `extranonce_size`; 4 widens and 1 narrows every extranonce-using miner to that many bytes.
+    extranonce_size: i8,
+    _max_extranonce: u32, // Kept for backward compatibility
     last_template_time: Arc<Mutex<Instant>>,
+    /// Broadcasts still being sent; a new block replaces them (see [`Self::prioritize_job`]).
+    pending_jobs: job_dispatch::JobQueue,
+    notify_cache: Arc<NotifyCache>,
     last_balance_check: Arc<Mutex<Instant>>,
     share_handler: Arc<ShareHandler>,
     instance_id: String, // Instance identifier for logging
+    skip_address_validation: bool,
+    worker_credentials: Option<Arc<WorkerCredentialStore>>,
+    /// Final say on `mining.authorize` (custom authentication backend).
+    authenticator: Arc<dyn WorkerAuthenticator>,
+    miner_detector: Arc<dyn MinerDetector>,
+    extranonce_allocator: Option<RangeAllocator>,
+    /// Nonce bits a miner may roll through extranonce2 (`max_nonce_range_bits`).
+    max_nonce_range_bits: u8,
+    /// `mining.submit` limit per session (`max_submits_per_second`; `None` = unlimited).
+    max_submits_per_second: Option<u32>,
+    advertise_transactions_support: bool,
+    pool_name: Option<String>,
+    /// Embedder callbacks for worker connect/disconnect.
+    pool_events: Arc<dyn PoolEventHandler>,
+    /// `max_sessions_per_wallet` / `max_workers_per_wallet` bookkeeping (`None` = unlimited).
+    wallet_sessions: Option<Arc<WalletSessionLimiter>>,
+    worker_name_policy: WorkerNamePolicy,
+    /// Last difficulties from earlier sessions (`var_diff_state_file`); `None` when VarDiff is off.
+    vardiff_store: Option<Arc<VarDiffStore>>,
+    /// Notify sessions whose difficulty was retargeted even when their template is unchanged.
+    clean_jobs_on_retarget: bool,
+    /// Per-session `mining.notify` queue length before older refreshes are dropped.
+    notify_queue_depth: usize,
+    /// A session on this instance has sent `mining.extranonce.subscribe`.
+    extranonce_subscriber_seen: AtomicBool,
 }
 
 impl ClientHandler {
@@ -32,23 +83,273 @@ impl ClientHandler {
         extranonce_size: i8,
         instance_id: String,
     ) -> Self {
-        let max_extranonce = if extranonce_size > 0 {
-            (2_f64.powi(8 * extranonce_size.min(3) as i32) - 1.0) as i32
-        } else {
-            0
-        };
+        let max_extranonce = max_extranonce_for_size(extranonce_size);
 
         Self {
             clients: Arc::new(Mutex::new(HashMap::new())),
             client_counter: AtomicI32::new(0),
-            min_share_diff,
-            _extranonce_size: extranonce_size,
+            min_share_diff: AtomicU64::new(min_share_diff.to_bits()),
+            extranonce_size,
             _max_extranonce: max_extranonce,
             last_template_time: Arc::new(Mutex::new(Instant::now())),
+            pending_jobs: job_dispatch::JobQueue::default(),
+            notify_cache: Arc::new(NotifyCache::default()),
             last_balance_check: Arc::new(Mutex::new(Instant::now())),
             share_handler,
             instance_id,
+            skip_address_validation: false,
+            worker_credentials: None,
+            authenticator: Arc::new(AllowAllAuthenticator),
+            miner_detector: Arc::new(DefaultMinerDetector),
+            extranonce_allocator: None,
+            max_nonce_range_bits: MAX_NONCE_RANGE_BITS,
+            max_submits_per_second: None,
+            advertise_transactions_support: false,
+            pool_name: None,
+            pool_events: Arc::new(NoopPoolEventHandler),
+            wallet_sessions: None,
+            worker_name_policy: WorkerNamePolicy::default(),
+            vardiff_store: None,
+            clean_jobs_on_retarget: true,
+            notify_queue_depth: DEFAULT_NOTIFY_QUEUE_DEPTH,
+            extranonce_subscriber_seen: AtomicBool::new(false),
+        }
+    }
+
+    pub fn min_share_diff(&self) -> f64 {
+        f64::from_bits(self.min_share_diff.load(Ordering::Relaxed))
+    }
+
+    /// Starting difficulty for sessions that have not been given one yet; connected miners keep theirs.
+    pub fn set_min_share_diff(&self, diff: f64) {
+        self.min_share_diff.store(diff.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Initialize a new session's mining state and return its starting difficulty (suggested value
+    /// or `min_share_diff`); `None` once the session has one.
+    pub fn initialize_session_difficulty(&self, ctx: &StratumContext) -> Option<f64> {
+        let state = GetMiningState(ctx);
+        job_dispatch::initialize_client_diff(&self.instance_id, ctx, &state, self.min_share_diff())
+    }
+
+    /// Disable strict Kaspa address validation in `mining.authorize` (devnet/simnet setups).
+    pub fn with_skip_address_validation(mut self, skip: bool) -> Self {
+        self.skip_address_validation = skip;
+        self
+    }
+
+    pub fn skip_address_validation(&self) -> bool {
+        self.skip_address_validation
+    }
+
+    /// Check `mining.authorize` passwords against a shared credential store.
+    pub fn with_worker_credentials(mut self, store: Option<Arc<WorkerCredentialStore>>) -> Self {
+        self.worker_credentials = store;
+        self
+    }
+
+    pub fn worker_credentials(&self) -> Option<&Arc<WorkerCredentialStore>> {
+        self.worker_credentials.as_ref()
+    }
+
+    /// Ask `authenticator` about every `mining.authorize` (`None` keeps the allow-all default).
+    pub fn with_worker_authenticator(
+        mut self,
+        authenticator: Option<Arc<dyn WorkerAuthenticator>>,
+    ) -> Self {
+        if let Some(authenticator) = authenticator {
+            self.authenticator = authenticator;
+        }
+        self
+    }
+
+    pub fn authenticator(&self) -> &Arc<dyn WorkerAuthenticator> {
+        &self.authenticator
+    }
+
+    /// Length and character limits for worker names in `mining.authorize`.
+    pub fn with_worker_name_policy(mut self, policy: WorkerNamePolicy) -> Self {
+        self.worker_name_policy = policy;
+        self
+    }
+
+    pub fn worker_name_policy(&self) -> &WorkerNamePolicy {
+        &self.worker_name_policy
+    }
+
+    /// Refuse `mining.authorize` once a wallet has `max_sessions` sessions or `max_workers` distinct
+    /// worker names (`None` = unlimited).
+    pub fn with_wallet_limits(
+        mut self,
+        max_sessions: Option<u32>,
+        max_workers: Option<u32>,
+    ) -> Self {
+        self.wallet_sessions = (max_sessions.is_some() || max_workers.is_some())
+            .then(|| Arc::new(WalletSessionLimiter::new(max_sessions, max_workers)));
+        self
+    }
+
+    pub fn wallet_sessions(&self) -> Option<&Arc<WalletSessionLimiter>> {
+        self.wallet_sessions.as_ref()
+    }
+
+    /// Start returning workers near their saved difficulty and keep the saved values current.
+    pub fn with_vardiff_store(mut self, store: Option<Arc<VarDiffStore>>) -> Self {
+        self.vardiff_store = store;
+        self
+    }
+
+    /// Start an authorized worker at its saved difficulty hint, unless the miner already suggested
+    /// one. Returns the difficulty applied.
+    pub fn apply_vardiff_hint(&self, ctx: &StratumContext) -> Option<f64> {
+        let store = self.vardiff_store.as_ref()?;
+        if ctx.state.stratum_diff().is_some() {
+            return None;
         }
+        let wallet = ctx.identity.lock().wallet_addr.clone();
+        let worker = ctx.effective_worker_name();
+        let hint = store.initial_difficulty_hint(&wallet, &worker)?;
+        let applied = self.apply_suggested_difficulty(ctx, hint as f64);
+        debug!(
+            "{} [VARDIFF] Starting {}.{} at saved hint {} (applied {})",
+            self.instance_id, wallet, worker, hint, applied
+        );
+        Some(applied)
+    }
+
+    fn record_session_vardiff(&self, store: &VarDiffStore, ctx: &StratumContext) {
+        let wallet = ctx.identity.lock().wallet_addr.clone();
+        if wallet.is_empty() || !ctx.state.is_initialized() {
+            return;
+        }
+        let difficulty = self.share_handler.get_client_vardiff(ctx);
+        store.record(&wallet, &ctx.effective_worker_name(), difficulty);
+    }
+
+    /// Save every authorized session's current difficulty to the VarDiff state store.
+    pub fn record_vardiff_state(&self) {
+        let Some(store) = self.vardiff_store.as_ref() else {
+            return;
+        };
+        let clients = self.clients.lock().values().cloned().collect::<Vec<_>>();
+        for ctx in clients {
+            self.record_session_vardiff(store, &ctx);
+        }
+    }
+
+    /// Apply a miner-suggested difficulty (`mining.suggest_target`), clamped to
+    /// `[min_share_diff, MAX_SUGGESTED_SHARE_DIFF]`. Returns the difficulty actually applied.
+    ///
+    /// Before the first job the value is only stored and becomes the initial difficulty; afterwards
+    /// it is sent to the miner right away.
+    pub fn apply_suggested_difficulty(&self, ctx: &StratumContext, diff: f64) -> f64 {
+        let min_diff = self.min_share_diff();
+        let max_diff = MAX_SUGGESTED_SHARE_DIFF.max(min_diff);
+        let diff = if diff.is_finite() {
+            diff.clamp(min_diff, max_diff)
+        } else {
+            min_diff
+        };
+
+        let state = GetMiningState(ctx);
+        let remote_app = ctx.identity.lock().remote_app.clone();
+        let mut stratum_diff = KaspaDiff::new();
+        stratum_diff.set_diff_value_for_miner(diff, &remote_app);
+        state.set_stratum_diff(stratum_diff);
+        self.share_handler.set_client_vardiff(ctx, diff);
+
+        if state.is_initialized() {
+            if !ctx.identity.lock().wallet_addr.is_empty() {
+                update_worker_difficulty(&worker_context(&self.instance_id, ctx, remote_app), diff);
+            }
+            job_dispatch::send_client_diff(&self.instance_id, ctx, &state, diff);
+        }
+        debug!(
+            "{} [DIFFICULTY] Applied suggested difficulty {} for {}",
+            self.instance_id, diff, ctx.remote_addr
+        );
+        diff
+    }
+
+    /// Use a custom miner classifier; unrecognized user-agents still fall back to the built-in rules.
+    pub fn with_miner_detector(mut self, detector: Option<Arc<dyn MinerDetector>>) -> Self {
+        if let Some(detector) = detector {
+            self.miner_detector = detector;
+        }
+        self
+    }
+
+    /// Hand out extranonces from this instance's own range instead of the shared counter.
+    pub fn with_extranonce_range(mut self, range: Option<ExtranonceRange>) -> Self {
+        self.extranonce_allocator = range.map(RangeAllocator::new);
+        self
+    }
+
+    /// Cap the nonce bits miners control through extranonce2 (rounded down to whole bytes, 8..=64).
+    pub fn with_max_nonce_range_bits(mut self, bits: u8) -> Self {
+        self.max_nonce_range_bits = (bits / 8).clamp(1, 8) * 8;
+        self
+    }
+
+    /// Rate-limit each session's `mining.submit` (`None` = unlimited).
+    pub fn with_max_submits_per_second(mut self, max_per_second: Option<u32>) -> Self {
+        self.max_submits_per_second = max_per_second;
+        self
+    }
+
+    /// List `mining.get_transactions` among the subscriptions in NiceHash-style subscribe results.
+    pub fn with_advertise_transactions_support(mut self, advertise: bool) -> Self {
+        self.advertise_transactions_support = advertise;
+        self
+    }
+
+    pub fn advertise_transactions_support(&self) -> bool {
+        self.advertise_transactions_support
+    }
+
+    /// Name announced to miners with `mining.set_pool` after authorization.
+    pub fn with_pool_name(mut self, pool_name: Option<String>) -> Self {
+        self.pool_name = pool_name.filter(|name| !name.trim().is_empty());
+        self
+    }
+
+    pub fn pool_name(&self) -> Option<&str> {
+        self.pool_name.as_deref()
+    }
+
+    /// Report worker connects/disconnects to `handler` (`None` keeps the no-op default).
+    pub fn with_pool_event_handler(mut self, handler: Option<Arc<dyn PoolEventHandler>>) -> Self {
+        if let Some(handler) = handler {
+            self.pool_events = handler;
+        }
+        self
+    }
+
+    pub fn pool_events(&self) -> &Arc<dyn PoolEventHandler> {
+        &self.pool_events
+    }
+
+    /// Follow each VarDiff retarget with a fresh job (`clean_jobs_on_retarget`, default true); when
+    /// off, a refresh that only changed the difficulty sends no `mining.notify`.
+    pub fn with_clean_jobs_on_retarget(mut self, enabled: bool) -> Self {
+        self.clean_jobs_on_retarget = enabled;
+        self
+    }
+
+    /// Queue at most `depth` `mining.notify` lines per session (`notify_queue_depth`, at least 1).
+    pub fn with_notify_queue_depth(mut self, depth: usize) -> Self {
+        self.notify_queue_depth = depth.max(1);
+        self
+    }
+
+    pub fn extranonce_range(&self) -> Option<ExtranonceRange> {
+        self.extranonce_allocator
+            .as_ref()
+            .map(RangeAllocator::range)
     }
 
     pub fn on_connect(&self, ctx: Arc<StratumContext>) {
@@ -59,6 +360,8 @@ impl ClientHandler {
         *ctx.extranonce.lock() = String::new();
 
         ctx.set_id(idx);
+        ctx.set_max_nonce_range_bits(self.max_nonce_range_bits);
+        ctx.set_max_submits_per_second(self.max_submits_per_second);
         self.clients.lock().insert(idx, Arc::clone(&ctx));
 
         debug!(
@@ -88,23 +391,86 @@ impl ClientHandler {
     /// Assign extranonce to a client based on detected miner type
     /// Called from handle_subscribe after miner type is detected
     pub fn assign_extranonce_for_miner(&self, ctx: &StratumContext, remote_app: &str) {
-        handshake::assign_extranonce_for_miner(ctx, remote_app);
+        let mut profile = self.detect_miner(remote_app);
+        if matches!(
+            self.extranonce_size,
+            EXTRANONCE_SIZE_U8 | EXTRANONCE_SIZE_U32
+        ) && profile.extranonce_size > 0
+        {
+            profile.extranonce_size = self.extranonce_size;
+        }
+        let protocol = ctx.identity.lock().protocol_version.clone();
+        if let Some(format) = protocol.as_deref().and_then(subscribe_format_for_protocol) {
+            debug!(
+                "{} announced {}, using {} subscribe format",
+                ctx.remote_addr,
+                protocol.as_deref().unwrap_or_default(),
+                format.name()
+            );
+            profile.subscribe_format = format;
+        }
+        // Without `extranonce2_size` in the subscribe result the extranonce only reaches the miner
+        // through `mining.set_extranonce`; miners too old for that (BzMiner before v18) get none
+        if !profile.sends_extranonce2_size()
+            && !crate::user_agent::UserAgent::parse(remote_app).supports_set_extranonce()
+        {
+            profile.extranonce_size = 0;
+        }
+        ctx.set_miner_profile(profile);
+        handshake::assign_extranonce_for_miner(
+            ctx,
+            &profile,
+            remote_app,
+            self.extranonce_allocator.as_ref(),
+        );
+    }
+
+    /// Classify a miner with the configured detector (built-in rules as fallback).
+    pub fn detect_miner(&self, remote_app: &str) -> MinerProfile {
+        resolve_miner_profile(self.miner_detector.as_ref(), remote_app)
     }
 
     pub fn on_disconnect(&self, ctx: &StratumContext) {
         ctx.disconnect();
-        let mut clients = self.clients.lock();
+        ctx.release_wallet_session();
+        if ctx.supports_set_extranonce() {
+            record_extranonce_subscriber(&self.instance_id, false);
+        }
+        if let Some(store) = self.vardiff_store.as_ref() {
+            self.record_session_vardiff(store, ctx);
+        }
         if let Some(id) = ctx.id() {
             debug!("removing client {}", id);
-            clients.remove(&id);
+            self.clients.lock().remove(&id);
             debug!("removed client {}", id);
         }
+        WORKER_REGISTRY.unregister(&self.instance_id, ctx);
         if !ctx.identity.lock().wallet_addr.is_empty() {
             let remote_app = ctx.identity.lock().remote_app.clone();
             record_disconnect(&worker_context(&self.instance_id, ctx, remote_app));
+            self.pool_events
+                .on_worker_disconnected(WorkerDisconnectedEvent {
+                    worker: WorkerInfo::from_context(&self.instance_id, ctx),
+                    connected_for: ctx.connected_at().elapsed(),
+                });
+        }
+        let user_agent = ctx.identity.lock().user_agent.take();
+        if let Some(user_agent) = user_agent {
+            record_software_version_disconnected(&user_agent);
         }
     }
 
+    /// Publish the age of the oldest connected session (`ks_session_active_duration_seconds`).
+    pub fn record_longest_session_age(&self) {
+        let oldest = self
+            .clients
+            .lock()
+            .values()
+            .map(|ctx| ctx.connected_at().elapsed())
+            .max();
+        record_longest_session_age(&self.instance_id, oldest);
+    }
+
     pub fn disconnect_all(&self) {
         let clients = {
             let guard = self.clients.lock();
@@ -118,12 +484,139 @@ impl ClientHandler {
         self.clients.lock().clear();
     }
 
+    /// Count a session that just sent `mining.extranonce.subscribe`; the first one on the instance is
+    /// logged.
+    pub fn on_extranonce_subscribe(&self, ctx: &StratumContext) {
+        record_extranonce_subscriber(&self.instance_id, true);
+        if !self
+            .extranonce_subscriber_seen
+            .swap(true, Ordering::Relaxed)
+        {
+            info!(
+                "{} First mining.extranonce.subscribe miner connected ({}, {})",
+                self.instance_id,
+                ctx.remote_addr,
+                ctx.identity.lock().remote_app
+            );
+        }
+    }
+
+    /// Give a session a new extranonce. Miners that sent `mining.extranonce.subscribe` get
+    /// `mining.set_extranonce`; any other miner is sent `client.reconnect` back to this bridge and
+    /// disconnected, since it cannot pick up the change mid-session.
+    pub async fn change_extranonce(&self, ctx: Arc<StratumContext>, extranonce: String) {
+        *ctx.extranonce.lock() = extranonce;
+        if ctx.supports_set_extranonce() {
+            if let Err(e) = crate::default_client::send_extranonce(Arc::clone(&ctx)).await {
+                debug!(
+                    "{} failed sending mining.set_extranonce to {}: {}",
+                    self.instance_id, ctx.remote_addr, e
+                );
+                ctx.disconnect();
+            }
+            return;
+        }
+        // No params: reconnect to the same host and port
+        let event = crate::jsonrpc_event::JsonRpcEvent {
+            jsonrpc: "2.0".to_string(),
+            method: "client.reconnect".to_string(),
+            id: None,
+            params: Vec::new(),
+        };
+        let _ = ctx.send(event).await;
+        info!(
+            "{} Reconnecting {} for an extranonce change (no mining.extranonce.subscribe)",
+            self.instance_id, ctx.remote_addr
+        );
+        ctx.disconnect();
+    }
+
+    /// Close every session authorized for `wallet`. Returns the number of sessions closed.
+    pub fn disconnect_wallet(&self, wallet: &str) -> usize {
+        let clients = {
+            let guard = self.clients.lock();
+            guard.values().cloned().collect::<Vec<_>>()
+        };
+
+        let mut closed = 0;
+        for client in clients {
+            if client.connected() && client.identity.lock().wallet_addr == wallet {
+                client.disconnect();
+                closed += 1;
+            }
+        }
+        info!(
+            "{} Disconnected {} session(s) of wallet {}",
+            self.instance_id, closed, wallet
+        );
+        closed
+    }
+
+    /// Send `client.reconnect` to every session, then close sessions still connected after `delay`.
+    /// Returns the number of sessions notified.
+    pub async fn broadcast_reconnect(&self, host: &str, port: u16, delay: Duration) -> usize {
+        let clients = {
+            let guard = self.clients.lock();
+            guard.values().cloned().collect::<Vec<_>>()
+        };
+
+        let event = crate::jsonrpc_event::JsonRpcEvent {
+            jsonrpc: "2.0".to_string(),
+            method: "client.reconnect".to_string(),
+            id: None,
+            params: vec![
+                serde_json::Value::String(host.to_string()),
+                serde_json::Value::from(port),
+                serde_json::Value::from(0),
+            ],
+        };
+
+        let mut notified = 0;
+        for client in &clients {
+            if !client.connected() {
+                continue;
+            }
+            match client.send(event.clone()).await {
+                Ok(()) => notified += 1,
+                Err(e) => debug!(
+                    "{} failed sending client.reconnect to {}: {}",
+                    self.instance_id, client.remote_addr, e
+                ),
+            }
+        }
+        record_reconnect_broadcast(&self.instance_id);
+        info!(
+            "{} Sent client.reconnect ({}:{}) to {} session(s); closing leftovers in {:?}",
+            self.instance_id, host, port, notified, delay
+        );
+
+        let instance_id = self.instance_id.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let remaining: Vec<_> = clients.into_iter().filter(|c| c.connected()).collect();
+            if !remaining.is_empty() {
+                info!(
+                    "{} Closing {} session(s) that did not reconnect",
+                    instance_id,
+                    remaining.len()
+                );
+            }
+            for client in remaining {
+                client.disconnect();
+            }
+        });
+
+        notified
+    }
+
     /// Send an immediate job to a specific client (for use after authorization)
-    /// This ensures IceRiver and other ASICs get a job immediately, not waiting for polling
+    /// This ensures IceRiver and other ASICs get a job immediately, not waiting for polling.
+    /// `resend_difficulty` repeats the current difficulty first (re-authorizing sessions).
     pub async fn send_immediate_job_to_client<T: KaspaApiTrait + Send + Sync + ?Sized + 'static>(
         &self,
         client: Arc<StratumContext>,
         kaspa_api: Arc<T>,
+        resend_difficulty: bool,
     ) {
         // Check if client has wallet address
         let _wallet_addr_str = {
@@ -149,7 +642,7 @@ impl ClientHandler {
         let client_clone = Arc::clone(&client);
         let kaspa_api_clone = Arc::clone(&kaspa_api);
         let share_handler = Arc::clone(&self.share_handler);
-        let min_diff = self.min_share_diff;
+        let min_diff = self.min_share_diff();
         let instance_id = self.instance_id.clone();
 
         tokio::spawn(async move {
@@ -159,11 +652,13 @@ impl ClientHandler {
                 share_handler,
                 min_diff,
                 instance_id,
+                resend_difficulty,
             )
             .await;
         });
     }
 
+    /// Refresh every session's job (ticker path); skipped within 250ms of the previous broadcast.
     pub async fn new_block_available<T: KaspaApiTrait + Send + Sync + 'static>(
         &self,
         kaspa_api: Arc<T>,
@@ -177,6 +672,36 @@ impl ClientHandler {
             *last_time = Instant::now();
         }
 
+        let job = self.pending_jobs.push(JobTemplate::refresh());
+        self.broadcast_job(job, kaspa_api).await;
+    }
+
+    /// Send a new block's job to every session right away: pending broadcasts are cleared (any still
+    /// in progress stop at their next session) and `job` goes out with `clean_jobs` set, bypassing the
+    /// 250ms rate limit.
+    pub async fn prioritize_job<T: KaspaApiTrait + Send + Sync + 'static>(
+        &self,
+        job: JobTemplate,
+        kaspa_api: Arc<T>,
+    ) {
+        let (job, superseded) = self.pending_jobs.prioritize(job);
+        *self.last_template_time.lock() = Instant::now();
+        if superseded > 0 {
+            debug!(
+                "[{}] new block superseded {} pending job broadcast(s)",
+                self.instance_id, superseded
+            );
+        }
+        self.broadcast_job(job, kaspa_api).await;
+    }
+
+    async fn broadcast_job<T: KaspaApiTrait + Send + Sync + 'static>(
+        &self,
+        job: JobTemplate,
+        kaspa_api: Arc<T>,
+    ) {
+        record_jobs_broadcast(&self.instance_id, job.clean_jobs);
+
         let clients = {
             let clients_guard = self.clients.lock();
             clients_guard.values().cloned().collect::<Vec<_>>()
@@ -194,6 +719,13 @@ impl ClientHandler {
             if client_count > 0 {
                 tokio::time::sleep(Duration::from_micros(500)).await;
             }
+            if !self.pending_jobs.is_pending(&job) {
+                debug!(
+                    "[{}] job broadcast superseded after {} session(s)",
+                    self.instance_id, client_count
+                );
+                break;
+            }
             client_count += 1;
 
             // Collect wallet address for balance checking
@@ -207,8 +739,12 @@ impl ClientHandler {
             let client_clone = Arc::clone(&client);
             let kaspa_api_clone = Arc::clone(&kaspa_api);
             let share_handler = Arc::clone(&self.share_handler);
-            let min_diff = self.min_share_diff;
+            let min_diff = self.min_share_diff();
             let instance_id = self.instance_id.clone();
+            let notify_cache = Arc::clone(&self.notify_cache);
+            let clean_jobs = job.clean_jobs;
+            let clean_jobs_on_retarget = self.clean_jobs_on_retarget;
+            let notify_queue_depth = self.notify_queue_depth;
 
             tokio::spawn(async move {
                 job_dispatch::new_block_job_task(
@@ -217,10 +753,15 @@ impl ClientHandler {
                     share_handler,
                     min_diff,
                     instance_id,
+                    notify_cache,
+                    clean_jobs,
+                    clean_jobs_on_retarget,
+                    notify_queue_depth,
                 )
                 .await;
             });
         }
+        self.pending_jobs.finish(&job);
 
         // Check balances periodically
         {
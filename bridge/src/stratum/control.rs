@@ -0,0 +1,45 @@
+//! Per-instance control channel used by the operator HTTP API to reach a running stratum server.
+//!
+//! `runner` creates one `mpsc` channel per instance, registers the sender here under the instance
+//! number, and hands the receiver to the stratum server via `BridgeConfig::control_rx`.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Queue depth for each instance control channel.
+pub const CONTROL_CHANNEL_CAPACITY: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlMessage {
+    /// Send `client.reconnect` to every session, then close whatever is still connected after `delay`.
+    Reconnect {
+        host: String,
+        port: u16,
+        delay: Duration,
+    },
+    /// Send every session a fresh job now instead of waiting for the next template.
+    BroadcastNewJob,
+    /// Close every session authorized for this wallet address.
+    DisconnectWallet(String),
+    /// New `min_share_diff` for sessions that have not been given a difficulty yet.
+    UpdateMinDiff(u32),
+    /// Stop this instance: close the listener and every session. Other instances keep running.
+    Shutdown,
+}
+
+static CONTROL_SENDERS: LazyLock<Mutex<HashMap<usize, mpsc::Sender<ControlMessage>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Create the control channel for `instance_num` and register its sender.
+pub fn register_control_channel(instance_num: usize) -> mpsc::Receiver<ControlMessage> {
+    let (tx, rx) = mpsc::channel(CONTROL_CHANNEL_CAPACITY);
+    CONTROL_SENDERS.lock().insert(instance_num, tx);
+    rx
+}
+
+pub fn control_sender(instance_num: usize) -> Option<mpsc::Sender<ControlMessage>> {
+    CONTROL_SENDERS.lock().get(&instance_num).cloned()
+}
@@ -1,16 +1,15 @@
+use crate::bridge_error::BridgeError;
 use crate::jsonrpc_event::{JsonRpcEvent, JsonRpcResponse};
 use crate::stratum_context::StratumContext;
+use crate::stratum_error::StratumError;
+use crate::wallet_sessions::WalletLimitExceeded;
+use crate::worker_credentials::CredentialRejection;
 use kaspa_addresses::Address;
 use regex::Regex;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::{Arc, LazyLock};
 
-/// Regex for matching miners that use big job format
-/// Matches: BzMiner, IceRiverMiner (from client_handler.go bigJobRegex)
-static BIG_JOB_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r".*(BzMiner|IceRiverMiner).*").unwrap());
-
 /// Regex for matching wallet addresses
 static WALLET_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"kaspa(test|dev)?:([a-z0-9]{61}|[a-z0-9]{63})").unwrap());
@@ -46,7 +45,7 @@ pub fn default_handlers() -> HashMap<String, crate::stratum_listener::EventHandl
         Arc::new(|ctx: Arc<StratumContext>, event: JsonRpcEvent| {
             let ctx = ctx.clone();
             let event = event.clone();
-            Box::pin(async move { handle_extranonce_subscribe(ctx, event).await })
+            Box::pin(async move { handle_extranonce_subscribe(ctx, event, None).await })
                 as std::pin::Pin<
                     Box<
                         dyn std::future::Future<
@@ -92,6 +91,54 @@ pub fn default_handlers() -> HashMap<String, crate::stratum_listener::EventHandl
         }) as crate::stratum_listener::EventHandler,
     );
 
+    handlers.insert(
+        "mining.ping".to_string(),
+        Arc::new(|ctx: Arc<StratumContext>, event: JsonRpcEvent| {
+            let ctx = ctx.clone();
+            let event = event.clone();
+            Box::pin(async move { handle_ping(ctx, event).await })
+                as std::pin::Pin<
+                    Box<
+                        dyn std::future::Future<
+                                Output = Result<(), Box<dyn std::error::Error + Send + Sync>>,
+                            > + Send,
+                    >,
+                >
+        }) as crate::stratum_listener::EventHandler,
+    );
+
+    handlers.insert(
+        "mining.get_transactions".to_string(),
+        Arc::new(|ctx: Arc<StratumContext>, event: JsonRpcEvent| {
+            let ctx = ctx.clone();
+            let event = event.clone();
+            Box::pin(async move { handle_get_transactions(ctx, event).await })
+                as std::pin::Pin<
+                    Box<
+                        dyn std::future::Future<
+                                Output = Result<(), Box<dyn std::error::Error + Send + Sync>>,
+                            > + Send,
+                    >,
+                >
+        }) as crate::stratum_listener::EventHandler,
+    );
+
+    handlers.insert(
+        "mining.pong".to_string(),
+        Arc::new(|ctx: Arc<StratumContext>, event: JsonRpcEvent| {
+            let ctx = ctx.clone();
+            let event = event.clone();
+            Box::pin(async move { handle_pong(ctx, event).await })
+                as std::pin::Pin<
+                    Box<
+                        dyn std::future::Future<
+                                Output = Result<(), Box<dyn std::error::Error + Send + Sync>>,
+                            > + Send,
+                    >,
+                >
+        }) as crate::stratum_listener::EventHandler,
+    );
+
     handlers
 }
 
@@ -115,10 +162,27 @@ pub async fn handle_subscribe(
         event.params.len()
     );
 
+    if let Err(error) = ctx.check_subscribe() {
+        return Err(reject_out_of_order(&ctx, &event, error).await);
+    }
+
     // Extract remote app from params if present
     if let Some(Value::String(app)) = event.params.first() {
-        ctx.identity.lock().remote_app = app.clone();
-        tracing::debug!("[SUBSCRIBE] Extracted app from params[0]: '{}'", app);
+        let user_agent = crate::user_agent::UserAgent::parse(app);
+        let previous = {
+            let mut id = ctx.identity.lock();
+            id.remote_app = app.clone();
+            id.user_agent.replace(user_agent.clone())
+        };
+        if let Some(previous) = previous {
+            crate::prom::record_software_version_disconnected(&previous);
+        }
+        crate::prom::record_software_version_connected(&user_agent);
+        tracing::debug!(
+            "[SUBSCRIBE] Extracted app from params[0]: '{}' ({:?})",
+            app,
+            user_agent
+        );
     } else {
         tracing::warn!(
             "[SUBSCRIBE] No app string in params[0], params: {:?}",
@@ -126,6 +190,14 @@ pub async fn handle_subscribe(
         );
     }
 
+    let protocol_version = crate::miner_detect::protocol_version_from_params(&event.params);
+    tracing::debug!(
+        "[SUBSCRIBE] {} negotiated protocol: {}",
+        ctx.remote_addr,
+        protocol_version.as_deref().unwrap_or("none")
+    );
+    ctx.identity.lock().protocol_version = protocol_version;
+
     let remote_app = ctx.identity.lock().remote_app.clone();
 
     tracing::info!(
@@ -135,12 +207,17 @@ pub async fn handle_subscribe(
         ctx.remote_port
     );
 
+    let advertise_transactions = client_handler
+        .as_ref()
+        .is_some_and(|h| h.advertise_transactions_support());
+
     // Auto-detect miner type and assign appropriate extranonce
     if let Some(handler) = client_handler {
         handler.assign_extranonce_for_miner(&ctx, &remote_app);
     }
 
     let extranonce = ctx.extranonce.lock().clone();
+    ctx.mark_subscribed(extranonce.clone());
 
     tracing::debug!(
         "[SUBSCRIBE] Client info - app: '{}', extranonce: '{}', addr: {}",
@@ -149,12 +226,9 @@ pub async fn handle_subscribe(
         ctx.remote_addr
     );
 
-    // Check if this is a Bitmain miner - use same detection logic as assign_extranonce_for_miner
-    // (case-insensitive matching for consistency)
-    let remote_app_lower = remote_app.to_lowercase();
-    let is_bitmain = remote_app_lower.contains("godminer")
-        || remote_app_lower.contains("bitmain")
-        || remote_app_lower.contains("antminer");
+    // Subscribe response shape comes from the miner profile detected above
+    let profile = ctx.miner_profile();
+    let is_bitmain = profile.is_bitmain();
     tracing::debug!(
         "[SUBSCRIBE] Detected miner type - Remote app: '{}', Is Bitmain: {}",
         remote_app,
@@ -171,20 +245,40 @@ pub async fn handle_subscribe(
         );
     }
 
-    let response = if is_bitmain {
-        // Bitmain format - extranonce in subscribe response
-        let extranonce2_size = 8 - (extranonce.len() / 2);
+    let response = if profile.is_nicehash() {
+        // NiceHash format - subscription tuples, extranonce1, extranonce2_size
+        let session_id = format!("{:08x}", ctx.id().unwrap_or_default());
+        let extranonce2_size = ctx.extranonce2_size();
+        let mut result = nicehash_subscribe_result(&session_id, &extranonce, extranonce2_size);
+        if advertise_transactions
+            && let Some(subscriptions) = result.get_mut(0).and_then(Value::as_array_mut)
+        {
+            subscriptions.push(serde_json::json!(["mining.get_transactions", session_id]));
+        }
+        tracing::debug!(
+            "[SUBSCRIBE] ===== USING NICEHASH SUBSCRIBE FORMAT FOR {} ===== {}",
+            ctx.remote_addr,
+            result
+        );
+        JsonRpcResponse::new(&event, Some(result), None)
+    } else if profile.sends_extranonce2_size() {
+        // Bitmain / Goldshell / KaspaStratum format - extranonce in subscribe response
+        let format_name = profile.subscribe_format.name();
+        let extranonce2_size = ctx.extranonce2_size();
         tracing::debug!(
-            "[SUBSCRIBE] ===== USING BITMAIN SUBSCRIBE FORMAT FOR {} =====",
+            "[SUBSCRIBE] ===== USING {} SUBSCRIBE FORMAT FOR {} =====",
+            format_name.to_uppercase(),
             ctx.remote_addr
         );
         tracing::debug!(
-            "[SUBSCRIBE] Bitmain extranonce: '{}', extranonce2_size: {}",
+            "[SUBSCRIBE] {} extranonce: '{}', extranonce2_size: {}",
+            format_name,
             extranonce,
             extranonce2_size
         );
         tracing::debug!(
-            "[SUBSCRIBE] Bitmain response: [null, '{}', {}]",
+            "[SUBSCRIBE] {} response: [null, '{}', {}]",
+            format_name,
             extranonce,
             extranonce2_size
         );
@@ -200,7 +294,7 @@ pub async fn handle_subscribe(
     } else {
         // Standard format (for IceRiver, BzMiner, and other miners)
         // Extranonce will be sent via mining.set_extranonce after authorize
-        if BIG_JOB_REGEX.is_match(&remote_app) {
+        if profile.job_format != crate::miner_detect::JobFormat::Legacy {
             tracing::debug!(
                 "[SUBSCRIBE] Using standard subscribe format for IceRiver/BzMiner {}",
                 ctx.remote_addr
@@ -240,10 +334,49 @@ pub async fn handle_subscribe(
     Ok(())
 }
 
-/// Handle extranonce subscribe request
-async fn handle_extranonce_subscribe(
+/// Answer a method the session state does not allow yet with `error`, and return it for the read loop
+/// to log.
+pub async fn reject_out_of_order(
+    ctx: &StratumContext,
+    event: &JsonRpcEvent,
+    error: StratumError,
+) -> Box<dyn std::error::Error + Send + Sync> {
+    let _ = ctx
+        .reply(JsonRpcResponse::stratum_error(
+            event.id.clone(),
+            error.clone(),
+        ))
+        .await;
+    BridgeError::OutOfOrder {
+        method: event.method.clone(),
+        error,
+    }
+    .into_boxed_stratum()
+}
+
+/// NiceHash subscribe result:
+/// `[[["mining.set_difficulty", session], ["mining.notify", session]], extranonce1, extranonce2_size]`.
+pub fn nicehash_subscribe_result(
+    session_id: &str,
+    extranonce: &str,
+    extranonce2_size: usize,
+) -> Value {
+    serde_json::json!([
+        [
+            ["mining.set_difficulty", session_id],
+            ["mining.notify", session_id]
+        ],
+        extranonce,
+        extranonce2_size
+    ])
+}
+
+/// Handle extranonce subscribe request: the session opts in to `mining.set_extranonce` whenever the
+/// bridge changes its extranonce (sessions that did not are reconnected instead).
+pub async fn handle_extranonce_subscribe(
     ctx: Arc<StratumContext>,
     event: JsonRpcEvent,
+    client_handler: Option<Arc<crate::client_handler::ClientHandler>>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     tracing::debug!(
         "[EXTRANONCE_SUBSCRIBE] ===== EXTRANONCE SUBSCRIBE FROM {} =====",
@@ -251,6 +384,12 @@ async fn handle_extranonce_subscribe(
     );
     tracing::debug!("[EXTRANONCE_SUBSCRIBE] Event ID: {:?}", event.id);
 
+    if ctx.set_supports_set_extranonce()
+        && let Some(handler) = client_handler.as_ref()
+    {
+        handler.on_extranonce_subscribe(&ctx);
+    }
+
     let response = JsonRpcResponse::new(&event, Some(Value::Bool(true)), None);
     let response_json = serde_json::to_string(&response).unwrap_or_else(|_| "failed".to_string());
     tracing::debug!(
@@ -293,16 +432,23 @@ pub async fn handle_authorize(
         event.params.len()
     );
 
-    if event.params.is_empty() {
-        tracing::error!("[AUTHORIZE] ERROR: Empty params from {}", ctx.remote_addr);
-        return Err("malformed event from miner, expected param[0] to be address".into());
+    // Before any address parsing, credential lookup or auth backend call
+    if let Err(error) = ctx.check_authorize() {
+        return Err(reject_out_of_order(&ctx, &event, error).await);
     }
 
-    let address_value = event.params.first().ok_or("missing address parameter")?;
-
-    let address_str = address_value
-        .as_str()
-        .ok_or("expected param[0] to be address string")?;
+    let Some(address_str) = event.params.first().and_then(Value::as_str) else {
+        tracing::info!(
+            "[AUTHORIZE] Rejecting authorize without an address string from {}:{}",
+            ctx.remote_addr,
+            ctx.remote_port
+        );
+        crate::prom::record_authorize_rejection("invalid_address");
+        let _ = ctx
+            .reply_unauthorized(event.id.clone(), "Missing worker address")
+            .await;
+        return Err("malformed event from miner, expected param[0] to be address".into());
+    };
 
     tracing::debug!(
         "[AUTHORIZE] Address string from params[0]: '{}'",
@@ -332,11 +478,104 @@ pub async fn handle_authorize(
         }
     }
 
+    // Worker names must stay dashboard/URL safe (`worker_name_max_length` / `worker_name_allowed_chars`)
+    if let Some(handler) = client_handler.as_ref()
+        && let Err(reason) = handler.worker_name_policy().check(&worker_name)
+    {
+        tracing::info!(
+            "[AUTHORIZE] Rejecting worker name {:?} from {}:{}: {}",
+            worker_name,
+            ctx.remote_addr,
+            ctx.remote_port,
+            reason
+        );
+        crate::prom::record_authorize_rejection("invalid_worker_name");
+        let _ = ctx
+            .reply_unauthorized(event.id.clone(), "Invalid worker name")
+            .await;
+        return Err(format!("invalid worker name: {}", reason).into());
+    }
+
     // Clean and validate wallet address
     tracing::debug!("[AUTHORIZE] Cleaning wallet address: '{}'", address);
-    address = clean_wallet(&address)?;
+    let skip_validation = client_handler
+        .as_ref()
+        .is_some_and(|h| h.skip_address_validation());
+    let cleaned = clean_wallet(&address);
+    if !skip_validation
+        && !cleaned
+            .as_deref()
+            .is_ok_and(crate::validation::validate_kaspa_address)
+    {
+        tracing::info!(
+            "[AUTHORIZE] Rejecting invalid Kaspa address '{}' from {}:{}",
+            address,
+            ctx.remote_addr,
+            ctx.remote_port
+        );
+        crate::prom::record_authorize_rejection("invalid_address");
+        let _ = ctx.reply_invalid_address(event.id.clone()).await;
+        return Err(format!("Invalid Kaspa address format: '{}'", address).into());
+    }
+    address = cleaned?;
     tracing::debug!("[AUTHORIZE] Cleaned address: '{}'", address);
 
+    // Private pools: check the password (params[1]) against the credential store
+    if let Some(store) = client_handler.as_ref().and_then(|h| h.worker_credentials()) {
+        let password = event.params.get(1).and_then(Value::as_str);
+        if let Err(rejection) = store.check(&address, password) {
+            tracing::info!(
+                "[AUTHORIZE] Rejecting {} from {}:{}: {}",
+                address,
+                ctx.remote_addr,
+                ctx.remote_port,
+                rejection.message()
+            );
+            let message = match rejection {
+                CredentialRejection::AuthenticationRequired => {
+                    crate::prom::record_authorize_rejection("missing_password");
+                    rejection.message()
+                }
+                // The miner only learns that it failed, not whether the wallet or password was wrong
+                CredentialRejection::WrongPassword | CredentialRejection::UnknownWallet => {
+                    crate::prom::record_authorize_rejection("wrong_password");
+                    "Authentication failed"
+                }
+            };
+            let _ = ctx.reply_unauthorized(event.id.clone(), message).await;
+            return Err(format!("authorize rejected: {}", rejection.message()).into());
+        }
+    }
+
+    // Custom authentication backend (`auth_endpoint` or an embedder's `WorkerAuthenticator`)
+    if let Some(handler) = client_handler.as_ref() {
+        let password = event.params.get(1).and_then(Value::as_str).unwrap_or("");
+        let reason = match handler
+            .authenticator()
+            .authorize(&address, &worker_name, password)
+            .await
+        {
+            crate::worker_auth::AuthResult::Allowed => None,
+            crate::worker_auth::AuthResult::Denied(reason) => Some(reason),
+            crate::worker_auth::AuthResult::RateLimit => {
+                Some("Too many authorization attempts, retry later".to_string())
+            }
+        };
+        if let Some(reason) = reason {
+            tracing::info!(
+                "[AUTHORIZE] Authentication backend rejected {}.{} from {}:{}: {}",
+                address,
+                worker_name,
+                ctx.remote_addr,
+                ctx.remote_port,
+                reason
+            );
+            crate::prom::record_authorize_rejection("auth_backend");
+            let _ = ctx.reply_unauthorized(event.id.clone(), &reason).await;
+            return Err(format!("authorize rejected: {}", reason).into());
+        }
+    }
+
     tracing::debug!(
         "[AUTHORIZE] Final parsed - address: '{}', worker: '{}', canxium: '{}'",
         address,
@@ -344,6 +583,55 @@ pub async fn handle_authorize(
         canxium_address
     );
 
+    // Public pools: cap concurrent sessions and distinct worker names per wallet
+    // (`max_sessions_per_wallet` / `max_workers_per_wallet`); unnamed sessions share one worker slot
+    if let Some(handler) = client_handler.as_ref()
+        && let Some(limiter) = handler.wallet_sessions()
+        && !ctx.holds_wallet_session(&address, &worker_name)
+    {
+        // Re-authorizing as another wallet or worker gives up the old slot first
+        ctx.release_wallet_session();
+        match limiter.try_acquire(&address, &worker_name) {
+            Ok(guard) => ctx.set_wallet_session(guard),
+            Err(WalletLimitExceeded::Sessions(active)) => {
+                tracing::info!(
+                    "[AUTHORIZE] Rejecting {}:{}: wallet {} already has {} sessions (limit {})",
+                    ctx.remote_addr,
+                    ctx.remote_port,
+                    address,
+                    active,
+                    limiter.max_sessions().unwrap_or(active)
+                );
+                crate::prom::record_session_rejected_wallet_limit(handler.instance_id());
+                crate::prom::record_authorize_rejection("wallet_limit");
+                let _ = ctx
+                    .reply_unauthorized(event.id.clone(), "Too many sessions for this wallet")
+                    .await;
+                return Err(format!("wallet {} at session limit", address).into());
+            }
+            Err(WalletLimitExceeded::Workers(active)) => {
+                tracing::info!(
+                    "[AUTHORIZE] Rejecting {}:{} worker '{}': wallet {} already has {} workers (limit {})",
+                    ctx.remote_addr,
+                    ctx.remote_port,
+                    worker_name,
+                    address,
+                    active,
+                    limiter.max_workers().unwrap_or(active)
+                );
+                crate::prom::record_session_rejected_worker_limit(handler.instance_id());
+                crate::prom::record_authorize_rejection("wallet_limit");
+                let message = format!(
+                    "Too many workers for this wallet ({} connected, limit {})",
+                    active,
+                    limiter.max_workers().unwrap_or(active)
+                );
+                let _ = ctx.reply_unauthorized(event.id.clone(), &message).await;
+                return Err(format!("wallet {} at worker limit", address).into());
+            }
+        }
+    }
+
     {
         let mut id = ctx.identity.lock();
         id.wallet_addr = address.clone();
@@ -351,6 +639,14 @@ pub async fn handle_authorize(
     }
     ctx.ensure_default_worker_name();
     let worker_name = ctx.effective_worker_name();
+    ctx.mark_authorized(address.clone(), worker_name.clone());
+    // Lets `log_suppress_wallets` quiet this session's debug logs
+    tracing::Span::current().record(crate::log_suppress::WALLET_FIELD, address.as_str());
+
+    // Returning workers start near their last difficulty (`var_diff_state_file`)
+    if let Some(handler) = client_handler.as_ref() {
+        handler.apply_vardiff_hint(&ctx);
+    }
 
     let remote_app = ctx.identity.lock().remote_app.clone();
     tracing::info!(
@@ -363,6 +659,21 @@ pub async fn handle_authorize(
 
     if let Some(ref client_handler) = client_handler {
         client_handler.sync_worker_prom_metrics(&ctx);
+        crate::worker_registry::WORKER_REGISTRY.register(client_handler.instance_id(), &ctx);
+        client_handler.pool_events().on_worker_connected(
+            crate::pool_events::WorkerConnectedEvent {
+                worker: crate::pool_events::WorkerInfo::from_context(
+                    client_handler.instance_id(),
+                    &ctx,
+                ),
+                remote_app: remote_app.clone(),
+                difficulty: ctx
+                    .state
+                    .stratum_diff()
+                    .map(|d| d.diff_value)
+                    .unwrap_or_else(|| client_handler.min_share_diff()),
+            },
+        );
     }
 
     if !canxium_address.is_empty() {
@@ -407,6 +718,29 @@ pub async fn handle_authorize(
         );
     }
 
+    // Proxies (and NiceHash) assume difficulty 1 until told otherwise, so the starting difficulty
+    // (suggested, VarDiff hint, or `min_share_diff`) goes out in order before the first job.
+    // Initializing the session here keeps the job paths from sending it a second time
+    let initial_diff = match client_handler.as_ref() {
+        Some(handler) => handler.initialize_session_difficulty(&ctx),
+        None if !ctx.state.is_initialized() => ctx
+            .state
+            .stratum_diff()
+            .map(|d| d.diff_value)
+            .filter(|d| *d > 0.0),
+        None => None,
+    };
+    if let Some(diff) = initial_diff {
+        send_set_difficulty(&ctx, diff)
+            .await
+            .map_err(|e| format!("failed to send difficulty: {}", e))?;
+        tracing::debug!(
+            "[AUTHORIZE] Sent initial difficulty {} to {}",
+            diff,
+            ctx.remote_addr
+        );
+    }
+
     let wallet_addr = ctx.identity.lock().wallet_addr.clone();
     let mut log_message = format!("[AUTHORIZE] Client authorized - address: {}", wallet_addr);
     if !canxium_address.is_empty() {
@@ -414,16 +748,20 @@ pub async fn handle_authorize(
     }
     tracing::debug!("{}", log_message);
 
+    let pool_name = client_handler
+        .as_ref()
+        .and_then(|h| h.pool_name().map(str::to_string));
+
     // CRITICAL: Send immediate job after authorization (IceRiver KS2L expects this)
     // Don't wait for polling loop - send job immediately
-    // Difficulty will be sent inside send_immediate_job_to_client
+    // A re-authorizing session gets its difficulty inside send_immediate_job_to_client
     if let (Some(client_handler), Some(kaspa_api)) = (client_handler, kaspa_api) {
         tracing::debug!(
             "[AUTHORIZE] Step 3-4: Triggering immediate job send for client {} (extranonce already sent)",
             ctx.remote_addr
         );
         client_handler
-            .send_immediate_job_to_client(ctx.clone(), kaspa_api)
+            .send_immediate_job_to_client(ctx.clone(), kaspa_api, initial_diff.is_none())
             .await;
     } else {
         // Fallback: let polling loop handle it (may cause disconnects for IceRiver)
@@ -432,6 +770,17 @@ pub async fn handle_authorize(
         );
     }
 
+    // Informational only, so it goes after the first job to keep the IceRiver ordering above intact
+    if let Some(pool_name) = pool_name {
+        ctx.send(JsonRpcEvent::new(
+            None,
+            "mining.set_pool",
+            vec![Value::String(pool_name)],
+        ))
+        .await
+        .map_err(|e| format!("failed to send pool name: {}", e))?;
+    }
+
     tracing::debug!(
         "[AUTHORIZE] ===== AUTHORIZE COMPLETE FOR {} =====",
         ctx.remote_addr
@@ -439,6 +788,77 @@ pub async fn handle_authorize(
     Ok(())
 }
 
+/// `mining.set_difficulty` in the miner's notification format: method and params only for IceRiver,
+/// `{"id":null,...}` for everyone else.
+async fn send_set_difficulty(
+    ctx: &StratumContext,
+    diff: f64,
+) -> Result<(), crate::stratum_context::ErrorDisconnected> {
+    let diff_value = serde_json::Number::from_f64(diff)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::from(diff as u64));
+    if ctx.miner_profile().job_format == crate::miner_detect::JobFormat::IceRiver {
+        ctx.send_notification("mining.set_difficulty", vec![diff_value])
+            .await
+    } else {
+        let message = serde_json::json!({
+            "id": null,
+            "method": "mining.set_difficulty",
+            "params": [diff_value],
+        });
+        ctx.send_serialized(format!("{message}\n").as_bytes()).await
+    }
+}
+
+/// Handle `mining.suggest_target` (older firmware suggests a 256-bit big-endian hex target
+/// instead of calling `mining.suggest_difficulty`).
+pub async fn handle_suggest_target(
+    ctx: Arc<StratumContext>,
+    event: JsonRpcEvent,
+    client_handler: Option<Arc<crate::client_handler::ClientHandler>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    tracing::debug!(
+        "[SUGGEST_TARGET] Request from {} params: {:?}",
+        ctx.remote_addr,
+        event.params
+    );
+
+    let target = event.params.first().and_then(|v| v.as_str()).unwrap_or("");
+    let difficulty = match crate::difficulty::target_hex_to_difficulty(target) {
+        Ok(d) => d,
+        Err(e) => {
+            tracing::warn!(
+                "[SUGGEST_TARGET] Ignoring invalid target from {}: {}",
+                ctx.remote_addr,
+                e
+            );
+            ctx.reply(JsonRpcResponse::stratum_error(
+                event.id.clone(),
+                StratumError::unknown("Invalid target"),
+            ))
+            .await
+            .map_err(|e| format!("failed to send response to suggest_target: {}", e))?;
+            return Ok(());
+        }
+    };
+
+    if let Some(client_handler) = client_handler {
+        let suggested = crate::difficulty::hash_difficulty_to_share_diff(difficulty);
+        let applied = client_handler.apply_suggested_difficulty(&ctx, suggested);
+        tracing::info!(
+            "[SUGGEST_TARGET] {} suggested difficulty {} (applied {})",
+            ctx.remote_addr,
+            suggested,
+            applied
+        );
+    }
+
+    ctx.reply(JsonRpcResponse::new(&event, Some(Value::Bool(true)), None))
+        .await
+        .map_err(|e| format!("failed to send response to suggest_target: {}", e))?;
+    Ok(())
+}
+
 /// Handle submit request (stub - actual implementation in share_handler)
 async fn handle_submit(
     ctx: Arc<StratumContext>,
@@ -455,6 +875,91 @@ async fn handle_submit(
     Ok(())
 }
 
+/// Handle miner keepalive `mining.ping` with `"pong"`.
+pub async fn handle_ping(
+    ctx: Arc<StratumContext>,
+    event: JsonRpcEvent,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    tracing::debug!("[PING] mining.ping from {}", ctx.remote_addr);
+    ctx.reply(JsonRpcResponse::new(
+        &event,
+        Some(Value::String("pong".to_string())),
+        None,
+    ))
+    .await
+    .map_err(|e| format!("failed to send response to ping: {}", e))?;
+    Ok(())
+}
+
+/// Handle `mining.get_transactions` (asked by some proxies after authorize) with an empty list: Kaspa
+/// templates are not exposed over Stratum, but an answer keeps the proxy from waiting forever.
+pub async fn handle_get_transactions(
+    ctx: Arc<StratumContext>,
+    event: JsonRpcEvent,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let remote_app = ctx.identity.lock().remote_app.clone();
+    tracing::debug!(
+        "[GET_TRANSACTIONS] mining.get_transactions from {} ('{}')",
+        ctx.remote_addr,
+        remote_app
+    );
+    ctx.reply(JsonRpcResponse::new(
+        &event,
+        Some(Value::Array(vec![])),
+        None,
+    ))
+    .await
+    .map_err(|e| format!("failed to send response to get_transactions: {}", e))?;
+    Ok(())
+}
+
+/// Version bits offered on `mining.multi_version` (BIP 320 general purpose bits).
+pub const VERSION_ROLLING_MASK: u32 = 0x1fff_e000;
+
+/// Handle `mining.multi_version` (sent by some Bitmain firmware when `version_rolling` is on): the
+/// session gets [`VERSION_ROLLING_MASK`], narrowed to a hex mask in params[0] if the miner sent one,
+/// and is told so with `mining.set_version_mask`.
+pub async fn handle_multi_version(
+    ctx: Arc<StratumContext>,
+    event: JsonRpcEvent,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let requested = event
+        .params
+        .first()
+        .and_then(Value::as_str)
+        .and_then(|hex| u32::from_str_radix(hex.trim_start_matches("0x"), 16).ok());
+    let mask = requested.map_or(VERSION_ROLLING_MASK, |bits| bits & VERSION_ROLLING_MASK);
+    tracing::debug!(
+        "[MULTI_VERSION] {} params {:?}, version mask {:08x}",
+        ctx.remote_addr,
+        event.params,
+        mask
+    );
+    ctx.set_version_rolling_mask(Some(mask));
+
+    ctx.reply(JsonRpcResponse::new(&event, Some(Value::Bool(true)), None))
+        .await
+        .map_err(|e| format!("failed to send response to multi_version: {}", e))?;
+    let message = serde_json::json!({
+        "id": null,
+        "method": "mining.set_version_mask",
+        "params": [format!("{mask:08x}")],
+    });
+    ctx.send_serialized(format!("{message}\n").as_bytes())
+        .await
+        .map_err(|e| format!("failed to send set_version_mask: {}", e))?;
+    Ok(())
+}
+
+/// Handle `mining.pong` sent in answer to a server ping (the read loop already recorded the activity).
+async fn handle_pong(
+    ctx: Arc<StratumContext>,
+    _event: JsonRpcEvent,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    tracing::debug!("[PING] mining.pong from {}", ctx.remote_addr);
+    Ok(())
+}
+
 /// Process Canxium address
 fn process_canxium_address(address: &str) -> String {
     let mut addr = address.to_string();
@@ -501,7 +1006,7 @@ fn clean_wallet(input: &str) -> Result<String, Box<dyn std::error::Error + Send
 }
 
 /// Send extranonce to client
-async fn send_extranonce(
+pub(crate) async fn send_extranonce(
     ctx: Arc<StratumContext>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     tracing::debug!(
@@ -518,33 +1023,35 @@ async fn send_extranonce(
         extranonce
     );
 
-    // Bitmain requires extranonce2_size parameter - use same detection logic as assign_extranonce_for_miner
-    // (case-insensitive matching for consistency)
-    let remote_app_lower = remote_app.to_lowercase();
-    let is_bitmain = remote_app_lower.contains("godminer")
-        || remote_app_lower.contains("bitmain")
-        || remote_app_lower.contains("antminer");
+    // Bitmain, Goldshell and KaspaStratum require extranonce2_size parameter (same profile as the subscribe response)
+    let profile = ctx.miner_profile();
+    let is_bitmain = profile.is_bitmain();
     tracing::debug!(
-        "[EXTRANONCE] Detected miner type - Remote app: '{}', Is Bitmain: {}",
+        "[EXTRANONCE] Detected miner type - Remote app: '{}', Is Bitmain: {}, Is Goldshell: {}",
         remote_app,
-        is_bitmain
+        is_bitmain,
+        profile.is_goldshell()
     );
 
-    let params = if is_bitmain {
-        let extranonce2_size = 8 - (extranonce.len() / 2);
+    let params = if profile.sends_extranonce2_size() {
+        let format_name = profile.subscribe_format.name();
+        let extranonce2_size = ctx.extranonce2_size();
         tracing::debug!(
-            "[EXTRANONCE] ===== USING BITMAIN EXTRANONCE FORMAT FOR {} =====",
+            "[EXTRANONCE] ===== USING {} EXTRANONCE FORMAT FOR {} =====",
+            format_name.to_uppercase(),
             ctx.remote_addr
         );
         tracing::debug!(
-            "[EXTRANONCE] Bitmain extranonce: '{}' ({} bytes), extranonce2_size: {} (calculated: 8 - {} / 2)",
+            "[EXTRANONCE] {} extranonce: '{}' ({} bytes), extranonce2_size: {} (from {} extranonce hex chars)",
+            format_name,
             extranonce,
             extranonce.len() / 2,
             extranonce2_size,
             extranonce.len()
         );
         tracing::debug!(
-            "[EXTRANONCE] Bitmain params: ['{}', {}]",
+            "[EXTRANONCE] {} params: ['{}', {}]",
+            format_name,
             extranonce,
             extranonce2_size
         );
@@ -558,7 +1065,7 @@ async fn send_extranonce(
     };
 
     // IceRiver expects minimal notification format (method + params only, no id or jsonrpc)
-    let is_iceriver = remote_app.contains("IceRiver");
+    let is_iceriver = profile.job_format == crate::miner_detect::JobFormat::IceRiver;
 
     if is_iceriver {
         tracing::debug!("[EXTRANONCE] Using minimal format for IceRiver (no id/jsonrpc)");
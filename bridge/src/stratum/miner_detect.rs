@@ -0,0 +1,378 @@
+//! Miner classification from the `mining.subscribe` user-agent.
+//!
+//! A [`MinerProfile`] captures every per-miner protocol difference the bridge cares about
+//! (extranonce size, `mining.notify` job layout, subscribe response shape). Library users can plug in
+//! their own [`MinerDetector`] via `BridgeConfig::miner_detector`; anything it does not recognize
+//! falls back to [`DefaultMinerDetector`].
+
+use crate::user_agent::UserAgent;
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::num::NonZeroUsize;
+
+/// User-agent substrings (lowercase) for Bitmain / GodMiner firmware.
+pub const BITMAIN_KEYWORDS: &[&str] = &["godminer", "bitmain", "antminer"];
+
+/// User-agent substrings (lowercase) for Goldshell firmware (KD-Box, KD6, HS-Box).
+pub const GOLDSHELL_KEYWORDS: &[&str] = &["goldshell"];
+
+/// User-agent substrings (lowercase) for NiceHash rental sessions (`NiceHashStratum/1.0.0`).
+pub const NICEHASH_KEYWORDS: &[&str] = &["nicehash"];
+
+/// User-agent substrings (lowercase) for IceRiver firmware.
+pub const ICERIVER_KEYWORDS: &[&str] = &["iceriver", "icemining", "icm"];
+
+/// User-agent substrings (lowercase) for SRBMiner (`SRBMiner-MULTI/2.6.7`).
+pub const SRBMINER_KEYWORDS: &[&str] = &["srbminer", "srb-"];
+
+/// Software names (case-sensitive) for miners that take the single-hex "big job" format.
+pub const BIG_JOB_KEYWORDS: &[&str] = &["BzMiner", "IceRiverMiner"];
+
+/// Protocol prefix (lowercase) in `mining.subscribe` `params[1]` that selects the
+/// `[null, extranonce, extranonce2_size]` subscribe response whatever the user-agent says.
+pub const KASPA_STRATUM_PROTOCOL: &str = "kaspastratum/";
+
+/// Extranonce bytes for every miner except Bitmain (which gets none).
+pub const EXTRANONCE_SIZE_NON_BITMAIN: i8 = 2;
+
+/// `extranonce_size` that switches extranonce-using miners to 4-byte extranonces (`extranonce2_size` 4).
+pub const EXTRANONCE_SIZE_U32: i8 = 4;
+
+/// `extranonce_size` that narrows extranonce-using miners to 1-byte extranonces (`extranonce2_size` 7),
+/// splitting the nonce space 256 ways for clustered pools.
+pub const EXTRANONCE_SIZE_U8: i8 = 1;
+
+/// Layout of the job data in `mining.notify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JobFormat {
+    /// Single 80-char hex string built by `generate_iceriver_job_params`.
+    IceRiver,
+    /// Single 80-char hex string built by `generate_large_job_params` (BzMiner).
+    BigJob,
+    /// `[u64; 4]` header array followed by the timestamp.
+    Legacy,
+}
+
+/// Shape of the `mining.subscribe` result and `mining.set_extranonce` params.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscribeFormat {
+    /// `[true, "EthereumStratum/1.0.0"]`; extranonce follows via `mining.set_extranonce`.
+    Standard,
+    /// `[null, extranonce, extranonce2_size]`.
+    Bitmain,
+    /// `[null, extranonce, extranonce2_size]` with the bridge-assigned extranonce; `mining.set_extranonce`
+    /// also carries `extranonce2_size`.
+    Goldshell,
+    /// `[[["mining.set_difficulty", session], ["mining.notify", session]], extranonce, extranonce2_size]`;
+    /// `mining.set_difficulty` is sent before the first job after authorize.
+    NiceHash,
+    /// Asked for with the `KaspaStratum/*` protocol; encoded like [`SubscribeFormat::Goldshell`].
+    KaspaStratum,
+}
+
+impl SubscribeFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SubscribeFormat::Standard => "Standard",
+            SubscribeFormat::Bitmain => "Bitmain",
+            SubscribeFormat::Goldshell => "Goldshell",
+            SubscribeFormat::NiceHash => "NiceHash",
+            SubscribeFormat::KaspaStratum => "KaspaStratum",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinerProfile {
+    /// Extranonce bytes assigned by the bridge (0 leaves the full nonce to the miner).
+    pub extranonce_size: i8,
+    pub job_format: JobFormat,
+    pub subscribe_format: SubscribeFormat,
+}
+
+impl Default for MinerProfile {
+    /// Profile for unrecognized miners.
+    fn default() -> Self {
+        Self {
+            extranonce_size: EXTRANONCE_SIZE_NON_BITMAIN,
+            job_format: JobFormat::Legacy,
+            subscribe_format: SubscribeFormat::Standard,
+        }
+    }
+}
+
+impl MinerProfile {
+    pub fn is_bitmain(&self) -> bool {
+        self.subscribe_format == SubscribeFormat::Bitmain
+    }
+
+    pub fn is_goldshell(&self) -> bool {
+        self.subscribe_format == SubscribeFormat::Goldshell
+    }
+
+    pub fn is_nicehash(&self) -> bool {
+        self.subscribe_format == SubscribeFormat::NiceHash
+    }
+
+    /// Whether the subscribe result and `mining.set_extranonce` include `extranonce2_size`.
+    pub fn sends_extranonce2_size(&self) -> bool {
+        matches!(
+            self.subscribe_format,
+            SubscribeFormat::Bitmain
+                | SubscribeFormat::Goldshell
+                | SubscribeFormat::NiceHash
+                | SubscribeFormat::KaspaStratum
+        )
+    }
+}
+
+/// Goldshell KD-Box / KD6 / HS-Box: 2-byte extranonce, legacy jobs, Goldshell subscribe shape.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GoldshellMinerProfile;
+
+impl GoldshellMinerProfile {
+    pub const PROFILE: MinerProfile = MinerProfile {
+        extranonce_size: EXTRANONCE_SIZE_NON_BITMAIN,
+        job_format: JobFormat::Legacy,
+        subscribe_format: SubscribeFormat::Goldshell,
+    };
+}
+
+impl MinerDetector for GoldshellMinerProfile {
+    fn detect(&self, user_agent: &str) -> Option<MinerProfile> {
+        contains_any_lower(
+            &UserAgent::parse(user_agent).search_text(),
+            GOLDSHELL_KEYWORDS,
+        )
+        .then_some(Self::PROFILE)
+    }
+}
+
+/// SRBMiner-MULTI: 2-byte extranonce delivered by `mining.set_extranonce` after authorize, like
+/// BzMiner, but jobs in the legacy layout rather than BzMiner's single hex string. It reads the
+/// `[u64; 4]` header words as little-endian and submits the full 8-byte nonce as big-endian hex with
+/// the assigned extranonce as its leading bytes. The user-agent it sends as the first subscribe
+/// param (its `agent`) is followed by `EthereumStratum/1.0.0`, which does not change the profile.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SrbMinerProfile;
+
+impl SrbMinerProfile {
+    pub const PROFILE: MinerProfile = MinerProfile {
+        extranonce_size: EXTRANONCE_SIZE_NON_BITMAIN,
+        job_format: JobFormat::Legacy,
+        subscribe_format: SubscribeFormat::Standard,
+    };
+}
+
+impl MinerDetector for SrbMinerProfile {
+    fn detect(&self, user_agent: &str) -> Option<MinerProfile> {
+        contains_any_lower(
+            &UserAgent::parse(user_agent).search_text(),
+            SRBMINER_KEYWORDS,
+        )
+        .then_some(Self::PROFILE)
+    }
+}
+
+/// Classifies a miner from its `mining.subscribe` user-agent. Return `None` to defer to the built-in rules.
+pub trait MinerDetector: Send + Sync {
+    fn detect(&self, user_agent: &str) -> Option<MinerProfile>;
+}
+
+/// Built-in keyword rules for NiceHash, Bitmain, Goldshell, IceRiver, SRBMiner, and BzMiner.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultMinerDetector;
+
+fn contains_any_lower(user_agent_lower: &str, keywords: &[&str]) -> bool {
+    keywords.iter().any(|k| user_agent_lower.contains(k))
+}
+
+impl MinerDetector for DefaultMinerDetector {
+    fn detect(&self, user_agent: &str) -> Option<MinerProfile> {
+        let ua = UserAgent::parse(user_agent);
+        let lower = ua.search_text();
+        if contains_any_lower(&lower, NICEHASH_KEYWORDS) {
+            return Some(MinerProfile {
+                subscribe_format: SubscribeFormat::NiceHash,
+                ..MinerProfile::default()
+            });
+        }
+        if contains_any_lower(&lower, BITMAIN_KEYWORDS) {
+            return Some(MinerProfile {
+                extranonce_size: 0,
+                job_format: JobFormat::Legacy,
+                subscribe_format: SubscribeFormat::Bitmain,
+            });
+        }
+        if contains_any_lower(&lower, GOLDSHELL_KEYWORDS) {
+            return Some(GoldshellMinerProfile::PROFILE);
+        }
+        if contains_any_lower(&lower, ICERIVER_KEYWORDS) {
+            return Some(MinerProfile {
+                job_format: JobFormat::IceRiver,
+                ..MinerProfile::default()
+            });
+        }
+        if contains_any_lower(&lower, SRBMINER_KEYWORDS) {
+            return Some(SrbMinerProfile::PROFILE);
+        }
+        if BIG_JOB_KEYWORDS.iter().any(|k| ua.software.contains(k)) {
+            return Some(MinerProfile {
+                job_format: JobFormat::BigJob,
+                ..MinerProfile::default()
+            });
+        }
+        None
+    }
+}
+
+/// `mining.subscribe` `params[1]` when it names a protocol (`Name/version`) rather than a session id.
+pub fn protocol_version_from_params(params: &[serde_json::Value]) -> Option<String> {
+    let protocol = params.get(1)?.as_str()?.trim();
+    let (name, version) = protocol.split_once('/')?;
+    (!name.is_empty() && !version.is_empty()).then(|| protocol.to_string())
+}
+
+/// Subscribe format a miner asks for by protocol. Only `KaspaStratum/*` overrides the user-agent
+/// profile: `EthereumStratum/1.0.0` is sent by most firmware (NiceHash included) whatever they parse.
+pub fn subscribe_format_for_protocol(protocol: &str) -> Option<SubscribeFormat> {
+    protocol
+        .to_lowercase()
+        .starts_with(KASPA_STRATUM_PROTOCOL)
+        .then_some(SubscribeFormat::KaspaStratum)
+}
+
+/// Number of distinct user-agents kept in the detection cache.
+pub const MINER_TYPE_CACHE_CAPACITY: usize = 1024;
+
+static MINER_TYPE_CACHE: Lazy<Mutex<LruCache<String, MinerProfile>>> = Lazy::new(|| {
+    Mutex::new(LruCache::new(
+        NonZeroUsize::new(MINER_TYPE_CACHE_CAPACITY).unwrap(),
+    ))
+});
+
+/// Built-in classification of `user_agent`, memoized so reconnecting miners skip the keyword scan.
+pub fn detect_miner_type(user_agent: &str) -> MinerProfile {
+    if let Some(profile) = MINER_TYPE_CACHE.lock().get(user_agent) {
+        return *profile;
+    }
+    let profile = DefaultMinerDetector.detect(user_agent).unwrap_or_default();
+    MINER_TYPE_CACHE.lock().put(user_agent.to_string(), profile);
+    profile
+}
+
+/// Drop every cached classification (call after changing the detection rules at runtime).
+pub fn clear_miner_type_cache() {
+    MINER_TYPE_CACHE.lock().clear();
+}
+
+/// Run `detector`, then the built-in rules, then fall back to [`MinerProfile::default`].
+pub fn resolve_miner_profile(detector: &dyn MinerDetector, user_agent: &str) -> MinerProfile {
+    detector
+        .detect(user_agent)
+        .unwrap_or_else(|| detect_miner_type(user_agent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_detector_matches_existing_rules() {
+        let d = DefaultMinerDetector;
+        assert!(d.detect("GodMiner/1.0").unwrap().is_bitmain());
+        assert_eq!(d.detect("Antminer KS3").unwrap().extranonce_size, 0);
+        assert_eq!(
+            d.detect("IceRiver KS2L").unwrap().job_format,
+            JobFormat::IceRiver
+        );
+        assert_eq!(
+            d.detect("IceRiverMiner").unwrap().job_format,
+            JobFormat::IceRiver
+        );
+        assert_eq!(
+            d.detect("BzMiner-v21.3.0").unwrap().job_format,
+            JobFormat::BigJob
+        );
+        assert_eq!(
+            d.detect("Goldshell KD-Box Pro"),
+            Some(GoldshellMinerProfile::PROFILE)
+        );
+        let nh = d.detect("NiceHashStratum/1.0.0").unwrap();
+        assert!(nh.is_nicehash());
+        assert_eq!(nh.extranonce_size, EXTRANONCE_SIZE_NON_BITMAIN);
+        assert_eq!(
+            GoldshellMinerProfile::PROFILE.extranonce_size,
+            EXTRANONCE_SIZE_NON_BITMAIN
+        );
+        assert_eq!(
+            d.detect("SRBMiner-MULTI/2.6.7"),
+            Some(SrbMinerProfile::PROFILE)
+        );
+        assert_eq!(d.detect("SRB-MULTI 2.6"), Some(SrbMinerProfile::PROFILE));
+        assert_eq!(d.detect("lolMiner 1.8"), None);
+        assert_eq!(
+            resolve_miner_profile(&d, "lolMiner 1.8"),
+            MinerProfile::default()
+        );
+    }
+
+    #[test]
+    fn protocol_in_subscribe_params() {
+        use serde_json::json;
+        assert_eq!(
+            protocol_version_from_params(&[json!("BzMiner/21.0.0"), json!("KaspaStratum/1.0")]),
+            Some("KaspaStratum/1.0".to_string())
+        );
+        // Resume session id, not a protocol
+        assert_eq!(
+            protocol_version_from_params(&[json!("lolMiner"), json!("0000002a")]),
+            None
+        );
+        assert_eq!(protocol_version_from_params(&[json!("lolMiner")]), None);
+
+        assert_eq!(
+            subscribe_format_for_protocol("KaspaStratum/1.0"),
+            Some(SubscribeFormat::KaspaStratum)
+        );
+        assert_eq!(subscribe_format_for_protocol("EthereumStratum/1.0.0"), None);
+    }
+
+    #[test]
+    fn detect_miner_type_is_cached() {
+        let ua = "BzMiner-v21.3.0 cache-test";
+        let first = detect_miner_type(ua);
+        assert_eq!(first.job_format, JobFormat::BigJob);
+        assert_eq!(MINER_TYPE_CACHE.lock().peek(ua), Some(&first));
+        assert_eq!(detect_miner_type(ua), first);
+
+        clear_miner_type_cache();
+        assert_eq!(MINER_TYPE_CACHE.lock().peek(ua), None);
+        assert_eq!(detect_miner_type(ua), first);
+    }
+
+    #[test]
+    fn custom_detector_overrides_and_falls_back() {
+        struct Custom;
+        impl MinerDetector for Custom {
+            fn detect(&self, user_agent: &str) -> Option<MinerProfile> {
+                user_agent
+                    .starts_with("AfterMarket")
+                    .then_some(MinerProfile {
+                        extranonce_size: 0,
+                        job_format: JobFormat::BigJob,
+                        subscribe_format: SubscribeFormat::Bitmain,
+                    })
+            }
+        }
+
+        let p = resolve_miner_profile(&Custom, "AfterMarket/2.0");
+        assert_eq!(p.job_format, JobFormat::BigJob);
+        assert!(p.is_bitmain());
+        assert_eq!(
+            resolve_miner_profile(&Custom, "IceRiver KS0").job_format,
+            JobFormat::IceRiver
+        );
+    }
+}
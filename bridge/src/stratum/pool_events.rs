@@ -0,0 +1,81 @@
+//! Worker lifecycle callbacks for embedders (database updates, notifications). Install a
+//! [`PoolEventHandler`] via `BridgeConfig::pool_event_handler`; the default is [`NoopPoolEventHandler`].
+//!
+//! Callbacks run inline on the session or share-processing task, so slow work should be handed off
+//! (e.g. to a channel) rather than done in the callback.
+
+use crate::stratum_context::StratumContext;
+use std::time::Duration;
+
+/// Who an event is about: one Stratum session of an authorized worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerInfo {
+    pub instance_id: String,
+    /// Per-instance session number (reused after a restart).
+    pub session_id: i32,
+    /// Kaspa payout address from `mining.authorize`.
+    pub wallet_addr: String,
+    pub worker_name: String,
+    pub remote_addr: String,
+}
+
+impl WorkerInfo {
+    pub fn from_context(instance_id: &str, ctx: &StratumContext) -> Self {
+        Self {
+            instance_id: instance_id.to_string(),
+            session_id: *ctx.id.lock(),
+            wallet_addr: ctx.identity.lock().wallet_addr.clone(),
+            worker_name: ctx.effective_worker_name(),
+            remote_addr: ctx.remote_addr.clone(),
+        }
+    }
+}
+
+/// Sent after a successful `mining.authorize`.
+#[derive(Debug, Clone)]
+pub struct WorkerConnectedEvent {
+    pub worker: WorkerInfo,
+    /// Miner user-agent from `mining.subscribe`.
+    pub remote_app: String,
+    /// Share difficulty assigned at authorization.
+    pub difficulty: f64,
+}
+
+/// Sent when an authorized worker's session closes.
+#[derive(Debug, Clone)]
+pub struct WorkerDisconnectedEvent {
+    pub worker: WorkerInfo,
+    /// Time since the connection was accepted.
+    pub connected_for: Duration,
+}
+
+/// Sent for every share that met the worker's difficulty.
+#[derive(Debug, Clone)]
+pub struct ShareAcceptedEvent {
+    pub worker: WorkerInfo,
+    /// Stratum difficulty the share was checked against.
+    pub difficulty: f64,
+}
+
+/// Sent when kaspad accepts a block mined from a share (before blue confirmation).
+#[derive(Debug, Clone)]
+pub struct BlockFoundEvent {
+    pub worker: WorkerInfo,
+    pub block_hash: String,
+    pub nonce: u64,
+    pub blue_score: u64,
+}
+
+/// Receives worker lifecycle events; every method defaults to doing nothing.
+pub trait PoolEventHandler: Send + Sync {
+    fn on_worker_connected(&self, _event: WorkerConnectedEvent) {}
+    fn on_worker_disconnected(&self, _event: WorkerDisconnectedEvent) {}
+    fn on_share_accepted(&self, _event: ShareAcceptedEvent) {}
+    fn on_block_found(&self, _event: BlockFoundEvent) {}
+}
+
+/// Ignores every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopPoolEventHandler;
+
+impl PoolEventHandler for NoopPoolEventHandler {}
@@ -1,20 +1,41 @@
-//! Stratum per-connection state: identity, TCP halves, and JSON-RPC outbound I/O.
+//! Stratum per-connection state: identity, transport halves, and JSON-RPC outbound I/O.
 //!
-//! Connection lifecycle and accessors live here; verbose send/reply logging is in [`outbound`].
+//! Connection lifecycle and accessors live here; verbose send/reply logging is in [`outbound`], and
+//! the subscribe/authorize/mining state machine in [`session_state`].
 
+mod notify_queue;
 mod outbound;
+mod session_state;
+mod submit_rate;
+mod transport;
 mod types;
 
+pub use notify_queue::{DEFAULT_NOTIFY_QUEUE_DEPTH, NotifyQueue};
+pub use session_state::SessionState;
+pub use submit_rate::{
+    DEFAULT_MAX_SUBMITS_PER_SECOND, SubmitRateDecision, SubmitRateLimiter, SubmitRateWarning,
+};
+pub use transport::StratumTransport;
 pub use types::{ClientIdentity, ContextSummary, ErrorDisconnected};
 
+use transport::BoxedTransport;
+
+use types::SessionActivity;
+
+use crate::miner_detect::{DefaultMinerDetector, MinerProfile, resolve_miner_profile};
+use crate::wallet_sessions::WalletSessionGuard;
 use parking_lot::Mutex;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
 use tokio::io::AsyncWriteExt;
-use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 
+/// Full 8-byte nonce: `max_nonce_range_bits` default, no extranonce2 limit.
+pub const MAX_NONCE_RANGE_BITS: u8 = 64;
+
 /// Stratum client context
+#[derive(Clone)]
 pub struct StratumContext {
     pub remote_addr: String,
     pub remote_port: u16,
@@ -24,20 +45,44 @@ pub struct StratumContext {
     pub state: Arc<crate::mining_state::MiningState>,
     disconnecting: Arc<AtomicBool>,
     write_lock: Arc<AtomicBool>,
-    read_half: Arc<Mutex<Option<tokio::io::ReadHalf<TcpStream>>>>,
-    write_half: Arc<Mutex<Option<tokio::io::WriteHalf<TcpStream>>>>,
+    read_half: Arc<Mutex<Option<tokio::io::ReadHalf<BoxedTransport>>>>,
+    write_half: Arc<Mutex<Option<tokio::io::WriteHalf<BoxedTransport>>>>,
     on_disconnect: mpsc::UnboundedSender<Arc<StratumContext>>,
+    /// Replies collected while a JSON-RPC batch is being handled (`None` outside a batch).
+    batch_replies: Arc<Mutex<Option<Vec<crate::jsonrpc_event::JsonRpcResponse>>>>,
+    /// Miner profile detected at `mining.subscribe` (see [`crate::miner_detect`]).
+    miner_profile: Arc<Mutex<Option<MinerProfile>>>,
+    /// Last inbound message and outstanding server `mining.ping` (session keepalive).
+    activity: Arc<Mutex<SessionActivity>>,
+    /// Nonce bits this miner may roll via extranonce2 (see [`Self::extranonce2_size`]).
+    max_nonce_range_bits: Arc<AtomicU8>,
+    /// Where the session is in subscribe → authorize → mining (see [`SessionState`]).
+    session_state: Arc<Mutex<SessionState>>,
+    /// When the connection was accepted.
+    connected_at: Instant,
+    /// Round trip of the latest `submit_block` RPC for a block this session found (ms).
+    submit_rtt_ms: Arc<Mutex<f64>>,
+    /// Slot held against `max_sessions_per_wallet` / `max_workers_per_wallet` while authorized.
+    wallet_session: Arc<Mutex<Option<WalletSessionGuard>>>,
+    /// `mining.submit` token bucket (`max_submits_per_second`); `None` = unlimited.
+    submit_rate: Arc<Mutex<Option<SubmitRateLimiter>>>,
+    /// `mining.notify` lines waiting for the socket (`notify_queue_depth`).
+    notify_queue: Arc<Mutex<NotifyQueue>>,
+    /// Sent `mining.extranonce.subscribe`: the extranonce may change mid-session via `mining.set_extranonce`.
+    supports_set_extranonce: Arc<AtomicBool>,
+    /// Version bits agreed on through `mining.multi_version` (`None` = not negotiated).
+    version_rolling_mask: Arc<Mutex<Option<u32>>>,
 }
 
 impl StratumContext {
     pub fn new(
         remote_addr: String,
         remote_port: u16,
-        stream: TcpStream,
+        stream: impl StratumTransport,
         state: Arc<crate::mining_state::MiningState>,
         on_disconnect: mpsc::UnboundedSender<Arc<StratumContext>>,
     ) -> Arc<Self> {
-        let (read_half, write_half) = tokio::io::split(stream);
+        let (read_half, write_half) = tokio::io::split(Box::new(stream) as BoxedTransport);
         Arc::new(Self {
             remote_addr,
             remote_port,
@@ -50,9 +95,72 @@ impl StratumContext {
             read_half: Arc::new(Mutex::new(Some(read_half))),
             write_half: Arc::new(Mutex::new(Some(write_half))),
             on_disconnect,
+            batch_replies: Arc::new(Mutex::new(None)),
+            miner_profile: Arc::new(Mutex::new(None)),
+            activity: Arc::new(Mutex::new(SessionActivity::new())),
+            max_nonce_range_bits: Arc::new(AtomicU8::new(MAX_NONCE_RANGE_BITS)),
+            session_state: Arc::new(Mutex::new(SessionState::Connected)),
+            connected_at: Instant::now(),
+            submit_rtt_ms: Arc::new(Mutex::new(0.0)),
+            wallet_session: Arc::new(Mutex::new(None)),
+            submit_rate: Arc::new(Mutex::new(None)),
+            notify_queue: Arc::new(Mutex::new(NotifyQueue::default())),
+            supports_set_extranonce: Arc::new(AtomicBool::new(false)),
+            version_rolling_mask: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Whether the miner opted in to mid-session `mining.set_extranonce` (`mining.extranonce.subscribe`).
+    pub fn supports_set_extranonce(&self) -> bool {
+        self.supports_set_extranonce.load(Ordering::Acquire)
+    }
+
+    /// Record `mining.extranonce.subscribe`; `false` when the session had already sent it.
+    pub fn set_supports_set_extranonce(&self) -> bool {
+        !self.supports_set_extranonce.swap(true, Ordering::AcqRel)
+    }
+
+    /// Version bits the miner may roll, once negotiated with `mining.multi_version`.
+    pub fn version_rolling_mask(&self) -> Option<u32> {
+        *self.version_rolling_mask.lock()
+    }
+
+    pub fn set_version_rolling_mask(&self, mask: Option<u32>) {
+        *self.version_rolling_mask.lock() = mask;
+    }
+
+    /// When the connection was accepted.
+    pub fn connected_at(&self) -> Instant {
+        self.connected_at
+    }
+
+    /// Round trip of the latest `submit_block` RPC for this session's blocks in milliseconds (0 before
+    /// the first block).
+    pub fn submit_rtt_ms(&self) -> f64 {
+        *self.submit_rtt_ms.lock()
+    }
+
+    pub fn set_submit_rtt_ms(&self, rtt_ms: f64) {
+        *self.submit_rtt_ms.lock() = rtt_ms;
+    }
+
+    /// Whether this session already counts against `wallet`'s limits as `worker`.
+    pub fn holds_wallet_session(&self, wallet: &str, worker: &str) -> bool {
+        self.wallet_session
+            .lock()
+            .as_ref()
+            .is_some_and(|guard| guard.wallet() == wallet && guard.worker() == worker)
+    }
+
+    /// Keep `guard` for the life of the session, freeing any slot held for another wallet.
+    pub fn set_wallet_session(&self, guard: WalletSessionGuard) {
+        *self.wallet_session.lock() = Some(guard);
+    }
+
+    pub fn release_wallet_session(&self) {
+        self.wallet_session.lock().take();
+    }
+
     /// Check if client is connected
     pub fn connected(&self) -> bool {
         !self.disconnecting.load(Ordering::Acquire)
@@ -91,6 +199,93 @@ impl StratumContext {
         self.remote_port
     }
 
+    /// Miner profile stored at subscribe, or the built-in classification of `remote_app`.
+    pub fn miner_profile(&self) -> MinerProfile {
+        if let Some(profile) = *self.miner_profile.lock() {
+            return profile;
+        }
+        let remote_app = self.identity.lock().remote_app.clone();
+        resolve_miner_profile(&DefaultMinerDetector, &remote_app)
+    }
+
+    pub fn set_miner_profile(&self, profile: MinerProfile) {
+        *self.miner_profile.lock() = Some(profile);
+    }
+
+    /// Note an inbound message; any traffic also answers an outstanding server ping.
+    pub fn record_activity(&self) {
+        let mut activity = self.activity.lock();
+        activity.last_message = Instant::now();
+        activity.ping_sent_at = None;
+    }
+
+    pub fn set_max_nonce_range_bits(&self, bits: u8) {
+        self.max_nonce_range_bits.store(bits, Ordering::Relaxed);
+    }
+
+    /// Limit this session to `max_per_second` submits (`None` or `0` = unlimited).
+    pub fn set_max_submits_per_second(&self, max_per_second: Option<u32>) {
+        *self.submit_rate.lock() = max_per_second
+            .filter(|max| *max > 0)
+            .map(|max| SubmitRateLimiter::new(max, Instant::now()));
+    }
+
+    /// Take a token for one `mining.submit`.
+    pub fn check_submit_rate(&self) -> SubmitRateDecision {
+        match self.submit_rate.lock().as_mut() {
+            Some(limiter) => limiter.check(Instant::now()),
+            None => SubmitRateDecision::Allow,
+        }
+    }
+
+    /// Whether `max_nonce_range_bits` narrows extranonce2 below what the extranonce leaves free.
+    pub fn nonce_range_limited(&self) -> bool {
+        self.max_nonce_range_bits.load(Ordering::Relaxed) < MAX_NONCE_RANGE_BITS
+    }
+
+    /// Bytes of the 8-byte nonce the miner rolls: what the extranonce leaves free, capped by
+    /// `max_nonce_range_bits`; the remaining bytes stay zero for the server.
+    pub fn extranonce2_size(&self) -> usize {
+        let free = 8 - (self.extranonce.lock().len() / 2).min(8);
+        let cap = self.max_nonce_range_bits.load(Ordering::Relaxed) as usize / 8;
+        free.min(cap)
+    }
+
+    /// Keep a copy of the latest inbound line for panic reports.
+    pub fn record_message(&self, line: &str) {
+        const MAX_KEPT_CHARS: usize = 512;
+        self.activity.lock().last_line = line.chars().take(MAX_KEPT_CHARS).collect();
+    }
+
+    /// Latest inbound line seen by the read loop (empty before the first message).
+    pub fn last_message(&self) -> String {
+        self.activity.lock().last_line.clone()
+    }
+
+    /// Time since the last inbound message.
+    pub fn idle_for(&self) -> Duration {
+        self.activity.lock().last_message.elapsed()
+    }
+
+    /// Time since an unanswered server `mining.ping` was sent, if one is outstanding.
+    pub fn ping_outstanding_for(&self) -> Option<Duration> {
+        self.activity.lock().ping_sent_at.map(|t| t.elapsed())
+    }
+
+    pub fn mark_ping_sent(&self) {
+        self.activity.lock().ping_sent_at = Some(Instant::now());
+    }
+
+    /// Restart the share-idle window (`idle_share_timeout_secs`).
+    pub fn record_accepted_share(&self) {
+        self.activity.lock().share_window_start = Some(Instant::now());
+    }
+
+    /// Time since authorize or the last accepted share, whichever is later (`None` before authorize).
+    pub fn share_idle_for(&self) -> Option<Duration> {
+        self.activity.lock().share_window_start.map(|t| t.elapsed())
+    }
+
     /// Assign a stable display label when the miner omits `wallet.worker` in authorize.
     ///
     /// Uses connection id (`asic-3`), not IP, so Prometheus/dashboard metrics stay non-empty and
@@ -178,25 +373,7 @@ impl StratumContext {
     /// Get a reference to the read half (for reading)
     pub fn get_read_half(
         &self,
-    ) -> parking_lot::MutexGuard<'_, Option<tokio::io::ReadHalf<TcpStream>>> {
+    ) -> parking_lot::MutexGuard<'_, Option<tokio::io::ReadHalf<BoxedTransport>>> {
         self.read_half.lock()
     }
 }
-
-impl Clone for StratumContext {
-    fn clone(&self) -> Self {
-        Self {
-            remote_addr: self.remote_addr.clone(),
-            remote_port: self.remote_port,
-            identity: self.identity.clone(),
-            id: self.id.clone(),
-            extranonce: self.extranonce.clone(),
-            state: self.state.clone(),
-            disconnecting: self.disconnecting.clone(),
-            write_lock: self.write_lock.clone(),
-            read_half: self.read_half.clone(),
-            write_half: self.write_half.clone(),
-            on_disconnect: self.on_disconnect.clone(),
-        }
-    }
-}
@@ -0,0 +1,102 @@
+//! Per-session `mining.notify` queue (`notify_queue_depth`). Job tasks queue their line and return;
+//! whichever task finds no writer active drains the queue, so a miner with a full TCP buffer holds up
+//! only its own jobs. Past the depth the oldest refresh is dropped (the oldest job of any kind when
+//! every queued one is a new block), so the latest job always reaches the miner.
+
+use bytes::Bytes;
+use std::collections::VecDeque;
+
+/// Default for `notify_queue_depth`.
+pub const DEFAULT_NOTIFY_QUEUE_DEPTH: usize = 2;
+
+struct QueuedNotify {
+    line: Bytes,
+    clean_jobs: bool,
+}
+
+#[derive(Default)]
+pub struct NotifyQueue {
+    pending: VecDeque<QueuedNotify>,
+    /// A task is writing the queue out.
+    draining: bool,
+}
+
+impl NotifyQueue {
+    /// Queue `line`, trimming the queue to `depth` (at least 1). Returns how many notifies were
+    /// dropped and whether the caller must drain the queue.
+    pub fn push(&mut self, line: Bytes, clean_jobs: bool, depth: usize) -> (usize, bool) {
+        self.pending.push_back(QueuedNotify { line, clean_jobs });
+        let mut dropped = 0;
+        while self.pending.len() > depth.max(1) {
+            let oldest = self
+                .pending
+                .iter()
+                .position(|notify| !notify.clean_jobs)
+                .filter(|idx| *idx + 1 < self.pending.len())
+                .unwrap_or(0);
+            self.pending.remove(oldest);
+            dropped += 1;
+        }
+        let drain = !self.draining;
+        self.draining = true;
+        (dropped, drain)
+    }
+
+    /// Next line to write; the drain ends (and the next push starts a new one) once it is empty.
+    pub fn pop(&mut self) -> Option<Bytes> {
+        let next = self.pending.pop_front().map(|notify| notify.line);
+        if next.is_none() {
+            self.draining = false;
+        }
+        next
+    }
+
+    /// Forget every queued line after a failed write.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.draining = false;
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(job: u8) -> Bytes {
+        Bytes::from(vec![job])
+    }
+
+    #[test]
+    fn drops_oldest_refresh_and_keeps_latest_job() {
+        let mut queue = NotifyQueue::default();
+        assert_eq!(queue.push(line(1), true, 2), (0, true));
+        assert_eq!(queue.push(line(2), false, 2), (0, false));
+        // Over depth: the refresh (2) goes, the new block (1) stays
+        assert_eq!(queue.push(line(3), false, 2), (1, false));
+        assert_eq!(queue.pop(), Some(line(1)));
+        assert_eq!(queue.pop(), Some(line(3)));
+        assert_eq!(queue.pop(), None);
+
+        // The drain ended, so the next push starts another
+        assert_eq!(queue.push(line(4), true, 2), (0, true));
+        assert_eq!(queue.push(line(5), true, 2), (0, false));
+        // Only new blocks queued: the oldest goes, never the one just pushed
+        assert_eq!(queue.push(line(6), false, 2), (1, false));
+        assert_eq!(queue.push(line(7), true, 1), (2, false));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop(), Some(line(7)));
+
+        queue.push(line(8), false, 2);
+        queue.clear();
+        assert!(queue.is_empty());
+        assert_eq!(queue.push(line(9), false, 2), (0, true));
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::atomic::Ordering;
 use std::time::Duration;
 
+use bytes::Bytes;
 use hex;
 use serde_json::Value;
 use tokio::io::AsyncWriteExt;
@@ -8,6 +9,7 @@ use tokio::io::AsyncWriteExt;
 use super::{ErrorDisconnected, StratumContext};
 use crate::jsonrpc_event::{JsonRpcEvent, JsonRpcResponse};
 use crate::log_colors::LogColors;
+use crate::stratum_error::StratumError;
 
 impl StratumContext {
     /// Send a JSON-RPC response
@@ -16,6 +18,11 @@ impl StratumContext {
             return Err(ErrorDisconnected);
         }
 
+        if let Some(batch) = self.batch_replies.lock().as_mut() {
+            batch.push(response);
+            return Ok(());
+        }
+
         let json = serde_json::to_string(&response).map_err(|_| ErrorDisconnected)?;
         let data = format!("{}\n", json);
 
@@ -156,6 +163,37 @@ impl StratumContext {
         Ok(())
     }
 
+    /// Start collecting [`reply`](Self::reply) responses for a JSON-RPC batch.
+    pub(crate) fn begin_batch(&self) {
+        *self.batch_replies.lock() = Some(Vec::new());
+    }
+
+    /// Whether [`reply`](Self::reply) responses are currently being collected for a batch.
+    pub(crate) fn in_batch(&self) -> bool {
+        self.batch_replies.lock().is_some()
+    }
+
+    /// Stop collecting and send the batched responses as a single JSON array line.
+    pub(crate) async fn finish_batch(&self) -> Result<(), ErrorDisconnected> {
+        let Some(responses) = self.batch_replies.lock().take() else {
+            return Ok(());
+        };
+        if responses.is_empty() {
+            return Ok(());
+        }
+        if self.disconnecting.load(Ordering::Acquire) {
+            return Err(ErrorDisconnected);
+        }
+        let json = serde_json::to_string(&responses).map_err(|_| ErrorDisconnected)?;
+        tracing::debug!(
+            "{} {} {}",
+            LogColors::bridge_to_asic("[BRIDGE->ASIC]"),
+            LogColors::label("  - Batch Response:"),
+            json
+        );
+        self.write_data(format!("{}\n", json).as_bytes()).await
+    }
+
     /// Send a JSON-RPC event
     pub async fn send(&self, event: JsonRpcEvent) -> Result<(), ErrorDisconnected> {
         if self.disconnecting.load(Ordering::Acquire) {
@@ -517,6 +555,49 @@ impl StratumContext {
         Ok(())
     }
 
+    /// Write an already serialized, `\n`-terminated line (a cached `mining.notify`).
+    pub async fn send_serialized(&self, line: &[u8]) -> Result<(), ErrorDisconnected> {
+        if self.disconnecting.load(Ordering::Acquire) {
+            return Err(ErrorDisconnected);
+        }
+
+        tracing::debug!(
+            "{} {} {}",
+            LogColors::bridge_to_asic("[BRIDGE->ASIC]"),
+            format!("{}:{}", self.remote_addr, self.remote_port),
+            String::from_utf8_lossy(line).trim_end()
+        );
+
+        self.write_data(line).await
+    }
+
+    /// Queue a serialized `mining.notify` behind at most `depth` others (see [`super::notify_queue`])
+    /// and write the queue out unless another job task already is. Returns how many older notifies
+    /// were dropped.
+    pub async fn send_notify(
+        &self,
+        line: Bytes,
+        clean_jobs: bool,
+        depth: usize,
+    ) -> Result<usize, ErrorDisconnected> {
+        if self.disconnecting.load(Ordering::Acquire) {
+            return Err(ErrorDisconnected);
+        }
+        let (dropped, drain) = self.notify_queue.lock().push(line, clean_jobs, depth);
+        if drain {
+            loop {
+                let Some(line) = self.notify_queue.lock().pop() else {
+                    break;
+                };
+                if let Err(e) = self.send_serialized(&line).await {
+                    self.notify_queue.lock().clear();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(dropped)
+    }
+
     /// Write data to the connection with backoff
     async fn write_data(&self, data: &[u8]) -> Result<(), ErrorDisconnected> {
         // Check if already disconnected
@@ -539,9 +620,13 @@ impl StratumContext {
                 let result = if let Some(mut write_half) = write_half_opt {
                     let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
 
-                    // Try to write directly (no need to wait for writable)
-                    let write_result =
-                        tokio::time::timeout_at(deadline, write_half.write_all(data)).await;
+                    // Try to write directly (no need to wait for writable); the flush pushes
+                    // buffered WebSocket frames out and is a no-op for TCP
+                    let write_result = tokio::time::timeout_at(deadline, async {
+                        write_half.write_all(data).await?;
+                        write_half.flush().await
+                    })
+                    .await;
 
                     // Put write half back regardless of result
                     {
@@ -589,8 +674,13 @@ impl StratumContext {
         tracing::debug!(
             "[BRIDGE->ASIC] Preparing STALE SHARE response (Error Code: 21, Job not found)"
         );
-        self.reply(JsonRpcResponse::error(id, 21, "Job not found", None))
-            .await
+        self.reply(JsonRpcResponse::stratum_error(
+            id,
+            StratumError::JobNotFound {
+                custom_message: None,
+            },
+        ))
+        .await
     }
 
     /// Reply with duplicate share error
@@ -598,11 +688,11 @@ impl StratumContext {
         tracing::debug!(
             "[BRIDGE->ASIC] Preparing DUPLICATE SHARE response (Error Code: 22, Duplicate share submitted)"
         );
-        self.reply(JsonRpcResponse::error(
+        self.reply(JsonRpcResponse::stratum_error(
             id,
-            22,
-            "Duplicate share submitted",
-            None,
+            StratumError::DuplicateShare {
+                custom_message: None,
+            },
         ))
         .await
     }
@@ -612,8 +702,42 @@ impl StratumContext {
         tracing::debug!(
             "[BRIDGE->ASIC] Preparing BAD SHARE response (Error Code: 20, Unknown problem)"
         );
-        self.reply(JsonRpcResponse::error(id, 20, "Unknown problem", None))
-            .await
+        self.reply(JsonRpcResponse::stratum_error(
+            id,
+            StratumError::UnknownError {
+                custom_message: None,
+            },
+        ))
+        .await
+    }
+
+    /// Reply with invalid wallet address error (authorize)
+    pub async fn reply_invalid_address(&self, id: Option<Value>) -> Result<(), ErrorDisconnected> {
+        tracing::debug!(
+            "[BRIDGE->ASIC] Preparing INVALID ADDRESS response (Error Code: 24, Invalid Kaspa address)"
+        );
+        self.reply(JsonRpcResponse::stratum_error(
+            id,
+            StratumError::unauthorized("Invalid Kaspa address: expected kaspa: prefix"),
+        ))
+        .await
+    }
+
+    /// Reply with authorization failure (credential store)
+    pub async fn reply_unauthorized(
+        &self,
+        id: Option<Value>,
+        message: &str,
+    ) -> Result<(), ErrorDisconnected> {
+        tracing::debug!(
+            "[BRIDGE->ASIC] Preparing UNAUTHORIZED response (Error Code: 24, {})",
+            message
+        );
+        self.reply(JsonRpcResponse::stratum_error(
+            id,
+            StratumError::unauthorized(message),
+        ))
+        .await
     }
 
     /// Reply with low difficulty share error
@@ -624,11 +748,11 @@ impl StratumContext {
         tracing::debug!(
             "[BRIDGE->ASIC] Preparing LOW DIFFICULTY SHARE response (Error Code: 23, Invalid difficulty)"
         );
-        self.reply(JsonRpcResponse::error(
+        self.reply(JsonRpcResponse::stratum_error(
             Some(id.clone()),
-            23,
-            "Invalid difficulty",
-            None,
+            StratumError::LowDifficultyShare {
+                custom_message: None,
+            },
         ))
         .await
         .map_err(|e| {
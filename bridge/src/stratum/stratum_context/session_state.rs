@@ -0,0 +1,160 @@
+//! Connection lifecycle as one state instead of implied flags: a session moves through
+//! `Connected` → `Subscribed` → `Authorized` → `Mining`, and a method that arrives out of order gets
+//! the matching Stratum error.
+
+use super::StratumContext;
+use crate::stratum_error::StratumError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionState {
+    Connected,
+    Subscribed {
+        extranonce: String,
+    },
+    Authorized {
+        wallet: String,
+        worker: String,
+        extranonce: String,
+    },
+    /// At least one `mining.notify` has been sent since authorize.
+    Mining {
+        wallet: String,
+        worker: String,
+        extranonce: String,
+    },
+}
+
+impl SessionState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SessionState::Connected => "connected",
+            SessionState::Subscribed { .. } => "subscribed",
+            SessionState::Authorized { .. } => "authorized",
+            SessionState::Mining { .. } => "mining",
+        }
+    }
+
+    fn extranonce(&self) -> &str {
+        match self {
+            SessionState::Connected => "",
+            SessionState::Subscribed { extranonce }
+            | SessionState::Authorized { extranonce, .. }
+            | SessionState::Mining { extranonce, .. } => extranonce,
+        }
+    }
+}
+
+impl StratumContext {
+    pub fn session_state(&self) -> SessionState {
+        self.session_state.lock().clone()
+    }
+
+    /// `mining.subscribe` is accepted once, on a fresh connection.
+    pub fn check_subscribe(&self) -> Result<(), StratumError> {
+        match *self.session_state.lock() {
+            SessionState::Connected => Ok(()),
+            _ => Err(StratumError::unknown("Already subscribed")),
+        }
+    }
+
+    pub fn mark_subscribed(&self, extranonce: String) {
+        *self.session_state.lock() = SessionState::Subscribed { extranonce };
+    }
+
+    /// `mining.authorize` needs a subscribe first; later calls (proxies adding workers) are allowed.
+    pub fn check_authorize(&self) -> Result<(), StratumError> {
+        match *self.session_state.lock() {
+            SessionState::Connected => Err(StratumError::NotSubscribed {
+                custom_message: None,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record the authorized worker; a session already mining keeps receiving jobs.
+    pub fn mark_authorized(&self, wallet: String, worker: String) {
+        self.activity
+            .lock()
+            .share_window_start
+            .get_or_insert_with(std::time::Instant::now);
+        let mut state = self.session_state.lock();
+        let extranonce = state.extranonce().to_string();
+        *state = match *state {
+            SessionState::Mining { .. } => SessionState::Mining {
+                wallet,
+                worker,
+                extranonce,
+            },
+            _ => SessionState::Authorized {
+                wallet,
+                worker,
+                extranonce,
+            },
+        };
+    }
+
+    /// `mining.submit` needs an authorized worker that has been sent a job.
+    pub fn check_submit(&self) -> Result<(), StratumError> {
+        match *self.session_state.lock() {
+            SessionState::Mining { .. } => Ok(()),
+            SessionState::Authorized { .. } => Err(StratumError::JobNotFound {
+                custom_message: Some("No job sent yet".to_string()),
+            }),
+            SessionState::Connected | SessionState::Subscribed { .. } => {
+                Err(StratumError::Unauthorized {
+                    custom_message: None,
+                })
+            }
+        }
+    }
+
+    /// Called as the first job goes out after authorize; other states are left as they are.
+    pub fn mark_mining(&self) {
+        let mut state = self.session_state.lock();
+        *state = match std::mem::replace(&mut *state, SessionState::Connected) {
+            SessionState::Authorized {
+                wallet,
+                worker,
+                extranonce,
+            } => SessionState::Mining {
+                wallet,
+                worker,
+                extranonce,
+            },
+            other => other,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn methods_follow_the_session_lifecycle() {
+        let (ctx, _client) = crate::test_support::test_context().await;
+
+        assert_eq!(ctx.check_authorize().unwrap_err().code(), 25);
+        assert_eq!(ctx.check_submit().unwrap_err().code(), 24);
+        ctx.check_subscribe().unwrap();
+
+        ctx.mark_subscribed("0001".to_string());
+        assert!(ctx.check_subscribe().is_err());
+        ctx.check_authorize().unwrap();
+
+        ctx.mark_authorized("kaspa:wallet".to_string(), "rig1".to_string());
+        assert_eq!(ctx.check_submit().unwrap_err().code(), 21);
+
+        ctx.mark_mining();
+        ctx.check_submit().unwrap();
+        ctx.mark_authorized("kaspa:wallet".to_string(), "rig2".to_string());
+        assert_eq!(
+            ctx.session_state(),
+            SessionState::Mining {
+                wallet: "kaspa:wallet".to_string(),
+                worker: "rig2".to_string(),
+                extranonce: "0001".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Per-session `mining.submit` token bucket (`max_submits_per_second`): the bucket holds five
+//! seconds of submits and refills at the configured rate. Shares over the limit are turned down
+//! before they reach kaspad, and a session that keeps flooding is dropped.
+
+use std::time::{Duration, Instant};
+
+/// Default for `max_submits_per_second`.
+pub const DEFAULT_MAX_SUBMITS_PER_SECOND: u32 = 10;
+/// Seconds of submits the bucket can hold.
+const BURST_SECONDS: f64 = 5.0;
+/// Submit rate, as a multiple of the limit, that gets a second warning.
+const HEAVY_FLOOD_FACTOR: f64 = 10.0;
+/// Submit rate, as a multiple of the limit, that disconnects once sustained for [`FLOOD_GRACE`].
+const DISCONNECT_FACTOR: f64 = 100.0;
+const FLOOD_GRACE: Duration = Duration::from_secs(10);
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// What to do with one `mining.submit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitRateDecision {
+    Allow,
+    /// Reject the share; `warn` is set the first time the session is limited and again the first
+    /// time it reaches 10x the limit.
+    Limit {
+        warn: Option<SubmitRateWarning>,
+    },
+    /// The session has sent 100x the limit for longer than the grace period.
+    Disconnect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitRateWarning {
+    Limited,
+    TenTimesLimit,
+}
+
+#[derive(Debug, Clone)]
+pub struct SubmitRateLimiter {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+    window_start: Instant,
+    window_submits: u32,
+    flooding_since: Option<Instant>,
+    warned: bool,
+    warned_heavy: bool,
+}
+
+impl SubmitRateLimiter {
+    /// `max_per_second` must be non-zero.
+    pub fn new(max_per_second: u32, now: Instant) -> Self {
+        let rate = f64::from(max_per_second.max(1));
+        Self {
+            rate,
+            capacity: rate * BURST_SECONDS,
+            tokens: rate * BURST_SECONDS,
+            last_refill: now,
+            window_start: now,
+            window_submits: 0,
+            flooding_since: None,
+            warned: false,
+            warned_heavy: false,
+        }
+    }
+
+    pub fn max_per_second(&self) -> u32 {
+        self.rate as u32
+    }
+
+    pub fn check(&mut self, now: Instant) -> SubmitRateDecision {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+
+        // Submits per one-second window, for the flood thresholds
+        if now.saturating_duration_since(self.window_start) >= RATE_WINDOW {
+            if f64::from(self.window_submits) < self.rate * DISCONNECT_FACTOR {
+                self.flooding_since = None;
+            }
+            self.window_start = now;
+            self.window_submits = 0;
+        }
+        self.window_submits += 1;
+        let window_rate = f64::from(self.window_submits);
+        if window_rate >= self.rate * DISCONNECT_FACTOR {
+            let since = *self.flooding_since.get_or_insert(self.window_start);
+            if now.saturating_duration_since(since) >= FLOOD_GRACE {
+                return SubmitRateDecision::Disconnect;
+            }
+        }
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return SubmitRateDecision::Allow;
+        }
+
+        let warn = if !self.warned {
+            self.warned = true;
+            Some(SubmitRateWarning::Limited)
+        } else if !self.warned_heavy && window_rate >= self.rate * HEAVY_FLOOD_FACTOR {
+            self.warned_heavy = true;
+            Some(SubmitRateWarning::TenTimesLimit)
+        } else {
+            None
+        };
+        SubmitRateDecision::Limit { warn }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_then_limited_with_warnings() {
+        let start = Instant::now();
+        let mut limiter = SubmitRateLimiter::new(10, start);
+        for _ in 0..50 {
+            assert_eq!(limiter.check(start), SubmitRateDecision::Allow);
+        }
+        assert_eq!(
+            limiter.check(start),
+            SubmitRateDecision::Limit {
+                warn: Some(SubmitRateWarning::Limited)
+            }
+        );
+        for _ in 0..48 {
+            assert_eq!(
+                limiter.check(start),
+                SubmitRateDecision::Limit { warn: None }
+            );
+        }
+        // 100th submit in the window: 10x the limit
+        assert_eq!(
+            limiter.check(start),
+            SubmitRateDecision::Limit {
+                warn: Some(SubmitRateWarning::TenTimesLimit)
+            }
+        );
+
+        // Refill: 0.5s at 10/s gives five more submits
+        let later = start + Duration::from_millis(500);
+        for _ in 0..5 {
+            assert_eq!(limiter.check(later), SubmitRateDecision::Allow);
+        }
+    }
+
+    #[test]
+    fn sustained_flood_disconnects() {
+        let start = Instant::now();
+        let mut limiter = SubmitRateLimiter::new(1, start);
+        let mut decision = SubmitRateDecision::Allow;
+        'outer: for second in 0..12 {
+            let now = start + Duration::from_secs(second);
+            for _ in 0..100 {
+                decision = limiter.check(now);
+                if decision == SubmitRateDecision::Disconnect {
+                    assert!(second >= 10);
+                    break 'outer;
+                }
+            }
+        }
+        assert_eq!(decision, SubmitRateDecision::Disconnect);
+    }
+}
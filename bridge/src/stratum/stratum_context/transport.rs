@@ -0,0 +1,10 @@
+//! Byte-stream transport under a Stratum session (TCP, or WebSocket via the line adapter).
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Anything the session can read `\n`-delimited JSON-RPC from and write it to.
+pub trait StratumTransport: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> StratumTransport for T {}
+
+pub(crate) type BoxedTransport = Box<dyn StratumTransport>;
@@ -10,6 +10,33 @@ pub struct ClientIdentity {
     pub worker_name: String,
     pub canxium_addr: String,
     pub remote_app: String,
+    /// Parsed form of `remote_app`, set at `mining.subscribe`.
+    pub user_agent: Option<crate::user_agent::UserAgent>,
+    /// Stratum protocol announced in `mining.subscribe` `params[1]` (e.g. `EthereumStratum/1.0.0`).
+    pub protocol_version: Option<String>,
+}
+
+/// Inbound traffic bookkeeping for the session keepalive.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionActivity {
+    pub(crate) last_message: std::time::Instant,
+    pub(crate) ping_sent_at: Option<std::time::Instant>,
+    /// Most recent inbound line (truncated), logged if the session panics.
+    pub(crate) last_line: String,
+    /// Start of the current share-idle window: authorize, then each accepted share (`None` before
+    /// authorize).
+    pub(crate) share_window_start: Option<std::time::Instant>,
+}
+
+impl SessionActivity {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_message: std::time::Instant::now(),
+            ping_sent_at: None,
+            last_line: String::new(),
+            share_window_start: None,
+        }
+    }
 }
 
 /// Context summary for logging
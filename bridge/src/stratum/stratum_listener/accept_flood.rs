@@ -0,0 +1,123 @@
+//! Accept-loop backpressure (`max_global_accepts_per_second`): a sliding-window count of new
+//! connections across all peers. While the rate is above the limit, the listener sleeps
+//! `accept_backoff_ms` between `accept()` calls so a connection flood queues in the kernel instead
+//! of spawning sessions as fast as it arrives.
+
+use std::time::{Duration, Instant};
+
+/// Default for `max_global_accepts_per_second`.
+pub const DEFAULT_MAX_GLOBAL_ACCEPTS_PER_SECOND: u32 = 1000;
+/// Default for `accept_backoff_ms`.
+pub const DEFAULT_ACCEPT_BACKOFF_MS: u64 = 10;
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Flood threshold and the pause applied between accepts while it is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcceptFloodConfig {
+    pub max_accepts_per_second: u32,
+    pub backoff: Duration,
+}
+
+/// Outcome of one accepted connection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AcceptPacing {
+    /// Estimated accepts per second over the last window.
+    pub rate: f64,
+    /// Sleep this long before the next `accept()`.
+    pub backoff: Option<Duration>,
+    /// The rate just crossed above the limit (`Some(true)`) or dropped back under it (`Some(false)`).
+    pub flood_changed: Option<bool>,
+}
+
+/// Sliding-window accept counter: the current one-second window plus the previous one, weighted
+/// by how much of it still overlaps the last second.
+#[derive(Debug, Clone)]
+pub struct ConnectionFloodDetector {
+    config: AcceptFloodConfig,
+    window_start: Instant,
+    current: u32,
+    previous: u32,
+    flooding: bool,
+}
+
+impl ConnectionFloodDetector {
+    pub fn new(config: AcceptFloodConfig, now: Instant) -> Self {
+        Self {
+            config,
+            window_start: now,
+            current: 0,
+            previous: 0,
+            flooding: false,
+        }
+    }
+
+    pub fn is_flooding(&self) -> bool {
+        self.flooding
+    }
+
+    fn roll(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed >= WINDOW * 2 {
+            self.previous = 0;
+            self.current = 0;
+            self.window_start = now;
+        } else if elapsed >= WINDOW {
+            self.previous = self.current;
+            self.current = 0;
+            self.window_start += WINDOW;
+        }
+    }
+
+    fn rate(&self, now: Instant) -> f64 {
+        let into_window = now
+            .saturating_duration_since(self.window_start)
+            .as_secs_f64();
+        let previous_weight = (1.0 - into_window / WINDOW.as_secs_f64()).max(0.0);
+        f64::from(self.current) + f64::from(self.previous) * previous_weight
+    }
+
+    /// Count one accepted connection and decide whether the loop should slow down.
+    pub fn record_accept(&mut self, now: Instant) -> AcceptPacing {
+        self.roll(now);
+        self.current += 1;
+        let rate = self.rate(now);
+        let flooding = rate > f64::from(self.config.max_accepts_per_second);
+        let flood_changed = (flooding != self.flooding).then_some(flooding);
+        self.flooding = flooding;
+        AcceptPacing {
+            rate,
+            backoff: flooding.then_some(self.config.backoff),
+            flood_changed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backs_off_above_the_limit_and_recovers() {
+        let start = Instant::now();
+        let config = AcceptFloodConfig {
+            max_accepts_per_second: 5,
+            backoff: Duration::from_millis(10),
+        };
+        let mut detector = ConnectionFloodDetector::new(config, start);
+        for _ in 0..5 {
+            let pacing = detector.record_accept(start);
+            assert_eq!(pacing.backoff, None);
+            assert_eq!(pacing.flood_changed, None);
+        }
+        let pacing = detector.record_accept(start);
+        assert_eq!(pacing.backoff, Some(Duration::from_millis(10)));
+        assert_eq!(pacing.flood_changed, Some(true));
+        assert!(detector.is_flooding());
+
+        // Halfway into the next window, half of the previous six still count: 3 + 1 = 4
+        let pacing = detector.record_accept(start + Duration::from_millis(1500));
+        assert!((pacing.rate - 4.0).abs() < 1e-9);
+        assert_eq!(pacing.flood_changed, Some(false));
+        assert_eq!(pacing.backoff, None);
+    }
+}
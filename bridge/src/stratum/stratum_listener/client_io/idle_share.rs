@@ -0,0 +1,59 @@
+//! Share-idle watchdog (`idle_share_timeout_secs`): an authorized session that answers pings but
+//! submits no accepted share for the whole window is closed. Dead TCP connections are the keepalive's job.
+
+use crate::stratum_context::StratumContext;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+pub(crate) async fn run_idle_share_watchdog(ctx: Arc<StratumContext>, timeout: Duration) {
+    let tick = (timeout / 4).clamp(Duration::from_millis(50), Duration::from_secs(5));
+    loop {
+        tokio::time::sleep(tick).await;
+        if !ctx.connected() {
+            break;
+        }
+        if let Some(idle) = ctx.share_idle_for()
+            && idle >= timeout
+        {
+            info!(
+                "[IDLE] {}:{} worker '{}' submitted no shares in {:?}, disconnecting",
+                ctx.remote_addr,
+                ctx.remote_port,
+                ctx.effective_worker_name(),
+                timeout
+            );
+            ctx.disconnect();
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn authorized_session_without_shares_is_disconnected() {
+        let (ctx, _client) = crate::test_support::test_context().await;
+        assert_eq!(ctx.share_idle_for(), None, "no window before authorize");
+
+        let task = tokio::spawn(run_idle_share_watchdog(
+            ctx.clone(),
+            Duration::from_millis(300),
+        ));
+        ctx.mark_subscribed("0001".to_string());
+        ctx.mark_authorized("kaspa:wallet".to_string(), "rig1".to_string());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        ctx.record_accepted_share();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(ctx.connected(), "an accepted share restarts the window");
+
+        tokio::time::timeout(Duration::from_secs(2), task)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!ctx.connected(), "session that never submits is closed");
+    }
+}
@@ -0,0 +1,101 @@
+//! Server-initiated `mining.ping` for idle sessions; sessions that stay silent past the timeout are closed.
+
+use crate::jsonrpc_event::JsonRpcEvent;
+use crate::stratum_context::StratumContext;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use super::super::types::SessionKeepaliveConfig;
+
+/// `{"id":null,"result":"pong",...}` sent back for a server ping (no `method`, so not an event).
+pub(crate) fn is_pong_response(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line).is_ok_and(|v| {
+        v.get("method").is_none() && v.get("result").and_then(|r| r.as_str()) == Some("pong")
+    })
+}
+
+pub(crate) async fn run_session_keepalive(
+    ctx: Arc<StratumContext>,
+    config: SessionKeepaliveConfig,
+) {
+    let tick = (config.interval.min(config.timeout) / 2).max(Duration::from_millis(50));
+    loop {
+        tokio::time::sleep(tick).await;
+        if !ctx.connected() {
+            break;
+        }
+
+        match ctx.ping_outstanding_for() {
+            Some(waited) if waited >= config.timeout => {
+                info!(
+                    "[KEEPALIVE] {}:{} did not answer mining.ping within {:?}, disconnecting",
+                    ctx.remote_addr, ctx.remote_port, config.timeout
+                );
+                ctx.disconnect();
+                break;
+            }
+            Some(_) => {}
+            None if ctx.idle_for() >= config.interval => {
+                debug!(
+                    "[KEEPALIVE] {}:{} idle for {:?}, sending mining.ping",
+                    ctx.remote_addr,
+                    ctx.remote_port,
+                    ctx.idle_for()
+                );
+                ctx.mark_ping_sent();
+                let ping = JsonRpcEvent {
+                    id: Some(serde_json::Value::Null),
+                    jsonrpc: "2.0".to_string(),
+                    method: "mining.ping".to_string(),
+                    params: vec![],
+                };
+                if ctx.send(ping).await.is_err() {
+                    break;
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncBufReadExt;
+
+    #[test]
+    fn recognizes_pong_responses() {
+        assert!(is_pong_response(
+            r#"{"id":null,"result":"pong","error":null}"#
+        ));
+        assert!(!is_pong_response(r#"{"id":1,"result":true,"error":null}"#));
+        assert!(!is_pong_response(
+            r#"{"id":1,"method":"mining.ping","params":[]}"#
+        ));
+    }
+
+    #[tokio::test]
+    async fn silent_session_is_pinged_then_disconnected() {
+        let (ctx, client) = crate::test_support::test_context().await;
+
+        let config = SessionKeepaliveConfig {
+            interval: Duration::from_millis(100),
+            timeout: Duration::from_millis(200),
+        };
+        let task = tokio::spawn(run_session_keepalive(ctx.clone(), config));
+
+        let mut lines = tokio::io::BufReader::new(client).lines();
+        let ping: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(ping["id"], serde_json::Value::Null);
+        assert_eq!(ping["method"], "mining.ping");
+        assert_eq!(ping["params"], serde_json::json!([]));
+
+        tokio::time::timeout(Duration::from_secs(2), task)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!ctx.connected(), "session that missed the pong is closed");
+    }
+}
@@ -2,6 +2,10 @@
 //!
 //! The full loop lives in [`read_loop`] to keep this module root as a thin entry point.
 
+mod idle_share;
+mod keepalive;
 mod read_loop;
 
+pub(crate) use idle_share::run_idle_share_watchdog;
+pub(crate) use keepalive::run_session_keepalive;
 pub(crate) use read_loop::spawn_client_listener;
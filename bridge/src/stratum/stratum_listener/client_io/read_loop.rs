@@ -1,5 +1,7 @@
+use crate::jsonrpc_event::{JsonRpcEvent, JsonRpcResponse};
 use crate::log_colors::LogColors;
 use crate::stratum_context::StratumContext;
+use crate::stratum_error::StratumError;
 use crate::stratum_line_codec::{
     line_looks_like_http, push_lossy_and_drain_lines, strip_nul_bytes,
 };
@@ -9,11 +11,20 @@ use std::sync::Arc;
 use tokio::io::AsyncReadExt;
 use tracing::{debug, error, info, warn};
 
+use super::super::session_error::{SessionAction, StratumSessionError};
 use super::super::types::EventHandler;
+use super::keepalive::is_pong_response;
+
+/// Sent before closing a session that reached `max_parse_errors`.
+const TOO_MANY_INVALID_MESSAGES: &[u8] =
+    b"{\"id\":null,\"error\":[20,\"Protocol error: too many invalid messages\",null],\"result\":null}\n";
 
 pub(crate) async fn spawn_client_listener(
     ctx: Arc<StratumContext>,
     handler_map: &Arc<HashMap<String, EventHandler>>,
+    instance_id: &str,
+    max_batch_size: usize,
+    max_parse_errors: Option<u32>,
 ) {
     debug!(
         "[CLIENT_LISTENER] Starting client listener for {}:{}",
@@ -22,6 +33,8 @@ pub(crate) async fn spawn_client_listener(
     let mut buffer = [0u8; 1024];
     let mut line_buffer = String::new();
     let mut first_message = true;
+    // Unparsable lines since the last message that parsed
+    let mut parse_errors: u32 = 0;
 
     loop {
         // Check if disconnected
@@ -98,6 +111,7 @@ pub(crate) async fn spawn_client_listener(
                     "[CLIENT_LISTENER] Read {} bytes from {}:{}",
                     n, ctx.remote_addr, ctx.remote_port
                 );
+                ctx.record_activity();
 
                 // Remove null bytes and process
                 let data: Vec<u8> = strip_nul_bytes(&buffer[..n]);
@@ -463,8 +477,48 @@ pub(crate) async fn spawn_client_listener(
                         hex::encode(line.as_bytes())
                     );
 
+                    ctx.record_message(&line);
+
+                    // Answer to a server keepalive ping (activity already recorded above)
+                    if is_pong_response(&line) {
+                        debug!(
+                            "[KEEPALIVE] pong from {}:{}",
+                            ctx.remote_addr, ctx.remote_port
+                        );
+                        continue;
+                    }
+
+                    // JSON-RPC batch: `[{...},{...}]` on a single line
+                    if line.trim_start().starts_with('[') {
+                        match dispatch_batch(&ctx, handler_map, &line, max_batch_size).await {
+                            Ok(BatchOutcome::Dispatched) => parse_errors = 0,
+                            Ok(BatchOutcome::Rejected) => {
+                                if parse_error_limit_reached(
+                                    &ctx,
+                                    &mut parse_errors,
+                                    max_parse_errors,
+                                    instance_id,
+                                )
+                                .await
+                                {
+                                    ctx.disconnect();
+                                    break;
+                                }
+                            }
+                            Err(err) => {
+                                parse_errors = 0;
+                                if session_action(&ctx, &err) == SessionAction::Disconnect {
+                                    ctx.disconnect();
+                                    break;
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
                     match crate::jsonrpc_event::unmarshal_event(&line) {
                         Ok(event) => {
+                            parse_errors = 0;
                             let params_str = serde_json::to_string(&event.params)
                                 .unwrap_or_else(|_| "[]".to_string());
 
@@ -553,105 +607,11 @@ pub(crate) async fn spawn_client_listener(
                                 );
                             }
 
-                            if let Some(handler) = handler_map.get(&event.method) {
-                                debug!(
-                                    "{}",
-                                    LogColors::asic_to_bridge("===== PROCESSING MESSAGE ===== ")
-                                );
-                                debug!(
-                                    "{} {} {}",
-                                    LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                    LogColors::label("  - Handler Found:"),
-                                    "YES"
-                                );
-                                debug!(
-                                    "{} {} {}",
-                                    LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                    LogColors::label("  - Method:"),
-                                    format!("'{}'", event.method)
-                                );
-                                debug!(
-                                    "{} {}",
-                                    LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                    "  - Starting handler execution..."
-                                );
-                                if let Err(e) = handler(ctx.clone(), event).await {
-                                    let error_msg = e.to_string();
-                                    if error_msg.contains("stale")
-                                        || error_msg.contains("job does not exist")
-                                    {
-                                        // Log stale job errors as debug (expected behavior, not important)
-                                        debug!(
-                                            "{}",
-                                            LogColors::asic_to_bridge(
-                                                "===== HANDLER EXECUTION RESULT ===== "
-                                            )
-                                        );
-                                        debug!(
-                                            "{} {} {}",
-                                            LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                            LogColors::validation("  - Result:"),
-                                            "STALE JOB (expected - job no longer exists)"
-                                        );
-                                        debug!(
-                                            "{} {} {}",
-                                            LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                            LogColors::label("  - Error Message:"),
-                                            error_msg
-                                        );
-                                    } else if error_msg.contains("job id is not parsable") {
-                                        // Log parsing errors as warnings
-                                        warn!(
-                                            "{} {} {}",
-                                            LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                            LogColors::error("  - Result:"),
-                                            "ERROR (job ID parsing failed)"
-                                        );
-                                        warn!(
-                                            "{} {} {}",
-                                            LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                            LogColors::label("  - Error Message:"),
-                                            error_msg
-                                        );
-                                    } else {
-                                        error!(
-                                            "{} {} {}",
-                                            LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                            LogColors::error("  - Result:"),
-                                            "ERROR (handler execution failed)"
-                                        );
-                                        error!(
-                                            "{} {} {}",
-                                            LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                            LogColors::label("  - Error Message:"),
-                                            error_msg
-                                        );
-                                    }
-                                } else {
-                                    debug!(
-                                        "{}",
-                                        LogColors::asic_to_bridge(
-                                            "===== HANDLER EXECUTION RESULT ===== "
-                                        )
-                                    );
-                                    debug!(
-                                        "{} {} {}",
-                                        LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                        LogColors::label("  - Result:"),
-                                        "SUCCESS"
-                                    );
-                                    debug!(
-                                        "{} {}",
-                                        LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
-                                        "  - Message processed successfully"
-                                    );
-                                }
-                                debug!(
-                                    "{}",
-                                    LogColors::asic_to_bridge(
-                                        "========================================"
-                                    )
-                                );
+                            if let Err(err) = dispatch_event(&ctx, handler_map, event).await
+                                && session_action(&ctx, &err) == SessionAction::Disconnect
+                            {
+                                ctx.disconnect();
+                                break;
                             }
                         }
                         Err(e) => {
@@ -768,6 +728,17 @@ pub(crate) async fn spawn_client_listener(
                                     "========================================"
                                 )
                             );
+                            if parse_error_limit_reached(
+                                &ctx,
+                                &mut parse_errors,
+                                max_parse_errors,
+                                instance_id,
+                            )
+                            .await
+                            {
+                                ctx.disconnect();
+                                break;
+                            }
                         }
                     }
                 }
@@ -822,3 +793,230 @@ pub(crate) async fn spawn_client_listener(
 
     ctx.disconnect();
 }
+
+/// Count one unparsable message. Once `max_parse_errors` arrive in a row, send the protocol error
+/// and return `true` so the caller closes the session.
+async fn parse_error_limit_reached(
+    ctx: &StratumContext,
+    parse_errors: &mut u32,
+    max_parse_errors: Option<u32>,
+    instance_id: &str,
+) -> bool {
+    *parse_errors += 1;
+    if max_parse_errors.is_none_or(|max| *parse_errors < max) {
+        warn!(
+            "[ASIC->BRIDGE] unparsable message {} from {}:{}",
+            match max_parse_errors {
+                Some(max) => format!("{}/{}", parse_errors, max),
+                None => parse_errors.to_string(),
+            },
+            ctx.remote_addr,
+            ctx.remote_port
+        );
+        return false;
+    }
+    info!(
+        "[CONNECTION] dropping {}:{} after {} consecutive unparsable messages",
+        ctx.remote_addr, ctx.remote_port, parse_errors
+    );
+    let _ = ctx.send_serialized(TOO_MANY_INVALID_MESSAGES).await;
+    crate::prom::record_session_closed_parse_errors(instance_id);
+    true
+}
+
+/// Run the registered handler for `event` and log the outcome.
+async fn dispatch_event(
+    ctx: &Arc<StratumContext>,
+    handler_map: &Arc<HashMap<String, EventHandler>>,
+    event: JsonRpcEvent,
+) -> Result<(), StratumSessionError> {
+    let Some(handler) = handler_map.get(&event.method) else {
+        return Err(StratumSessionError::UnknownMethod {
+            method: event.method,
+        });
+    };
+    debug!(
+        "{}",
+        LogColors::asic_to_bridge("===== PROCESSING MESSAGE ===== ")
+    );
+    debug!(
+        "{} {} {}",
+        LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
+        LogColors::label("  - Handler Found:"),
+        "YES"
+    );
+    debug!(
+        "{} {} {}",
+        LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
+        LogColors::label("  - Method:"),
+        format!("'{}'", event.method)
+    );
+    debug!(
+        "{} {}",
+        LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
+        "  - Starting handler execution..."
+    );
+    let result = handler(ctx.clone(), event)
+        .await
+        .map_err(StratumSessionError::from_handler_error);
+    match &result {
+        // Already answered with a Stratum error; `session_action` logs it
+        Err(StratumSessionError::OutOfOrder { .. }) => {}
+        Err(e) => {
+            error!(
+                "{} {} {}",
+                LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
+                LogColors::error("  - Result:"),
+                "ERROR (handler execution failed)"
+            );
+            error!(
+                "{} {} {}",
+                LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
+                LogColors::label("  - Error Message:"),
+                e
+            );
+        }
+        Ok(()) => {
+            debug!(
+                "{}",
+                LogColors::asic_to_bridge("===== HANDLER EXECUTION RESULT ===== ")
+            );
+            debug!(
+                "{} {} {}",
+                LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
+                LogColors::label("  - Result:"),
+                "SUCCESS"
+            );
+            debug!(
+                "{} {}",
+                LogColors::asic_to_bridge("[ASIC->BRIDGE]"),
+                "  - Message processed successfully"
+            );
+        }
+    }
+    debug!(
+        "{}",
+        LogColors::asic_to_bridge("========================================")
+    );
+    result
+}
+
+/// Whether a batch line was dispatched or turned away as a whole.
+enum BatchOutcome {
+    Dispatched,
+    /// Unparsable or larger than `max_batch_size`; counts toward `max_parse_errors`.
+    Rejected,
+}
+
+/// Handle a JSON-RPC batch line: every element is dispatched in order and the replies are sent back
+/// as one JSON array (the submit handler answers batched shares in place instead of queueing them). Batches larger than `max_batch_size` get a single error response.
+async fn dispatch_batch(
+    ctx: &Arc<StratumContext>,
+    handler_map: &Arc<HashMap<String, EventHandler>>,
+    line: &str,
+    max_batch_size: usize,
+) -> Result<BatchOutcome, StratumSessionError> {
+    let items: Vec<serde_json::Value> = match serde_json::from_str(line) {
+        Ok(items) => items,
+        Err(e) => {
+            warn!(
+                "[ASIC->BRIDGE] Malformed JSON-RPC batch from {}:{}: {}",
+                ctx.remote_addr, ctx.remote_port, e
+            );
+            let _ = ctx
+                .reply(JsonRpcResponse::stratum_error(
+                    None,
+                    StratumError::unknown("Malformed batch"),
+                ))
+                .await;
+            return Ok(BatchOutcome::Rejected);
+        }
+    };
+
+    if items.len() > max_batch_size {
+        warn!(
+            "[ASIC->BRIDGE] JSON-RPC batch of {} requests from {}:{} exceeds max_batch_size {}",
+            items.len(),
+            ctx.remote_addr,
+            ctx.remote_port,
+            max_batch_size
+        );
+        let _ = ctx
+            .reply(JsonRpcResponse::stratum_error(
+                None,
+                StratumError::unknown(format!("Batch too large (max {})", max_batch_size)),
+            ))
+            .await;
+        return Ok(BatchOutcome::Rejected);
+    }
+
+    debug!(
+        "[ASIC->BRIDGE] JSON-RPC batch of {} requests from {}:{}",
+        items.len(),
+        ctx.remote_addr,
+        ctx.remote_port
+    );
+
+    let mut outcome = Ok(BatchOutcome::Dispatched);
+    ctx.begin_batch();
+    for item in items {
+        match serde_json::from_value::<JsonRpcEvent>(item.clone()) {
+            Ok(event) => match dispatch_event(ctx, handler_map, event).await {
+                Err(err) if err.action() == SessionAction::Disconnect => {
+                    outcome = Err(err);
+                    break;
+                }
+                Err(err) => {
+                    session_action(ctx, &err);
+                }
+                Ok(()) => {}
+            },
+            Err(e) => {
+                let id = item.get("id").cloned();
+                let _ = ctx
+                    .reply(JsonRpcResponse::stratum_error(
+                        id,
+                        StratumError::unknown(format!("Invalid request: {}", e)),
+                    ))
+                    .await;
+            }
+        }
+    }
+    if let Err(e) = ctx.finish_batch().await {
+        debug!(
+            "[BRIDGE->ASIC] Failed sending batch response to {}:{}: {}",
+            ctx.remote_addr, ctx.remote_port, e
+        );
+    }
+    outcome
+}
+
+/// Log an error from one inbound message and decide whether the session survives it.
+fn session_action(ctx: &StratumContext, err: &StratumSessionError) -> SessionAction {
+    match err {
+        StratumSessionError::InvalidJson { .. } => warn!(
+            "[CONNECTION] dropping {}:{} after unparsable message: {}",
+            ctx.remote_addr, ctx.remote_port, err
+        ),
+        StratumSessionError::UnknownMethod { method } => debug!(
+            "[ASIC->BRIDGE] no handler for '{}' from {}:{}, ignoring",
+            method, ctx.remote_addr, ctx.remote_port
+        ),
+        StratumSessionError::ConnectionClosed => debug!(
+            "[CONNECTION] {}:{} closed while handling a message",
+            ctx.remote_addr, ctx.remote_port
+        ),
+        StratumSessionError::OutOfOrder { .. } => debug!(
+            "[ASIC->BRIDGE] {}:{} ({}): {}",
+            ctx.remote_addr,
+            ctx.remote_port,
+            ctx.session_state().name(),
+            err
+        ),
+        // Rejected shares and node failures are logged by `dispatch_event`
+        StratumSessionError::KaspaRpcError(_)
+        | StratumSessionError::ShareRejected { .. }
+        | StratumSessionError::Timeout => {}
+    }
+    err.action()
+}
@@ -1,12 +1,306 @@
-use crate::net_utils::bind_addr_from_port;
-use crate::stratum_context::StratumContext;
+use crate::health_check::GlobalWorkerCount;
+use crate::ip_filter::IpAccessList;
+use crate::net_utils::{TcpKeepaliveConfig, bind_addr_from_port, bind_stratum_listener};
+use crate::stratum_context::{StratumContext, StratumTransport};
+use crate::stratum_error::StratumError;
+use futures_util::FutureExt;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
-use tokio::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
 use tokio::sync::{mpsc, watch};
-use tracing::{debug, error, info};
+use tracing::{Instrument, debug, error, info, warn};
 
-use super::client_io::spawn_client_listener;
-use super::types::StratumListenerConfig;
+use super::accept_flood::ConnectionFloodDetector;
+use super::client_io::{run_idle_share_watchdog, run_session_keepalive, spawn_client_listener};
+use super::types::{EventHandler, SessionKeepaliveConfig, StratumListenerConfig, WorkerCapacity};
+
+/// Seconds a miner turned away at `max_workers` is asked to wait before connecting to the overflow pool.
+pub(super) const CAPACITY_RECONNECT_DELAY_SECS: u64 = 5;
+
+/// Apply the optional IP denylist/allowlist to a freshly accepted peer.
+pub(super) fn peer_admitted(ip_access: Option<&IpAccessList>, addr: &SocketAddr) -> bool {
+    let Some(acl) = ip_access else {
+        return true;
+    };
+    match acl.check(addr.ip()) {
+        Ok(()) => true,
+        Err(reason) => {
+            info!("[CONNECTION] dropping {} ({})", addr, reason.as_str());
+            crate::prom::record_connection_denied(reason.as_str());
+            crate::prom::record_authorize_rejection("ip_denied");
+            false
+        }
+    }
+}
+
+/// Tell a peer refused by the IP allowlist/denylist why (error 25) and close its connection.
+pub(super) fn reject_denied_peer(mut stream: impl StratumTransport, addr: SocketAddr) {
+    tokio::spawn(async move {
+        let error: serde_json::Value = StratumError::NotSubscribed {
+            custom_message: Some("Connection not allowed from your IP".to_string()),
+        }
+        .into();
+        let reply = serde_json::json!({ "id": null, "result": null, "error": error });
+        let line = format!("{reply}\n");
+        if let Err(e) = stream.write_all(line.as_bytes()).await {
+            debug!("[CONNECTION] failed sending IP denial to {}: {}", addr, e);
+        }
+        let _ = stream.shutdown().await;
+    });
+}
+
+/// Configure TCP keepalive on an accepted socket; failures are logged and the connection kept.
+pub(super) fn apply_keepalive(
+    keepalive: Option<&TcpKeepaliveConfig>,
+    stream: &tokio::net::TcpStream,
+) {
+    if let Some(ka) = keepalive
+        && let Err(e) = crate::net_utils::apply_tcp_keepalive(stream, ka)
+    {
+        debug!("[CONNECTION] failed to set TCP keepalive: {}", e);
+    }
+}
+
+/// Text of a panic payload (`panic!` with a literal or a formatted message).
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("<non-string panic payload>")
+}
+
+/// Releases a slot in [`SessionStarter::active_sessions`] when the session task ends (or panics).
+struct ActiveSessionGuard(Arc<AtomicUsize>);
+
+impl Drop for ActiveSessionGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Everything needed to turn an accepted transport into a running session; shared by the TCP and
+/// WebSocket accept loops.
+#[derive(Clone)]
+pub(super) struct SessionStarter {
+    pub(super) instance_id: String,
+    pub(super) handler_map: Arc<HashMap<String, EventHandler>>,
+    pub(super) on_connect: Arc<dyn Fn(Arc<StratumContext>) + Send + Sync>,
+    pub(super) max_batch_size: usize,
+    pub(super) max_parse_errors: Option<u32>,
+    pub(super) session_keepalive: Option<SessionKeepaliveConfig>,
+    pub(super) idle_share_timeout: Option<Duration>,
+    pub(super) disconnect_tx: mpsc::UnboundedSender<Arc<StratumContext>>,
+    pub(super) capacity: Option<WorkerCapacity>,
+    /// Sessions currently running on this instance, across both accept loops.
+    pub(super) active_sessions: Arc<AtomicUsize>,
+    /// Sessions running on every instance (health check and `ks_stratum_total_workers`).
+    pub(super) total_workers: GlobalWorkerCount,
+}
+
+impl SessionStarter {
+    /// Create the context, run `on_connect`, and spawn the read loop (plus keepalive) for one client.
+    /// At `max_workers` the client is sent `client.reconnect` (if an overflow pool is set) and closed.
+    pub(super) fn start(&self, stream: impl StratumTransport, addr: SocketAddr) {
+        let active = self.active_sessions.fetch_add(1, Ordering::AcqRel) + 1;
+        let session_guard = ActiveSessionGuard(Arc::clone(&self.active_sessions));
+        if let Some(capacity) = &self.capacity
+            && active > capacity.max_workers
+        {
+            drop(session_guard);
+            self.reject_at_capacity(stream, addr, capacity);
+            return;
+        }
+        let worker_guard = self.total_workers.track();
+
+        let remote_addr = addr.ip().to_string();
+        let remote_port = addr.port();
+
+        // Create new MiningState for each client
+        // Each client gets its own isolated state, just like in Go
+        use crate::mining_state::MiningState;
+        let state = Arc::new(MiningState::new());
+
+        debug!(
+            "[CONNECTION] Creating StratumContext for {}:{}",
+            remote_addr, remote_port
+        );
+        let ctx = StratumContext::new(
+            remote_addr.clone(),
+            remote_port,
+            stream,
+            state,
+            self.disconnect_tx.clone(),
+        );
+        debug!("[CONNECTION] StratumContext created successfully");
+
+        debug!("[CONNECTION] Calling on_connect handler");
+        (self.on_connect)(ctx.clone());
+        debug!("[CONNECTION] on_connect handler completed");
+
+        debug!(
+            "[CONNECTION] Spawning client listener task for {}:{}",
+            remote_addr, remote_port
+        );
+        let ctx_clone = ctx.clone();
+        let handler_map = self.handler_map.clone();
+        let max_batch_size = self.max_batch_size;
+        let max_parse_errors = self.max_parse_errors;
+        let instance_id = self.instance_id.clone();
+        let span = tracing::info_span!(
+            "session",
+            peer = tracing::field::Empty,
+            wallet = tracing::field::Empty
+        );
+        tokio::spawn(
+            async move {
+                let _session_guard = session_guard;
+                let _worker_guard = worker_guard;
+                tracing::Span::current().record("peer", addr.to_string());
+                debug!(
+                    "[CONNECTION] Client listener task started for {}:{}",
+                    ctx_clone.remote_addr, ctx_clone.remote_port
+                );
+                // A panic in message handling closes this session only; log it with the peer and
+                // the message that triggered it
+                let session = AssertUnwindSafe(spawn_client_listener(
+                    Arc::clone(&ctx_clone),
+                    &handler_map,
+                    &instance_id,
+                    max_batch_size,
+                    max_parse_errors,
+                ))
+                .catch_unwind()
+                .await;
+                if let Err(payload) = session {
+                    error!(
+                        "[CONNECTION] session {} panicked: {} (last message: {:?})",
+                        addr,
+                        panic_message(payload.as_ref()),
+                        ctx_clone.last_message()
+                    );
+                    crate::prom::record_session_panic(&instance_id);
+                    ctx_clone.disconnect();
+                }
+                crate::prom::record_session_duration(
+                    &instance_id,
+                    ctx_clone.connected_at().elapsed(),
+                );
+                debug!("[CONNECTION] Client listener task ended");
+            }
+            .instrument(span.clone()),
+        );
+        if let Some(timeout) = self.idle_share_timeout {
+            tokio::spawn(run_idle_share_watchdog(ctx.clone(), timeout).instrument(span.clone()));
+        }
+        if let Some(keepalive) = self.session_keepalive {
+            tokio::spawn(run_session_keepalive(ctx, keepalive).instrument(span));
+        }
+        debug!(
+            "[CONNECTION] ===== CONNECTION SETUP COMPLETE FOR {}:{} =====",
+            remote_addr, remote_port
+        );
+    }
+
+    /// Point a miner at the overflow pool (when configured) and close its connection.
+    fn reject_at_capacity(
+        &self,
+        mut stream: impl StratumTransport,
+        addr: SocketAddr,
+        capacity: &WorkerCapacity,
+    ) {
+        warn!(
+            "[CONNECTION] {} at max_workers ({}), rejecting {}",
+            self.instance_id, capacity.max_workers, addr
+        );
+        crate::prom::record_connection_rejected_capacity(&self.instance_id);
+        let overflow_pool = capacity.overflow_pool.clone();
+        tokio::spawn(async move {
+            if let Some((host, port)) = overflow_pool {
+                let reconnect = serde_json::json!({
+                    "id": null,
+                    "method": "client.reconnect",
+                    "params": [host, port, CAPACITY_RECONNECT_DELAY_SECS],
+                });
+                let line = format!("{reconnect}\n");
+                if let Err(e) = stream.write_all(line.as_bytes()).await {
+                    debug!(
+                        "[CONNECTION] failed sending client.reconnect to {}: {}",
+                        addr, e
+                    );
+                }
+            }
+            let _ = stream.shutdown().await;
+        });
+    }
+}
+
+/// Accepted TCP connection: ACL, keepalive, then a plain `\n`-delimited session.
+fn start_tcp_session(
+    config: &StratumListenerConfig,
+    starter: &SessionStarter,
+    stream: tokio::net::TcpStream,
+    addr: SocketAddr,
+) {
+    if !peer_admitted(config.ip_access.as_deref(), &addr) {
+        reject_denied_peer(stream, addr);
+        return;
+    }
+    apply_keepalive(config.tcp_keepalive.as_ref(), &stream);
+
+    debug!(
+        "[CONNECTION] new client connecting - {}:{}",
+        addr.ip(),
+        addr.port()
+    );
+    debug!("[CONNECTION] ===== TCP CONNECTION ESTABLISHED =====");
+    debug!("[CONNECTION] Remote address: {}:{}", addr.ip(), addr.port());
+    debug!("[CONNECTION] Local address: {:?}", stream.local_addr());
+    debug!("[CONNECTION] Connection accepted successfully");
+
+    starter.start(stream, addr);
+}
+
+fn log_accept_error(e: &std::io::Error) {
+    error!("[CONNECTION] ===== FAILED TO ACCEPT INCOMING CONNECTION =====");
+    error!("[CONNECTION] Error: {}", e);
+    error!("[CONNECTION] Error kind: {:?}", e.kind());
+    error!(
+        "[CONNECTION] Failed to accept connection: {} (kind: {:?})",
+        e,
+        e.kind()
+    );
+}
+
+/// Count an accepted connection (TCP or WebSocket); during a flood, pause before the next `accept()`.
+pub(super) async fn pace_accepts(
+    detector: Option<&parking_lot::Mutex<ConnectionFloodDetector>>,
+    instance_id: &str,
+) {
+    let Some(detector) = detector else {
+        return;
+    };
+    let pacing = detector.lock().record_accept(std::time::Instant::now());
+    crate::prom::record_accept_rate(instance_id, pacing.rate, pacing.backoff.is_some());
+    match pacing.flood_changed {
+        Some(true) => warn!(
+            "[CONNECTION] {} new connections/s exceeds max_global_accepts_per_second; slowing accepts",
+            pacing.rate.round()
+        ),
+        Some(false) => info!(
+            "[CONNECTION] connection rate back to normal ({} /s); accepting at full speed",
+            pacing.rate.round()
+        ),
+        None => {}
+    }
+    if let Some(backoff) = pacing.backoff {
+        tokio::time::sleep(backoff).await;
+    }
+}
 
 pub(crate) async fn listen_impl(
     config: &StratumListenerConfig,
@@ -19,11 +313,12 @@ pub(crate) async fn listen_impl(
     // Ensure we bind to IPv4 (0.0.0.0) when given a bare port like ":5555" / "5555".
     let addr_str = bind_addr_from_port(&config.port);
 
-    let listener = TcpListener::bind(&addr_str)
-        .await
+    let listener = bind_stratum_listener(&addr_str, config.reuse_port)
         .map_err(|e| format!("failed listening to socket {}: {}", config.port, e))?;
 
     debug!("Stratum listener started on {}", config.port);
+    // Counts this instance as `Running` for `/healthz/ready` while the accept loop is alive.
+    let _running_guard = crate::health_check::RunningInstanceGuard::new();
 
     let (disconnect_tx, mut disconnect_rx) = mpsc::unbounded_channel::<Arc<StratumContext>>();
     let disconnect_tx_clone = disconnect_tx.clone();
@@ -65,6 +360,42 @@ pub(crate) async fn listen_impl(
         }
     });
 
+    let starter = SessionStarter {
+        instance_id: config.instance_id.clone(),
+        handler_map: config.handler_map.clone(),
+        on_connect: Arc::clone(&config.on_connect),
+        max_batch_size: config.max_batch_size,
+        max_parse_errors: config.max_parse_errors,
+        session_keepalive: config.session_keepalive,
+        idle_share_timeout: config.idle_share_timeout,
+        disconnect_tx: disconnect_tx_clone,
+        capacity: config.capacity.clone(),
+        active_sessions: Arc::new(AtomicUsize::new(0)),
+        total_workers: config.total_workers.clone(),
+    };
+    // Shared with the WebSocket accept loop so the limit covers both ports
+    let flood_detector = config.accept_flood.map(|flood| {
+        Arc::new(parking_lot::Mutex::new(ConnectionFloodDetector::new(
+            flood,
+            std::time::Instant::now(),
+        )))
+    });
+
+    if let Some(ref ws_port) = config.ws_port {
+        let ws_addr = bind_addr_from_port(ws_port);
+        let ws_listener = bind_stratum_listener(&ws_addr, config.reuse_port)
+            .map_err(|e| format!("failed listening to websocket socket {}: {}", ws_port, e))?;
+        info!("Stratum WebSocket listener started on {}", ws_port);
+        tokio::spawn(super::ws::serve_ws(
+            ws_listener,
+            starter.clone(),
+            config.ip_access.clone(),
+            config.tcp_keepalive,
+            flood_detector.clone(),
+            shutdown_rx.clone(),
+        ));
+    }
+
     loop {
         if let Some(ref mut rx) = shutdown_rx {
             tokio::select! {
@@ -77,58 +408,15 @@ pub(crate) async fn listen_impl(
                 result = listener.accept() => {
                     match result {
                         Ok((stream, addr)) => {
-                        let remote_addr = addr.ip().to_string();
-                        let remote_port = addr.port();
-
-                        debug!("[CONNECTION] new client connecting - {}:{}", remote_addr, remote_port);
-                        debug!("[CONNECTION] ===== TCP CONNECTION ESTABLISHED =====");
-                        debug!("[CONNECTION] Remote address: {}:{}", remote_addr, remote_port);
-                        debug!("[CONNECTION] Local address: {:?}", stream.local_addr());
-                        debug!("[CONNECTION] Connection accepted successfully");
-
-                        // Create new MiningState for each client
-                        // Each client gets its own isolated state, just like in Go
-                        use crate::mining_state::MiningState;
-                        let state = Arc::new(MiningState::new());
-
-                        // Clone for logging after move
-                        let remote_addr_for_log = remote_addr.clone();
-                        let remote_port_for_log = remote_port;
-
-                        debug!("[CONNECTION] Creating StratumContext for {}:{}", remote_addr_for_log, remote_port_for_log);
-                        let ctx = StratumContext::new(
-                            remote_addr,
-                            remote_port,
-                            stream,
-                            state,
-                            disconnect_tx_clone.clone(),
-                        );
-                        debug!("[CONNECTION] StratumContext created successfully");
-
-                        debug!("[CONNECTION] Calling on_connect handler");
-                        (config.on_connect)(ctx.clone());
-                        debug!("[CONNECTION] on_connect handler completed");
-
-                        // Spawn client handler
-                        debug!("[CONNECTION] Spawning client listener task for {}:{}", remote_addr_for_log, remote_port_for_log);
-                        let ctx_clone = ctx.clone();
-                        let handler_map = config.handler_map.clone();
-                        tokio::spawn(async move {
-                            debug!("[CONNECTION] Client listener task started for {}:{}", ctx_clone.remote_addr, ctx_clone.remote_port);
-                            spawn_client_listener(ctx_clone, &handler_map).await;
-                            debug!("[CONNECTION] Client listener task ended");
-                        });
-                        debug!("[CONNECTION] ===== CONNECTION SETUP COMPLETE FOR {}:{} =====", remote_addr_for_log, remote_port_for_log);
-                    }
-                        Err(e) => {
-                        if shutting_down.load(std::sync::atomic::Ordering::Acquire) {
-                            info!("stopping listening due to server shutdown");
-                            break;
+                            start_tcp_session(config, &starter, stream, addr);
+                            pace_accepts(flood_detector.as_deref(), &config.instance_id).await;
                         }
-                        error!("[CONNECTION] ===== FAILED TO ACCEPT INCOMING CONNECTION =====");
-                        error!("[CONNECTION] Error: {}", e);
-                        error!("[CONNECTION] Error kind: {:?}", e.kind());
-                        error!("[CONNECTION] Failed to accept connection: {} (kind: {:?})", e, e.kind());
+                        Err(e) => {
+                            if shutting_down.load(std::sync::atomic::Ordering::Acquire) {
+                                info!("stopping listening due to server shutdown");
+                                break;
+                            }
+                            log_accept_error(&e);
                         }
                     }
                 }
@@ -136,76 +424,15 @@ pub(crate) async fn listen_impl(
         } else {
             match listener.accept().await {
                 Ok((stream, addr)) => {
-                    let remote_addr = addr.ip().to_string();
-                    let remote_port = addr.port();
-
-                    debug!(
-                        "[CONNECTION] new client connecting - {}:{}",
-                        remote_addr, remote_port
-                    );
-                    debug!("[CONNECTION] ===== TCP CONNECTION ESTABLISHED =====");
-                    debug!(
-                        "[CONNECTION] Remote address: {}:{}",
-                        remote_addr, remote_port
-                    );
-                    debug!("[CONNECTION] Local address: {:?}", stream.local_addr());
-                    debug!("[CONNECTION] Connection accepted successfully");
-
-                    use crate::mining_state::MiningState;
-                    let state = Arc::new(MiningState::new());
-
-                    let remote_addr_for_log = remote_addr.clone();
-                    let remote_port_for_log = remote_port;
-
-                    debug!(
-                        "[CONNECTION] Creating StratumContext for {}:{}",
-                        remote_addr_for_log, remote_port_for_log
-                    );
-                    let ctx = StratumContext::new(
-                        remote_addr,
-                        remote_port,
-                        stream,
-                        state,
-                        disconnect_tx_clone.clone(),
-                    );
-                    debug!("[CONNECTION] StratumContext created successfully");
-
-                    debug!("[CONNECTION] Calling on_connect handler");
-                    (config.on_connect)(ctx.clone());
-                    debug!("[CONNECTION] on_connect handler completed");
-
-                    debug!(
-                        "[CONNECTION] Spawning client listener task for {}:{}",
-                        remote_addr_for_log, remote_port_for_log
-                    );
-                    let ctx_clone = ctx.clone();
-                    let handler_map = config.handler_map.clone();
-                    tokio::spawn(async move {
-                        debug!(
-                            "[CONNECTION] Client listener task started for {}:{}",
-                            ctx_clone.remote_addr, ctx_clone.remote_port
-                        );
-                        spawn_client_listener(ctx_clone, &handler_map).await;
-                        debug!("[CONNECTION] Client listener task ended");
-                    });
-                    debug!(
-                        "[CONNECTION] ===== CONNECTION SETUP COMPLETE FOR {}:{} =====",
-                        remote_addr_for_log, remote_port_for_log
-                    );
+                    start_tcp_session(config, &starter, stream, addr);
+                    pace_accepts(flood_detector.as_deref(), &config.instance_id).await;
                 }
                 Err(e) => {
                     if shutting_down.load(std::sync::atomic::Ordering::Acquire) {
                         info!("stopping listening due to server shutdown");
                         break;
                     }
-                    error!("[CONNECTION] ===== FAILED TO ACCEPT INCOMING CONNECTION =====");
-                    error!("[CONNECTION] Error: {}", e);
-                    error!("[CONNECTION] Error kind: {:?}", e.kind());
-                    error!(
-                        "[CONNECTION] Failed to accept connection: {} (kind: {:?})",
-                        e,
-                        e.kind()
-                    );
+                    log_accept_error(&e);
                 }
             }
         }
@@ -213,3 +440,213 @@ pub(crate) async fn listen_impl(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc_event::JsonRpcEvent;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn panicking_handler_closes_only_its_session() {
+        let mut handlers: HashMap<String, EventHandler> = HashMap::new();
+        handlers.insert(
+            "mining.subscribe".to_string(),
+            Arc::new(|_ctx: Arc<StratumContext>, event: JsonRpcEvent| {
+                Box::pin(async move {
+                    if event.params.is_empty() {
+                        panic!("subscribe without params");
+                    }
+                    Ok(())
+                })
+                    as std::pin::Pin<
+                        Box<
+                            dyn std::future::Future<
+                                    Output = Result<(), Box<dyn std::error::Error + Send + Sync>>,
+                                > + Send,
+                        >,
+                    >
+            }) as EventHandler,
+        );
+        let (disconnect_tx, mut disconnect_rx) = mpsc::unbounded_channel();
+        let starter = SessionStarter {
+            instance_id: "panic-test".to_string(),
+            handler_map: Arc::new(handlers),
+            on_connect: Arc::new(|_| {}),
+            max_batch_size: 1,
+            max_parse_errors: None,
+            session_keepalive: None,
+            idle_share_timeout: None,
+            disconnect_tx,
+            capacity: None,
+            active_sessions: Arc::new(AtomicUsize::new(0)),
+            total_workers: GlobalWorkerCount::default(),
+        };
+
+        let (stream, mut client) = crate::test_support::socket_pair().await;
+        let peer = stream.peer_addr().unwrap();
+        starter.start(stream, peer);
+
+        client
+            .write_all(b"{\"id\":1,\"method\":\"mining.subscribe\",\"params\":[]}\n")
+            .await
+            .unwrap();
+        let ctx = tokio::time::timeout(Duration::from_secs(2), disconnect_rx.recv())
+            .await
+            .expect("panicked session is disconnected")
+            .unwrap();
+        assert!(!ctx.connected());
+        assert!(ctx.last_message().contains("mining.subscribe"));
+    }
+
+    #[tokio::test]
+    async fn sessions_beyond_max_workers_are_redirected() {
+        let (disconnect_tx, _disconnect_rx) = mpsc::unbounded_channel();
+        let starter = SessionStarter {
+            instance_id: "capacity-test".to_string(),
+            handler_map: Arc::new(HashMap::new()),
+            on_connect: Arc::new(|_| {}),
+            max_batch_size: 1,
+            max_parse_errors: None,
+            session_keepalive: None,
+            idle_share_timeout: None,
+            disconnect_tx,
+            capacity: Some(WorkerCapacity {
+                max_workers: 1,
+                overflow_pool: Some(("backup-pool.example.com".to_string(), 5555)),
+            }),
+            active_sessions: Arc::new(AtomicUsize::new(0)),
+            total_workers: GlobalWorkerCount::default(),
+        };
+
+        let (stream, _first) = crate::test_support::socket_pair().await;
+        let peer = stream.peer_addr().unwrap();
+        starter.start(stream, peer);
+
+        let (stream, second) = crate::test_support::socket_pair().await;
+        let peer = stream.peer_addr().unwrap();
+        starter.start(stream, peer);
+
+        let mut lines = tokio::io::BufReader::new(second).lines();
+        let line = tokio::time::timeout(Duration::from_secs(2), lines.next_line())
+            .await
+            .expect("rejected client gets a reply")
+            .unwrap()
+            .unwrap();
+        let reconnect: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            reconnect,
+            serde_json::json!({
+                "id": null,
+                "method": "client.reconnect",
+                "params": ["backup-pool.example.com", 5555, 5],
+            })
+        );
+        assert!(lines.next_line().await.unwrap().is_none());
+        assert_eq!(starter.active_sessions.load(Ordering::Acquire), 1);
+        // Turned-away miners never count toward the bridge total
+        assert_eq!(starter.total_workers.load(), 1);
+    }
+
+    #[tokio::test]
+    async fn repeated_invalid_json_closes_the_session() {
+        let (disconnect_tx, mut disconnect_rx) = mpsc::unbounded_channel();
+        let starter = SessionStarter {
+            instance_id: "parse-error-test".to_string(),
+            handler_map: Arc::new(HashMap::new()),
+            on_connect: Arc::new(|_| {}),
+            max_batch_size: 1,
+            max_parse_errors: Some(3),
+            session_keepalive: None,
+            idle_share_timeout: None,
+            disconnect_tx,
+            capacity: None,
+            active_sessions: Arc::new(AtomicUsize::new(0)),
+            total_workers: GlobalWorkerCount::default(),
+        };
+
+        let (stream, client) = crate::test_support::socket_pair().await;
+        let peer = stream.peer_addr().unwrap();
+        starter.start(stream, peer);
+
+        let (read, mut write) = client.into_split();
+        // A message that parses in between resets the count
+        write
+            .write_all(b"not json\n{\"id\":1,\"method\":\"mining.noop\",\"params\":[]}\n")
+            .await
+            .unwrap();
+        write.write_all(b"{{\n}}\nnope\n").await.unwrap();
+
+        let mut lines = tokio::io::BufReader::new(read).lines();
+        let line = tokio::time::timeout(Duration::from_secs(2), lines.next_line())
+            .await
+            .expect("client gets a protocol error")
+            .unwrap()
+            .unwrap();
+        let reply: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            reply,
+            serde_json::json!({
+                "id": null,
+                "error": [20, "Protocol error: too many invalid messages", null],
+                "result": null,
+            })
+        );
+        let ctx = tokio::time::timeout(Duration::from_secs(2), disconnect_rx.recv())
+            .await
+            .expect("session is closed")
+            .unwrap();
+        assert!(!ctx.connected());
+    }
+
+    #[tokio::test]
+    async fn rejected_batches_count_as_parse_errors() {
+        let (disconnect_tx, mut disconnect_rx) = mpsc::unbounded_channel();
+        let starter = SessionStarter {
+            instance_id: "batch-parse-error-test".to_string(),
+            handler_map: Arc::new(HashMap::new()),
+            on_connect: Arc::new(|_| {}),
+            max_batch_size: 1,
+            max_parse_errors: Some(2),
+            session_keepalive: None,
+            idle_share_timeout: None,
+            disconnect_tx,
+            capacity: None,
+            active_sessions: Arc::new(AtomicUsize::new(0)),
+            total_workers: GlobalWorkerCount::default(),
+        };
+
+        let (stream, client) = crate::test_support::socket_pair().await;
+        let peer = stream.peer_addr().unwrap();
+        starter.start(stream, peer);
+
+        let (read, mut write) = client.into_split();
+        // One malformed batch and one over `max_batch_size`
+        write
+            .write_all(b"[{\"id\":1,\n[{\"id\":1,\"method\":\"a\",\"params\":[]},{\"id\":2,\"method\":\"b\",\"params\":[]}]\n")
+            .await
+            .unwrap();
+
+        let mut lines = tokio::io::BufReader::new(read).lines();
+        let mut replies = Vec::new();
+        while let Ok(Ok(Some(line))) =
+            tokio::time::timeout(Duration::from_secs(2), lines.next_line()).await
+        {
+            replies.push(line);
+        }
+        assert!(replies[0].contains("Malformed batch"), "{replies:?}");
+        assert!(
+            replies
+                .last()
+                .unwrap()
+                .contains("Protocol error: too many invalid messages"),
+            "{replies:?}"
+        );
+        let ctx = tokio::time::timeout(Duration::from_secs(2), disconnect_rx.recv())
+            .await
+            .expect("session is closed")
+            .unwrap();
+        assert!(!ctx.connected());
+    }
+}
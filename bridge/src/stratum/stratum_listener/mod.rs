@@ -1,13 +1,24 @@
 //! Stratum TCP listener: accept loop, per-client read/framing, JSON-RPC dispatch.
 //!
-//! Internal modules: `types` (config + handler types), `listen` (bind/accept), `client_io/` (per-client read loop).
+//! Internal modules: `types` (config + handler types), `listen` (bind/accept), `accept_flood` (accept
+//! rate backpressure), `ws` (optional WebSocket transport), `client_io/` (per-client read loop),
+//! `session_error` (typed per-message outcomes).
 
+mod accept_flood;
 mod client_io;
 mod listen;
+mod session_error;
 mod types;
+mod ws;
 
+pub use accept_flood::{
+    AcceptFloodConfig, AcceptPacing, ConnectionFloodDetector, DEFAULT_ACCEPT_BACKOFF_MS,
+    DEFAULT_MAX_GLOBAL_ACCEPTS_PER_SECOND,
+};
+pub use session_error::{SessionAction, ShareRejectionReason, StratumSessionError};
 pub use types::{
-    EventHandler, StateGenerator, StratumClientListener, StratumListenerConfig, StratumStats,
+    EventHandler, SessionKeepaliveConfig, StateGenerator, StratumClientListener,
+    StratumListenerConfig, StratumStats, WorkerCapacity,
 };
 
 use crate::jsonrpc_event::JsonRpcEvent;
@@ -0,0 +1,136 @@
+//! Typed outcome of handling one inbound line, so the read loop can tell "drop this miner" apart from
+//! "log and keep reading".
+
+use crate::bridge_error::BridgeError;
+use crate::share_handler::{SubmitError, SubmitRunError};
+use crate::stratum_error::StratumError;
+use thiserror::Error;
+
+/// Why a share was turned down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareRejectionReason {
+    /// The job is no longer stored.
+    Stale,
+    Duplicate,
+    LowDifficulty,
+    /// Params could not be parsed (job id, nonce, param count).
+    Malformed,
+}
+
+impl std::fmt::Display for ShareRejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ShareRejectionReason::Stale => "stale",
+            ShareRejectionReason::Duplicate => "duplicate",
+            ShareRejectionReason::LowDifficulty => "low difficulty",
+            ShareRejectionReason::Malformed => "malformed",
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StratumSessionError {
+    /// The miner sent something that is not JSON-RPC; `raw` is the parser's description.
+    #[error("invalid JSON from miner: {raw}")]
+    InvalidJson { raw: String },
+    #[error("unknown method '{method}'")]
+    UnknownMethod { method: String },
+    /// The method is not allowed in the session's current state; the miner was sent `error`.
+    #[error("'{method}' out of order: {error}")]
+    OutOfOrder { method: String, error: StratumError },
+    /// A handler failed outside the share pipeline (template fetch, node RPC).
+    #[error("kaspad RPC failed: {0}")]
+    KaspaRpcError(anyhow::Error),
+    #[error("share rejected ({reason})")]
+    ShareRejected { reason: ShareRejectionReason },
+    #[error("connection closed")]
+    ConnectionClosed,
+    #[error("timed out")]
+    Timeout,
+}
+
+/// What the read loop does after a [`StratumSessionError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionAction {
+    Continue,
+    Disconnect,
+}
+
+impl StratumSessionError {
+    pub fn action(&self) -> SessionAction {
+        match self {
+            StratumSessionError::InvalidJson { .. } | StratumSessionError::ConnectionClosed => {
+                SessionAction::Disconnect
+            }
+            StratumSessionError::UnknownMethod { .. }
+            | StratumSessionError::OutOfOrder { .. }
+            | StratumSessionError::KaspaRpcError(_)
+            | StratumSessionError::ShareRejected { .. }
+            | StratumSessionError::Timeout => SessionAction::Continue,
+        }
+    }
+
+    /// Classify the boxed error returned by an [`super::EventHandler`].
+    pub fn from_handler_error(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match err.downcast::<BridgeError>() {
+            Ok(bridge) => match *bridge {
+                BridgeError::Submit(e) => e.into(),
+                BridgeError::OutOfOrder { method, error } => {
+                    StratumSessionError::OutOfOrder { method, error }
+                }
+            },
+            Err(other) => StratumSessionError::KaspaRpcError(anyhow::anyhow!(other)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for StratumSessionError {
+    fn from(e: serde_json::Error) -> Self {
+        StratumSessionError::InvalidJson { raw: e.to_string() }
+    }
+}
+
+impl From<SubmitRunError> for StratumSessionError {
+    fn from(e: SubmitRunError) -> Self {
+        match e {
+            SubmitRunError::Validation(SubmitError::StaleJob) => {
+                StratumSessionError::ShareRejected {
+                    reason: ShareRejectionReason::Stale,
+                }
+            }
+            SubmitRunError::Validation(_) => StratumSessionError::ShareRejected {
+                reason: ShareRejectionReason::Malformed,
+            },
+            SubmitRunError::StratumDisconnected(_) | SubmitRunError::ReplyFailed(_) => {
+                StratumSessionError::ConnectionClosed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_handler_errors() {
+        let stale =
+            BridgeError::from(SubmitRunError::from(SubmitError::StaleJob)).into_boxed_stratum();
+        let err = StratumSessionError::from_handler_error(stale);
+        assert!(matches!(
+            err,
+            StratumSessionError::ShareRejected {
+                reason: ShareRejectionReason::Stale
+            }
+        ));
+        assert_eq!(err.action(), SessionAction::Continue);
+
+        let rpc = StratumSessionError::from_handler_error("template fetch failed".into());
+        assert!(matches!(rpc, StratumSessionError::KaspaRpcError(_)));
+        assert_eq!(rpc.action(), SessionAction::Continue);
+
+        let json =
+            StratumSessionError::from(serde_json::from_str::<serde_json::Value>("{").unwrap_err());
+        assert_eq!(json.action(), SessionAction::Disconnect);
+    }
+}
@@ -2,6 +2,7 @@ use crate::jsonrpc_event::JsonRpcEvent;
 use crate::stratum_context::StratumContext;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Event handler function type
 pub type EventHandler = Arc<
@@ -33,10 +34,52 @@ pub struct StratumStats {
     pub disconnects: u64,
 }
 
+/// Server-initiated `mining.ping` for idle sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionKeepaliveConfig {
+    /// Ping a session after this long without an inbound message.
+    pub interval: Duration,
+    /// Disconnect if nothing comes back within this long after the ping.
+    pub timeout: Duration,
+}
+
+/// Per-instance session limit (`max_workers`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerCapacity {
+    /// Sessions (TCP and WebSocket together) allowed at once.
+    pub max_workers: usize,
+    /// `host:port` sent in `client.reconnect` to miners turned away; `None` just closes the socket.
+    pub overflow_pool: Option<(String, u16)>,
+}
+
 /// Configuration for the Stratum listener
 pub struct StratumListenerConfig {
+    /// Instance label for per-instance metrics (`session_panics_total`).
+    pub instance_id: String,
     pub handler_map: Arc<HashMap<String, EventHandler>>,
     pub on_connect: Arc<dyn Fn(Arc<StratumContext>) + Send + Sync>,
     pub on_disconnect: Arc<dyn Fn(Arc<StratumContext>) + Send + Sync>,
     pub port: String,
+    /// Optional IP allowlist/denylist applied right after `accept()`.
+    pub ip_access: Option<Arc<crate::ip_filter::IpAccessList>>,
+    /// TCP keepalive applied to every accepted socket (`None` leaves OS defaults).
+    pub tcp_keepalive: Option<crate::net_utils::TcpKeepaliveConfig>,
+    /// Largest JSON-RPC batch (`[{...},{...}]` line) accepted from a client.
+    pub max_batch_size: usize,
+    /// Close a session after this many consecutive unparsable lines (`None` = never).
+    pub max_parse_errors: Option<u32>,
+    /// Application-level `mining.ping` keepalive (`None` disables it).
+    pub session_keepalive: Option<SessionKeepaliveConfig>,
+    /// Close authorized sessions with no accepted share for this long (`None` disables it).
+    pub idle_share_timeout: Option<Duration>,
+    /// Bind with `SO_REUSEPORT` so a replacement process can listen on the same port.
+    pub reuse_port: bool,
+    /// Extra port that accepts the same Stratum protocol over WebSocket text frames.
+    pub ws_port: Option<String>,
+    /// Reject new sessions beyond this many (`None` = unlimited).
+    pub capacity: Option<WorkerCapacity>,
+    /// Sessions open across all instances; each admitted session counts itself here.
+    pub total_workers: crate::health_check::GlobalWorkerCount,
+    /// Slow the accept loop down during connection floods (`None` disables it).
+    pub accept_flood: Option<super::AcceptFloodConfig>,
 }
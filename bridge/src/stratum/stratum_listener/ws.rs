@@ -0,0 +1,265 @@
+//! WebSocket transport: each text frame carries one JSON-RPC line, so the session above it is the
+//! same `\n`-delimited Stratum code used for TCP.
+
+use crate::ip_filter::IpAccessList;
+use crate::net_utils::TcpKeepaliveConfig;
+use futures_util::{Sink, Stream};
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info};
+
+use super::accept_flood::ConnectionFloodDetector;
+use super::listen::{
+    SessionStarter, apply_keepalive, pace_accepts, peer_admitted, reject_denied_peer,
+};
+
+/// A client that has not finished the WebSocket upgrade by then is dropped.
+const WS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Byte-stream view of a WebSocket: inbound text/binary frames are read back with a trailing `\n`,
+/// and every `\n`-terminated line written is sent as one text frame.
+pub(crate) struct WsLineStream<S> {
+    inner: WebSocketStream<S>,
+    read_buf: Vec<u8>,
+    read_pos: usize,
+    write_buf: Vec<u8>,
+}
+
+impl<S> WsLineStream<S> {
+    pub(crate) fn new(inner: WebSocketStream<S>) -> Self {
+        Self {
+            inner,
+            read_buf: Vec::new(),
+            read_pos: 0,
+            write_buf: Vec::new(),
+        }
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> WsLineStream<S> {
+    /// Queue one text frame per complete line in `write_buf`.
+    fn poll_send_lines(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while let Some(end) = self.write_buf.iter().position(|b| *b == b'\n') {
+            ready!(Pin::new(&mut self.inner).poll_ready(cx)).map_err(io::Error::other)?;
+            let line: Vec<u8> = self.write_buf.drain(..=end).collect();
+            let text = String::from_utf8_lossy(&line[..end]).into_owned();
+            Pin::new(&mut self.inner)
+                .start_send(Message::Text(text))
+                .map_err(io::Error::other)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for WsLineStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.read_pos < this.read_buf.len() {
+                let n = buf.remaining().min(this.read_buf.len() - this.read_pos);
+                buf.put_slice(&this.read_buf[this.read_pos..this.read_pos + n]);
+                this.read_pos += n;
+                return Poll::Ready(Ok(()));
+            }
+            let data = match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(Message::Text(text))) => text.into_bytes(),
+                Some(Ok(Message::Binary(data))) => data,
+                // Close frame or end of stream reads as EOF
+                Some(Ok(Message::Close(_))) | None => return Poll::Ready(Ok(())),
+                // Ping/pong are answered by tungstenite itself
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Poll::Ready(Err(io::Error::other(e))),
+            };
+            this.read_buf = data;
+            this.read_buf.push(b'\n');
+            this.read_pos = 0;
+        }
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite for WsLineStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.write_buf.extend_from_slice(buf);
+        // Frames that cannot be queued yet go out on the next write or flush
+        if let Poll::Ready(Err(e)) = this.poll_send_lines(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_lines(cx))?;
+        Pin::new(&mut this.inner)
+            .poll_flush(cx)
+            .map_err(io::Error::other)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        Pin::new(&mut self.get_mut().inner)
+            .poll_close(cx)
+            .map_err(io::Error::other)
+    }
+}
+
+/// Accept loop for `ws_port`: WebSocket upgrade, then the same session as the TCP listener. Accepts
+/// count toward the same `max_global_accepts_per_second` limit as the TCP port.
+pub(super) async fn serve_ws(
+    listener: TcpListener,
+    starter: SessionStarter,
+    ip_access: Option<Arc<IpAccessList>>,
+    tcp_keepalive: Option<TcpKeepaliveConfig>,
+    flood_detector: Option<Arc<parking_lot::Mutex<ConnectionFloodDetector>>>,
+    mut shutdown_rx: Option<watch::Receiver<bool>>,
+) {
+    loop {
+        let accepted = match shutdown_rx {
+            Some(ref mut rx) => tokio::select! {
+                _ = rx.wait_for(|v| *v) => break,
+                result = listener.accept() => result,
+            },
+            None => listener.accept().await,
+        };
+        let (stream, addr) = match accepted {
+            Ok(pair) => pair,
+            Err(e) => {
+                debug!("[WEBSOCKET] failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let admitted = peer_admitted(ip_access.as_deref(), &addr);
+        if admitted {
+            apply_keepalive(tcp_keepalive.as_ref(), &stream);
+        }
+
+        // Upgrade off the accept loop so a slow handshake does not hold up other clients
+        let session_starter = starter.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(
+                WS_HANDSHAKE_TIMEOUT,
+                tokio_tungstenite::accept_async(stream),
+            )
+            .await
+            {
+                // Denied peers get the same error 25 as on the TCP port, as a text frame
+                Ok(Ok(ws)) if !admitted => reject_denied_peer(WsLineStream::new(ws), addr),
+                Ok(Ok(ws)) => {
+                    debug!("[WEBSOCKET] upgraded connection from {}", addr);
+                    session_starter.start(WsLineStream::new(ws), addr);
+                }
+                Ok(Err(e)) => debug!("[WEBSOCKET] handshake with {} failed: {}", addr, e),
+                Err(_) => debug!(
+                    "[WEBSOCKET] handshake with {} timed out after {:?}",
+                    addr, WS_HANDSHAKE_TIMEOUT
+                ),
+            }
+        });
+        pace_accepts(flood_detector.as_deref(), &starter.instance_id).await;
+    }
+    info!("stopping WebSocket listener due to server shutdown");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    #[tokio::test]
+    async fn frames_map_to_lines_both_ways() {
+        let (stream, client_tcp) = crate::test_support::socket_pair().await;
+        let addr = stream.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            WsLineStream::new(tokio_tungstenite::accept_async(stream).await.unwrap())
+        });
+        let (mut client, _) = tokio_tungstenite::client_async(format!("ws://{addr}/"), client_tcp)
+            .await
+            .unwrap();
+        let server = server.await.unwrap();
+        let (read, mut write) = tokio::io::split(server);
+
+        client
+            .send(Message::Text(
+                r#"{"id":1,"method":"mining.subscribe","params":[]}"#.to_string(),
+            ))
+            .await
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(read).read_line(&mut line).await.unwrap();
+        assert_eq!(
+            line,
+            "{\"id\":1,\"method\":\"mining.subscribe\",\"params\":[]}\n"
+        );
+
+        write.write_all(b"{\"id\":1}\n{\"id\":2}\n").await.unwrap();
+        write.flush().await.unwrap();
+        assert_eq!(
+            client.next().await.unwrap().unwrap(),
+            Message::Text("{\"id\":1}".to_string())
+        );
+        assert_eq!(
+            client.next().await.unwrap().unwrap(),
+            Message::Text("{\"id\":2}".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn denied_peers_get_error_25_as_a_frame() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (disconnect_tx, _disconnect_rx) = tokio::sync::mpsc::unbounded_channel();
+        let starter = SessionStarter {
+            instance_id: "ws-deny-test".to_string(),
+            handler_map: Arc::new(std::collections::HashMap::new()),
+            on_connect: Arc::new(|_| {}),
+            max_batch_size: 1,
+            max_parse_errors: None,
+            session_keepalive: None,
+            idle_share_timeout: None,
+            disconnect_tx,
+            capacity: None,
+            active_sessions: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            total_workers: crate::health_check::GlobalWorkerCount::default(),
+        };
+        let acl = IpAccessList::parse(&[], &["127.0.0.1".to_string()]).unwrap();
+        tokio::spawn(serve_ws(
+            listener,
+            starter,
+            Some(Arc::new(acl)),
+            None,
+            None,
+            None,
+        ));
+
+        let tcp = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let (mut client, _) = tokio_tungstenite::client_async(format!("ws://{addr}/"), tcp)
+            .await
+            .unwrap();
+        let Message::Text(reply) = client.next().await.unwrap().unwrap() else {
+            panic!("expected a text frame");
+        };
+        let reply: serde_json::Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(
+            reply["error"],
+            serde_json::json!([25, "Not subscribed", "Connection not allowed from your IP"])
+        );
+    }
+}
@@ -1,18 +1,51 @@
 use crate::{
-    BridgeError,
-    client_handler::ClientHandler,
-    default_client::{default_handlers, handle_authorize, handle_subscribe},
+    block_webhook::BlockWebhook,
+    client_handler::{ClientHandler, ExtranonceRange, JobTemplate},
+    control::ControlMessage,
+    default_client::{
+        default_handlers, handle_authorize, handle_extranonce_subscribe, handle_multi_version,
+        handle_subscribe, handle_suggest_target, reject_out_of_order,
+    },
+    ip_filter::IpAccessList,
     jsonrpc_event::JsonRpcEvent,
     kaspaapi::KaspaApi,
-    share_handler::{KaspaApiTrait, ShareHandler},
-    stratum_context::StratumContext,
-    stratum_listener::{StratumListener, StratumListenerConfig},
+    miner_detect::MinerDetector,
+    net_utils::TcpKeepaliveConfig,
+    pool_events::PoolEventHandler,
+    share_handler::{KaspaApiTrait, SHARE_QUEUE_CAPACITY, ShareAuditLog, ShareHandler, ShareQueue},
+    stratum_context::{StratumContext, SubmitRateDecision, SubmitRateWarning},
+    stratum_listener::{
+        AcceptFloodConfig, SessionKeepaliveConfig, StratumListener, StratumListenerConfig,
+        WorkerCapacity,
+    },
+    validation::WorkerNamePolicy,
+    wallet_rotator::WalletRotator,
+    worker_auth::WorkerAuthenticator,
+    worker_credentials::WorkerCredentialStore,
 };
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::watch;
+use tokio::sync::{mpsc, watch};
 use tracing::{debug, info, warn};
 
+/// Default for `max_batch_size` when the config leaves it unset.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
+/// Default for `max_parse_errors` when the config leaves it unset.
+pub const DEFAULT_MAX_PARSE_ERRORS: u32 = 5;
+
+/// Default for `ntime_max_delta_secs` when the config leaves it unset.
+pub const DEFAULT_NTIME_MAX_DELTA_SECS: i64 = 7200;
+
+/// Default for `block_submit_retries` when the config leaves it unset.
+pub const DEFAULT_BLOCK_SUBMIT_RETRIES: u32 = 2;
+
+/// Default for `block_submit_retry_delay_ms` when the config leaves it unset.
+pub const DEFAULT_BLOCK_SUBMIT_RETRY_DELAY_MS: u64 = 100;
+
+/// How often `ks_session_active_duration_seconds` is recomputed.
+const SESSION_AGE_REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+
 pub struct BridgeConfig {
     pub instance_id: String, // Instance identifier for logging (e.g., "Instance 1", "Instance 2")
     pub stratum_port: String,
@@ -28,7 +61,82 @@ pub struct BridgeConfig {
     pub var_diff_stats: bool,
     pub extranonce_size: u8,
     pub pow2_clamp: bool,
+    /// Up/down multiplier limits for each VarDiff retarget.
+    pub var_diff_steps: crate::share_handler::VarDiffState,
     pub coinbase_tag_suffix: Option<String>,
+    pub skip_address_validation: bool,
+    pub allowed_ips: Vec<String>,
+    pub denied_ips: Vec<String>,
+    pub worker_credentials: Option<Arc<WorkerCredentialStore>>,
+    /// Shared `mining.submit` audit trail (`share_audit_log`).
+    pub share_audit: Option<Arc<ShareAuditLog>>,
+    /// Saved per-worker VarDiff difficulties (`var_diff_state_file`), shared by all instances.
+    pub vardiff_store: Option<Arc<crate::share_handler::VarDiffStore>>,
+    /// Round-robin payout wallets (`kaspad_wallets`), shared by all instances.
+    pub wallet_rotator: Option<Arc<WalletRotator>>,
+    /// Session total shared by all instances (`None` counts this instance on its own).
+    pub total_workers: Option<crate::health_check::GlobalWorkerCount>,
+    /// Authorized sessions allowed per wallet address (`None` = unlimited).
+    pub max_sessions_per_wallet: Option<u32>,
+    /// Distinct worker names allowed per wallet address (`None` = unlimited).
+    pub max_workers_per_wallet: Option<u32>,
+    /// Worker name limits enforced in `mining.authorize`.
+    pub worker_name_policy: WorkerNamePolicy,
+    pub tcp_keepalive: Option<TcpKeepaliveConfig>,
+    /// Operator control messages (`POST /api/v1/instances/{id}/reconnect`); `None` disables the channel.
+    pub control_rx: Option<mpsc::Receiver<ControlMessage>>,
+    /// Largest JSON-RPC batch accepted on one line (see `GlobalConfig::max_batch_size`).
+    pub max_batch_size: usize,
+    /// Consecutive invalid lines before a session is closed (`None` = never).
+    pub max_parse_errors: Option<u32>,
+    /// Custom miner classification; unrecognized user-agents fall back to `DefaultMinerDetector`.
+    pub miner_detector: Option<Arc<dyn MinerDetector>>,
+    /// Server `mining.ping` for idle sessions (`None` disables it).
+    pub session_keepalive: Option<SessionKeepaliveConfig>,
+    /// Disconnect authorized sessions that submit no accepted share for this long (`None` = off).
+    pub idle_share_timeout: Option<Duration>,
+    /// Extranonce values reserved for this instance (`None` shares the process-wide counter).
+    pub extranonce_range: Option<ExtranonceRange>,
+    /// Set `SO_REUSEPORT` on the Stratum socket (see `GlobalConfig::use_reuseport`).
+    pub reuse_port: bool,
+    /// Also serve Stratum over WebSocket on this port (`None` = TCP only).
+    pub ws_port: Option<String>,
+    /// `submit_block` RPCs in flight at once on this instance (at least 1).
+    pub max_submit_concurrency: u8,
+    /// Nonce bits miners may roll through extranonce2 (64 = no limit).
+    pub max_nonce_range_bits: u8,
+    /// `mining.submit` calls allowed per session per second (`None` = unlimited).
+    pub max_submits_per_second: Option<u32>,
+    /// Accept-loop backpressure during connection floods (`None` = off).
+    pub accept_flood: Option<AcceptFloodConfig>,
+    /// Largest gap between a share's `ntime` and the clock (`None` skips the check).
+    pub ntime_max_delta_secs: Option<i64>,
+    /// List `mining.get_transactions` in subscribe results that carry a subscription list.
+    pub advertise_transactions_support: bool,
+    /// Negotiate a version mask on `mining.multi_version` (see `GlobalConfig::version_rolling`).
+    pub version_rolling: bool,
+    /// Sent as `mining.set_pool` after authorization (`None` sends nothing).
+    pub pool_name: Option<String>,
+    /// Embedder callbacks for worker lifecycle events (`None` = `NoopPoolEventHandler`).
+    pub pool_event_handler: Option<Arc<dyn PoolEventHandler>>,
+    /// Warn when a `submit_block` RPC to kaspad takes longer than this.
+    pub kaspad_rtt_warn_threshold: Duration,
+    /// Extra `submit_block` attempts after an RPC error.
+    pub block_submit_retries: u32,
+    /// Pause between block submission attempts.
+    pub block_submit_retry_delay: Duration,
+    /// Announce found blocks to a chat webhook (`None` = off).
+    pub block_webhook: Option<Arc<BlockWebhook>>,
+    /// Most concurrent sessions on this instance (`None` = unlimited).
+    pub max_workers: Option<u32>,
+    /// Where miners beyond `max_workers` are sent with `client.reconnect` (`None` just closes them).
+    pub overflow_pool: Option<(String, u16)>,
+    /// Custom check for `mining.authorize` (`None` = `AllowAllAuthenticator`).
+    pub worker_authenticator: Option<Arc<dyn WorkerAuthenticator>>,
+    /// Send a fresh job after every VarDiff retarget instead of waiting for a new template.
+    pub clean_jobs_on_retarget: bool,
+    /// `mining.notify` lines queued per session before older refreshes are dropped (at least 1).
+    pub notify_queue_depth: usize,
 }
 
 /// Start block template listener with concrete KaspaApi
@@ -41,11 +149,17 @@ pub async fn start_block_template_listener_with_api(
     let client_handler_cb = Arc::clone(&client_handler);
     let kaspa_api_cb = Arc::clone(&kaspa_api);
 
-    let block_cb = move || {
+    let block_cb = move |new_block: bool| {
         let client_handler = Arc::clone(&client_handler_cb);
         let kaspa_api = Arc::clone(&kaspa_api_cb);
         tokio::spawn(async move {
-            client_handler.new_block_available(kaspa_api).await;
+            if new_block {
+                client_handler
+                    .prioritize_job(JobTemplate::new_block(), kaspa_api)
+                    .await;
+            } else {
+                client_handler.new_block_available(kaspa_api).await;
+            }
         });
     };
 
@@ -76,47 +190,137 @@ pub async fn listen_and_serve_with_shutdown<T: KaspaApiTrait + Send + Sync + 'st
     listen_and_serve_impl(config, kaspa_api, concrete_kaspa_api, Some(shutdown_rx)).await
 }
 
-async fn listen_and_serve_impl<T: KaspaApiTrait + Send + Sync + 'static>(
-    config: BridgeConfig,
-    kaspa_api: Arc<T>,
-    concrete_kaspa_api: Option<Arc<KaspaApi>>,
-    shutdown_rx: Option<watch::Receiver<bool>>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Calculate min diff with pow2 clamp if needed
-    let mut min_diff = config.min_share_diff as f64;
-    if config.pow2_clamp && min_diff > 0.0 {
+/// WARN when a session first hits `max_submits_per_second`, and again at 10x the limit.
+fn log_submit_rate_warning(ctx: &StratumContext, warn: Option<SubmitRateWarning>) {
+    let Some(warning) = warn else {
+        return;
+    };
+    let level = match warning {
+        SubmitRateWarning::Limited => "exceeds",
+        SubmitRateWarning::TenTimesLimit => "is at 10x",
+    };
+    warn!(
+        "{} ({}) {} max_submits_per_second; extra shares are rejected without reaching kaspad",
+        ctx.remote_addr,
+        ctx.effective_worker_name(),
+        level
+    );
+}
+
+/// Starting difficulty for `min_share_diff`: rounded down to a power of 2 (capped at
+/// `pow2_clamp_max`) with `pow2_clamp`, and 4 when unset.
+fn resolve_min_diff(min_share_diff: u32, pow2_clamp: bool, pow2_clamp_max: Option<f64>) -> f64 {
+    let mut min_diff = min_share_diff as f64;
+    if pow2_clamp && min_diff > 0.0 {
         min_diff = 2_f64.powi((min_diff.log2().floor()) as i32);
+        if let Some(max) = pow2_clamp_max {
+            min_diff = min_diff.min(max);
+        }
     }
     if min_diff == 0.0 {
         min_diff = 4.0;
     }
+    min_diff
+}
+
+/// Shutdown signal for one instance: set by `ControlMessage::Shutdown` or when the process-wide
+/// `shutdown_rx` (if any) fires.
+fn instance_shutdown_channel(
+    shutdown_rx: Option<watch::Receiver<bool>>,
+) -> (Arc<watch::Sender<bool>>, watch::Receiver<bool>) {
+    let (tx, rx) = watch::channel(false);
+    let tx = Arc::new(tx);
+    if let Some(mut shutdown_rx) = shutdown_rx {
+        let tx = Arc::clone(&tx);
+        tokio::spawn(async move {
+            if shutdown_rx.wait_for(|stop| *stop).await.is_ok() {
+                let _ = tx.send(true);
+            }
+        });
+    }
+    (tx, rx)
+}
+
+async fn listen_and_serve_impl<T: KaspaApiTrait + Send + Sync + 'static>(
+    mut config: BridgeConfig,
+    kaspa_api: Arc<T>,
+    concrete_kaspa_api: Option<Arc<KaspaApi>>,
+    shutdown_rx: Option<watch::Receiver<bool>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let min_diff = resolve_min_diff(
+        config.min_share_diff,
+        config.pow2_clamp,
+        config.var_diff_steps.pow2_clamp_max,
+    );
+    let (instance_shutdown_tx, shutdown_rx) = instance_shutdown_channel(shutdown_rx);
+    let shutdown_rx = Some(shutdown_rx);
 
     // Extranonce size is now auto-detected per client based on miner type
     // We still need to pass a value to ClientHandler::new() for backward compatibility,
     // but it will be ignored as extranonce is assigned per-client in handle_subscribe
     // Default to 2 (for IceRiver/BzMiner/Goldshell) as that's the most common case
     let extranonce_size = if config.extranonce_size > 0 {
-        config.extranonce_size.min(3) as i8
+        config.extranonce_size.min(4) as i8
     } else {
         2 // Default to 2, will be auto-detected per client anyway
     };
 
     // Create share handler with instance identifier
     let instance_id = config.instance_id.clone();
-    let share_handler = Arc::new(ShareHandler::new(instance_id.clone()));
+    let share_handler = Arc::new(
+        ShareHandler::new(instance_id.clone())
+            .with_ntime_max_delta_secs(config.ntime_max_delta_secs)
+            .with_var_diff_steps(config.var_diff_steps)
+            .with_pool_event_handler(config.pool_event_handler.clone())
+            .with_kaspad_rtt_warn_threshold(config.kaspad_rtt_warn_threshold)
+            .with_block_submit_retries(config.block_submit_retries, config.block_submit_retry_delay)
+            .with_block_webhook(config.block_webhook.clone())
+            .with_share_audit(config.share_audit.clone())
+            .with_wallet_rotator(config.wallet_rotator.clone()),
+    );
 
     // Create client handler
     // Note: extranonce_size parameter is now only used for backward compatibility
     // Actual extranonce assignment happens per-client in handle_subscribe based on detected miner type
-    let client_handler = Arc::new(ClientHandler::new(
-        Arc::clone(&share_handler),
-        min_diff,
-        extranonce_size,
-        instance_id.clone(),
-    ));
+    let client_handler = Arc::new(
+        ClientHandler::new(
+            Arc::clone(&share_handler),
+            min_diff,
+            extranonce_size,
+            instance_id.clone(),
+        )
+        .with_skip_address_validation(config.skip_address_validation)
+        .with_worker_credentials(config.worker_credentials.clone())
+        .with_worker_authenticator(config.worker_authenticator.clone())
+        .with_wallet_limits(
+            config.max_sessions_per_wallet,
+            config.max_workers_per_wallet,
+        )
+        .with_worker_name_policy(config.worker_name_policy.clone())
+        .with_vardiff_store(
+            config
+                .var_diff
+                .then(|| config.vardiff_store.clone())
+                .flatten(),
+        )
+        .with_miner_detector(config.miner_detector.clone())
+        .with_extranonce_range(config.extranonce_range)
+        .with_max_nonce_range_bits(config.max_nonce_range_bits)
+        .with_max_submits_per_second(config.max_submits_per_second)
+        .with_advertise_transactions_support(config.advertise_transactions_support)
+        .with_pool_name(config.pool_name.clone())
+        .with_pool_event_handler(config.pool_event_handler.clone())
+        .with_clean_jobs_on_retarget(config.clean_jobs_on_retarget)
+        .with_notify_queue_depth(config.notify_queue_depth),
+    );
 
     let shutdown_rx_for_bg = shutdown_rx.clone();
 
+    let ip_access = IpAccessList::parse(&config.allowed_ips, &config.denied_ips).map_err(|e| {
+        Box::new(std::io::Error::other(e.to_string())) as Box<dyn std::error::Error + Send + Sync>
+    })?;
+    let ip_access = (!ip_access.is_empty()).then(|| Arc::new(ip_access));
+
     // Setup default handlers
     let mut handlers = default_handlers();
 
@@ -141,6 +345,28 @@ async fn listen_and_serve_impl<T: KaspaApiTrait + Send + Sync + 'static>(
     };
     handlers.insert("mining.subscribe".to_string(), subscribe_handler);
 
+    // Track extranonce.subscribe opt-ins per instance
+    let extranonce_subscribe_handler = {
+        let client_handler = Arc::clone(&client_handler);
+        Arc::new(move |ctx: Arc<StratumContext>, event: JsonRpcEvent| {
+            let client_handler = Arc::clone(&client_handler);
+            Box::pin(
+                async move { handle_extranonce_subscribe(ctx, event, Some(client_handler)).await },
+            )
+                as std::pin::Pin<
+                    Box<
+                        dyn std::future::Future<
+                                Output = Result<(), Box<dyn std::error::Error + Send + Sync>>,
+                            > + Send,
+                    >,
+                >
+        }) as crate::stratum_listener::EventHandler
+    };
+    handlers.insert(
+        "mining.extranonce.subscribe".to_string(),
+        extranonce_subscribe_handler,
+    );
+
     // Override authorize handler to send immediate job (critical for IceRiver KS2L)
     let authorize_handler = {
         let client_handler = Arc::clone(&client_handler);
@@ -170,19 +396,81 @@ async fn listen_and_serve_impl<T: KaspaApiTrait + Send + Sync + 'static>(
     };
     handlers.insert("mining.authorize".to_string(), authorize_handler);
 
-    // Override submit handler
+    // Miner-suggested difficulty as a hex target (older firmware)
+    let suggest_target_handler = {
+        let client_handler = Arc::clone(&client_handler);
+        Arc::new(move |ctx: Arc<StratumContext>, event: JsonRpcEvent| {
+            let client_handler = Arc::clone(&client_handler);
+            Box::pin(async move { handle_suggest_target(ctx, event, Some(client_handler)).await })
+                as std::pin::Pin<
+                    Box<
+                        dyn std::future::Future<
+                                Output = Result<(), Box<dyn std::error::Error + Send + Sync>>,
+                            > + Send,
+                    >,
+                >
+        }) as crate::stratum_listener::EventHandler
+    };
+    handlers.insert("mining.suggest_target".to_string(), suggest_target_handler);
+
+    // Version mask negotiation for Bitmain firmware; otherwise the method stays unknown
+    if config.version_rolling {
+        handlers.insert(
+            "mining.multi_version".to_string(),
+            Arc::new(|ctx: Arc<StratumContext>, event: JsonRpcEvent| {
+                Box::pin(async move { handle_multi_version(ctx, event).await })
+                    as std::pin::Pin<
+                        Box<
+                            dyn std::future::Future<
+                                    Output = Result<(), Box<dyn std::error::Error + Send + Sync>>,
+                                > + Send,
+                        >,
+                    >
+            }) as crate::stratum_listener::EventHandler,
+        );
+    }
+
+    // Override submit handler: shares go through the instance queue so the read loop
+    // never waits on a kaspad RPC (batched shares are handled in place so their replies
+    // make the batch's response array)
+    let share_queue = ShareQueue::spawn(
+        Arc::clone(&share_handler),
+        Arc::clone(&kaspa_api) as Arc<dyn KaspaApiTrait + Send + Sync>,
+        SHARE_QUEUE_CAPACITY,
+        config.max_submit_concurrency,
+    );
     let submit_handler = {
-        let share_handler = Arc::clone(&share_handler);
-        let kaspa_api = Arc::clone(&kaspa_api);
         Arc::new(move |ctx: Arc<StratumContext>, event: JsonRpcEvent| {
-            let share_handler = Arc::clone(&share_handler);
-            let kaspa_api = Arc::clone(&kaspa_api);
-            let ctx_clone = Arc::clone(&ctx);
+            let share_queue = share_queue.clone();
             Box::pin(async move {
-                share_handler
-                    .handle_submit(ctx_clone, event, kaspa_api)
-                    .await
-                    .map_err(|e| BridgeError::from(e).into_boxed_stratum())
+                if let Err(error) = ctx.check_submit() {
+                    return Err(reject_out_of_order(&ctx, &event, error).await);
+                }
+                match ctx.check_submit_rate() {
+                    SubmitRateDecision::Allow => {}
+                    SubmitRateDecision::Limit { warn } => {
+                        log_submit_rate_warning(&ctx, warn);
+                        if let Some(id) = &event.id {
+                            let _ = ctx.reply_low_diff_share(id).await;
+                        }
+                        return Ok(());
+                    }
+                    SubmitRateDecision::Disconnect => {
+                        warn!(
+                            "disconnecting {} ({}): sustained mining.submit flood above 100x max_submits_per_second",
+                            ctx.remote_addr,
+                            ctx.effective_worker_name()
+                        );
+                        ctx.disconnect();
+                        return Ok(());
+                    }
+                }
+                if ctx.in_batch() {
+                    // Batch replies go out as one array when the batch ends, so answer now
+                    share_queue.submit_now(ctx, event).await;
+                    return Ok(());
+                }
+                share_queue.enqueue(ctx, event).await
             })
                 as std::pin::Pin<
                     Box<
@@ -199,8 +487,23 @@ async fn listen_and_serve_impl<T: KaspaApiTrait + Send + Sync + 'static>(
     // Each client will get its own MiningState (created in stratum_listener)
     // Each client gets its own isolated state
     let listener_config = StratumListenerConfig {
+        instance_id: instance_id.clone(),
         port: config.stratum_port.clone(),
         handler_map: Arc::new(handlers),
+        ip_access,
+        tcp_keepalive: config.tcp_keepalive,
+        max_batch_size: config.max_batch_size.max(1),
+        max_parse_errors: config.max_parse_errors,
+        session_keepalive: config.session_keepalive,
+        idle_share_timeout: config.idle_share_timeout,
+        reuse_port: config.reuse_port,
+        ws_port: config.ws_port.clone(),
+        capacity: config.max_workers.map(|max_workers| WorkerCapacity {
+            max_workers: max_workers as usize,
+            overflow_pool: config.overflow_pool.clone(),
+        }),
+        accept_flood: config.accept_flood,
+        total_workers: config.total_workers.clone().unwrap_or_default(),
         on_connect: Arc::new({
             let client_handler = Arc::clone(&client_handler);
             move |ctx: Arc<StratumContext>| {
@@ -215,6 +518,90 @@ async fn listen_and_serve_impl<T: KaspaApiTrait + Send + Sync + 'static>(
         }),
     };
 
+    // Operator control channel (`/api/v1/instances/{id}/...` on the HTTP API)
+    if let Some(mut control_rx) = config.control_rx.take() {
+        let client_handler = Arc::clone(&client_handler);
+        let kaspa_api = Arc::clone(&kaspa_api);
+        let instance_shutdown_tx = Arc::clone(&instance_shutdown_tx);
+        let mut stop_rx = instance_shutdown_tx.subscribe();
+        let pow2_clamp = config.pow2_clamp;
+        let pow2_clamp_max = config.var_diff_steps.pow2_clamp_max;
+        tokio::spawn(async move {
+            loop {
+                let msg = tokio::select! {
+                    _ = stop_rx.wait_for(|stop| *stop) => break,
+                    msg = control_rx.recv() => match msg {
+                        Some(msg) => msg,
+                        None => break,
+                    },
+                };
+                match msg {
+                    ControlMessage::Reconnect { host, port, delay } => {
+                        client_handler.broadcast_reconnect(&host, port, delay).await;
+                    }
+                    ControlMessage::BroadcastNewJob => {
+                        info!(
+                            "{} Broadcasting a new job on operator request",
+                            client_handler.instance_id()
+                        );
+                        client_handler
+                            .prioritize_job(JobTemplate::new_block(), Arc::clone(&kaspa_api))
+                            .await;
+                    }
+                    ControlMessage::DisconnectWallet(wallet) => {
+                        client_handler.disconnect_wallet(&wallet);
+                    }
+                    ControlMessage::UpdateMinDiff(min_share_diff) => {
+                        let min_diff = resolve_min_diff(min_share_diff, pow2_clamp, pow2_clamp_max);
+                        client_handler.set_min_share_diff(min_diff);
+                        info!(
+                            "{} min_share_diff changed to {} for new sessions",
+                            client_handler.instance_id(),
+                            min_diff
+                        );
+                    }
+                    ControlMessage::Shutdown => {
+                        info!(
+                            "{} Shutting down on operator request",
+                            client_handler.instance_id()
+                        );
+                        let _ = instance_shutdown_tx.send(true);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    // Keep the longest-session-age gauge and saved VarDiff difficulties current between
+    // connects and disconnects
+    {
+        let client_handler = Arc::clone(&client_handler);
+        let mut shutdown_rx = shutdown_rx_for_bg.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SESSION_AGE_REFRESH_INTERVAL);
+            loop {
+                if let Some(ref mut rx) = shutdown_rx {
+                    tokio::select! {
+                        _ = rx.changed() => {
+                            if *rx.borrow() {
+                                break;
+                            }
+                        }
+                        _ = interval.tick() => {
+                            client_handler.record_longest_session_age();
+                            client_handler.record_vardiff_state();
+                        }
+                    }
+                } else {
+                    interval.tick().await;
+                    client_handler.record_longest_session_age();
+                    client_handler.record_vardiff_state();
+                }
+            }
+        });
+    }
+
     // Start vardiff thread if enabled
     if config.var_diff {
         let shares_per_min = if config.shares_per_min > 0 {
@@ -269,14 +656,33 @@ async fn listen_and_serve_impl<T: KaspaApiTrait + Send + Sync + 'static>(
         let client_handler_cb = Arc::clone(&client_handler);
         let kaspa_api_cb = Arc::clone(&kaspa_api);
 
-        let block_cb = move || {
+        let block_cb = move |new_block: bool| {
             let client_handler = Arc::clone(&client_handler_cb);
             let kaspa_api = Arc::clone(&kaspa_api_cb);
             tokio::spawn(async move {
-                client_handler.new_block_available(kaspa_api).await;
+                if new_block {
+                    client_handler
+                        .prioritize_job(JobTemplate::new_block(), kaspa_api)
+                        .await;
+                } else {
+                    client_handler.new_block_available(kaspa_api).await;
+                }
             });
         };
 
+        // Push fresh work as soon as kaspad is back instead of waiting for the next ticker refresh
+        let client_handler_rc = Arc::clone(&client_handler);
+        let kaspa_api_rc = Arc::clone(&kaspa_api);
+        concrete_api.set_reconnect_handler(Arc::new(move || {
+            let client_handler = Arc::clone(&client_handler_rc);
+            let kaspa_api = Arc::clone(&kaspa_api_rc);
+            tokio::spawn(async move {
+                client_handler
+                    .prioritize_job(JobTemplate::new_block(), kaspa_api)
+                    .await;
+            });
+        }));
+
         // Start notification-based listener with ticker fallback
         // Method signature: start_block_template_listener(self: Arc<Self>, ...)
         // Call the method directly on Arc<KaspaApi> (it's an instance method taking Arc<Self>)
@@ -368,8 +774,10 @@ async fn listen_and_serve_impl<T: KaspaApiTrait + Send + Sync + 'static>(
         listener.listen().await
     };
 
-    // Ensure all clients are disconnected when listener stops (shutdown or error)
+    // Ensure all clients are disconnected when listener stops (shutdown or error), and stop this
+    // instance's background tasks
     client_handler.disconnect_all();
+    instance_shutdown_tx.send_replace(true);
 
     listen_result
 }
@@ -0,0 +1,211 @@
+//! Structured parsing of the miner software string sent in `mining.subscribe` params[0].
+//!
+//! Handles the common shapes seen in the field: `BzMiner-v21.3.0`, `GodMiner/1.0`,
+//! `IceRiver-KS2L v2.1.4`, `lolMiner 1.8`, `Goldshell KD-Box Pro`.
+
+use semver::Version;
+
+/// First BzMiner release that handles `mining.set_extranonce`.
+pub const BZMINER_SET_EXTRANONCE_MIN: Version = Version::new(18, 0, 0);
+
+/// Software names (lowercase prefixes) given their own `ks_software_version` label; everything
+/// else is reported as `other` so arbitrary user-agents cannot grow the metric without bound.
+const METRIC_SOFTWARE: &[&str] = &[
+    "bzminer",
+    "iceriver",
+    "godminer",
+    "antminer",
+    "bitmain",
+    "goldshell",
+    "nicehash",
+    "srbminer",
+    "lolminer",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UserAgent {
+    pub software: String,
+    pub version: Option<Version>,
+    /// Model / firmware tokens that are neither the software name nor its version.
+    pub firmware: Option<String>,
+}
+
+/// Parse `v21.3.0`, `1.0`, `2.1.4-beta` into a semver version (missing minor/patch become 0).
+fn parse_version(token: &str) -> Option<Version> {
+    let token = token
+        .strip_prefix('v')
+        .or_else(|| token.strip_prefix('V'))
+        .unwrap_or(token);
+    if !token.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    if let Ok(v) = Version::parse(token) {
+        return Some(v);
+    }
+    let parts = token
+        .split('.')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match parts[..] {
+        [major] => Some(Version::new(major, 0, 0)),
+        [major, minor] => Some(Version::new(major, minor, 0)),
+        [major, minor, patch] => Some(Version::new(major, minor, patch)),
+        _ => None,
+    }
+}
+
+impl UserAgent {
+    pub fn parse(raw: &str) -> Self {
+        let mut tokens = raw.split_whitespace();
+        let Some(first) = tokens.next() else {
+            return Self::default();
+        };
+
+        let mut version = None;
+        let mut firmware = Vec::new();
+        let software = if let Some((name, rest)) = first.split_once('/') {
+            match parse_version(rest) {
+                Some(v) => version = Some(v),
+                None if !rest.is_empty() => firmware.push(rest.to_string()),
+                None => {}
+            }
+            name
+        } else if let Some((name, rest)) = first.split_once('-') {
+            match parse_version(rest) {
+                Some(v) => version = Some(v),
+                None => firmware.push(rest.to_string()),
+            }
+            name
+        } else {
+            first
+        };
+
+        for token in tokens {
+            if version.is_none()
+                && let Some(v) = parse_version(token)
+            {
+                version = Some(v);
+                continue;
+            }
+            firmware.push(token.to_string());
+        }
+
+        Self {
+            software: software.to_string(),
+            version,
+            firmware: (!firmware.is_empty()).then(|| firmware.join(" ")),
+        }
+    }
+
+    /// Lowercase software name and firmware, for keyword matching (the version is left out).
+    pub fn search_text(&self) -> String {
+        match &self.firmware {
+            Some(fw) => format!("{} {}", self.software, fw).to_lowercase(),
+            None => self.software.to_lowercase(),
+        }
+    }
+
+    /// Whether the miner handles `mining.set_extranonce` after authorize.
+    /// BzMiner only does from v18.0.0; unknown versions are assumed current.
+    pub fn supports_set_extranonce(&self) -> bool {
+        if self.software.eq_ignore_ascii_case("bzminer") {
+            return self
+                .version
+                .as_ref()
+                .is_none_or(|v| *v >= BZMINER_SET_EXTRANONCE_MIN);
+        }
+        true
+    }
+
+    /// `(software, version)` label values for the `ks_software_version` metric: a known software
+    /// name or `other`, and `major.minor` only.
+    pub fn metric_labels(&self) -> (String, String) {
+        let lower = self.software.to_lowercase();
+        let software = if lower.is_empty() {
+            "unknown"
+        } else {
+            METRIC_SOFTWARE
+                .iter()
+                .find(|name| lower.starts_with(*name))
+                .copied()
+                .unwrap_or("other")
+        };
+        let version = self.version.as_ref().map_or_else(
+            || "unknown".to_string(),
+            |v| format!("{}.{}", v.major, v.minor),
+        );
+        (software.to_string(), version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_common_miner_strings() {
+        let bz = UserAgent::parse("BzMiner-v21.3.0");
+        assert_eq!(bz.software, "BzMiner");
+        assert_eq!(bz.version, Some(Version::new(21, 3, 0)));
+        assert_eq!(bz.firmware, None);
+
+        let god = UserAgent::parse("GodMiner/1.0");
+        assert_eq!(god.software, "GodMiner");
+        assert_eq!(god.version, Some(Version::new(1, 0, 0)));
+
+        let ice = UserAgent::parse("IceRiver-KS2L v2.1.4");
+        assert_eq!(ice.software, "IceRiver");
+        assert_eq!(ice.version, Some(Version::new(2, 1, 4)));
+        assert_eq!(ice.firmware.as_deref(), Some("KS2L"));
+
+        let gs = UserAgent::parse("Goldshell KD-Box Pro");
+        assert_eq!(gs.software, "Goldshell");
+        assert_eq!(gs.version, None);
+        assert_eq!(gs.firmware.as_deref(), Some("KD-Box Pro"));
+        assert_eq!(gs.search_text(), "goldshell kd-box pro");
+
+        assert_eq!(UserAgent::parse(""), UserAgent::default());
+        assert_eq!(
+            UserAgent::parse("NiceHashStratum/1.0.0").version,
+            Some(Version::new(1, 0, 0))
+        );
+    }
+
+    #[test]
+    fn gates_set_extranonce_on_bzminer_version() {
+        assert!(UserAgent::parse("BzMiner-v21.3.0").supports_set_extranonce());
+        assert!(UserAgent::parse("BzMiner-v18.0.0").supports_set_extranonce());
+        assert!(!UserAgent::parse("BzMiner-v17.5.1").supports_set_extranonce());
+        assert!(UserAgent::parse("BzMiner").supports_set_extranonce());
+        assert!(UserAgent::parse("IceRiver-KS2L v1.0").supports_set_extranonce());
+    }
+
+    #[test]
+    fn metric_labels_are_bounded() {
+        let labels = |raw: &str| {
+            let (software, version) = UserAgent::parse(raw).metric_labels();
+            (software, version)
+        };
+        assert_eq!(
+            labels("BzMiner-v21.3.0"),
+            ("bzminer".to_string(), "21.3".to_string())
+        );
+        assert_eq!(
+            labels("SRBMiner-MULTI/2.6.7"),
+            ("srbminer".to_string(), "2.6".to_string())
+        );
+        assert_eq!(
+            labels("IceRiverMiner-v1.1"),
+            ("iceriver".to_string(), "1.1".to_string())
+        );
+        assert_eq!(
+            labels("my-custom-rig/0.0.1-build.4711"),
+            ("other".to_string(), "0.0".to_string())
+        );
+        assert_eq!(
+            labels("Goldshell KD-Box Pro"),
+            ("goldshell".to_string(), "unknown".to_string())
+        );
+        assert_eq!(labels(""), ("unknown".to_string(), "unknown".to_string()));
+    }
+}
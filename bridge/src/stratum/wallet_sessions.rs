@@ -0,0 +1,202 @@
+//! Per-wallet limits checked at `mining.authorize`: concurrent sessions (`max_sessions_per_wallet`)
+//! stop one address from opening thousands of connections on a public pool, and distinct worker names
+//! (`max_workers_per_wallet`) catch miners that register a new worker on every reconnect. Each
+//! authorized session holds a [`WalletSessionGuard`] that frees its slot when the session ends.
+
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Default for `max_workers_per_wallet` when the config leaves it unset.
+pub const DEFAULT_MAX_WORKERS_PER_WALLET: u32 = 1000;
+
+/// Why a wallet was refused another session; each variant carries the current count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletLimitExceeded {
+    /// `max_sessions_per_wallet` concurrent sessions are already open.
+    Sessions(u32),
+    /// `max_workers_per_wallet` distinct worker names are already connected.
+    Workers(u32),
+}
+
+/// Active authorized sessions and worker names per wallet address, shared by every session of an
+/// instance.
+#[derive(Debug)]
+pub struct WalletSessionLimiter {
+    max_sessions: Option<u32>,
+    max_workers: Option<u32>,
+    sessions: Arc<DashMap<String, AtomicU32>>,
+    /// Sessions per worker name, per wallet.
+    workers: Arc<DashMap<String, HashMap<String, u32>>>,
+}
+
+impl WalletSessionLimiter {
+    /// `None` for either limit leaves it unchecked.
+    pub fn new(max_sessions: Option<u32>, max_workers: Option<u32>) -> Self {
+        Self {
+            max_sessions,
+            max_workers,
+            sessions: Arc::new(DashMap::new()),
+            workers: Arc::new(DashMap::new()),
+        }
+    }
+
+    pub fn max_sessions(&self) -> Option<u32> {
+        self.max_sessions
+    }
+
+    pub fn max_workers(&self) -> Option<u32> {
+        self.max_workers
+    }
+
+    /// Take a slot for `worker` on `wallet`, or say which limit is already reached. A worker name
+    /// that is already connected does not count as a new worker.
+    pub fn try_acquire(
+        &self,
+        wallet: &str,
+        worker: &str,
+    ) -> Result<WalletSessionGuard, WalletLimitExceeded> {
+        let mut workers = self.workers.entry(wallet.to_string()).or_default();
+        if let Some(max_workers) = self.max_workers
+            && !workers.contains_key(worker)
+            && workers.len() >= max_workers as usize
+        {
+            return Err(WalletLimitExceeded::Workers(workers.len() as u32));
+        }
+
+        let sessions = self
+            .sessions
+            .entry(wallet.to_string())
+            .or_insert_with(|| AtomicU32::new(0));
+        let acquired = sessions.fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+            match self.max_sessions {
+                Some(max) if active >= max => None,
+                _ => Some(active + 1),
+            }
+        });
+        // Release `sessions` before touching `workers` again: both paths lock workers, then sessions
+        drop(sessions);
+        if let Err(active) = acquired {
+            drop(workers);
+            self.workers
+                .remove_if(wallet, |_, workers| workers.is_empty());
+            return Err(WalletLimitExceeded::Sessions(active));
+        }
+        *workers.entry(worker.to_string()).or_insert(0) += 1;
+
+        Ok(WalletSessionGuard {
+            wallet: wallet.to_string(),
+            worker: worker.to_string(),
+            sessions: Arc::clone(&self.sessions),
+            workers: Arc::clone(&self.workers),
+        })
+    }
+
+    /// Authorized sessions currently counted for `wallet`.
+    pub fn active_sessions(&self, wallet: &str) -> u32 {
+        self.sessions
+            .get(wallet)
+            .map_or(0, |count| count.load(Ordering::Acquire))
+    }
+
+    /// Distinct worker names currently connected for `wallet`.
+    pub fn active_workers(&self, wallet: &str) -> u32 {
+        self.workers
+            .get(wallet)
+            .map_or(0, |workers| workers.len() as u32)
+    }
+}
+
+/// One counted session; dropping it frees the slot.
+#[derive(Debug)]
+pub struct WalletSessionGuard {
+    wallet: String,
+    worker: String,
+    sessions: Arc<DashMap<String, AtomicU32>>,
+    workers: Arc<DashMap<String, HashMap<String, u32>>>,
+}
+
+impl WalletSessionGuard {
+    pub fn wallet(&self) -> &str {
+        &self.wallet
+    }
+
+    pub fn worker(&self) -> &str {
+        &self.worker
+    }
+}
+
+impl Drop for WalletSessionGuard {
+    fn drop(&mut self) {
+        // Same order as `try_acquire`: workers, then sessions
+        if let Some(mut workers) = self.workers.get_mut(&self.wallet)
+            && let Some(count) = workers.get_mut(&self.worker)
+        {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                workers.remove(&self.worker);
+            }
+        }
+        self.workers
+            .remove_if(&self.wallet, |_, workers| workers.is_empty());
+
+        if let Some(count) = self.sessions.get(&self.wallet) {
+            count.fetch_sub(1, Ordering::AcqRel);
+        }
+        self.sessions
+            .remove_if(&self.wallet, |_, count| count.load(Ordering::Acquire) == 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_limited_and_released_on_drop() {
+        let limiter = WalletSessionLimiter::new(Some(2), None);
+        let first = limiter.try_acquire("kaspa:a", "rig1").unwrap();
+        let second = limiter.try_acquire("kaspa:a", "rig1").unwrap();
+        assert_eq!(
+            limiter.try_acquire("kaspa:a", "rig1").unwrap_err(),
+            WalletLimitExceeded::Sessions(2)
+        );
+        assert!(
+            limiter.try_acquire("kaspa:b", "rig1").is_ok(),
+            "other wallets unaffected"
+        );
+
+        drop(first);
+        assert_eq!(limiter.active_sessions("kaspa:a"), 1);
+        let third = limiter.try_acquire("kaspa:a", "rig1").unwrap();
+        assert_eq!(limiter.active_sessions("kaspa:a"), 2);
+
+        drop(second);
+        drop(third);
+        assert!(limiter.sessions.is_empty(), "idle wallets are forgotten");
+        assert!(limiter.workers.is_empty());
+    }
+
+    #[test]
+    fn distinct_worker_names_are_limited() {
+        let limiter = WalletSessionLimiter::new(None, Some(2));
+        let rig1 = limiter.try_acquire("kaspa:a", "rig1").unwrap();
+        let _rig2 = limiter.try_acquire("kaspa:a", "rig2").unwrap();
+        let rig1_again = limiter.try_acquire("kaspa:a", "rig1").unwrap();
+        assert_eq!(
+            limiter.try_acquire("kaspa:a", "rig3").unwrap_err(),
+            WalletLimitExceeded::Workers(2)
+        );
+        assert_eq!(limiter.active_workers("kaspa:a"), 2);
+
+        drop(rig1);
+        assert_eq!(
+            limiter.active_workers("kaspa:a"),
+            2,
+            "rig1 still has a session"
+        );
+        drop(rig1_again);
+        assert!(limiter.try_acquire("kaspa:a", "rig3").is_ok());
+    }
+}
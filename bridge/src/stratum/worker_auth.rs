@@ -0,0 +1,300 @@
+//! Pluggable `mining.authorize` check for pools that keep their workers in an external system
+//! (database, Redis, HTTP service). Install a [`WorkerAuthenticator`] via
+//! `BridgeConfig::worker_authenticator`; the default [`AllowAllAuthenticator`] accepts every worker,
+//! and `auth_endpoint` in the config selects the built-in [`HttpAuthenticator`].
+//!
+//! The check runs after address validation and the `worker_credentials` file, inline on the
+//! session task, so an implementation should answer quickly or cache.
+
+use crate::app_config::{ConfigError, GlobalConfig};
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde_json::json;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+/// Default for `auth_timeout_ms`.
+pub const DEFAULT_AUTH_TIMEOUT_MS: u64 = 2000;
+/// Default for `auth_cache_ttl_secs`.
+pub const DEFAULT_AUTH_CACHE_TTL_SECS: u64 = 60;
+/// Distinct `(wallet, worker, password)` answers [`HttpAuthenticator`] remembers.
+const AUTH_CACHE_CAPACITY: usize = 10_000;
+/// Longest backend-supplied denial reason passed on to the miner.
+const MAX_REASON_LEN: usize = 200;
+
+/// Outcome of one authorization check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthResult {
+    Allowed,
+    /// Rejected; the reason is sent to the miner with error 24.
+    Denied(String),
+    /// The backend asks the worker to slow down; rejected without caching.
+    RateLimit,
+}
+
+/// Decides whether a worker may mine. `password` is `params[1]` of `mining.authorize` (empty when
+/// the miner sent none).
+#[async_trait::async_trait]
+pub trait WorkerAuthenticator: Send + Sync {
+    async fn authorize(&self, wallet: &str, worker: &str, password: &str) -> AuthResult;
+}
+
+/// Accepts every worker (the behavior without an authentication backend).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAllAuthenticator;
+
+#[async_trait::async_trait]
+impl WorkerAuthenticator for AllowAllAuthenticator {
+    async fn authorize(&self, _wallet: &str, _worker: &str, _password: &str) -> AuthResult {
+        AuthResult::Allowed
+    }
+}
+
+type CacheKey = (String, String, String);
+
+/// POSTs `{"wallet", "worker", "password"}` as JSON to `endpoint`. A 2xx status allows the worker,
+/// 429 rate-limits it, and any other answer denies it (the response body, if any, is the reason).
+/// Allowed and denied answers are cached for `cache_ttl`. An unreachable or timed-out backend denies
+/// without caching, so workers get in again once it recovers.
+pub struct HttpAuthenticator {
+    endpoint: String,
+    timeout: Duration,
+    cache_ttl: Duration,
+    client: reqwest::Client,
+    cache: Mutex<LruCache<CacheKey, (AuthResult, Instant)>>,
+}
+
+impl HttpAuthenticator {
+    pub fn new(endpoint: impl Into<String>, timeout: Duration, cache_ttl: Duration) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            timeout,
+            cache_ttl,
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+            cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(AUTH_CACHE_CAPACITY).expect("non-zero capacity"),
+            )),
+        }
+    }
+
+    /// Authenticator described by the global config, or `None` when `auth_endpoint` is unset.
+    pub fn from_global(global: &GlobalConfig) -> Result<Option<Self>, ConfigError> {
+        let Some(endpoint) = global
+            .auth_endpoint
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+        else {
+            return Ok(None);
+        };
+        if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+            return Err(ConfigError::InvalidSetting {
+                field: "auth_endpoint",
+                reason: "must be an http:// or https:// URL".to_string(),
+            });
+        }
+        let timeout_ms = global.auth_timeout_ms.unwrap_or(DEFAULT_AUTH_TIMEOUT_MS);
+        if timeout_ms == 0 {
+            return Err(ConfigError::InvalidSetting {
+                field: "auth_timeout_ms",
+                reason: "must be positive".to_string(),
+            });
+        }
+        Ok(Some(Self::new(
+            endpoint,
+            Duration::from_millis(timeout_ms),
+            Duration::from_secs(
+                global
+                    .auth_cache_ttl_secs
+                    .unwrap_or(DEFAULT_AUTH_CACHE_TTL_SECS),
+            ),
+        )))
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn cached(&self, key: &CacheKey) -> Option<AuthResult> {
+        let mut cache = self.cache.lock();
+        match cache.get(key) {
+            Some((result, at)) if at.elapsed() < self.cache_ttl => Some(result.clone()),
+            Some(_) => {
+                cache.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn ask_backend(&self, wallet: &str, worker: &str, password: &str) -> Option<AuthResult> {
+        let body = json!({ "wallet": wallet, "worker": worker, "password": password });
+        let request = self
+            .client
+            .post(&self.endpoint)
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("[AUTH] {} unreachable: {}", self.endpoint, e);
+                return None;
+            }
+        };
+        let status = response.status();
+        if status.is_success() {
+            return Some(AuthResult::Allowed);
+        }
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Some(AuthResult::RateLimit);
+        }
+        if status.is_server_error() {
+            tracing::warn!("[AUTH] {} returned HTTP {}", self.endpoint, status);
+            return None;
+        }
+        let reason = response.text().await.unwrap_or_default();
+        let reason: String = reason.trim().chars().take(MAX_REASON_LEN).collect();
+        Some(AuthResult::Denied(if reason.is_empty() {
+            "Worker not authorized".to_string()
+        } else {
+            reason
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl WorkerAuthenticator for HttpAuthenticator {
+    async fn authorize(&self, wallet: &str, worker: &str, password: &str) -> AuthResult {
+        let key = (wallet.to_string(), worker.to_string(), password.to_string());
+        if let Some(result) = self.cached(&key) {
+            return result;
+        }
+        let Some(result) = self.ask_backend(wallet, worker, password).await else {
+            return AuthResult::Denied("Authentication backend unavailable".to_string());
+        };
+        if result != AuthResult::RateLimit && !self.cache_ttl.is_zero() {
+            self.cache.lock().put(key, (result.clone(), Instant::now()));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Minimal HTTP backend: `secret` is allowed, `slow-down` is rate-limited, anything else is
+    /// denied. Returns its URL and the number of requests it has answered.
+    async fn spawn_backend() -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/auth", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while let Ok(n) = stream.read(&mut buf).await {
+                    request.extend_from_slice(&buf[..n]);
+                    if n == 0 || request.ends_with(b"}") {
+                        break;
+                    }
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                let request = String::from_utf8_lossy(&request);
+                let (status, body) = if request.contains("\"password\":\"secret\"") {
+                    ("200 OK", "")
+                } else if request.contains("\"password\":\"slow-down\"") {
+                    ("429 Too Many Requests", "")
+                } else {
+                    ("403 Forbidden", "unknown worker")
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, hits)
+    }
+
+    #[tokio::test]
+    async fn http_authenticator_maps_statuses_and_caches_answers() {
+        let (url, hits) = spawn_backend().await;
+        let auth = HttpAuthenticator::new(url, Duration::from_secs(2), Duration::from_secs(60));
+
+        assert_eq!(
+            auth.authorize("kaspa:q1", "rig1", "secret").await,
+            AuthResult::Allowed
+        );
+        assert_eq!(
+            auth.authorize("kaspa:q1", "rig1", "secret").await,
+            AuthResult::Allowed
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 1, "second answer is cached");
+
+        assert_eq!(
+            auth.authorize("kaspa:q1", "rig1", "wrong").await,
+            AuthResult::Denied("unknown worker".to_string())
+        );
+        assert_eq!(
+            auth.authorize("kaspa:q1", "rig1", "slow-down").await,
+            AuthResult::RateLimit
+        );
+        assert_eq!(
+            auth.authorize("kaspa:q1", "rig1", "slow-down").await,
+            AuthResult::RateLimit
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 4, "rate limits are not cached");
+    }
+
+    #[tokio::test]
+    async fn unreachable_backend_denies_and_config_is_validated() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let auth = HttpAuthenticator::new(
+            format!("http://127.0.0.1:{port}/auth"),
+            Duration::from_millis(500),
+            Duration::from_secs(60),
+        );
+        assert_eq!(
+            auth.authorize("kaspa:q1", "rig1", "x").await,
+            AuthResult::Denied("Authentication backend unavailable".to_string())
+        );
+
+        assert!(
+            HttpAuthenticator::from_global(&GlobalConfig::default())
+                .unwrap()
+                .is_none()
+        );
+        let global = GlobalConfig {
+            auth_endpoint: Some("ftp://auth.local".to_string()),
+            ..GlobalConfig::default()
+        };
+        assert!(HttpAuthenticator::from_global(&global).is_err());
+        let global = GlobalConfig {
+            auth_endpoint: Some("https://auth.local/check".to_string()),
+            ..GlobalConfig::default()
+        };
+        let auth = HttpAuthenticator::from_global(&global).unwrap().unwrap();
+        assert_eq!(auth.endpoint(), "https://auth.local/check");
+        assert_eq!(
+            auth.timeout(),
+            Duration::from_millis(DEFAULT_AUTH_TIMEOUT_MS)
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Per-wallet password store for private pools (`worker_credentials_file`).
+//!
+//! The file is a YAML map of `wallet: password`. It is read at startup and re-read on `SIGHUP`
+//! (Unix) so operators can add or remove workers without restarting the bridge.
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Why `mining.authorize` was refused by the credential store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialRejection {
+    /// `worker_password_required` is set and the miner sent no password.
+    AuthenticationRequired,
+    /// Wallet not listed while `worker_password_required` is set.
+    UnknownWallet,
+    /// Wallet listed with a different password.
+    WrongPassword,
+}
+
+impl CredentialRejection {
+    pub fn message(&self) -> &'static str {
+        match self {
+            CredentialRejection::AuthenticationRequired => "Authentication required",
+            CredentialRejection::UnknownWallet => "Unauthorized worker",
+            CredentialRejection::WrongPassword => "Invalid password",
+        }
+    }
+}
+
+pub struct WorkerCredentialStore {
+    path: Option<PathBuf>,
+    password_required: bool,
+    entries: RwLock<HashMap<String, String>>,
+}
+
+fn read_credentials_file(path: &Path) -> Result<HashMap<String, String>, anyhow::Error> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read worker credentials file {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    let raw: Option<HashMap<String, String>> = serde_yaml::from_str(&content).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to parse worker credentials file {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    Ok(raw
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(wallet, password)| (wallet.trim().to_string(), password))
+        .collect())
+}
+
+impl WorkerCredentialStore {
+    /// Load the store from `path` (if any). With no file the store only enforces `password_required`.
+    pub fn load(path: Option<PathBuf>, password_required: bool) -> Result<Self, anyhow::Error> {
+        let entries = match path.as_deref() {
+            Some(p) => read_credentials_file(p)?,
+            None => HashMap::new(),
+        };
+        Ok(Self {
+            path,
+            password_required,
+            entries: RwLock::new(entries),
+        })
+    }
+
+    /// Build a store from in-memory entries (tests and embedders).
+    pub fn from_entries(entries: HashMap<String, String>, password_required: bool) -> Self {
+        Self {
+            path: None,
+            password_required,
+            entries: RwLock::new(entries),
+        }
+    }
+
+    /// Re-read the credentials file. On error the previous entries are kept.
+    pub fn reload(&self) -> Result<usize, anyhow::Error> {
+        let Some(path) = self.path.as_deref() else {
+            return Ok(self.entries.read().len());
+        };
+        let entries = read_credentials_file(path)?;
+        let count = entries.len();
+        *self.entries.write() = entries;
+        Ok(count)
+    }
+
+    pub fn password_required(&self) -> bool {
+        self.password_required
+    }
+
+    /// Check a wallet/password pair from `mining.authorize`.
+    pub fn check(&self, wallet: &str, password: Option<&str>) -> Result<(), CredentialRejection> {
+        let password = password.filter(|p| !p.is_empty());
+        if self.password_required && password.is_none() {
+            return Err(CredentialRejection::AuthenticationRequired);
+        }
+        match self.entries.read().get(wallet) {
+            Some(expected) if password == Some(expected.as_str()) => Ok(()),
+            Some(_) => Err(CredentialRejection::WrongPassword),
+            None if self.password_required => Err(CredentialRejection::UnknownWallet),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reload `store` whenever the process receives `SIGHUP`. No-op on non-Unix targets.
+pub fn spawn_sighup_reload(
+    store: std::sync::Arc<WorkerCredentialStore>,
+    mut shutdown_rx: tokio::sync::watch::Receiver<bool>,
+) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut hup = match signal(SignalKind::hangup()) {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to install SIGHUP handler for worker credentials: {}",
+                    e
+                );
+                return;
+            }
+        };
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() { break; }
+                }
+                received = hup.recv() => {
+                    if received.is_none() { break; }
+                    match store.reload() {
+                        Ok(n) => tracing::info!("Reloaded worker credentials ({} wallets)", n),
+                        Err(e) => tracing::warn!("Worker credentials reload failed, keeping previous entries: {}", e),
+                    }
+                }
+            }
+        }
+    });
+
+    #[cfg(not(unix))]
+    {
+        let _ = (store, &mut shutdown_rx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(required: bool) -> WorkerCredentialStore {
+        let mut entries = HashMap::new();
+        entries.insert("kaspa:listed".to_string(), "secret".to_string());
+        WorkerCredentialStore::from_entries(entries, required)
+    }
+
+    #[test]
+    fn open_pool_allows_unlisted_wallets() {
+        let s = store(false);
+        assert_eq!(s.check("kaspa:other", None), Ok(()));
+        assert_eq!(s.check("kaspa:listed", Some("secret")), Ok(()));
+        assert_eq!(
+            s.check("kaspa:listed", Some("nope")),
+            Err(CredentialRejection::WrongPassword)
+        );
+    }
+
+    #[test]
+    fn required_mode_rejects_missing_password_and_unknown_wallet() {
+        let s = store(true);
+        assert_eq!(
+            s.check("kaspa:listed", None),
+            Err(CredentialRejection::AuthenticationRequired)
+        );
+        assert_eq!(
+            s.check("kaspa:listed", Some("")),
+            Err(CredentialRejection::AuthenticationRequired)
+        );
+        assert_eq!(
+            s.check("kaspa:other", Some("x")),
+            Err(CredentialRejection::UnknownWallet)
+        );
+        assert_eq!(s.check("kaspa:listed", Some("secret")), Ok(()));
+    }
+}
@@ -0,0 +1,194 @@
+//! Connected workers across every instance, served by `GET /api/v1/workers`. Sessions add their entry
+//! at `mining.authorize`, bump its counters as shares are judged and remove it on disconnect, so a
+//! listing copies the map instead of walking the Prometheus registry.
+
+use crate::mining_state::MiningState;
+use crate::stratum_context::StratumContext;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// `(instance_id, session id)`; session ids are only unique within an instance.
+pub type SessionId = (String, i32);
+
+/// Registry entry for one authorized session.
+#[derive(Debug)]
+pub struct ConnectedWorker {
+    pub wallet: String,
+    pub name: String,
+    pub instance: usize,
+    /// Live mining state, read for the current difficulty when listing.
+    state: Arc<MiningState>,
+    connected_at: Instant,
+    accepted: u64,
+    rejected: u64,
+    /// Sum of accepted share hash values (same units as `WorkStats::shares_diff`).
+    accepted_hash_value: f64,
+}
+
+/// One row of the `/api/v1/workers` response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkerRow {
+    pub wallet: String,
+    pub name: String,
+    pub instance: usize,
+    pub diff: f64,
+    pub hashrate_gh: f64,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub connected_secs: u64,
+}
+
+/// Filters from the `?instance=` and `?wallet=` query parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkerFilter {
+    pub instance: Option<usize>,
+    pub wallet: Option<String>,
+}
+
+impl WorkerFilter {
+    /// Parse `instance=1&wallet=kaspa:...` (an encoded `%3A` is accepted); unknown or malformed
+    /// parameters are ignored.
+    pub fn from_query(query: &str) -> Self {
+        let mut filter = Self::default();
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some(("instance", v)) => filter.instance = v.parse().ok(),
+                Some(("wallet", v)) if !v.is_empty() => {
+                    filter.wallet = Some(v.replace("%3A", ":").replace("%3a", ":"));
+                }
+                _ => {}
+            }
+        }
+        filter
+    }
+}
+
+/// Instance number from an id like `[Instance 2]`; `0` if it has another shape.
+pub(crate) fn instance_number(instance_id: &str) -> usize {
+    instance_id
+        .trim_start_matches("[Instance ")
+        .trim_end_matches(']')
+        .parse()
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Default)]
+pub struct SharedWorkerRegistry {
+    workers: DashMap<SessionId, ConnectedWorker>,
+}
+
+/// Process-wide registry shared by every instance and the web server.
+pub static WORKER_REGISTRY: Lazy<SharedWorkerRegistry> = Lazy::new(SharedWorkerRegistry::default);
+
+fn session_id(instance_id: &str, ctx: &StratumContext) -> Option<SessionId> {
+    ctx.id().map(|id| (instance_id.to_string(), id))
+}
+
+impl SharedWorkerRegistry {
+    /// Add (or replace) the entry for an authorized session.
+    pub fn register(&self, instance_id: &str, ctx: &StratumContext) {
+        let Some(key) = session_id(instance_id, ctx) else {
+            return;
+        };
+        let wallet = ctx.identity.lock().wallet_addr.clone();
+        self.workers.insert(
+            key,
+            ConnectedWorker {
+                wallet,
+                name: ctx.effective_worker_name(),
+                instance: instance_number(instance_id),
+                state: Arc::clone(&ctx.state),
+                connected_at: ctx.connected_at(),
+                accepted: 0,
+                rejected: 0,
+                accepted_hash_value: 0.0,
+            },
+        );
+    }
+
+    pub fn unregister(&self, instance_id: &str, ctx: &StratumContext) {
+        if let Some(key) = session_id(instance_id, ctx) {
+            self.workers.remove(&key);
+        }
+    }
+
+    pub fn record_accepted(&self, instance_id: &str, ctx: &StratumContext, hash_value: f64) {
+        if let Some(key) = session_id(instance_id, ctx)
+            && let Some(mut worker) = self.workers.get_mut(&key)
+        {
+            worker.accepted += 1;
+            worker.accepted_hash_value += hash_value;
+        }
+    }
+
+    /// Count a share that was turned down (low difficulty, stale or invalid).
+    pub fn record_rejected(&self, instance_id: &str, ctx: &StratumContext) {
+        if let Some(key) = session_id(instance_id, ctx)
+            && let Some(mut worker) = self.workers.get_mut(&key)
+        {
+            worker.rejected += 1;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    /// Workers matching `filter`, sorted by instance, wallet, then worker name.
+    pub fn list(&self, filter: &WorkerFilter) -> Vec<WorkerRow> {
+        let mut rows: Vec<WorkerRow> = self
+            .workers
+            .iter()
+            .filter(|w| filter.instance.is_none_or(|i| w.instance == i))
+            .filter(|w| filter.wallet.as_deref().is_none_or(|a| w.wallet == a))
+            .map(|w| {
+                let elapsed = w.connected_at.elapsed();
+                let secs = elapsed.as_secs_f64();
+                WorkerRow {
+                    wallet: w.wallet.clone(),
+                    name: w.name.clone(),
+                    instance: w.instance,
+                    diff: w.state.stratum_diff().map_or(0.0, |d| d.diff_value),
+                    hashrate_gh: if secs > 0.0 {
+                        w.accepted_hash_value / secs
+                    } else {
+                        0.0
+                    },
+                    accepted: w.accepted,
+                    rejected: w.rejected,
+                    connected_secs: elapsed.as_secs(),
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            (a.instance, &a.wallet, &a.name).cmp(&(b.instance, &b.wallet, &b.name))
+        });
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_filters_and_instance_ids_parse() {
+        assert_eq!(
+            WorkerFilter::from_query("instance=2&wallet=kaspa%3Aabc&x=1"),
+            WorkerFilter {
+                instance: Some(2),
+                wallet: Some("kaspa:abc".to_string()),
+            }
+        );
+        assert_eq!(WorkerFilter::from_query(""), WorkerFilter::default());
+        assert_eq!(instance_number("[Instance 3]"), 3);
+        assert_eq!(instance_number("custom"), 0);
+    }
+}
@@ -0,0 +1,207 @@
+//! Syslog output (`log_to_syslog`): each log line goes to the local syslog socket as an RFC 3164
+//! datagram, `<PRI>timestamp rkstratum[PROCID]: message`. PROCID is `instance-N` for lines tagged
+//! `[Instance N]` and the process id otherwise, so collectors can split a multi-instance bridge.
+
+use crate::app_config::{ConfigError, GlobalConfig};
+
+/// Tag in front of every syslog line.
+const SYSLOG_TAG: &str = "rkstratum";
+/// Facility used when `syslog_facility` is unset.
+pub const DEFAULT_SYSLOG_FACILITY: &str = "daemon";
+
+/// Numeric facility for `name` (`daemon`, `user`, `local0`..`local7`, ...).
+fn facility_code(name: &str) -> Option<u8> {
+    let code = match name {
+        "kern" => 0,
+        "user" => 1,
+        "mail" => 2,
+        "daemon" => 3,
+        "auth" => 4,
+        "syslog" => 5,
+        "lpr" => 6,
+        "news" => 7,
+        "uucp" => 8,
+        "cron" => 9,
+        "authpriv" => 10,
+        "ftp" => 11,
+        _ => {
+            let local = name.strip_prefix("local")?.parse::<u8>().ok()?;
+            if local > 7 {
+                return None;
+            }
+            16 + local
+        }
+    };
+    Some(code)
+}
+
+/// Facility named by `syslog_facility` (default `daemon`); an optional `log_` prefix is accepted.
+pub fn facility_from_global(global: &GlobalConfig) -> Result<u8, ConfigError> {
+    let name = global
+        .syslog_facility
+        .as_deref()
+        .unwrap_or(DEFAULT_SYSLOG_FACILITY)
+        .trim()
+        .to_ascii_lowercase();
+    let bare = name.strip_prefix("log_").unwrap_or(&name);
+    facility_code(bare).ok_or_else(|| ConfigError::InvalidSetting {
+        field: "syslog_facility",
+        reason: format!("'{name}' is not a syslog facility (daemon, user, local0-local7, ...)"),
+    })
+}
+
+fn severity(level: &tracing::Level) -> u8 {
+    match *level {
+        tracing::Level::ERROR => 3,
+        tracing::Level::WARN => 4,
+        tracing::Level::INFO => 6,
+        tracing::Level::DEBUG | tracing::Level::TRACE => 7,
+    }
+}
+
+/// `instance-N` for a line mentioning `[Instance N]`.
+fn instance_procid(message: &str) -> Option<String> {
+    let start = message.find("[Instance ")? + "[Instance ".len();
+    let end = message[start..].find(']')?;
+    let num = message[start..start + end].trim();
+    (!num.is_empty() && num.chars().all(|c| c.is_ascii_digit())).then(|| format!("instance-{num}"))
+}
+
+fn format_line(facility: u8, severity: u8, message: &str) -> String {
+    let procid = instance_procid(message).unwrap_or_else(|| std::process::id().to_string());
+    format!(
+        "<{}>{} {}[{}]: {}",
+        u16::from(facility) * 8 + u16::from(severity),
+        chrono::Local::now().format("%b %e %H:%M:%S"),
+        SYSLOG_TAG,
+        procid,
+        message.trim_end()
+    )
+}
+
+#[cfg(unix)]
+pub use unix::SyslogWriter;
+
+#[cfg(unix)]
+mod unix {
+    use super::{format_line, severity};
+    use std::io;
+    use std::os::unix::net::UnixDatagram;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// Sockets the local syslog daemon listens on (Linux, then macOS).
+    const SYSLOG_SOCKETS: [&str; 2] = ["/dev/log", "/var/run/syslog"];
+
+    /// `MakeWriter` for the syslog layer. Send failures (e.g. syslogd restarting) drop the line.
+    pub struct SyslogWriter {
+        socket: UnixDatagram,
+        facility: u8,
+    }
+
+    impl SyslogWriter {
+        /// Connect to the local syslog socket.
+        pub fn connect(facility: u8) -> io::Result<Self> {
+            let socket = UnixDatagram::unbound()?;
+            let mut last_err = None;
+            for path in SYSLOG_SOCKETS {
+                match socket.connect(path) {
+                    Ok(()) => return Ok(Self::with_socket(socket, facility)),
+                    Err(e) => last_err = Some(e),
+                }
+            }
+            Err(last_err.unwrap_or_else(|| io::Error::from(io::ErrorKind::NotFound)))
+        }
+
+        pub(crate) fn with_socket(socket: UnixDatagram, facility: u8) -> Self {
+            Self { socket, facility }
+        }
+    }
+
+    /// One formatted event, sent as a single datagram when dropped.
+    pub struct SyslogLine<'a> {
+        sink: &'a SyslogWriter,
+        severity: u8,
+        buf: Vec<u8>,
+    }
+
+    impl io::Write for SyslogLine<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.buf.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for SyslogLine<'_> {
+        fn drop(&mut self) {
+            if self.buf.is_empty() {
+                return;
+            }
+            let message = String::from_utf8_lossy(&self.buf);
+            let line = format_line(self.sink.facility, self.severity, &message);
+            let _ = self.sink.socket.send(line.as_bytes());
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for SyslogWriter {
+        type Writer = SyslogLine<'a>;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            SyslogLine {
+                sink: self,
+                severity: severity(&tracing::Level::INFO),
+                buf: Vec::new(),
+            }
+        }
+
+        fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> Self::Writer {
+            SyslogLine {
+                sink: self,
+                severity: severity(meta.level()),
+                buf: Vec::new(),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[test]
+    fn lines_carry_priority_and_instance_procid() {
+        let global = GlobalConfig {
+            syslog_facility: Some("LOG_LOCAL3".to_string()),
+            ..GlobalConfig::default()
+        };
+        assert_eq!(facility_from_global(&global).unwrap(), 19);
+        assert_eq!(facility_from_global(&GlobalConfig::default()).unwrap(), 3);
+        let global = GlobalConfig {
+            syslog_facility: Some("local8".to_string()),
+            ..GlobalConfig::default()
+        };
+        assert!(facility_from_global(&global).is_err());
+
+        let (tx, rx) = std::os::unix::net::UnixDatagram::pair().unwrap();
+        let writer = SyslogWriter::with_socket(tx, 3);
+        let mut line = writer.make_writer();
+        writeln!(line, "[INFO]  RKStratum: [Instance 2] block found").unwrap();
+        drop(line);
+
+        let mut buf = [0u8; 512];
+        let n = rx.recv(&mut buf).unwrap();
+        let received = String::from_utf8_lossy(&buf[..n]);
+        // daemon (3) * 8 + info (6)
+        assert!(received.starts_with("<30>"), "{received}");
+        assert!(
+            received
+                .ends_with(" rkstratum[instance-2]: [INFO]  RKStratum: [Instance 2] block found"),
+            "{received}"
+        );
+    }
+}
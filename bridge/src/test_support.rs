@@ -0,0 +1,31 @@
+//! Fixtures shared by the unit tests.
+
+use crate::mining_state::MiningState;
+use crate::stratum_context::StratumContext;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Connected loopback sockets: `(bridge side, miner side)`.
+pub(crate) async fn socket_pair() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let (accepted, client) = tokio::join!(
+        listener.accept(),
+        TcpStream::connect(listener.local_addr().unwrap())
+    );
+    (accepted.unwrap().0, client.unwrap())
+}
+
+/// Session for `127.0.0.1:12345` over a fresh [`socket_pair`], with the miner's end of the socket.
+pub(crate) async fn test_context() -> (Arc<StratumContext>, TcpStream) {
+    let (stream, client) = socket_pair().await;
+    let (tx, _rx) = mpsc::unbounded_channel();
+    let ctx = StratumContext::new(
+        "127.0.0.1".to_string(),
+        12345,
+        stream,
+        Arc::new(MiningState::new()),
+        tx,
+    );
+    (ctx, client)
+}
@@ -269,6 +269,40 @@ web_dashboard_port: "3031"
     assert_eq!(config.global.web_dashboard_port, ":3031");
 }
 
+#[cfg(test)]
+#[test]
+fn test_config_invalid_port_names_instance() {
+    // Test: Out-of-range ports and non-IP hosts are rejected at load time with the instance index
+    let yaml = r#"
+kaspad_address: "127.0.0.1:16110"
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+  - stratum_port: "999999"
+    min_share_diff: 4096
+"#;
+    let err = BridgeConfig::from_yaml(yaml).unwrap_err().to_string();
+    assert!(err.contains("instance 2:"), "{err}");
+    assert!(err.contains("out of range"), "{err}");
+
+    let yaml = r#"
+stratum_port: "not-an-ip:5555"
+min_share_diff: 8192
+"#;
+    assert!(BridgeConfig::from_yaml(yaml).is_err());
+
+    let yaml = r#"
+instances:
+  - stratum_port: "[::]:5555"
+    min_share_diff: 8192
+  - stratum_port: "0.0.0.0:5556"
+    min_share_diff: 8192
+"#;
+    let config = BridgeConfig::from_yaml(yaml).unwrap();
+    assert_eq!(config.instances[0].stratum_port, "[::]:5555");
+    assert_eq!(config.instances[1].stratum_port, "0.0.0.0:5556");
+}
+
 #[cfg(test)]
 #[test]
 fn test_config_duplicate_ports_error() {
@@ -291,6 +325,558 @@ instances:
     );
 }
 
+#[cfg(test)]
+#[test]
+fn test_config_duplicate_prom_ports_error() {
+    let yaml = r#"
+kaspad_address: "127.0.0.1:16110"
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+    prom_port: ":2114"
+  - stratum_port: ":5556"
+    min_share_diff: 4096
+  - stratum_port: ":5557"
+    min_share_diff: 4096
+    prom_port: "2114"
+"#;
+    let err = BridgeConfig::from_yaml(yaml).unwrap_err().to_string();
+    assert!(
+        err.contains("Duplicate prom_port: :2114 (instances 1 and 3)"),
+        "{err}"
+    );
+
+    // Instances without a prom_port never conflict
+    BridgeConfig::from_yaml(&yaml.replace("    prom_port: \"2114\"\n", "")).unwrap();
+
+    // A wildcard bind collides with the same port on a specific host
+    let err = BridgeConfig::from_yaml(
+        &yaml.replace("prom_port: \"2114\"", "prom_port: \"0.0.0.0:2114\""),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Duplicate prom_port"), "{err}");
+
+    // prom_port may not reuse another instance's stratum or ws port
+    let err = BridgeConfig::from_yaml(&yaml.replace("prom_port: \"2114\"", "prom_port: \"5555\""))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(
+            "prom_port :5555 of instance 3 collides with stratum_port :5555 of instance 1"
+        ),
+        "{err}"
+    );
+    let with_ws = yaml.replace(
+        "    prom_port: \"2114\"\n",
+        "    prom_port: \"2115\"\n    ws_port: \"2115\"\n",
+    );
+    let err = BridgeConfig::from_yaml(&with_ws).unwrap_err().to_string();
+    assert!(
+        err.contains("ws_port :2115 of instance 3 collides with prom_port"),
+        "{err}"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_extranonce_ranges() {
+    use crate::client_handler::ExtranonceRange;
+
+    let yaml = r#"
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+  - stratum_port: ":5556"
+    min_share_diff: 4096
+"#;
+    let ranges = BridgeConfig::from_yaml(yaml)
+        .unwrap()
+        .extranonce_ranges()
+        .unwrap();
+    assert_eq!(
+        ranges,
+        vec![
+            ExtranonceRange {
+                start: 0,
+                end: 0x8000
+            },
+            ExtranonceRange {
+                start: 0x8000,
+                end: 0x10000
+            },
+        ]
+    );
+
+    let overlapping = r#"
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+    extranonce_range_start: 0
+    extranonce_range_end: 40000
+  - stratum_port: ":5556"
+    min_share_diff: 4096
+"#;
+    let err = BridgeConfig::from_yaml(overlapping).unwrap_err();
+    assert!(err.to_string().contains("instances 1 and 2:"), "{err}");
+
+    // 1-byte extranonces split the 256 values instead
+    let one_byte = format!("extranonce_size: 1\n{yaml}");
+    let ranges = BridgeConfig::from_yaml(&one_byte)
+        .unwrap()
+        .extranonce_ranges()
+        .unwrap();
+    assert_eq!(
+        ranges[1],
+        ExtranonceRange {
+            start: 0x80,
+            end: 0x100
+        }
+    );
+    for invalid in ["extranonce_size: 3", "extranonce_size: 8"] {
+        let err = BridgeConfig::from_yaml(&format!("{invalid}\n{yaml}"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("extranonce_size"), "{err}");
+    }
+
+    // A range must fit the instance's extranonce width
+    let too_wide = r#"
+extranonce_size: 1
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+    extranonce_range_start: 0
+    extranonce_range_end: 300
+"#;
+    let err = BridgeConfig::from_yaml(too_wide).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("instance 1: extranonce_range_end 300 does not fit"),
+        "{err}"
+    );
+
+    // 1-byte 0x80 covers the same nonce prefixes as 2-byte 0x8000..=0x80ff
+    let mixed_widths = r#"
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+    extranonce_size: 1
+    extranonce_range_start: 128
+    extranonce_range_end: 128
+  - stratum_port: ":5556"
+    min_share_diff: 4096
+"#;
+    let err = BridgeConfig::from_yaml(mixed_widths).unwrap_err();
+    assert!(err.to_string().contains("overlap"), "{err}");
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_include_instance_files() {
+    // Test: `!include` inlines other files relative to the including file's directory
+    let dir = std::env::temp_dir().join(format!("rkstratum_include_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(dir.join("instances")).unwrap();
+    std::fs::write(
+        dir.join("config.yaml"),
+        r#"
+kaspad_address: "127.0.0.1:16110"
+var_diff: false
+instances:
+  - !include instances/a.yaml
+  - !include instances/b.yaml
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("instances/a.yaml"),
+        "stratum_port: \":5555\"\nmin_share_diff: 8192\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("instances/b.yaml"),
+        "stratum_port: \":5556\"\nmin_share_diff: 4096\nvar_diff: true\n",
+    )
+    .unwrap();
+
+    let config = BridgeConfig::from_yaml_file(&dir.join("config.yaml")).unwrap();
+    assert_eq!(config.global.kaspad_address, "127.0.0.1:16110");
+    assert!(!config.global.var_diff);
+    assert_eq!(config.instances.len(), 2);
+    assert_eq!(config.instances[0].stratum_port, ":5555");
+    assert_eq!(config.instances[0].min_share_diff, 8192);
+    assert_eq!(config.instances[1].stratum_port, ":5556");
+    assert_eq!(config.instances[1].min_share_diff, 4096);
+    assert_eq!(config.instances[1].var_diff, Some(true));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_strict_rejects_unknown_keys() {
+    // Test: strict mode lists every unrecognized key; without it typos are ignored
+    let yaml = r#"
+strict: true
+kaspadd_address: "127.0.0.1:16110"
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+    var_difff: true
+"#;
+    let err = BridgeConfig::from_yaml(yaml).unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "Unknown configuration keys: kaspadd_address, var_difff"
+    );
+    assert!(BridgeConfig::from_yaml(&yaml.replace("strict: true", "strict: false")).is_ok());
+
+    // Every key the bridge writes out is one it accepts back in strict mode
+    let mut config = BridgeConfig::default();
+    config.global.strict = Some(true);
+    let written = config.to_yaml().unwrap();
+    BridgeConfig::from_yaml(&written).unwrap();
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_pow2_clamp_max() {
+    let yaml = r#"
+pow2_clamp: true
+pow2_clamp_max: 67108864
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+  - stratum_port: ":5556"
+    min_share_diff: 8192
+    pow2_clamp_max: 1024
+"#;
+    let config = BridgeConfig::from_yaml(yaml).unwrap();
+    let caps: Vec<Option<f64>> = config
+        .instances
+        .iter()
+        .map(|i| {
+            i.effective_config(&config.global)
+                .var_diff_steps
+                .pow2_clamp_max
+        })
+        .collect();
+    assert_eq!(caps, vec![Some(67108864.0), Some(1024.0)]);
+
+    let err = BridgeConfig::from_yaml(&yaml.replace("1024", "1000"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("pow2_clamp_max"), "{err}");
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_min_share_diff_bounds() {
+    let yaml = r#"
+pow2_clamp: true
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 4
+  - stratum_port: ":5556"
+    min_share_diff: 3000
+    pow2_clamp: false
+"#;
+    BridgeConfig::from_yaml(yaml).unwrap();
+
+    let err = BridgeConfig::from_yaml(&yaml.replace("min_share_diff: 4", "min_share_diff: 0"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("instance 1: 0 is below"), "{err}");
+
+    // A power of 2 is required only while the clamp is on for that instance
+    let err = BridgeConfig::from_yaml(&yaml.replace("    pow2_clamp: false\n", ""))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("instance 2: 3000 is not a power of 2"),
+        "{err}"
+    );
+    let single = "min_share_diff: 3000\nstratum_port: \":5555\"\n";
+    BridgeConfig::from_yaml(single).unwrap();
+    assert!(BridgeConfig::from_yaml(&format!("pow2_clamp: true\n{single}")).is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_block_wait_time_bounds() {
+    let yaml = r#"
+block_wait_time: 200
+instances:
+  - stratum_port: ":5555"
+  - stratum_port: ":5556"
+    block_wait_time: 50
+"#;
+    let config = BridgeConfig::from_yaml(yaml).unwrap();
+    let warnings = config.block_wait_time_warnings();
+    // The push-subscribed first instance warns under 500 ms, the polling second under 100 ms
+    assert_eq!(warnings.len(), 2, "{warnings:?}");
+    assert!(warnings[0].starts_with("[Instance 1]"));
+    assert!(warnings[1].starts_with("[Instance 2]"));
+
+    let err = BridgeConfig::from_yaml(&yaml.replace("50", "5"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("block_wait_time_min_ms"), "{err}");
+
+    let escape_hatch = format!("block_wait_time_min_ms: 5\n{}", yaml.replace("50", "5"));
+    let config = BridgeConfig::from_yaml(&escape_hatch).unwrap();
+    assert!(config.block_wait_time_warnings().is_empty());
+    assert!(BridgeConfig::from_yaml(&escape_hatch.replace("min_ms: 5", "min_ms: 6")).is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_log_timestamp_format() {
+    use crate::tracing_setup::LogTimestampFormat;
+
+    let config = BridgeConfig::from_yaml("log_timestamp_format: UTC\n").unwrap();
+    assert_eq!(
+        LogTimestampFormat::from_global(&config.global).unwrap(),
+        LogTimestampFormat::Utc
+    );
+    assert_eq!(
+        LogTimestampFormat::from_global(&BridgeConfig::default().global).unwrap(),
+        LogTimestampFormat::Local
+    );
+    let err = BridgeConfig::from_yaml("log_timestamp_format: rfc2822\n")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("log_timestamp_format"), "{err}");
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_log_levels() {
+    use crate::tracing_setup::log_filter_directives;
+
+    let yaml = r#"
+log_levels:
+  "kaspa_stratum_bridge::share_handler": TRACE
+  "kaspa_stratum_bridge::prom": warn
+"#;
+    let config = BridgeConfig::from_yaml(yaml).unwrap();
+    if std::env::var_os("RUST_LOG").is_none() {
+        assert_eq!(
+            log_filter_directives("warn", config.global.log_levels.as_ref()),
+            "warn,kaspa_stratum_bridge::prom=warn,kaspa_stratum_bridge::share_handler=trace"
+        );
+    }
+
+    let err = BridgeConfig::from_yaml("log_levels: { kaspa_stratum_bridge: loud }\n")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("log_levels"), "{err}");
+    assert!(BridgeConfig::from_yaml("log_levels: { \"a=b\": info }\n").is_err());
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_diff_reports_changed_fields() {
+    // Test: diff() yields one entry per changed global or instance field
+    let old = BridgeConfig::from_yaml(
+        r#"
+kaspad_address: "127.0.0.1:16110"
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+"#,
+    )
+    .unwrap();
+    let new = BridgeConfig::from_yaml(
+        r#"
+kaspad_address: "10.0.0.2:16110"
+shares_per_min: 30
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+    var_diff: false
+"#,
+    )
+    .unwrap();
+
+    let changes = BridgeConfig::diff(&old, &new);
+    assert_eq!(changes.len(), 3, "{changes:?}");
+    assert_eq!(
+        changes[0],
+        crate::ConfigChange {
+            field: "kaspad_address".to_string(),
+            old_value: "127.0.0.1:16110".to_string(),
+            new_value: "10.0.0.2:16110".to_string(),
+        }
+    );
+    assert_eq!(changes[1].field, "shares_per_min");
+    assert_eq!(
+        (changes[1].old_value.as_str(), changes[1].new_value.as_str()),
+        ("20", "30")
+    );
+    assert_eq!(changes[2].field, "instances[0].var_diff");
+    assert_eq!(
+        (changes[2].old_value.as_str(), changes[2].new_value.as_str()),
+        ("unset", "false")
+    );
+    assert!(BridgeConfig::diff(&old, &old).is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn test_check_config_validates_without_starting() {
+    // Test: --check-config reports the instance count, or the first load error
+    use clap::Parser;
+
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/valid.yaml");
+    let cli = crate::cli::Cli::parse_from(["stratum-bridge", "--check", "--config", fixture]);
+    assert!(cli.check_config);
+    assert_eq!(crate::runner::check_config(&cli).unwrap(), 2);
+
+    let cli = crate::cli::Cli::parse_from([
+        "stratum-bridge",
+        "--check-config",
+        "--config",
+        fixture,
+        "--kaspad-address",
+        "no-port",
+    ]);
+    let err = crate::runner::check_config(&cli).unwrap_err().to_string();
+    assert!(err.contains("host:port"), "{err}");
+}
+
+#[cfg(test)]
+#[test]
+fn test_instance_effective_config_inherits_globals() {
+    // Test: unset per-instance overrides resolve to the global value, set ones win
+    let config = BridgeConfig::from_yaml(
+        r#"
+var_diff: false
+shares_per_min: 30
+block_wait_time: 750
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+    shares_per_min: 10
+"#,
+    )
+    .unwrap();
+    let effective = config.instances[0].effective_config(&config.global);
+    assert!(!effective.var_diff);
+    assert_eq!(effective.shares_per_min, 10);
+    assert_eq!(
+        effective.block_wait_time,
+        std::time::Duration::from_millis(750)
+    );
+    assert!(effective.log_to_file);
+
+    let listener: crate::StratumServerBridgeConfig = effective.into();
+    assert_eq!(listener.stratum_port, ":5555");
+    assert_eq!(listener.shares_per_min, 10);
+    assert!(!listener.var_diff);
+    assert_eq!(
+        listener.max_batch_size,
+        crate::stratum_server::DEFAULT_MAX_BATCH_SIZE
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_tcp_keepalive() {
+    // Test: keepalive is on by default, `tcp_keepalive_secs: 0` turns it off, bad values are rejected
+    use crate::net_utils::TcpKeepaliveConfig;
+    use std::time::Duration;
+
+    let yaml = r#"
+kaspad_address: "127.0.0.1:16110"
+stratum_port: ":5555"
+"#;
+    let keepalive = |yaml: &str| {
+        let config = BridgeConfig::from_yaml(yaml).unwrap();
+        let listener: crate::StratumServerBridgeConfig =
+            config.instances[0].effective_config(&config.global).into();
+        listener.tcp_keepalive
+    };
+
+    assert_eq!(
+        keepalive(yaml),
+        Some(TcpKeepaliveConfig {
+            time: Duration::from_secs(60),
+            interval: Duration::from_secs(10),
+            retries: 5,
+        })
+    );
+    // A zero interval is raised to one second
+    let custom = format!(
+        "tcp_keepalive_secs: 30\ntcp_keepalive_interval_secs: 0\ntcp_keepalive_retries: 2\n{yaml}"
+    );
+    assert_eq!(
+        keepalive(&custom),
+        Some(TcpKeepaliveConfig {
+            time: Duration::from_secs(30),
+            interval: Duration::from_secs(1),
+            retries: 2,
+        })
+    );
+    assert_eq!(keepalive(&format!("tcp_keepalive_secs: 0\n{yaml}")), None);
+
+    for invalid in ["tcp_keepalive_secs: -1", "tcp_keepalive_retries: many"] {
+        assert!(
+            BridgeConfig::from_yaml(&format!("{invalid}\n{yaml}")).is_err(),
+            "{invalid}"
+        );
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_config_from_json_matches_yaml() {
+    // Test: JSON configs parse into the same single- and multi-instance shapes and share validation
+    let single = BridgeConfig::from_json(
+        r#"{"kaspad_address": "127.0.0.1:16110", "stratum_port": ":5555", "min_share_diff": 4096, "block_wait_time": 500}"#,
+    )
+    .unwrap();
+    assert_eq!(single.instances.len(), 1);
+    assert_eq!(single.instances[0].stratum_port, ":5555");
+    assert_eq!(single.instances[0].min_share_diff, 4096);
+    assert_eq!(single.global.block_wait_time.as_millis(), 500);
+
+    let multi = BridgeConfig::from_json(
+        r#"{
+            "kaspad_address": "127.0.0.1:16110",
+            "instances": [
+                {"stratum_port": ":5555", "min_share_diff": 8192},
+                {"stratum_port": "5556", "min_share_diff": 4096, "var_diff": false}
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(multi.instances.len(), 2);
+    assert_eq!(multi.instances[1].stratum_port, ":5556");
+    assert_eq!(multi.instances[1].var_diff, Some(false));
+
+    let duplicate_json = r#"{"instances": [
+        {"stratum_port": ":5555", "min_share_diff": 8192},
+        {"stratum_port": ":5555", "min_share_diff": 4096}
+    ]}"#;
+    let duplicate_yaml = r#"
+instances:
+  - stratum_port: ":5555"
+    min_share_diff: 8192
+  - stratum_port: ":5555"
+    min_share_diff: 4096
+"#;
+    assert_eq!(
+        BridgeConfig::from_json(duplicate_json)
+            .unwrap_err()
+            .to_string(),
+        BridgeConfig::from_yaml(duplicate_yaml)
+            .unwrap_err()
+            .to_string()
+    );
+    assert!(BridgeConfig::from_json(r#"{"strict": true, "var_difff": true}"#).is_err());
+}
+
 #[cfg(test)]
 #[test]
 fn test_config_coinbase_tag_suffix_empty_string() {
@@ -542,6 +1128,35 @@ fn test_normalize_port_with_whitespace() {
     );
 }
 
+#[cfg(test)]
+#[test]
+fn test_parse_bind_address_forms() {
+    use crate::net_utils::parse_bind_address;
+    assert_eq!(parse_bind_address("5555").unwrap(), ":5555");
+    assert_eq!(parse_bind_address(" :5555 ").unwrap(), ":5555");
+    assert_eq!(parse_bind_address("0.0.0.0:5555").unwrap(), "0.0.0.0:5555");
+    assert_eq!(parse_bind_address("[::]:5555").unwrap(), "[::]:5555");
+    assert_eq!(parse_bind_address("[::1]:5555").unwrap(), "[::1]:5555");
+
+    for invalid in [
+        "",
+        ":",
+        "0",
+        ":70000",
+        "port",
+        "localhost:5555",
+        "256.0.0.1:5555",
+        "::1:5555",
+        "[::1]",
+        "[zz::1]:5555",
+    ] {
+        assert!(
+            parse_bind_address(invalid).is_err(),
+            "'{invalid}' should be rejected"
+        );
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_bind_addr_from_port_with_colon() {
@@ -569,6 +1184,20 @@ fn test_bind_addr_from_port_with_full_address() {
     assert_eq!(bind_addr_from_port("192.168.1.1:5555"), "192.168.1.1:5555");
 }
 
+#[cfg(all(test, target_os = "linux"))]
+#[tokio::test]
+async fn test_bind_stratum_listener_reuseport_shares_port() {
+    use crate::net_utils::bind_stratum_listener;
+    let first = bind_stratum_listener("127.0.0.1:0", true).unwrap();
+    let addr = first.local_addr().unwrap().to_string();
+    // The old process is still listening while the new one binds the same port.
+    let second = bind_stratum_listener(&addr, true).unwrap();
+    assert_eq!(second.local_addr().unwrap(), first.local_addr().unwrap());
+    drop(first);
+    drop(second);
+    assert!(bind_stratum_listener(&addr, false).is_ok());
+}
+
 #[cfg(test)]
 #[test]
 fn test_bind_addr_from_port_empty() {
@@ -764,6 +1393,16 @@ fn test_unmarshal_event_basic() {
     assert_eq!(event.params.len(), 1);
 }
 
+#[cfg(test)]
+#[test]
+fn test_unmarshal_event_null_or_missing_params_are_empty() {
+    use crate::jsonrpc_event::unmarshal_event;
+    let event = unmarshal_event(r#"{"id":1,"method":"mining.authorize","params":null}"#).unwrap();
+    assert!(event.params.is_empty());
+    let event = unmarshal_event(r#"{"id":1,"method":"mining.authorize"}"#).unwrap();
+    assert!(event.params.is_empty());
+}
+
 #[cfg(test)]
 #[test]
 fn test_unmarshal_event_with_null_id() {
@@ -1008,6 +1647,43 @@ fn test_mining_state_job_management() {
     assert!(retrieved2.is_some(), "Second job should be retrievable");
 }
 
+#[cfg(test)]
+#[test]
+fn test_mining_state_tracks_last_notified_template() {
+    // Test: a refresh with the template the miner already holds can skip its `mining.notify`
+    use crate::mining_state::{Job, MiningState};
+    use kaspa_consensus_core::block::Block;
+    use kaspa_hashes::Hash;
+
+    let state = MiningState::new();
+    let pre_pow_hash = Hash::from_bytes([7; 32]);
+    let block = Block::from_precomputed_hash(Hash::from_bytes([1; 32]), vec![]);
+    let timestamp = block.header.timestamp;
+    let job_id = state.add_job(Job {
+        block,
+        pre_pow_hash,
+    });
+    assert!(
+        !state.holds_job_for(&pre_pow_hash, timestamp),
+        "nothing notified yet"
+    );
+
+    state.set_last_notified_job_id(job_id);
+    assert_eq!(state.last_notified_job_id(), Some(job_id));
+    assert!(state.holds_job_for(&pre_pow_hash, timestamp));
+    assert!(!state.holds_job_for(&Hash::from_bytes([8; 32]), timestamp));
+    assert!(!state.holds_job_for(&pre_pow_hash, timestamp + 1));
+
+    // Once the job's slot is reused, the miner no longer holds it
+    for _ in 0..state.max_jobs() {
+        state.add_job(Job {
+            block: Block::from_precomputed_hash(Hash::from_bytes([2; 32]), vec![]),
+            pre_pow_hash: Hash::default(),
+        });
+    }
+    assert!(!state.holds_job_for(&pre_pow_hash, timestamp));
+}
+
 #[cfg(test)]
 #[test]
 fn test_mining_state_difficulty_management() {
@@ -1055,6 +1731,11 @@ fn test_mining_state_initialization_flag() {
         !state.is_initialized(),
         "State should be uninitialized after clearing flag"
     );
+
+    // Only the first caller claims the first difficulty
+    assert!(state.mark_initialized());
+    assert!(!state.mark_initialized());
+    assert!(state.is_initialized());
 }
 
 #[cfg(test)]
@@ -1405,7 +2086,47 @@ mod integration {
             var_diff_stats: false,
             extranonce_size: 4,
             pow2_clamp: false,
+            var_diff_steps: crate::share_handler::VarDiffState::default(),
             coinbase_tag_suffix: None,
+            skip_address_validation: false,
+            allowed_ips: Vec::new(),
+            denied_ips: Vec::new(),
+            worker_credentials: None,
+            share_audit: None,
+            vardiff_store: None,
+            wallet_rotator: None,
+            total_workers: None,
+            tcp_keepalive: None,
+            control_rx: None,
+            max_batch_size: 100,
+            max_parse_errors: None,
+            miner_detector: None,
+            session_keepalive: None,
+            idle_share_timeout: None,
+            max_sessions_per_wallet: None,
+            max_workers_per_wallet: None,
+            worker_name_policy: crate::validation::WorkerNamePolicy::default(),
+            extranonce_range: None,
+            reuse_port: false,
+            ws_port: None,
+            max_submit_concurrency: 1,
+            max_nonce_range_bits: 64,
+            max_submits_per_second: None,
+            accept_flood: None,
+            ntime_max_delta_secs: None,
+            advertise_transactions_support: false,
+            version_rolling: false,
+            pool_name: None,
+            pool_event_handler: None,
+            kaspad_rtt_warn_threshold: Duration::from_millis(500),
+            block_submit_retries: 2,
+            block_submit_retry_delay: Duration::from_millis(100),
+            block_webhook: None,
+            max_workers: None,
+            overflow_pool: None,
+            worker_authenticator: None,
+            clean_jobs_on_retarget: true,
+            notify_queue_depth: 2,
         };
 
         let bridge_handle = tokio::spawn(async move {
@@ -1471,7 +2192,6 @@ mod comprehensive_tests {
     use num_traits::Zero;
     use serde_json::json;
     use std::sync::Arc;
-    use tokio::sync::mpsc;
 
     // ========================================================================
     // HELPER FUNCTIONS FOR TESTING
@@ -1505,30 +2225,16 @@ mod comprehensive_tests {
         Block::from_arcs(Arc::new(header), Arc::new(vec![tx]))
     }
 
-    /// Create a test StratumContext for testing
-    /// This simulates a client connection without needing a real TCP connection
-    async fn create_test_context() -> Arc<StratumContext> {
-        // Create a dummy TCP stream by connecting to a listener
-        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
-        let addr = listener.local_addr().unwrap();
-
-        // Spawn a task to accept the connection
-        let accept_handle = tokio::spawn(async move { listener.accept().await });
-
-        // Connect to the listener
-        let _stream = tokio::net::TcpStream::connect(addr).await.unwrap();
-        let (accepted_stream, _) = accept_handle.await.unwrap().unwrap();
-
-        let state = Arc::new(MiningState::new());
-        let (tx, _rx) = mpsc::unbounded_channel();
-        StratumContext::new("127.0.0.1".to_string(), 12345, accepted_stream, state, tx)
+    /// Create a test StratumContext for testing, with the miner's end of its socket
+    async fn create_test_context() -> (Arc<StratumContext>, tokio::net::TcpStream) {
+        crate::test_support::test_context().await
     }
 
     /// Create a test StratumContext synchronously (for non-async tests)
     /// Uses a runtime to handle async operations
     fn create_test_context_sync() -> Arc<StratumContext> {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(create_test_context())
+        rt.block_on(create_test_context()).0
     }
 
     // ========================================================================
@@ -1546,7 +2252,7 @@ mod comprehensive_tests {
         // Test: mining.subscribe request and response
         // This demonstrates how miners connect and identify themselves
 
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
         let event = JsonRpcEvent::new(
             Some("1".to_string()),
             "mining.subscribe",
@@ -1582,7 +2288,7 @@ mod comprehensive_tests {
             "test-instance".to_string(),
         ));
 
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
         let event = JsonRpcEvent::new(
             Some("1".to_string()),
             "mining.subscribe",
@@ -1619,7 +2325,7 @@ mod comprehensive_tests {
             "test-instance".to_string(),
         ));
 
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
         let event = JsonRpcEvent::new(
             Some("1".to_string()),
             "mining.subscribe",
@@ -1635,13 +2341,86 @@ mod comprehensive_tests {
         assert!(extranonce.is_empty(), "Bitmain should not get extranonce");
     }
 
+    #[tokio::test]
+    async fn test_stratum_protocol_subscribe_srbminer_extranonce() {
+        // Test: SRBMiner gets a 2-byte extranonce; its agent and protocol params are both accepted
+
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(ClientHandler::new(
+            share_handler,
+            8192.0,
+            2,
+            "test-instance".to_string(),
+        ));
+
+        let (ctx, _client) = create_test_context().await;
+        let event = JsonRpcEvent::new(
+            Some("1".to_string()),
+            "mining.subscribe",
+            vec![
+                json!("SRBMiner-MULTI/2.6.7"),
+                json!("EthereumStratum/1.0.0"),
+            ],
+        );
+
+        let result = handle_subscribe(ctx.clone(), event, Some(client_handler.clone())).await;
+        assert!(result.is_ok(), "Subscribe should succeed");
+
+        let extranonce = ctx.extranonce.lock().clone();
+        assert_eq!(
+            extranonce.len(),
+            4,
+            "SRBMiner extranonce should be 2 bytes (4 hex chars)"
+        );
+        assert_eq!(
+            ctx.miner_profile(),
+            crate::miner_detect::SrbMinerProfile::PROFILE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stratum_protocol_subscribe_goldshell_extranonce() {
+        // Test: Goldshell miners get a 2-byte extranonce and the Goldshell subscribe format
+
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(ClientHandler::new(
+            share_handler,
+            8192.0,
+            2,
+            "test-instance".to_string(),
+        ));
+
+        let (ctx, _client) = create_test_context().await;
+        let event = JsonRpcEvent::new(
+            Some("1".to_string()),
+            "mining.subscribe",
+            vec![json!("Goldshell KD-Box Pro")],
+        );
+
+        let result = handle_subscribe(ctx.clone(), event, Some(client_handler.clone())).await;
+        assert!(result.is_ok(), "Subscribe should succeed");
+
+        let extranonce = ctx.extranonce.lock().clone();
+        assert_eq!(
+            extranonce.len(),
+            4,
+            "Goldshell extranonce should be 2 bytes (4 hex chars)"
+        );
+        assert!(
+            ctx.miner_profile().is_goldshell(),
+            "Goldshell profile should be stored on the session"
+        );
+    }
+
     // ========================================================================
     // MINER COMPATIBILITY TESTS
     // ========================================================================
     // These tests demonstrate how the bridge handles different miner types:
     // - IceRiver: Requires extranonce, single hex string job format
     // - Bitmain: No extranonce, array + timestamp job format
+    // - Goldshell: Requires extranonce, array + timestamp job format, extranonce in subscribe
     // - BzMiner: Requires extranonce, single hex string job format
+    // - SRBMiner: Requires extranonce, array + timestamp job format
     // ========================================================================
 
     #[test]
@@ -1682,6 +2461,56 @@ mod comprehensive_tests {
         assert!(extranonce.is_empty(), "Bitmain should not get extranonce");
     }
 
+    #[test]
+    fn test_miner_type_detection_srbminer() {
+        // Test: SRBMiner detection (2-byte extranonce, legacy jobs, standard subscribe)
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(ClientHandler::new(
+            share_handler,
+            8192.0,
+            2,
+            "test-instance".to_string(),
+        ));
+
+        let ctx = create_test_context_sync();
+        ctx.identity.lock().remote_app = "SRBMiner-MULTI/2.6.7".to_string();
+        client_handler.assign_extranonce_for_miner(&ctx, "SRBMiner-MULTI/2.6.7");
+
+        let extranonce = ctx.extranonce.lock().clone();
+        assert_eq!(extranonce.len(), 4, "SRBMiner should get 2-byte extranonce");
+        let profile = ctx.miner_profile();
+        assert!(!profile.is_bitmain());
+        assert!(!profile.sends_extranonce2_size());
+        assert_eq!(profile.job_format, crate::miner_detect::JobFormat::Legacy);
+    }
+
+    #[test]
+    fn test_miner_type_detection_goldshell() {
+        // Test: Goldshell miner detection (2-byte extranonce, legacy jobs)
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(ClientHandler::new(
+            share_handler,
+            8192.0,
+            2,
+            "test-instance".to_string(),
+        ));
+
+        let ctx = create_test_context_sync();
+        ctx.identity.lock().remote_app = "Goldshell HS-Box".to_string();
+        client_handler.assign_extranonce_for_miner(&ctx, "Goldshell HS-Box");
+
+        let extranonce = ctx.extranonce.lock().clone();
+        assert_eq!(
+            extranonce.len(),
+            4,
+            "Goldshell should get 2-byte extranonce"
+        );
+        let profile = ctx.miner_profile();
+        assert!(profile.is_goldshell());
+        assert!(!profile.is_bitmain());
+        assert_eq!(profile.job_format, crate::miner_detect::JobFormat::Legacy);
+    }
+
     #[test]
     fn test_miner_type_detection_bzminer() {
         // Test: BzMiner detection
@@ -1919,33 +2748,163 @@ mod comprehensive_tests {
         // Test: Extranonce assignment sequence
         // This demonstrates how extranonce values are assigned sequentially
 
-        use std::sync::atomic::{AtomicI32, Ordering};
+        use std::sync::atomic::{AtomicI32, Ordering};
+
+        // Simulate the global extranonce counter
+        let counter = AtomicI32::new(0);
+        let max_extranonce = (2_f64.powi(16) - 1.0) as i32; // 2 bytes = 65535
+
+        // Assign first extranonce
+        let extranonce1 = counter.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| {
+            if val < max_extranonce {
+                Some(val + 1)
+            } else {
+                Some(0)
+            }
+        });
+        assert!(extranonce1.is_ok());
+        assert_eq!(extranonce1.unwrap(), 0);
+
+        // Assign second extranonce
+        let extranonce2 = counter.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| {
+            if val < max_extranonce {
+                Some(val + 1)
+            } else {
+                Some(0)
+            }
+        });
+        assert!(extranonce2.is_ok());
+        assert_eq!(extranonce2.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_extranonce_four_byte_allocation() {
+        // Test: extranonce_size = 4 assigns 8-hex-char extranonces (extranonce2_size = 4)
+        use crate::client_handler::{
+            MAX_EXTRANONCE_VALUE, MAX_EXTRANONCE_VALUE_U32, max_extranonce_for_size,
+        };
+
+        assert_eq!(max_extranonce_for_size(0), 0);
+        assert_eq!(max_extranonce_for_size(2), MAX_EXTRANONCE_VALUE);
+        assert_eq!(max_extranonce_for_size(4), MAX_EXTRANONCE_VALUE_U32);
+
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let handler = ClientHandler::new(share_handler, 8192.0, 4, "test-instance".to_string());
+
+        let ctx_iceriver = create_test_context_sync();
+        let ctx_goldshell = create_test_context_sync();
+        let ctx_bitmain = create_test_context_sync();
+        handler.assign_extranonce_for_miner(&ctx_iceriver, "IceRiverMiner");
+        handler.assign_extranonce_for_miner(&ctx_goldshell, "Goldshell KD-Box");
+        handler.assign_extranonce_for_miner(&ctx_bitmain, "GodMiner/1.0");
+
+        let iceriver = ctx_iceriver.extranonce.lock().clone();
+        let goldshell = ctx_goldshell.extranonce.lock().clone();
+        assert_eq!(iceriver.len(), 8, "4-byte extranonce is 8 hex chars");
+        assert!(u32::from_str_radix(&iceriver, 16).is_ok());
+        assert_eq!(goldshell.len(), 8);
+        assert_ne!(iceriver, goldshell);
+        assert_eq!(8 - goldshell.len() / 2, 4, "extranonce2_size");
+        assert_eq!(ctx_goldshell.miner_profile().extranonce_size, 4);
+        assert!(
+            ctx_bitmain.extranonce.lock().is_empty(),
+            "Bitmain keeps the full nonce"
+        );
+    }
+
+    #[test]
+    fn test_extranonce_one_byte_allocation() {
+        // Test: extranonce_size = 1 assigns 2-hex-char extranonces (extranonce2_size = 7)
+        use crate::client_handler::{ExtranonceRange, max_extranonce_for_size};
+        use crate::miner_detect::{EXTRANONCE_SIZE_NON_BITMAIN, EXTRANONCE_SIZE_U8};
+
+        assert_eq!(max_extranonce_for_size(EXTRANONCE_SIZE_U8), 0xFF);
+        assert_eq!(
+            EXTRANONCE_SIZE_NON_BITMAIN, 2,
+            "default for extranonce_size 0 and 2"
+        );
+        assert_eq!(
+            ExtranonceRange::split(1, 2, EXTRANONCE_SIZE_U8),
+            ExtranonceRange {
+                start: 0x80,
+                end: 0x100
+            }
+        );
+
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let handler = ClientHandler::new(share_handler, 8192.0, 1, "test-instance".to_string());
+
+        let ctx_iceriver = create_test_context_sync();
+        let ctx_bitmain = create_test_context_sync();
+        handler.assign_extranonce_for_miner(&ctx_iceriver, "IceRiverMiner");
+        handler.assign_extranonce_for_miner(&ctx_bitmain, "GodMiner/1.0");
+
+        let iceriver = ctx_iceriver.extranonce.lock().clone();
+        assert_eq!(iceriver.len(), 2, "1-byte extranonce is 2 hex chars");
+        assert_eq!(ctx_iceriver.extranonce2_size(), 7);
+        assert_eq!(ctx_iceriver.miner_profile().extranonce_size, 1);
+        assert!(
+            ctx_bitmain.extranonce.lock().is_empty(),
+            "Bitmain keeps the full nonce"
+        );
+        assert_eq!(ctx_bitmain.extranonce2_size(), 8);
+    }
+
+    #[test]
+    fn test_old_bzminer_gets_no_extranonce() {
+        // Test: BzMiner before v18 cannot take `mining.set_extranonce`, so it mines without one
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let handler = ClientHandler::new(share_handler, 8192.0, 2, "test-instance".to_string());
+
+        let ctx_old = create_test_context_sync();
+        let ctx_new = create_test_context_sync();
+        handler.assign_extranonce_for_miner(&ctx_old, "BzMiner-v17.5.1");
+        handler.assign_extranonce_for_miner(&ctx_new, "BzMiner-v21.3.0");
 
-        // Simulate the global extranonce counter
-        let counter = AtomicI32::new(0);
-        let max_extranonce = (2_f64.powi(16) - 1.0) as i32; // 2 bytes = 65535
+        assert!(ctx_old.extranonce.lock().is_empty());
+        assert_eq!(ctx_old.miner_profile().extranonce_size, 0);
+        assert_eq!(ctx_old.extranonce2_size(), 8);
+        assert_eq!(ctx_new.extranonce.lock().len(), 4);
+    }
 
-        // Assign first extranonce
-        let extranonce1 = counter.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| {
-            if val < max_extranonce {
-                Some(val + 1)
-            } else {
-                Some(0)
-            }
-        });
-        assert!(extranonce1.is_ok());
-        assert_eq!(extranonce1.unwrap(), 0);
+    #[test]
+    fn test_extranonce_instance_range_allocation() {
+        // Test: an instance with a reserved range cycles through its own values only
+        use crate::client_handler::ExtranonceRange;
 
-        // Assign second extranonce
-        let extranonce2 = counter.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| {
-            if val < max_extranonce {
-                Some(val + 1)
-            } else {
-                Some(0)
-            }
-        });
-        assert!(extranonce2.is_ok());
-        assert_eq!(extranonce2.unwrap(), 1);
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let range = ExtranonceRange {
+            start: 0x8000,
+            end: 0x8002,
+        };
+        let handler = ClientHandler::new(share_handler, 8192.0, 2, "test-instance".to_string())
+            .with_extranonce_range(Some(range));
+        assert_eq!(handler.extranonce_range(), Some(range));
+
+        let assigned: Vec<String> = (0..3)
+            .map(|_| {
+                let ctx = create_test_context_sync();
+                handler.assign_extranonce_for_miner(&ctx, "IceRiverMiner");
+                ctx.extranonce.lock().clone()
+            })
+            .collect();
+        assert_eq!(assigned, vec!["8000", "8001", "8000"]);
+
+        // A range past the extranonce width is clamped, not handed to the global counter
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let handler = ClientHandler::new(share_handler, 8192.0, 1, "test-instance".to_string())
+            .with_extranonce_range(Some(ExtranonceRange {
+                start: 0xFE,
+                end: 0x8000,
+            }));
+        let assigned: Vec<String> = (0..3)
+            .map(|_| {
+                let ctx = create_test_context_sync();
+                handler.assign_extranonce_for_miner(&ctx, "IceRiverMiner");
+                ctx.extranonce.lock().clone()
+            })
+            .collect();
+        assert_eq!(assigned, vec!["fe", "ff", "fe"]);
     }
 
     #[test]
@@ -2137,6 +3096,56 @@ mod comprehensive_tests {
         );
     }
 
+    #[test]
+    fn test_session_duration_metrics() {
+        use crate::prom::{init_metrics, record_longest_session_age, record_session_duration};
+        use prometheus::gather;
+        use std::time::Duration;
+
+        init_metrics();
+        record_session_duration("session-metrics", Duration::from_secs(45));
+        record_longest_session_age("session-metrics", Some(Duration::from_secs(90)));
+
+        let families = gather();
+        let instance_metric = |name: &str| {
+            families
+                .iter()
+                .filter(|family| family.get_name() == name)
+                .flat_map(|family| family.get_metric())
+                .find(|metric| {
+                    metric
+                        .get_label()
+                        .iter()
+                        .any(|l| l.get_name() == "instance" && l.get_value() == "session-metrics")
+                })
+                .cloned()
+                .unwrap_or_else(|| panic!("{name} has no session-metrics series"))
+        };
+
+        let histogram = instance_metric("ks_worker_session_duration_seconds");
+        let histogram = histogram.get_histogram();
+        assert_eq!(histogram.get_sample_count(), 1);
+        let buckets: Vec<(f64, u64)> = histogram
+            .get_bucket()
+            .iter()
+            .map(|b| (b.get_upper_bound(), b.get_cumulative_count()))
+            .collect();
+        assert_eq!(
+            buckets,
+            vec![
+                (30.0, 0),
+                (300.0, 1),
+                (1800.0, 1),
+                (3600.0, 1),
+                (21600.0, 1),
+                (86400.0, 1)
+            ]
+        );
+
+        let gauge = instance_metric("ks_session_active_duration_seconds");
+        assert_eq!(gauge.get_gauge().get_value(), 90.0);
+    }
+
     #[test]
     fn test_share_handler_vardiff_management() {
         // Test: VarDiff difficulty management
@@ -2286,7 +3295,7 @@ mod comprehensive_tests {
             2,
             "test-instance".to_string(),
         ));
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
 
         // 2. Subscribe
         let subscribe_event = JsonRpcEvent::new(
@@ -2946,7 +3955,7 @@ mod comprehensive_tests {
     async fn test_wallet_address_cleaning_with_prefix() {
         // Test: Wallet addresses with kaspa:/kaspatest:/kaspadev: prefixes
         // These should be accepted as-is
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
         // Note: State is already created in create_test_context, no need to create another
 
         // Test kaspa: prefix
@@ -2964,12 +3973,13 @@ mod comprehensive_tests {
             addr1.starts_with("kaspa:"),
             "Address should have kaspa: prefix"
         );
+        ctx.mark_subscribed(String::new());
         let _result1: Result<(), _> = handle_authorize(ctx.clone(), event1, None, None).await;
         // Note: This will fail with invalid address, but we're testing the cleaning logic
         // In real scenario, valid addresses would work
 
         // Test kaspatest: prefix
-        let ctx2 = create_test_context().await;
+        let (ctx2, _client) = create_test_context().await;
         let event2 = JsonRpcEvent::new(
             Some("2".to_string()),
             "mining.authorize",
@@ -2983,10 +3993,11 @@ mod comprehensive_tests {
             addr2.starts_with("kaspatest:"),
             "Address should have kaspatest: prefix"
         );
+        ctx2.mark_subscribed(String::new());
         let _result2: Result<(), _> = handle_authorize(ctx2.clone(), event2, None, None).await;
 
         // Test kaspadev: prefix
-        let ctx3 = create_test_context().await;
+        let (ctx3, _client) = create_test_context().await;
         let event3 = JsonRpcEvent::new(
             Some("3".to_string()),
             "mining.authorize",
@@ -3000,13 +4011,14 @@ mod comprehensive_tests {
             addr3.starts_with("kaspadev:"),
             "Address should have kaspadev: prefix"
         );
+        ctx3.mark_subscribed(String::new());
         let _result3: Result<(), _> = handle_authorize(ctx3.clone(), event3, None, None).await;
     }
 
     #[tokio::test]
     async fn test_wallet_address_cleaning_without_prefix() {
         // Test: Wallet addresses without prefix should get kaspa: prefix added
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
         let event = JsonRpcEvent::new(
             Some("1".to_string()),
             "mining.authorize",
@@ -3023,6 +4035,7 @@ mod comprehensive_tests {
         );
 
         // handle_authorize will call clean_wallet which should add kaspa: prefix
+        ctx.mark_subscribed(String::new());
         let _result: Result<(), _> = handle_authorize(ctx.clone(), event, None, None).await;
         // Note: Actual validation requires valid address format
     }
@@ -3030,20 +4043,22 @@ mod comprehensive_tests {
     #[tokio::test]
     async fn test_wallet_address_cleaning_invalid_addresses() {
         // Test: Invalid addresses should be rejected
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
 
         // Test empty address
         let event1 = JsonRpcEvent::new(Some("1".to_string()), "mining.authorize", vec![json!("")]);
+        ctx.mark_subscribed(String::new());
         let result1: Result<(), _> = handle_authorize(ctx.clone(), event1, None, None).await;
         assert!(result1.is_err(), "Empty address should be rejected");
 
         // Test malformed address
-        let ctx2 = create_test_context().await;
+        let (ctx2, _client) = create_test_context().await;
         let event2 = JsonRpcEvent::new(
             Some("2".to_string()),
             "mining.authorize",
             vec![json!("invalid_address")],
         );
+        ctx2.mark_subscribed(String::new());
         let result2: Result<(), _> = handle_authorize(ctx2.clone(), event2, None, None).await;
         assert!(
             result2.is_err(),
@@ -3054,7 +4069,7 @@ mod comprehensive_tests {
     #[tokio::test]
     async fn test_wallet_address_cleaning_whitespace_handling() {
         // Test: Addresses with whitespace should be handled
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
         let event = JsonRpcEvent::new(
             Some("1".to_string()),
             "mining.authorize",
@@ -3071,6 +4086,7 @@ mod comprehensive_tests {
         );
 
         // Whitespace should be trimmed during processing
+        ctx.mark_subscribed(String::new());
         let _result: Result<(), _> = handle_authorize(ctx.clone(), event, None, None).await;
     }
 
@@ -3454,7 +4470,7 @@ mod comprehensive_tests {
     async fn test_stratum_context_connected_flag() {
         // Test: Connected/disconnected state management
         // Note: disconnect() may use async operations, so we use tokio::test
-        let ctx = create_test_context().await;
+        let (ctx, _client) = create_test_context().await;
 
         // Initially should be connected
         assert!(ctx.connected(), "Context should start connected");
@@ -3533,4 +4549,544 @@ mod comprehensive_tests {
             "Summary should contain remote app"
         );
     }
+
+    #[tokio::test]
+    async fn test_batch_replies_are_sent_as_one_array() {
+        use crate::jsonrpc_event::JsonRpcResponse;
+        use tokio::io::AsyncBufReadExt;
+
+        let (ctx, client) = create_test_context().await;
+
+        ctx.begin_batch();
+        ctx.reply(JsonRpcResponse::error(
+            Some(json!(1)),
+            21,
+            "Job not found",
+            None,
+        ))
+        .await
+        .unwrap();
+        ctx.reply(JsonRpcResponse::error(
+            Some(json!(2)),
+            22,
+            "Duplicate share submitted",
+            None,
+        ))
+        .await
+        .unwrap();
+        ctx.finish_batch().await.unwrap();
+
+        let mut line = String::new();
+        tokio::io::BufReader::new(client)
+            .read_line(&mut line)
+            .await
+            .unwrap();
+        let replies: Vec<serde_json::Value> = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(replies.len(), 2, "both replies should be in one array");
+        assert_eq!(replies[0]["id"], json!(1));
+        assert_eq!(replies[1]["id"], json!(2));
+    }
+
+    #[tokio::test]
+    async fn test_nicehash_subscribe_and_authorize_wire_format() {
+        use crate::default_client::nicehash_subscribe_result;
+        use tokio::io::AsyncBufReadExt;
+
+        assert_eq!(
+            nicehash_subscribe_result("0000002a", "0001", 6).to_string(),
+            r#"[[["mining.set_difficulty","0000002a"],["mining.notify","0000002a"]],"0001",6]"#
+        );
+
+        let (ctx, client) = create_test_context().await;
+        ctx.set_id(42);
+
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(ClientHandler::new(
+            share_handler,
+            8192.0,
+            2,
+            "test-instance".to_string(),
+        ));
+
+        let subscribe = JsonRpcEvent::new(
+            Some("1".to_string()),
+            "mining.subscribe",
+            vec![
+                json!("NiceHashStratum/1.0.0"),
+                json!("EthereumStratum/1.0.0"),
+            ],
+        );
+        handle_subscribe(ctx.clone(), subscribe, Some(client_handler.clone()))
+            .await
+            .unwrap();
+        let extranonce = ctx.extranonce.lock().clone();
+        assert_eq!(extranonce.len(), 4, "NiceHash gets a 2-byte extranonce");
+
+        let authorize = JsonRpcEvent::new(
+            Some("2".to_string()),
+            "mining.authorize",
+            vec![json!(
+                "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y.rig1"
+            )],
+        );
+        handle_authorize(ctx.clone(), authorize, Some(client_handler.clone()), None)
+            .await
+            .unwrap();
+
+        let mut lines = tokio::io::BufReader::new(client).lines();
+        let mut messages = Vec::new();
+        for _ in 0..4 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            messages.push(serde_json::from_str::<serde_json::Value>(&line).unwrap());
+        }
+        let [sub, auth, set_extranonce, set_difficulty] = &messages[..] else {
+            panic!("expected four messages");
+        };
+
+        assert_eq!(sub["id"], json!("1"));
+        assert_eq!(
+            sub["result"],
+            json!([
+                [
+                    ["mining.set_difficulty", "0000002a"],
+                    ["mining.notify", "0000002a"]
+                ],
+                extranonce,
+                6
+            ])
+        );
+
+        assert_eq!(auth["id"], json!("2"));
+        assert_eq!(auth["result"], json!(true));
+
+        assert_eq!(set_extranonce["method"], json!("mining.set_extranonce"));
+        assert_eq!(set_extranonce["params"], json!([extranonce, 6]));
+
+        assert_eq!(set_difficulty["method"], json!("mining.set_difficulty"));
+        assert_eq!(set_difficulty["params"], json!([8192.0]));
+
+        // The job paths see an initialized session and do not send the difficulty again
+        assert!(ctx.state.is_initialized());
+        assert_eq!(client_handler.initialize_session_difficulty(&ctx), None);
+    }
+
+    #[tokio::test]
+    async fn test_kaspa_stratum_protocol_selects_extranonce_subscribe() {
+        use tokio::io::AsyncBufReadExt;
+
+        let (ctx, client) = create_test_context().await;
+
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(ClientHandler::new(
+            share_handler,
+            8192.0,
+            2,
+            "test-instance".to_string(),
+        ));
+
+        // BzMiner alone gets the standard response; the protocol overrides it
+        let subscribe = JsonRpcEvent::new(
+            Some("1".to_string()),
+            "mining.subscribe",
+            vec![json!("BzMiner/21.0.0"), json!("KaspaStratum/1.0")],
+        );
+        handle_subscribe(ctx.clone(), subscribe, Some(client_handler))
+            .await
+            .unwrap();
+        assert_eq!(
+            ctx.identity.lock().protocol_version.as_deref(),
+            Some("KaspaStratum/1.0")
+        );
+        let profile = ctx.miner_profile();
+        assert_eq!(profile.job_format, crate::miner_detect::JobFormat::BigJob);
+        assert_eq!(
+            profile.subscribe_format,
+            crate::miner_detect::SubscribeFormat::KaspaStratum
+        );
+        assert!(!profile.is_goldshell());
+
+        let mut line = String::new();
+        tokio::io::BufReader::new(client)
+            .read_line(&mut line)
+            .await
+            .unwrap();
+        let sub: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+        let extranonce = ctx.extranonce.lock().clone();
+        assert_eq!(sub["result"], json!([null, extranonce, 6]));
+    }
+
+    #[tokio::test]
+    async fn test_extranonce_change_respects_extranonce_subscribe() {
+        use tokio::io::AsyncBufReadExt;
+
+        let client_handler = Arc::new(ClientHandler::new(
+            Arc::new(ShareHandler::new("test-instance".to_string())),
+            1.0,
+            2,
+            "test-instance".to_string(),
+        ));
+
+        let mut sessions = Vec::new();
+        for _ in 0..2 {
+            let (ctx, client) = create_test_context().await;
+            ctx.identity.lock().remote_app = "BzMiner/21.0.0".to_string();
+            sessions.push((ctx, tokio::io::BufReader::new(client).lines()));
+        }
+        let (subscribed, mut subscribed_lines) = sessions.remove(0);
+        let (legacy, mut legacy_lines) = sessions.remove(0);
+
+        let extranonce_subscribe =
+            JsonRpcEvent::new(Some("1".to_string()), "mining.extranonce.subscribe", vec![]);
+        crate::default_client::handle_extranonce_subscribe(
+            subscribed.clone(),
+            extranonce_subscribe,
+            Some(Arc::clone(&client_handler)),
+        )
+        .await
+        .unwrap();
+        assert!(subscribed.supports_set_extranonce());
+        assert!(!legacy.supports_set_extranonce());
+        let reply: serde_json::Value =
+            serde_json::from_str(&subscribed_lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(reply["result"], json!(true));
+
+        client_handler
+            .change_extranonce(subscribed.clone(), "beef".to_string())
+            .await;
+        let set_extranonce: serde_json::Value =
+            serde_json::from_str(&subscribed_lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(set_extranonce["method"], json!("mining.set_extranonce"));
+        assert_eq!(set_extranonce["params"][0], json!("beef"));
+        assert!(subscribed.connected());
+
+        client_handler
+            .change_extranonce(legacy.clone(), "cafe".to_string())
+            .await;
+        let reconnect: serde_json::Value =
+            serde_json::from_str(&legacy_lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(reconnect["method"], json!("client.reconnect"));
+        assert_eq!(reconnect["params"], json!([]));
+        assert!(!legacy.connected());
+    }
+
+    #[tokio::test]
+    async fn test_multi_version_negotiates_version_mask() {
+        use tokio::io::AsyncBufReadExt;
+
+        let (ctx, client) = create_test_context().await;
+        let mut lines = tokio::io::BufReader::new(client).lines();
+        assert_eq!(ctx.version_rolling_mask(), None);
+
+        let multi_version = JsonRpcEvent::new(
+            Some("3".to_string()),
+            "mining.multi_version",
+            vec![json!(4)],
+        );
+        crate::default_client::handle_multi_version(ctx.clone(), multi_version)
+            .await
+            .unwrap();
+        assert_eq!(ctx.version_rolling_mask(), Some(0x1fff_e000));
+        let reply: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(reply["id"], json!("3"));
+        assert_eq!(reply["result"], json!(true));
+        let set_mask: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(
+            set_mask,
+            json!({"id": null, "method": "mining.set_version_mask", "params": ["1fffe000"]})
+        );
+
+        // A narrower mask from the miner is kept inside the offered bits
+        let narrowed = JsonRpcEvent::new(
+            Some("4".to_string()),
+            "mining.multi_version",
+            vec![json!("ffffffff")],
+        );
+        crate::default_client::handle_multi_version(ctx.clone(), narrowed)
+            .await
+            .unwrap();
+        assert_eq!(ctx.version_rolling_mask(), Some(0x1fff_e000));
+        let narrowed = JsonRpcEvent::new(
+            Some("5".to_string()),
+            "mining.multi_version",
+            vec![json!("00ffe000")],
+        );
+        crate::default_client::handle_multi_version(ctx.clone(), narrowed)
+            .await
+            .unwrap();
+        assert_eq!(ctx.version_rolling_mask(), Some(0x00ff_e000));
+    }
+
+    #[tokio::test]
+    async fn test_authorize_before_subscribe_is_rejected() {
+        use tokio::io::AsyncBufReadExt;
+
+        let (ctx, client) = create_test_context().await;
+
+        let authorize = JsonRpcEvent::new(
+            Some("1".to_string()),
+            "mining.authorize",
+            vec![json!(
+                "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y.rig1"
+            )],
+        );
+        assert!(
+            handle_authorize(ctx.clone(), authorize, None, None)
+                .await
+                .is_err()
+        );
+        assert!(ctx.identity.lock().wallet_addr.is_empty());
+        // The session state is checked before the address is even looked at
+        let bad_address = JsonRpcEvent::new(
+            Some("2".to_string()),
+            "mining.authorize",
+            vec![json!("not-a-wallet.rig1")],
+        );
+        assert!(
+            handle_authorize(ctx.clone(), bad_address, None, None)
+                .await
+                .is_err()
+        );
+
+        let mut lines = tokio::io::BufReader::new(client).lines();
+        for id in ["1", "2"] {
+            let line = lines.next_line().await.unwrap().unwrap();
+            let reply: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(reply["id"], json!(id));
+            assert_eq!(reply["error"], json!([25, "Not subscribed", null]));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mining_ping_replies_pong() {
+        use crate::default_client::handle_ping;
+        use tokio::io::AsyncBufReadExt;
+
+        let (ctx, client) = create_test_context().await;
+
+        let event: JsonRpcEvent =
+            serde_json::from_str(r#"{"id":7,"method":"mining.ping","params":[]}"#).unwrap();
+        handle_ping(ctx, event).await.unwrap();
+
+        let mut line = String::new();
+        tokio::io::BufReader::new(client)
+            .read_line(&mut line)
+            .await
+            .unwrap();
+        let reply: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(reply["id"], json!(7));
+        assert_eq!(reply["result"], json!("pong"));
+        assert!(reply["error"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_get_transactions_and_advertised_subscription() {
+        use crate::default_client::handle_get_transactions;
+        use tokio::io::AsyncBufReadExt;
+
+        let (ctx, client) = create_test_context().await;
+        ctx.set_id(42);
+
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(
+            ClientHandler::new(share_handler, 8192.0, 2, "test-instance".to_string())
+                .with_advertise_transactions_support(true),
+        );
+        let subscribe = JsonRpcEvent::new(
+            Some("1".to_string()),
+            "mining.subscribe",
+            vec![json!("NiceHashStratum/1.0.0")],
+        );
+        handle_subscribe(ctx.clone(), subscribe, Some(client_handler))
+            .await
+            .unwrap();
+
+        let event: JsonRpcEvent =
+            serde_json::from_str(r#"{"id":8,"method":"mining.get_transactions","params":["1"]}"#)
+                .unwrap();
+        handle_get_transactions(ctx, event).await.unwrap();
+
+        let mut lines = tokio::io::BufReader::new(client).lines();
+        let sub: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(
+            sub["result"][0][2],
+            json!(["mining.get_transactions", "0000002a"])
+        );
+
+        let reply: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(reply["id"], json!(8));
+        assert_eq!(reply["result"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_authorize_announces_pool_name() {
+        use tokio::io::AsyncBufReadExt;
+
+        let (ctx, client) = create_test_context().await;
+
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(
+            ClientHandler::new(share_handler, 8192.0, 2, "test-instance".to_string())
+                .with_pool_name(Some("Pool East".to_string())),
+        );
+        let subscribe = JsonRpcEvent::new(
+            Some("1".to_string()),
+            "mining.subscribe",
+            vec![json!("IceRiverMiner-v1.1")],
+        );
+        handle_subscribe(ctx.clone(), subscribe, Some(client_handler.clone()))
+            .await
+            .unwrap();
+        let authorize = JsonRpcEvent::new(
+            Some("2".to_string()),
+            "mining.authorize",
+            vec![json!(
+                "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y.rig1"
+            )],
+        );
+        handle_authorize(ctx.clone(), authorize, Some(client_handler), None)
+            .await
+            .unwrap();
+
+        let mut lines = tokio::io::BufReader::new(client).lines();
+        let mut methods = Vec::new();
+        let set_pool = loop {
+            let line = lines.next_line().await.unwrap().unwrap();
+            let message: serde_json::Value = serde_json::from_str(&line).unwrap();
+            if message["method"] == json!("mining.set_pool") {
+                break message;
+            }
+            methods.push(message["method"].clone());
+            assert!(methods.len() < 5, "no mining.set_pool after {methods:?}");
+        };
+        assert_eq!(set_pool["params"], json!(["Pool East"]));
+    }
+
+    #[tokio::test]
+    async fn test_authorize_rejections_carry_a_reason() {
+        use crate::worker_credentials::WorkerCredentialStore;
+        use tokio::io::AsyncBufReadExt;
+
+        const WALLET: &str = "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y";
+        let (ctx, client) = create_test_context().await;
+
+        let credentials =
+            std::collections::HashMap::from([(WALLET.to_string(), "secret".to_string())]);
+        let share_handler = Arc::new(ShareHandler::new("test-instance".to_string()));
+        let client_handler = Arc::new(
+            ClientHandler::new(share_handler, 8192.0, 2, "test-instance".to_string())
+                .with_worker_credentials(Some(Arc::new(WorkerCredentialStore::from_entries(
+                    credentials,
+                    true,
+                )))),
+        );
+        ctx.mark_subscribed(String::new());
+        for (id, params) in [
+            ("1", vec![json!("not-a-wallet.rig1")]),
+            ("2", vec![json!(format!("{WALLET}.rig1")), json!("wrong")]),
+            ("3", vec![json!(format!("{WALLET}.rig1"))]),
+        ] {
+            let authorize = JsonRpcEvent::new(Some(id.to_string()), "mining.authorize", params);
+            assert!(
+                handle_authorize(ctx.clone(), authorize, Some(client_handler.clone()), None)
+                    .await
+                    .is_err()
+            );
+        }
+
+        let mut lines = tokio::io::BufReader::new(client).lines();
+        let mut errors = Vec::new();
+        for _ in 0..3 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            let reply: serde_json::Value = serde_json::from_str(&line).unwrap();
+            errors.push(reply["error"].clone());
+        }
+        assert_eq!(
+            errors[0],
+            json!([
+                24,
+                "Unauthorized worker",
+                "Invalid Kaspa address: expected kaspa: prefix"
+            ])
+        );
+        // Wrong passwords get a deliberately vague message
+        assert_eq!(
+            errors[1],
+            json!([24, "Unauthorized worker", "Authentication failed"])
+        );
+        // A missing password is named, since no credential is revealed by saying so
+        assert_eq!(
+            errors[2],
+            json!([24, "Unauthorized worker", "Authentication required"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pool_event_handler_sees_connect_and_disconnect() {
+        use crate::pool_events::{PoolEventHandler, WorkerConnectedEvent, WorkerDisconnectedEvent};
+
+        #[derive(Default)]
+        struct Recorder {
+            connected: parking_lot::Mutex<Vec<WorkerConnectedEvent>>,
+            disconnected: parking_lot::Mutex<Vec<WorkerDisconnectedEvent>>,
+        }
+
+        impl PoolEventHandler for Recorder {
+            fn on_worker_connected(&self, event: WorkerConnectedEvent) {
+                self.connected.lock().push(event);
+            }
+
+            fn on_worker_disconnected(&self, event: WorkerDisconnectedEvent) {
+                self.disconnected.lock().push(event);
+            }
+        }
+
+        let (ctx, _client) = create_test_context().await;
+
+        let recorder = Arc::new(Recorder::default());
+        let share_handler = Arc::new(ShareHandler::new("events-instance".to_string()));
+        let client_handler = Arc::new(
+            ClientHandler::new(share_handler, 8192.0, 2, "events-instance".to_string())
+                .with_pool_event_handler(Some(recorder.clone())),
+        );
+        client_handler.on_connect(ctx.clone());
+        let subscribe = JsonRpcEvent::new(
+            Some("1".to_string()),
+            "mining.subscribe",
+            vec![json!("IceRiverMiner-v1.1")],
+        );
+        handle_subscribe(ctx.clone(), subscribe, Some(client_handler.clone()))
+            .await
+            .unwrap();
+        let authorize = JsonRpcEvent::new(
+            Some("2".to_string()),
+            "mining.authorize",
+            vec![json!(
+                "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y.rig1"
+            )],
+        );
+        handle_authorize(ctx.clone(), authorize, Some(client_handler.clone()), None)
+            .await
+            .unwrap();
+        client_handler.on_disconnect(&ctx);
+
+        let connected = recorder.connected.lock();
+        assert_eq!(connected.len(), 1);
+        assert_eq!(connected[0].worker.instance_id, "events-instance");
+        assert_eq!(connected[0].worker.worker_name, "rig1");
+        assert!(
+            connected[0]
+                .worker
+                .wallet_addr
+                .starts_with("kaspa:qr5wl2hw")
+        );
+        assert!(connected[0].difficulty > 0.0);
+
+        let disconnected = recorder.disconnected.lock();
+        assert_eq!(disconnected.len(), 1);
+        assert_eq!(disconnected[0].worker, connected[0].worker);
+    }
 }
@@ -1,12 +1,14 @@
-use chrono::Local;
+use chrono::{Local, Utc};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::fmt;
-use std::sync::Mutex as StdMutex;
+use std::sync::{Mutex as StdMutex, OnceLock};
+use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::fmt::format::{FormatEvent, FormatFields, Writer};
-use tracing_subscriber::{EnvFilter, layer::SubscriberExt};
+use tracing_subscriber::{EnvFilter, Layer, Registry, layer::SubscriberExt, reload};
 
 use crate::BridgeConfig;
+use crate::app_config::{ConfigError, GlobalConfig};
 use crate::app_dirs;
 use crate::log_colors::LogColors;
 
@@ -22,8 +24,146 @@ pub(crate) fn register_instance(instance_id: String, instance_num: usize) {
     }
 }
 
+/// Handle for swapping the log filter after a config reload, with the default directives it was
+/// built from.
+static FILTER_RELOAD: OnceLock<(reload::Handle<EnvFilter, Registry>, String)> = OnceLock::new();
+
+/// Checks one `log_levels` entry: a target without `=`/`,` and a level name (`off`, `error` ..
+/// `trace`).
+pub fn check_log_level(target: &str, level: &str) -> Result<(), ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidSetting {
+        field: "log_levels",
+        reason,
+    };
+    let target = target.trim();
+    if target.is_empty() || target.contains(['=', ',', ' ']) {
+        return Err(invalid(format!("'{target}' is not a tracing target")));
+    }
+    level
+        .trim()
+        .parse::<LevelFilter>()
+        .map_err(|_| invalid(format!("'{level}' for {target} is not a log level")))?;
+    Ok(())
+}
+
+/// Filter directives: `RUST_LOG` when set, otherwise `default`, plus one `target=level` per
+/// `log_levels` entry whose target `RUST_LOG` does not already name.
+pub fn log_filter_directives(
+    default: &str,
+    log_levels: Option<&HashMap<String, String>>,
+) -> String {
+    let env = std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|value| !value.trim().is_empty());
+    let env_targets: Vec<&str> = env
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|directive| directive.split_once('=').map(|(target, _)| target.trim()))
+        .collect();
+    let mut directives = env.clone().unwrap_or_else(|| default.to_string());
+    let mut entries: Vec<(&String, &String)> = log_levels.into_iter().flatten().collect();
+    entries.sort();
+    for (target, level) in entries {
+        let target = target.trim();
+        if !env_targets.contains(&target) && check_log_level(target, level).is_ok() {
+            directives.push_str(&format!(
+                ",{}={}",
+                target,
+                level.trim().to_ascii_lowercase()
+            ));
+        }
+    }
+    directives
+}
+
+fn build_filter(directives: &str, default: &str) -> EnvFilter {
+    EnvFilter::try_new(directives).unwrap_or_else(|e| {
+        eprintln!("Invalid log filter '{}', using defaults: {}", directives, e);
+        EnvFilter::new(default)
+    })
+}
+
+/// Rebuild the log filter from reloaded `log_levels` (`RUST_LOG` still wins). No-op before
+/// [`init_tracing`].
+pub(crate) fn reload_log_levels(global: &GlobalConfig) {
+    let Some((handle, default)) = FILTER_RELOAD.get() else {
+        return;
+    };
+    let directives = log_filter_directives(default, global.log_levels.as_ref());
+    match handle.reload(build_filter(&directives, default)) {
+        Ok(()) => tracing::info!("Log filter is now {}", directives),
+        Err(e) => tracing::warn!("Failed to apply log_levels: {}", e),
+    }
+}
+
+/// Default for `log_buffer_capacity`.
+pub const DEFAULT_LOG_BUFFER_CAPACITY: usize = 131_072;
+
+/// Timestamp at the start of each log line (`log_timestamp_format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogTimestampFormat {
+    None,
+    /// ISO 8601 in UTC, e.g. `2025-01-31T14:03:07.512Z`.
+    Utc,
+    /// Local time with its offset, e.g. `2025-01-31 15:03:07.512+01:00`.
+    #[default]
+    Local,
+    /// Seconds since the Unix epoch with milliseconds, e.g. `1738332187.512`.
+    Unix,
+}
+
+impl LogTimestampFormat {
+    /// Format named by `log_timestamp_format` (`none`, `utc`, `local` or `unix`; default `local`).
+    pub fn from_global(global: &GlobalConfig) -> Result<Self, ConfigError> {
+        let Some(name) = global.log_timestamp_format.as_deref() else {
+            return Ok(Self::default());
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "utc" => Ok(Self::Utc),
+            "local" => Ok(Self::Local),
+            "unix" => Ok(Self::Unix),
+            other => Err(ConfigError::InvalidSetting {
+                field: "log_timestamp_format",
+                reason: format!("'{other}' is not one of none, utc, local, unix"),
+            }),
+        }
+    }
+
+    /// The format for the log file, which always carries timestamps.
+    fn for_file(self) -> Self {
+        match self {
+            Self::None => Self::Local,
+            other => other,
+        }
+    }
+
+    fn write(self, writer: &mut Writer<'_>) -> fmt::Result {
+        match self {
+            Self::None => Ok(()),
+            Self::Utc => write!(writer, "{} ", Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ")),
+            Self::Local => write!(
+                writer,
+                "{} ",
+                Local::now().format("%Y-%m-%d %H:%M:%S.%3f%:z")
+            ),
+            Self::Unix => {
+                let now = Utc::now();
+                write!(
+                    writer,
+                    "{}.{:03} ",
+                    now.timestamp(),
+                    now.timestamp_subsec_millis()
+                )
+            }
+        }
+    }
+}
+
 struct CustomFormatter {
     apply_colors: bool,
+    timestamps: LogTimestampFormat,
 }
 
 impl<S, N> FormatEvent<S, N> for CustomFormatter
@@ -39,8 +179,7 @@ where
     ) -> fmt::Result {
         let level = *event.metadata().level();
 
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S.%3f%:z");
-        write!(writer, "{} ", timestamp)?;
+        self.timestamps.write(&mut writer)?;
 
         // Collect the message into a string first so we can analyze it for color patterns
         let mut message_buf = String::new();
@@ -365,11 +504,57 @@ where
     }
 }
 
+/// Syslog layer when `log_to_syslog` is on and the local syslog socket answers. Lines are never
+/// colored and carry no timestamp of their own (syslog adds one).
+#[cfg(unix)]
+fn syslog_layer<S>(config: &BridgeConfig) -> Option<impl Layer<S>>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    if !config.global.log_to_syslog.unwrap_or(false) {
+        return None;
+    }
+    // Validated with the rest of the config
+    let facility = crate::syslog_sink::facility_from_global(&config.global).unwrap_or(3);
+    match crate::syslog_sink::SyslogWriter::connect(facility) {
+        Ok(writer) => Some(
+            tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
+                .event_format(CustomFormatter {
+                    apply_colors: false,
+                    timestamps: LogTimestampFormat::None,
+                }),
+        ),
+        Err(e) => {
+            eprintln!("Failed to connect to syslog, continuing without it: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn syslog_layer(config: &BridgeConfig) -> Option<tracing_subscriber::layer::Identity> {
+    if config.global.log_to_syslog.unwrap_or(false) {
+        eprintln!("log_to_syslog is only supported on Unix; ignoring it");
+    }
+    None
+}
+
+/// Install the console, file and syslog layers. `default_filter` applies when `RUST_LOG` is unset;
+/// `log_levels` from the config is added on top and can be changed later with [`reload_log_levels`].
 pub(crate) fn init_tracing(
     config: &BridgeConfig,
-    filter: EnvFilter,
+    default_filter: &str,
     inprocess_mode: bool,
 ) -> Option<tracing_appender::non_blocking::WorkerGuard> {
+    let directives = log_filter_directives(default_filter, config.global.log_levels.as_ref());
+    let (filter, filter_handle) = reload::Layer::new(build_filter(&directives, default_filter));
+    let _ = FILTER_RELOAD.set((filter_handle, default_filter.to_string()));
+
+    // Validated with the rest of the config; an unreadable value keeps the default
+    let timestamps = LogTimestampFormat::from_global(&config.global).unwrap_or_default();
+
     // Setup file logging if enabled (check if any instance has logging enabled)
     // For multi-instance, we use global log_to_file setting or first instance's setting
     let should_log_to_file = config.global.log_to_file
@@ -382,28 +567,49 @@ pub(crate) fn init_tracing(
     // Note: The file_guard must be kept alive for the lifetime of the program
     // to ensure logs are flushed to the file
     let file_guard: Option<tracing_appender::non_blocking::WorkerGuard> = if should_log_to_file {
-        // Create log file with timestamp
-        use std::time::SystemTime;
-        let timestamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let log_filename = format!("RKStratum_{}.log", timestamp);
+        // Create log file with timestamp, rolled over by size (`log_max_file_bytes` / `log_max_files`)
         let log_dir = app_dirs::get_bridge_logs_dir();
-        let _ = std::fs::create_dir_all(&log_dir);
-        let log_path = log_dir.join(&log_filename);
-
-        // Use tracing-appender for file logging
-        let file_appender = tracing_appender::rolling::never(&log_dir, &log_filename);
-        let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+        let file_appender = match crate::log_rotation::SizeRotatingAppender::new(
+            &log_dir,
+            config
+                .global
+                .log_max_file_bytes
+                .unwrap_or(crate::log_rotation::DEFAULT_LOG_MAX_FILE_BYTES),
+            config
+                .global
+                .log_max_files
+                .unwrap_or(crate::log_rotation::DEFAULT_LOG_MAX_FILES),
+        ) {
+            Ok(appender) => appender,
+            Err(e) => {
+                eprintln!("Failed to open a log file in {}: {}", log_dir.display(), e);
+                return None;
+            }
+        };
+        let log_path = file_appender.path().to_path_buf();
+
+        // Use tracing-appender for file logging. Lines go through a bounded channel to a writer
+        // thread; when it is full the logging task waits, or the line is dropped in lossy mode.
+        let drop_on_full = config.global.log_drop_on_full.unwrap_or(false);
+        let (non_blocking, guard) = tracing_appender::non_blocking::NonBlockingBuilder::default()
+            .buffered_lines_limit(
+                config
+                    .global
+                    .log_buffer_capacity
+                    .unwrap_or(DEFAULT_LOG_BUFFER_CAPACITY),
+            )
+            .lossy(drop_on_full)
+            .finish(file_appender);
 
         let subscriber = tracing_subscriber::registry()
             .with(filter)
+            .with(crate::log_suppress::WalletSuppressLayer)
             .with(
                 tracing_subscriber::fmt::layer()
                     .with_ansi(LogColors::should_colorize())
                     .event_format(CustomFormatter {
                         apply_colors: LogColors::should_colorize(),
+                        timestamps,
                     }),
             )
             .with(
@@ -412,12 +618,19 @@ pub(crate) fn init_tracing(
                     .with_ansi(false)
                     .event_format(CustomFormatter {
                         apply_colors: false,
+                        timestamps: timestamps.for_file(),
                     }),
-            );
+            )
+            .with(syslog_layer(config));
 
         match tracing::subscriber::set_global_default(subscriber) {
             Ok(()) => {
                 eprintln!("Logging to file: {}", log_path.display());
+                if drop_on_full {
+                    tracing::warn!(
+                        "log_drop_on_full is set: file log lines are dropped while the log buffer is full"
+                    );
+                }
                 Some(guard)
             }
             Err(e) => {
@@ -429,13 +642,18 @@ pub(crate) fn init_tracing(
             }
         }
     } else {
-        let subscriber = tracing_subscriber::registry().with(filter).with(
-            tracing_subscriber::fmt::layer()
-                .with_ansi(LogColors::should_colorize())
-                .event_format(CustomFormatter {
-                    apply_colors: LogColors::should_colorize(),
-                }),
-        );
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(crate::log_suppress::WalletSuppressLayer)
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(LogColors::should_colorize())
+                    .event_format(CustomFormatter {
+                        apply_colors: LogColors::should_colorize(),
+                        timestamps,
+                    }),
+            )
+            .with(syslog_layer(config));
 
         if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
             eprintln!(
@@ -0,0 +1,112 @@
+//! IP allowlist / denylist for the Stratum accept loop.
+//!
+//! Entries accept CIDR notation (`192.168.1.0/24`, `fd00::/8`) or bare addresses (treated as `/32` or `/128`).
+//! The denylist is checked first; a non-empty allowlist then rejects anything not listed.
+
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+/// Why a peer was refused (also the `reason` label on `ks_connections_denied_total`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpDenyReason {
+    Denylist,
+    Allowlist,
+}
+
+impl IpDenyReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IpDenyReason::Denylist => "denylist",
+            IpDenyReason::Allowlist => "allowlist",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IpAccessList {
+    allowed: Vec<IpNet>,
+    denied: Vec<IpNet>,
+}
+
+fn parse_entries(entries: &[String], field: &str) -> Result<Vec<IpNet>, anyhow::Error> {
+    entries
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<IpNet>()
+                .or_else(|_| s.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| {
+                    anyhow::anyhow!("invalid {} entry '{}': expected IP or CIDR", field, s)
+                })
+        })
+        .collect()
+}
+
+impl IpAccessList {
+    /// Parse `allowed_ips` / `denied_ips` config values.
+    pub fn parse(allowed: &[String], denied: &[String]) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            allowed: parse_entries(allowed, "allowed_ips")?,
+            denied: parse_entries(denied, "denied_ips")?,
+        })
+    }
+
+    /// `true` when neither list has entries (every peer is admitted).
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty() && self.denied.is_empty()
+    }
+
+    pub fn check(&self, ip: IpAddr) -> Result<(), IpDenyReason> {
+        // Peers on a dual-stack socket may show up as IPv4-mapped IPv6.
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        if self.denied.iter().any(|net| net.contains(&ip)) {
+            return Err(IpDenyReason::Denylist);
+        }
+        if !self.allowed.is_empty() && !self.allowed.iter().any(|net| net.contains(&ip)) {
+            return Err(IpDenyReason::Allowlist);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(allowed: &[&str], denied: &[&str]) -> IpAccessList {
+        let a: Vec<String> = allowed.iter().map(|s| s.to_string()).collect();
+        let d: Vec<String> = denied.iter().map(|s| s.to_string()).collect();
+        IpAccessList::parse(&a, &d).unwrap()
+    }
+
+    #[test]
+    fn denylist_wins_over_allowlist() {
+        let acl = list(&["192.168.1.0/24"], &["192.168.1.13"]);
+        assert_eq!(
+            acl.check("192.168.1.13".parse().unwrap()),
+            Err(IpDenyReason::Denylist)
+        );
+        assert_eq!(acl.check("192.168.1.14".parse().unwrap()), Ok(()));
+        assert_eq!(
+            acl.check("10.0.0.1".parse().unwrap()),
+            Err(IpDenyReason::Allowlist)
+        );
+    }
+
+    #[test]
+    fn empty_lists_admit_everyone_and_mapped_v6_matches_v4() {
+        assert!(list(&[], &[]).check("203.0.113.9".parse().unwrap()).is_ok());
+        let acl = list(&["10.0.0.0/8"], &[]);
+        assert!(acl.check("::ffff:10.1.2.3".parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        assert!(IpAccessList::parse(&["10.0.0.0/33".to_string()], &[]).is_err());
+        assert!(IpAccessList::parse(&[], &["not-an-ip".to_string()]).is_err());
+    }
+}
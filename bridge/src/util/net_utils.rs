@@ -22,6 +22,53 @@ pub fn normalize_port(port_or_addr: &str) -> String {
     }
 }
 
+/// Strictly validate a port-or-address string and return its normalized form.
+///
+/// Accepts `port`, `:port`, `host:port`, and `[v6]:port`. The port must be 1–65535 and the host (if
+/// present) a literal IPv4/IPv6 address (`0.0.0.0` and `[::]` included), checked by parsing the result
+/// as a [`std::net::SocketAddr`]. The error is a human-readable reason suitable for config diagnostics.
+pub fn parse_bind_address(s: &str) -> Result<String, String> {
+    let normalized = normalize_port(s);
+    if normalized.is_empty() {
+        return Err("address is empty".to_string());
+    }
+    let Some((host, port)) = normalized.rsplit_once(':') else {
+        return Err("expected `port`, `:port`, or `host:port`".to_string());
+    };
+    let port_num: u32 = port
+        .parse()
+        .map_err(|_| format!("port '{}' is not a number", port))?;
+    if !(1..=65535).contains(&port_num) {
+        return Err(format!("port {} is out of range 1-65535", port_num));
+    }
+    // Port-only forms bind every interface, so they are checked as `0.0.0.0:port`
+    let socket_addr = if host.is_empty() {
+        format!("0.0.0.0{}", normalized)
+    } else {
+        normalized.clone()
+    };
+    if socket_addr.parse::<std::net::SocketAddr>().is_err() {
+        return Err(format!(
+            "host '{}' is not a valid IPv4 address or bracketed IPv6 address",
+            host
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Whether two addresses from [`parse_bind_address`] would claim the same socket: the same port on
+/// the same host, or on any host when either side is port-only, `0.0.0.0`, or `[::]`.
+pub fn bind_addresses_collide(a: &str, b: &str) -> bool {
+    let is_wildcard = |host: &str| matches!(host, "" | "0.0.0.0" | "[::]");
+    match (a.rsplit_once(':'), b.rsplit_once(':')) {
+        (Some((host_a, port_a)), Some((host_b, port_b))) => {
+            port_a.parse::<u16>().ok() == port_b.parse::<u16>().ok()
+                && (host_a == host_b || is_wildcard(host_a) || is_wildcard(host_b))
+        }
+        _ => a == b,
+    }
+}
+
 /// Convert a port-or-address string into a concrete bind address suitable for `SocketAddr::parse()`.
 ///
 /// Used for **Stratum** listeners. Port-only forms (`:5555`, `5555`) bind to **`0.0.0.0`** so miners
@@ -80,3 +127,99 @@ pub fn http_operator_dashboard_origin(port_or_addr: &str) -> Option<String> {
     let connect = http_connect_addr_for_operator_dashboard(&bind);
     Some(format!("http://{}/", connect.trim_end_matches('/')))
 }
+
+/// TCP keepalive probe settings applied to accepted Stratum sockets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpKeepaliveConfig {
+    /// Idle time before the first probe.
+    pub time: std::time::Duration,
+    /// Gap between unanswered probes (ignored where the OS does not support it).
+    pub interval: std::time::Duration,
+    /// Unanswered probes before the connection is dropped (ignored where unsupported).
+    pub retries: u32,
+}
+
+/// Enable `SO_KEEPALIVE` on an accepted stream so NAT tables keep idle miner connections alive.
+pub fn apply_tcp_keepalive(
+    stream: &tokio::net::TcpStream,
+    cfg: &TcpKeepaliveConfig,
+) -> std::io::Result<()> {
+    let keepalive = socket2::TcpKeepalive::new().with_time(cfg.time);
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "freebsd",
+        windows
+    ))]
+    let keepalive = keepalive.with_interval(cfg.interval);
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "freebsd"
+    ))]
+    let keepalive = keepalive.with_retries(cfg.retries);
+    socket2::SockRef::from(stream).set_tcp_keepalive(&keepalive)
+}
+
+/// Bind a Stratum listener with `SO_REUSEADDR` (quick rebind after a crash) and, when `reuse_port`
+/// is set, `SO_REUSEPORT` so a new bridge process can share the port while the old one drains.
+pub fn bind_stratum_listener(
+    addr: &str,
+    reuse_port: bool,
+) -> std::io::Result<tokio::net::TcpListener> {
+    use std::net::ToSocketAddrs;
+
+    let addr = addr.to_socket_addrs()?.next().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("no address resolved for {addr}"),
+        )
+    })?;
+    let socket = socket2::Socket::new(
+        socket2::Domain::for_address(addr),
+        socket2::Type::STREAM,
+        Some(socket2::Protocol::TCP),
+    )?;
+    // On Windows SO_REUSEADDR lets another process take over a live port, so leave it off there.
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    if reuse_port {
+        #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+        socket.set_reuse_port(true)?;
+        #[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))]
+        tracing::warn!("use_reuseport is not supported on this platform; ignoring");
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    tokio::net::TcpListener::from_std(socket.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn apply_tcp_keepalive_sets_probe_settings() {
+        let (stream, _client) = crate::test_support::socket_pair().await;
+        let sock = socket2::SockRef::from(&stream);
+        assert!(!sock.keepalive().unwrap(), "off until applied");
+
+        let cfg = TcpKeepaliveConfig {
+            time: Duration::from_secs(42),
+            interval: Duration::from_secs(7),
+            retries: 3,
+        };
+        apply_tcp_keepalive(&stream, &cfg).unwrap();
+        assert!(sock.keepalive().unwrap());
+        #[cfg(target_os = "linux")]
+        {
+            assert_eq!(sock.keepalive_time().unwrap(), cfg.time);
+            assert_eq!(sock.keepalive_interval().unwrap(), cfg.interval);
+            assert_eq!(sock.keepalive_retries().unwrap(), cfg.retries);
+        }
+    }
+}
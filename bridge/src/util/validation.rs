@@ -0,0 +1,134 @@
+//! Input validation for miner-supplied values (wallet addresses and worker names in `mining.authorize`).
+
+use kaspa_addresses::Address;
+
+/// Network prefixes accepted by [`validate_kaspa_address`].
+const ACCEPTED_PREFIXES: [&str; 2] = ["kaspa:", "kaspatest:"];
+
+/// Bech32 payload length range after the prefix: 61 chars (32-byte Schnorr / P2SH) up to 63 chars (33-byte ECDSA).
+const MIN_PAYLOAD_LEN: usize = 61;
+const MAX_PAYLOAD_LEN: usize = 63;
+
+/// Kaspa bech32 charset (lowercase only).
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Returns `true` when `addr` is a well-formed mainnet/testnet Kaspa address: `kaspa:` or `kaspatest:` prefix,
+/// payload length within the expected range, bech32 charset, and a valid checksum.
+pub fn validate_kaspa_address(addr: &str) -> bool {
+    let Some(payload) = ACCEPTED_PREFIXES
+        .iter()
+        .find_map(|prefix| addr.strip_prefix(prefix))
+    else {
+        return false;
+    };
+
+    if !(MIN_PAYLOAD_LEN..=MAX_PAYLOAD_LEN).contains(&payload.len()) {
+        return false;
+    }
+
+    if !payload.chars().all(|c| BECH32_CHARSET.contains(c)) {
+        return false;
+    }
+
+    Address::try_from(addr).is_ok()
+}
+
+/// Default for `worker_name_max_length`.
+pub const DEFAULT_WORKER_NAME_MAX_LENGTH: usize = 64;
+
+/// Punctuation allowed in worker names besides ASCII letters and digits when
+/// `worker_name_allowed_chars` is unset.
+const DEFAULT_WORKER_NAME_EXTRA_CHARS: &str = "_-.";
+
+/// Limits on the worker part of `wallet.worker` (`worker_name_max_length`,
+/// `worker_name_allowed_chars`), so names stay safe to show in dashboards, URLs, and HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerNamePolicy {
+    /// Longest accepted name, in characters.
+    pub max_length: usize,
+    /// Every character a name may use; `None` means ASCII letters, digits, and `_-.`.
+    pub allowed_chars: Option<String>,
+}
+
+impl Default for WorkerNamePolicy {
+    fn default() -> Self {
+        Self {
+            max_length: DEFAULT_WORKER_NAME_MAX_LENGTH,
+            allowed_chars: None,
+        }
+    }
+}
+
+impl WorkerNamePolicy {
+    /// `Err` with the reason when `name` is too long or uses a character outside the allowed set.
+    /// An empty name passes (the bridge assigns a default later).
+    pub fn check(&self, name: &str) -> Result<(), String> {
+        let length = name.chars().count();
+        if length > self.max_length {
+            return Err(format!("{} characters (max {})", length, self.max_length));
+        }
+        let allowed = |c: char| match &self.allowed_chars {
+            Some(chars) => chars.contains(c),
+            None => c.is_ascii_alphanumeric() || DEFAULT_WORKER_NAME_EXTRA_CHARS.contains(c),
+        };
+        match name.chars().find(|c| !allowed(*c)) {
+            Some(c) => Err(format!("character {:?} is not allowed", c)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worker_names_are_checked_for_length_and_characters() {
+        let policy = WorkerNamePolicy::default();
+        assert!(policy.check("rig-01_a").is_ok());
+        assert!(policy.check("").is_ok());
+        assert!(policy.check("rig/1").is_err());
+        assert!(policy.check("<script>").is_err());
+        assert!(policy.check(&"a".repeat(65)).is_err());
+
+        let custom = WorkerNamePolicy {
+            max_length: 4,
+            allowed_chars: Some("abc@".to_string()),
+        };
+        assert!(custom.check("a@bc").is_ok());
+        assert!(custom.check("abcab").is_err());
+        assert!(custom.check("ab1").is_err());
+    }
+
+    #[test]
+    fn accepts_mainnet_and_testnet_addresses() {
+        assert!(validate_kaspa_address(
+            "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y"
+        ));
+        assert!(validate_kaspa_address(
+            "kaspatest:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsaqqr9z7q"
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_prefix_length_charset_and_checksum() {
+        // Ethereum-style address
+        assert!(!validate_kaspa_address(
+            "0x52908400098527886E0F7030069857D2E4169EE7"
+        ));
+        // Devnet prefix is not accepted
+        assert!(!validate_kaspa_address(
+            "kaspadev:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y"
+        ));
+        // Too short
+        assert!(!validate_kaspa_address("kaspa:qr5wl2hw4vk374"));
+        // 'b' is outside the bech32 charset
+        assert!(!validate_kaspa_address(
+            "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0b"
+        ));
+        // Checksum mismatch (last char changed)
+        assert!(!validate_kaspa_address(
+            "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0q"
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+//! Malformed and out-of-order client input against a live listener. Nothing here may take the
+//! bridge down: unparsable lines are skipped, requests the bridge can read but not honour get a
+//! Stratum error, and after each case a `mining.extranonce.subscribe` probe must still be answered
+//! on the same connection. The one input that ends a session on purpose is a line over the 64 KiB
+//! limit; the listener must keep accepting new connections after it.
+
+mod common;
+
+use common::{Miner, WALLET, start_bridge};
+use serde_json::{Value, json};
+
+/// Send a request the bridge always answers and expect its reply as the very next line, which also
+/// shows the input before it got no reply of its own.
+async fn assert_alive(miner: &mut Miner, id: u64) {
+    miner
+        .send(id, "mining.extranonce.subscribe", json!([]))
+        .await;
+    let reply = miner.next_message().await;
+    assert_eq!(reply["id"], json!(id), "{reply}");
+    assert_eq!(reply["result"], json!(true), "{reply}");
+}
+
+fn error_code(reply: &Value) -> &Value {
+    &reply["error"][0]
+}
+
+#[tokio::test]
+async fn adversarial_input_does_not_take_the_bridge_down() {
+    let bridge = start_bridge().await;
+    let mut miner = Miner::connect(&bridge.addr).await;
+
+    // 1. Empty line: skipped
+    miner.send_raw(b"\n").await.unwrap();
+    assert_alive(&mut miner, 100).await;
+
+    // 2. A line over 1 MiB: the session is closed at the line limit, the listener survives. The
+    // bridge may stop reading before the whole line is written, so write errors are expected.
+    let mut oversized = vec![b'a'; 1024 * 1024 + 1];
+    oversized.push(b'\n');
+    let _ = miner.send_raw(&oversized).await;
+    assert!(
+        miner.closed_by_bridge().await,
+        "oversized line must end the session"
+    );
+    let mut miner = Miner::connect(&bridge.addr).await;
+    assert_alive(&mut miner, 101).await;
+
+    // 3. Valid JSON that is not an object: skipped
+    miner.send_raw(b"42\n").await.unwrap();
+    assert_alive(&mut miner, 102).await;
+
+    // 4. Object without `method`: skipped
+    miner.send_raw(b"{\"id\":7,\"params\":[]}\n").await.unwrap();
+    assert_alive(&mut miner, 103).await;
+
+    // 5. JSON array: handled as a batch, each entry that is not a request gets error 20
+    miner.send_raw(b"[1,\"two\"]\n").await.unwrap();
+    let reply = miner.next_message().await;
+    let replies = reply.as_array().expect("batch reply is an array");
+    assert_eq!(replies.len(), 2, "{reply}");
+    for entry in replies {
+        assert_eq!(error_code(entry), &json!(20), "{reply}");
+    }
+    assert_alive(&mut miner, 104).await;
+
+    // 6. Every byte value, newline-terminated (0x0A splits it into two garbage lines): skipped
+    let mut binary: Vec<u8> = (0..=255u8).collect();
+    binary.push(b'\n');
+    miner.send_raw(&binary).await.unwrap();
+    assert_alive(&mut miner, 105).await;
+
+    // 7. Submit straight after subscribe: rejected as unauthorized (24)
+    miner
+        .send(
+            1,
+            "mining.subscribe",
+            json!(["IceRiverMiner-v1.1", "EthereumStratum/1.0.0"]),
+        )
+        .await;
+    assert_eq!(
+        miner.response(1).await["result"],
+        json!([true, "EthereumStratum/1.0.0"])
+    );
+    miner
+        .send(2, "mining.submit", json!([WALLET, "1", "0000000000000000"]))
+        .await;
+    let reply = miner.response(2).await;
+    assert_eq!(error_code(&reply), &json!(24), "{reply}");
+    assert_alive(&mut miner, 106).await;
+
+    // 8. Authorize with `params: null`: rejected as unauthorized (24)
+    miner
+        .send_raw(b"{\"id\":3,\"method\":\"mining.authorize\",\"params\":null}\n")
+        .await
+        .unwrap();
+    let reply = miner.response(3).await;
+    assert_eq!(error_code(&reply), &json!(24), "{reply}");
+    assert_alive(&mut miner, 107).await;
+
+    // The session is still usable for a real authorize
+    miner
+        .send(4, "mining.authorize", json!([format!("{WALLET}.rig"), "x"]))
+        .await;
+    assert_eq!(miner.response(4).await["result"], json!(true));
+}
@@ -0,0 +1,295 @@
+//! Shared pieces of the TCP integration tests: a mock kaspad and a line-oriented Stratum miner.
+
+#![allow(dead_code)]
+
+use kaspa_consensus_core::block::Block;
+use kaspa_consensus_core::header::Header;
+use kaspa_consensus_core::subnets::SubnetworkId;
+use kaspa_consensus_core::tx::{ScriptPublicKey, Transaction, TransactionOutput};
+use kaspa_hashes::Hash;
+use kaspa_rpc_core::{SubmitBlockReport, SubmitBlockResponse};
+use kaspa_stratum_bridge::share_handler::KaspaApiTrait;
+use kaspa_stratum_bridge::{
+    GlobalConfig, InstanceConfig, StratumServerBridgeConfig as StratumBridgeConfig,
+    listen_and_serve_with_shutdown,
+};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::TcpStream;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::watch;
+use tokio::time::timeout;
+
+pub const WALLET: &str = "kaspa:qr5wl2hw4vk374vrnk59jnh64tyj8nvsmax3s0gw5ej2yukwlc3gsuxxc2u0y";
+/// Compact target of roughly 2^255: every other nonce is a block.
+pub const EASY_BITS: u32 = 0x207fffff;
+const MAX_SUBMITS: u64 = 64;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Stand-in for kaspad: serves the same template until [`MockKaspad::next_template`] and accepts
+/// every block.
+pub struct MockKaspad {
+    /// Written into the header's DAA score, so each template has its own pre-PoW hash.
+    template_version: AtomicU64,
+    timestamp: AtomicU64,
+    pub blocks_submitted: AtomicU32,
+}
+
+impl Default for MockKaspad {
+    fn default() -> Self {
+        Self {
+            template_version: AtomicU64::new(1),
+            timestamp: AtomicU64::new(unix_millis()),
+            blocks_submitted: AtomicU32::new(0),
+        }
+    }
+}
+
+impl MockKaspad {
+    /// Switch to a new block template, as if a block had arrived.
+    pub fn next_template(&self) {
+        self.timestamp.store(unix_millis(), Ordering::SeqCst);
+        self.template_version.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[async_trait::async_trait]
+impl KaspaApiTrait for MockKaspad {
+    async fn get_block_template(&self, _: &str, _: &str, _: &str) -> anyhow::Result<Block> {
+        let mut header = Header::from_precomputed_hash(Hash::from_bytes([1; 32]), vec![]);
+        header.daa_score = self.template_version.load(Ordering::SeqCst);
+        header.timestamp = self.timestamp.load(Ordering::SeqCst);
+        header.bits = EASY_BITS;
+        let coinbase = Transaction::new(
+            0,
+            vec![],
+            vec![TransactionOutput::new(
+                0,
+                ScriptPublicKey::from_vec(0, vec![]),
+            )],
+            0,
+            SubnetworkId::from_bytes([0; 20]),
+            0,
+            vec![],
+        );
+        Ok(Block::from_arcs(Arc::new(header), Arc::new(vec![coinbase])))
+    }
+
+    async fn submit_block(&self, _: Block) -> anyhow::Result<SubmitBlockResponse> {
+        self.blocks_submitted.fetch_add(1, Ordering::SeqCst);
+        Ok(SubmitBlockResponse {
+            report: SubmitBlockReport::Success,
+        })
+    }
+
+    async fn get_balances_by_addresses(&self, _: &[String]) -> anyhow::Result<Vec<(String, u64)>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_current_block_color(&self, _: &str) -> anyhow::Result<bool> {
+        Ok(true)
+    }
+
+    async fn is_node_synced_for_mining(&self) -> bool {
+        true
+    }
+}
+
+/// `127.0.0.1:<port>` with a port that was free a moment ago.
+pub fn free_local_addr() -> String {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    format!("127.0.0.1:{port}")
+}
+
+/// Poll until something accepts connections on `addr`.
+pub async fn wait_for_listener(addr: &str) {
+    for _ in 0..50 {
+        if TcpStream::connect(addr).await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("nothing listening on {addr}");
+}
+
+/// A running bridge instance; dropping it stops the listener.
+pub struct TestBridge {
+    pub addr: String,
+    pub kaspad: Arc<MockKaspad>,
+    _shutdown_tx: watch::Sender<bool>,
+}
+
+/// Start one instance with default settings (no VarDiff, `min_share_diff: 1`) on a free port.
+pub async fn start_bridge() -> TestBridge {
+    let addr = free_local_addr();
+    let global = GlobalConfig::builder()
+        .kaspad_address("127.0.0.1:16110")
+        .var_diff(false)
+        .build()
+        .unwrap();
+    let instance = InstanceConfig::builder()
+        .stratum_port(addr.clone())
+        .min_share_diff(1)
+        .build()
+        .unwrap();
+    let mut config: StratumBridgeConfig = instance.effective_config(&global).into();
+    config.instance_id = "[Instance 1]".to_string();
+
+    let kaspad = Arc::new(MockKaspad::default());
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::spawn(listen_and_serve_with_shutdown(
+        config,
+        Arc::clone(&kaspad),
+        None,
+        shutdown_rx,
+    ));
+    wait_for_listener(&addr).await;
+    TestBridge {
+        addr,
+        kaspad,
+        _shutdown_tx: shutdown_tx,
+    }
+}
+
+/// Line-oriented Stratum client that remembers what the server told it.
+pub struct Miner {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+    pub extranonce: Option<String>,
+    pub job_id: Option<String>,
+    /// Params of the latest `mining.notify`.
+    pub job_params: Option<Value>,
+}
+
+impl Miner {
+    pub async fn connect(addr: &str) -> Self {
+        let (reader, writer) = TcpStream::connect(addr).await.unwrap().into_split();
+        Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            extranonce: None,
+            job_id: None,
+            job_params: None,
+        }
+    }
+
+    pub async fn send(&mut self, id: u64, method: &str, params: Value) {
+        let mut line = json!({ "id": id, "method": method, "params": params }).to_string();
+        line.push('\n');
+        self.writer.write_all(line.as_bytes()).await.unwrap();
+    }
+
+    /// Write `bytes` as-is, for input that is not a well-formed request.
+    pub async fn send_raw(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.writer.write_all(bytes).await
+    }
+
+    /// Whether the bridge closes the connection (EOF or reset) before [`READ_TIMEOUT`], skipping
+    /// anything it sends first.
+    pub async fn closed_by_bridge(&mut self) -> bool {
+        timeout(READ_TIMEOUT, async {
+            while let Ok(Some(_)) = self.lines.next_line().await {}
+        })
+        .await
+        .is_ok()
+    }
+
+    /// Next server line, with notifications folded into the miner state.
+    pub async fn next_message(&mut self) -> Value {
+        let line = timeout(READ_TIMEOUT, self.lines.next_line())
+            .await
+            .expect("timed out waiting for the bridge")
+            .unwrap()
+            .expect("bridge closed the connection");
+        let msg: Value = serde_json::from_str(&line).unwrap();
+        match msg["method"].as_str() {
+            Some("mining.notify") => {
+                self.job_id = Some(match &msg["params"][0] {
+                    Value::String(id) => id.clone(),
+                    id => id.to_string(),
+                });
+                self.job_params = Some(msg["params"].clone());
+            }
+            Some("mining.set_extranonce") => {
+                self.extranonce = msg["params"][0].as_str().map(str::to_string);
+            }
+            _ => {}
+        }
+        msg
+    }
+
+    /// Read until the response to request `id`.
+    pub async fn response(&mut self, id: u64) -> Value {
+        loop {
+            let msg = self.next_message().await;
+            if msg["method"].is_null() && msg["id"] == json!(id) {
+                return msg;
+            }
+        }
+    }
+
+    pub async fn wait_for_job(&mut self) {
+        while self.job_id.is_none() {
+            self.next_message().await;
+        }
+    }
+
+    /// Subscribe as an IceRiver KS-series miner, authorize `worker` and wait for the first job and
+    /// the `mining.set_extranonce` that follows authorize.
+    pub async fn handshake_iceriver(&mut self, worker: &str) {
+        self.send(
+            1,
+            "mining.subscribe",
+            json!(["IceRiverMiner-v1.1", "EthereumStratum/1.0.0"]),
+        )
+        .await;
+        let subscribe = self.response(1).await;
+        assert_eq!(
+            subscribe["result"],
+            json!([true, "EthereumStratum/1.0.0"]),
+            "{subscribe}"
+        );
+
+        self.send(2, "mining.authorize", json!([worker, "x"])).await;
+        assert_eq!(self.response(2).await["result"], json!(true));
+        self.wait_for_job().await;
+        while self.extranonce.is_none() {
+            self.next_message().await;
+        }
+    }
+
+    /// Submit distinct nonces against the current job until one is accepted; returns how many it took.
+    pub async fn submit_until_accepted(&mut self, worker: &str) -> u64 {
+        let extranonce = self.extranonce.clone().unwrap_or_default();
+        let nonce_width = 16 - extranonce.len();
+        for id in 3..3 + MAX_SUBMITS {
+            let job_id = self.job_id.clone().unwrap();
+            let nonce = format!("{:0width$x}", id * 0x9e37_79b9, width = nonce_width);
+            assert_eq!(nonce.len(), nonce_width);
+            self.send(id, "mining.submit", json!([worker, job_id, nonce]))
+                .await;
+            let response = self.response(id).await;
+            if response["result"] == json!(true) {
+                assert!(response["error"].is_null(), "{response}");
+                return id - 2;
+            }
+            // Anything short of the block target is a low-difficulty reject
+            assert_eq!(response["error"][0], json!(23), "{response}");
+        }
+        panic!("no share accepted after {MAX_SUBMITS} submits");
+    }
+}
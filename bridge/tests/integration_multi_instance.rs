@@ -0,0 +1,112 @@
+//! Two bridge instances from one multi-instance config, sharing a single kaspad client the way
+//! `runner::run_with_config` shares its `KaspaApi`: both must hand their miners the same template and
+//! both must push the next one within `block_wait_time`.
+
+mod common;
+
+use common::{Miner, MockKaspad, WALLET, free_local_addr, wait_for_listener};
+use kaspa_stratum_bridge::{
+    BridgeConfig, StratumServerBridgeConfig as StratumBridgeConfig, listen_and_serve_with_shutdown,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+const BLOCK_WAIT_TIME: Duration = Duration::from_millis(500);
+const NOTIFY_TOLERANCE: Duration = Duration::from_millis(100);
+
+/// Start every instance of a two-instance config against `kaspad`; returns their stratum addresses.
+async fn start_instances(
+    kaspad: &Arc<MockKaspad>,
+    shutdown_rx: watch::Receiver<bool>,
+) -> Vec<String> {
+    let addrs = [free_local_addr(), free_local_addr()];
+    let yaml = format!(
+        r#"
+kaspad_address: "127.0.0.1:16110"
+block_wait_time: {}
+var_diff: false
+instances:
+  - stratum_port: "{}"
+    min_share_diff: 1
+  - stratum_port: "{}"
+    min_share_diff: 1
+"#,
+        BLOCK_WAIT_TIME.as_millis(),
+        addrs[0],
+        addrs[1]
+    );
+    let config = BridgeConfig::from_yaml(&yaml).unwrap();
+    let extranonce_ranges = config.extranonce_ranges().unwrap();
+
+    for (idx, instance) in config.instances.iter().enumerate() {
+        let bridge_config = StratumBridgeConfig {
+            instance_id: format!("[Instance {}]", idx + 1),
+            extranonce_range: Some(extranonce_ranges[idx]),
+            ..instance.effective_config(&config.global).into()
+        };
+        tokio::spawn(listen_and_serve_with_shutdown(
+            bridge_config,
+            Arc::clone(kaspad),
+            None,
+            shutdown_rx.clone(),
+        ));
+    }
+    for addr in &addrs {
+        wait_for_listener(addr).await;
+    }
+    addrs.to_vec()
+}
+
+/// Header hash of the miner's current IceRiver job (`[job_id, header_hex]`).
+fn template_header(miner: &Miner) -> String {
+    miner.job_params.as_ref().expect("no job yet")[1]
+        .as_str()
+        .expect("IceRiver header hex")
+        .to_string()
+}
+
+/// Read until a job for a template other than `previous` arrives; returns its header and when.
+async fn wait_for_new_template(miner: &mut Miner, previous: &str) -> (String, Instant) {
+    loop {
+        let msg = miner.next_message().await;
+        if msg["method"] == Value::from("mining.notify") && template_header(miner) != previous {
+            return (template_header(miner), Instant::now());
+        }
+    }
+}
+
+#[tokio::test]
+async fn instances_share_kaspad_and_push_the_same_templates() {
+    let kaspad = Arc::new(MockKaspad::default());
+    let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+    let addrs = start_instances(&kaspad, shutdown_rx).await;
+
+    let mut first = Miner::connect(&addrs[0]).await;
+    let mut second = Miner::connect(&addrs[1]).await;
+    first.handshake_iceriver(&format!("{WALLET}.rig1")).await;
+    second.handshake_iceriver(&format!("{WALLET}.rig2")).await;
+    assert_ne!(
+        first.extranonce, second.extranonce,
+        "instances hand out extranonces from separate ranges"
+    );
+
+    let header = template_header(&first);
+    assert_eq!(header, template_header(&second));
+
+    kaspad.next_template();
+    let changed_at = Instant::now();
+    let ((first_header, first_at), (second_header, second_at)) = tokio::join!(
+        wait_for_new_template(&mut first, &header),
+        wait_for_new_template(&mut second, &header),
+    );
+    assert_eq!(first_header, second_header);
+    for at in [first_at, second_at] {
+        let elapsed = at.duration_since(changed_at);
+        assert!(
+            elapsed <= BLOCK_WAIT_TIME + NOTIFY_TOLERANCE,
+            "new template took {elapsed:?} to reach the miner"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! End-to-end Stratum session against a real listener: `mining.subscribe` → `mining.authorize` →
+//! `mining.notify` → `mining.submit`, over TCP on an ephemeral port. kaspad is replaced by
+//! `common::MockKaspad`, which hands out an easy template (about half of all nonces meet the network
+//! target) and accepts every submitted block, so the test can keep submitting until a share is
+//! accepted.
+
+mod common;
+
+use common::{Miner, WALLET, start_bridge};
+use serde_json::json;
+use std::sync::atomic::Ordering;
+
+#[tokio::test]
+async fn bitmain_share_flow() {
+    let bridge = start_bridge().await;
+    let mut miner = Miner::connect(&bridge.addr).await;
+    let worker = format!("{WALLET}.antminer");
+
+    miner
+        .send(1, "mining.subscribe", json!(["GodMiner/2.0.0"]))
+        .await;
+    let subscribe = miner.response(1).await;
+    // Bitmain gets `[null, extranonce, extranonce2_size]` in the subscribe response
+    let result = subscribe["result"].as_array().expect("subscribe result");
+    assert_eq!(result.len(), 3, "{subscribe}");
+    assert!(result[0].is_null());
+    let extranonce = result[1].as_str().expect("extranonce").to_string();
+    let extranonce2_size = result[2].as_u64().expect("extranonce2_size");
+    assert_eq!(extranonce2_size as usize, 8 - extranonce.len() / 2);
+    miner.extranonce = Some(extranonce);
+
+    miner
+        .send(2, "mining.authorize", json!([worker, "x"]))
+        .await;
+    assert_eq!(miner.response(2).await["result"], json!(true));
+    miner.wait_for_job().await;
+
+    let submits = miner.submit_until_accepted(&worker).await;
+    assert!(submits >= 1);
+    assert!(bridge.kaspad.blocks_submitted.load(Ordering::SeqCst) >= 1);
+}
+
+#[tokio::test]
+async fn bitmain_gets_difficulty_before_first_job() {
+    let bridge = start_bridge().await;
+    let mut miner = Miner::connect(&bridge.addr).await;
+
+    miner
+        .send(1, "mining.subscribe", json!(["GodMiner/2.0.0"]))
+        .await;
+    miner.response(1).await;
+    miner
+        .send(
+            2,
+            "mining.authorize",
+            json!([format!("{WALLET}.antminer"), "x"]),
+        )
+        .await;
+    assert_eq!(miner.response(2).await["result"], json!(true));
+
+    let mut difficulty = None;
+    while miner.job_id.is_none() {
+        let msg = miner.next_message().await;
+        if msg["method"] == json!("mining.set_difficulty") {
+            difficulty = Some(msg);
+        }
+    }
+    let difficulty = difficulty.expect("mining.set_difficulty before the first mining.notify");
+    assert!(difficulty["id"].is_null(), "{difficulty}");
+    // `min_share_diff: 1` with VarDiff off
+    assert_eq!(difficulty["params"], json!([1.0]));
+}
+
+#[tokio::test]
+async fn iceriver_share_flow() {
+    let bridge = start_bridge().await;
+    let mut miner = Miner::connect(&bridge.addr).await;
+    let worker = format!("{WALLET}.ks2l");
+
+    // Non-Bitmain miners learn their extranonce from `mining.set_extranonce` after authorize
+    miner.handshake_iceriver(&worker).await;
+    let extranonce = miner.extranonce.clone().unwrap();
+    assert_eq!(extranonce.len(), 4, "IceRiver gets a 2-byte extranonce");
+
+    let submits = miner.submit_until_accepted(&worker).await;
+    assert!(submits >= 1);
+    assert!(bridge.kaspad.blocks_submitted.load(Ordering::SeqCst) >= 1);
+}
@@ -0,0 +1,108 @@
+//! Property tests for VarDiff retargeting (`VarDiffState::compute_next_diff` /
+//! `emergency_next_diff`): arbitrary difficulties, share counts, windows and targets, including a
+//! zero target, a single share and `u32::MAX` difficulty.
+//!
+//! `PROPTEST_CASES=10000 cargo test -p kaspa-stratum-bridge --test var_diff_props`
+
+use kaspa_stratum_bridge::share_handler::VarDiffState;
+use proptest::prelude::*;
+
+/// Slack for float rounding in the bound checks.
+const EPS: f64 = 1e-9;
+
+fn var_diff_state() -> impl Strategy<Value = VarDiffState> {
+    (1.5f64..4.0, 0.25f64..0.75, prop::option::of(2.0f64..20.0)).prop_map(
+        |(step_up, step_down, emergency_threshold)| VarDiffState {
+            step_up,
+            step_down,
+            emergency_threshold,
+            ..VarDiffState::default()
+        },
+    )
+}
+
+proptest! {
+    /// A retarget moves by at most one step (two with the power-of-two clamp, which rounds away
+    /// from `current`) and never goes below 1.
+    #[test]
+    fn retarget_stays_within_one_step(
+        steps in var_diff_state(),
+        current_diff in any::<u32>(),
+        actual_shares in any::<u32>(),
+        window_secs in any::<u32>(),
+        target_shares in any::<u32>(),
+        clamp_pow2 in any::<bool>(),
+    ) {
+        let current = f64::from(current_diff);
+        let next = steps.compute_next_diff(
+            current,
+            f64::from(actual_shares),
+            f64::from(window_secs),
+            f64::from(target_shares),
+            clamp_pow2,
+        );
+        if let Some(next) = next {
+            prop_assert!(current_diff > 0 && window_secs > 0);
+            prop_assert!(next.is_finite() && next >= 1.0);
+            let slack = if clamp_pow2 { 2.0 } else { 1.0 };
+            prop_assert!(next <= current * steps.step_up * slack * (1.0 + EPS));
+            prop_assert!(next >= ((current * steps.step_down).max(1.0) / slack) * (1.0 - EPS));
+        }
+    }
+
+    #[test]
+    fn emergency_retarget_is_finite_and_at_least_one(
+        steps in var_diff_state(),
+        current_diff in any::<u32>(),
+        recent_spm in any::<u32>(),
+        target_shares in any::<u32>(),
+        clamp_pow2 in any::<bool>(),
+    ) {
+        let next = steps.emergency_next_diff(
+            f64::from(current_diff),
+            f64::from(recent_spm),
+            f64::from(target_shares),
+            clamp_pow2,
+        );
+        if let Some((next, ratio)) = next {
+            prop_assert!(next.is_finite() && next >= 1.0);
+            prop_assert!(ratio.is_finite() && ratio > 0.0);
+        }
+    }
+
+    /// A miner with constant hashrate finds exactly the expected number of shares for the current
+    /// difficulty each window. Over 100 retargets the distance to the equilibrium difficulty, in
+    /// log terms, never grows: a step can stop short of it but not overshoot.
+    #[test]
+    fn constant_hashrate_never_oscillates_wider(
+        steps in var_diff_state(),
+        start_diff in 1u32..=u32::MAX,
+        hashrate in 1u32..=u32::MAX,
+        window_secs in 30u32..=600,
+        target_shares in 1u32..=120,
+    ) {
+        // Shares per minute at difficulty 1 is `hashrate`, so the target rate is met at this diff
+        let equilibrium = (f64::from(hashrate) / f64::from(target_shares)).max(1.0);
+        let window = f64::from(window_secs);
+        let mut diff = f64::from(start_diff);
+        let mut amplitude = (diff / equilibrium).ln().abs();
+        for _ in 0..100 {
+            let shares = f64::from(hashrate) / diff * window / 60.0;
+            if let Some(next) =
+                steps.compute_next_diff(diff, shares, window, f64::from(target_shares), false)
+            {
+                diff = next;
+            }
+            let next_amplitude = (diff / equilibrium).ln().abs();
+            prop_assert!(
+                next_amplitude <= amplitude + EPS,
+                "diff {} moved away from {} ({} -> {})",
+                diff,
+                equilibrium,
+                amplitude,
+                next_amplitude
+            );
+            amplitude = next_amplitude;
+        }
+    }
+}